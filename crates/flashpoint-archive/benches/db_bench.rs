@@ -1,14 +1,13 @@
 use std::{fs::File, io::{BufReader, BufRead}, time::Duration};
 use criterion::{Criterion, criterion_group, criterion_main};
-use flashpoint_archive::{FlashpointArchive, game::search::GameFilter};
+use flashpoint_archive::{FlashpointArchive, game::search::GameFilter, profile::PerformanceProfile};
 use flashpoint_archive::game::search::GameSearch;
 use tokio::runtime::Runtime;
 
-const TEST_DATABASE: &str = "benches/flashpoint.sqlite";
-
 pub fn criterion_benchmark(c: &mut Criterion) {
+    let test_database = std::env::var("FPA_TEST_DATABASE").unwrap_or_else(|_| "benches/flashpoint.sqlite".to_owned());
     let mut flashpoint = FlashpointArchive::new();
-    flashpoint.load_database(TEST_DATABASE).expect("Failed to open database");
+    flashpoint.load_existing_database(&test_database).expect("Failed to open database");
     let rand_file = File::open("benches/1k_rand.txt").expect("Failed to open file");
     let rand_reader = BufReader::new(rand_file);
     let mut rand_game_ids = vec![];
@@ -117,6 +116,55 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     });
 
     group.finish();
+
+    // Reopening an up to date database should skip the migration machinery entirely
+    let mut startup = c.benchmark_group("startup");
+    startup.sample_size(50);
+    startup.bench_function("load_database current schema", |b| {
+        b.iter(|| {
+            let mut flashpoint = FlashpointArchive::new();
+            flashpoint.load_existing_database(&test_database).expect("Failed to open database");
+        })
+    });
+    startup.finish();
+
+    // Same searches against each per-connection pragma profile
+    let mut profiles = c.benchmark_group("profiles");
+    profiles.sample_size(10).measurement_time(Duration::from_secs(35));
+    for profile in [PerformanceProfile::Default, PerformanceProfile::LowMemory, PerformanceProfile::HighPerformance] {
+        let mut flashpoint = FlashpointArchive::new();
+        flashpoint.set_performance_profile(profile.clone());
+        flashpoint.load_existing_database(&test_database).expect("Failed to open database");
+        profiles.bench_function(format!("search 15 uncapped {:?}", profile), |b| {
+            b.to_async(Runtime::new().unwrap()).iter(|| async {
+                for search_term in &search_terms {
+                    let mut search = GameSearch::default();
+                    search.limit = 99999999999;
+                    search.filter.whitelist.title = Some(vec![search_term.clone()]);
+                    search.filter.exact_whitelist.library = Some(vec![String::from("arcade")]);
+                    flashpoint.search_games(&search).await.expect("Failed to search");
+                }
+            })
+        });
+    }
+    profiles.finish();
+
+    // Match everything index, the small page size makes for the largest keyset
+    let mut index = c.benchmark_group("search index");
+    index.sample_size(10).measurement_time(Duration::from_secs(35));
+    for page_size in [100, 1] {
+        let mut search = GameSearch::default();
+        search.limit = page_size;
+        let keyset = Runtime::new().unwrap().block_on(flashpoint.search_games_index(&mut search.clone(), None)).expect("Failed to index");
+        let keyset_bytes: usize = keyset.iter().map(|p| std::mem::size_of_val(p) + p.id.capacity() + p.order_val.capacity() + p.title.capacity()).sum();
+        eprintln!("page size {}: {} page tuples, ~{} KiB", page_size, keyset.len(), keyset_bytes / 1024);
+        index.bench_function(format!("match everything page size {}", page_size), |b| {
+            b.to_async(Runtime::new().unwrap()).iter(|| async {
+                flashpoint.search_games_index(&mut search.clone(), None).await.expect("Failed to index");
+            })
+        });
+    }
+    index.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -0,0 +1,35 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use flashpoint_archive::game::search::{build_search_query, parse_user_input, GameSearch};
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    // Query building alone, no database needed
+    let ids: Vec<String> = (0..10000).map(|idx| format!("{:08x}-0000-0000-0000-000000000000", idx)).collect();
+    let tags: Vec<String> = (0..50).map(|idx| format!("Tag {}", idx)).collect();
+
+    let mut search = GameSearch::default();
+    search.filter.exact_whitelist.id = Some(ids.clone());
+    search.filter.exact_blacklist.tags = Some(tags.clone());
+    search.filter.whitelist.developer = Some(vec![String::from("sega")]);
+
+    let mut match_any = GameSearch::default();
+    match_any.filter.match_any = true;
+    match_any.filter.exact_whitelist.library = Some(ids.clone());
+    match_any.filter.exact_whitelist.tags = Some(tags.clone());
+
+    let input = ids.iter().take(1000).map(|id| format!("id:{}", id)).collect::<Vec<String>>().join(" ");
+
+    let mut group = c.benchmark_group("query building");
+    group.bench_function("10k id whitelist", |b| {
+        b.iter(|| build_search_query(&search, "SELECT game.id FROM game"))
+    });
+    group.bench_function("10k exact values match any", |b| {
+        b.iter(|| build_search_query(&match_any, "SELECT game.id FROM game"))
+    });
+    group.bench_function("parse 1k id terms", |b| {
+        b.iter(|| parse_user_input(&input))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,43 @@
+use rusqlite::{Connection, Result};
+
+#[cfg_attr(feature = "napi", napi)]
+#[cfg_attr(not(feature = "napi"), derive(Clone))]
+#[derive(Debug, PartialEq)]
+pub enum CheckpointMode {
+    /// Checkpoint as many frames as possible without waiting on readers or writers
+    PASSIVE,
+    /// Wait for writers, then checkpoint every frame
+    FULL,
+    /// Like FULL, then wait for readers so the next writer restarts the log from the beginning
+    RESTART,
+    /// Like RESTART, and truncate the log file to zero bytes
+    TRUNCATE,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone)]
+pub struct CheckpointResult {
+    /// Another connection kept the checkpoint from completing
+    pub busy: bool,
+    /// Frames in the log, -1 when the database isn't in WAL mode
+    pub log_frames: i64,
+    /// Frames copied back into the database, -1 when the database isn't in WAL mode
+    pub checkpointed_frames: i64,
+}
+
+pub fn checkpoint(conn: &Connection, mode: CheckpointMode) -> Result<CheckpointResult> {
+    let mode = match mode {
+        CheckpointMode::PASSIVE => "PASSIVE",
+        CheckpointMode::FULL => "FULL",
+        CheckpointMode::RESTART => "RESTART",
+        CheckpointMode::TRUNCATE => "TRUNCATE",
+    };
+    conn.query_row(&format!("PRAGMA wal_checkpoint({})", mode), (), |row| {
+        Ok(CheckpointResult {
+            busy: row.get(0)?,
+            log_frames: row.get(1)?,
+            checkpointed_frames: row.get(2)?,
+        })
+    })
+}
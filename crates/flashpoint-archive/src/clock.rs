@@ -0,0 +1,57 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+/// Format every timestamp written to the database uses
+pub const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3fZ";
+
+/// Source of the current time. Swap in a `FixedClock` with `FlashpointArchive::set_clock` to control timestamps in tests.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    fn canonical_now(&self) -> String {
+        format_timestamp(&self.now())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+pub fn format_timestamp(date: &DateTime<Utc>) -> String {
+    date.format(TIMESTAMP_FORMAT).to_string()
+}
+
+/// Parses a timestamp in any format the database has been known to hold, values without a timezone are taken as UTC
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+        if let Ok(date) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(date.and_utc());
+        }
+    }
+    None
+}
+
+/// Rewrites a timestamp in the canonical format, values without a timezone are taken as UTC.
+/// Unparseable values are returned unchanged.
+pub fn normalize_timestamp(value: &str) -> String {
+    match parse_timestamp(value) {
+        Some(date) => format_timestamp(&date),
+        None => value.to_owned(),
+    }
+}
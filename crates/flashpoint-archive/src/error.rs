@@ -7,14 +7,44 @@ use snafu::prelude::*;
 pub enum Error {
     #[snafu(display("Database not initialized"))]
     DatabaseNotInitialized,
+    #[snafu(display("Failed to create connection pool: {}", source))]
+    PoolInit { source: r2d2::Error },
+    #[snafu(display("Failed to get a connection from the pool: {}", source))]
+    ConnectionCheckout { source: r2d2::Error },
+    #[snafu(display("Database file not found: {}", path))]
+    DatabaseNotFound { path: String },
+    #[snafu(display("Not a SQLite database: {} (starts with {})", path, header))]
+    NotADatabase { path: String, header: String },
+    #[snafu(display("Database is locked by another process: {}", path))]
+    DatabaseLocked { path: String },
     #[snafu(display("Database failed to migrate: {}", source))]
     DatabaseMigration { source: rusqlite_migration::Error },
     #[snafu(display("Invalid table name: {}", table_name))]
     InvalidTableName { table_name: String },
     #[snafu(display("SQLite error: {}", source))]
     SqliteError { source: rusqlite::Error },
+    #[snafu(display("Game not found: {}", id))]
+    GameNotFound { id: String },
+    #[snafu(display("Overlay not found: {}", id))]
+    OverlayNotFound { id: u32 },
+    #[snafu(display("Overlay {} is full, limit of {} staged operations", id, limit))]
+    OverlayFull { id: u32, limit: usize },
+    #[snafu(display("Cannot merge tag '{}' into '{}', both name the same tag", name, merged_into))]
+    MergeIntoSelf { name: String, merged_into: String },
+    #[snafu(display("Invalid search: {}", reason))]
+    InvalidSearch { reason: String },
+    #[snafu(display("Invalid patch at '{}': {}", path, reason))]
+    InvalidPatch { path: String, reason: String },
+    #[snafu(display("Invalid color '{}', expected #RGB or #RRGGBB", color))]
+    InvalidColor { color: String },
+    #[snafu(display("Invalid timestamp '{}'", timestamp))]
+    InvalidTimestamp { timestamp: String },
     #[snafu(display("Mutex lock failed"))]
     MutexLockFailed,
+    #[snafu(display("Database busy, gave up waiting for another write after {}ms", waited_ms))]
+    WriteBusy { waited_ms: u64 },
+    #[snafu(display("Query exceeded the configured timeout"))]
+    Timeout,
     #[snafu(display("Transaction already open"))]
     TransactionAlreadyOpen,
     #[snafu(display("Failed to parse date '{}': {}", date, source))]
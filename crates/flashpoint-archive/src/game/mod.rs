@@ -1,16 +1,18 @@
-use chrono::Utc;
 use rusqlite::{
     params,
     types::{FromSql, FromSqlError, Value, ValueRef},
     Connection, OptionalExtension, Result,
 };
+use snafu::ResultExt;
 use uuid::Uuid;
 use std::{collections::{HashMap, HashSet}, fmt::Display, ops::{Deref, DerefMut}, rc::Rc, vec::Vec};
 
-use crate::{tag::{Tag, self}, platform::{self, PlatformAppPath}, game_data::{GameData, PartialGameData}};
+use crate::{clock::{normalize_timestamp, parse_timestamp}, error, merge_game_filters, tag::{Tag, self}, platform::{self, PlatformAppPath}, game_data::{GameData, PartialGameData}, update::SqlVec};
 
-use self::search::{mark_index_dirty, GameSearch, GameSearchRelations};
+use self::search::{mark_index_dirty, GameFilter, GameSearch, GameSearchOrder, GameSearchRelations};
 
+#[cfg(feature = "serde")]
+pub mod patch;
 pub mod search;
 
 #[cfg(feature = "napi")]
@@ -213,6 +215,16 @@ pub struct AdditionalApp {
     pub parent_game_id: String,
 }
 
+/// A game with its data and add apps, created together by `create_full`
+#[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone)]
+pub struct CreateFullGame {
+    pub game: PartialGame,
+    pub add_apps: Vec<AdditionalApp>,
+    pub game_data: Vec<PartialGameData>,
+}
+
 #[cfg_attr(feature = "napi", napi(object))]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Debug, Clone)]
@@ -240,6 +252,8 @@ pub struct Game {
     pub legacy_application_path: String,
     pub legacy_launch_command: String,
     pub release_date: String,
+    /// First 4 digit year found in release_date, kept up to date by the database
+    pub release_year: Option<i64>,
     pub version: String,
     pub original_description: String,
     pub language: String,
@@ -254,6 +268,8 @@ pub struct Game {
     pub game_data: Option<Vec<GameData>>,
     pub add_apps: Option<Vec<AdditionalApp>>,
     pub ruffle_support: String,
+    /// Columns matched by the search's free text terms, only set when the search has `explain_matches`
+    pub matched_fields: Option<Vec<String>>,
 }
 
 #[cfg_attr(feature = "napi", napi(object))]
@@ -296,6 +312,34 @@ pub struct PartialGame {
     pub ruffle_support: Option<String>,
 }
 
+#[cfg_attr(feature = "napi", napi)]
+#[cfg_attr(not(feature = "napi"), derive(Clone))]
+#[derive(Debug, PartialEq)]
+pub enum ArchiveState {
+    NotArchived = 0,
+    Archived = 1,
+    Available = 2,
+}
+
+impl ArchiveState {
+    pub fn from_i64(value: i64) -> Option<ArchiveState> {
+        match value {
+            0 => Some(ArchiveState::NotArchived),
+            1 => Some(ArchiveState::Archived),
+            2 => Some(ArchiveState::Available),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ArchiveState::NotArchived => "Not Archived",
+            ArchiveState::Archived => "Archived",
+            ArchiveState::Available => "Available",
+        }
+    }
+}
+
 #[cfg_attr(feature = "napi", napi(object))]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Debug, Clone)]
@@ -304,7 +348,244 @@ pub struct GameRedirect {
     pub dest_id: String,
 }
 
+/// Games changed since a timestamp, e.g. the previous metadata update. Each list is paged separately.
+#[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Default)]
+pub struct ChangesReport {
+    /// Added after the timestamp, newest first
+    pub new_games: Vec<Game>,
+    /// Added before but modified after the timestamp, most recently modified first
+    pub updated_games: Vec<Game>,
+    /// Soft deleted after the timestamp
+    pub deleted_ids: Vec<String>,
+    pub total_new: i64,
+    pub total_updated: i64,
+    pub total_deleted: i64,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone)]
+pub struct DiskUsageReport {
+    /// Bytes used by all game data present on disk
+    pub total_size: i64,
+    /// Number of game data packs present on disk
+    pub installed_count: i64,
+    /// Games using the most space, largest first
+    pub top_games: Vec<GameDiskUsage>,
+    /// Largest library first
+    pub libraries: Vec<LibraryDiskUsage>,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone)]
+pub struct GameDiskUsage {
+    pub id: String,
+    pub title: String,
+    pub library: String,
+    pub size: i64,
+    pub installed_count: i64,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone)]
+pub struct LibraryDiskUsage {
+    pub library: String,
+    pub size: i64,
+    pub installed_count: i64,
+}
+
+/// How saves treat the game's dateModified column
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum DateModifiedPolicy {
+    /// Use the partial's value, or now when it has none
+    Auto,
+    /// Leave the stored value untouched, even if the partial has one. For repairs that mustn't look like local edits.
+    Preserve,
+    /// Write this value verbatim
+    Force(String),
+}
+
+/// A problem found by `validate` which would leave a saved game in a bad state
+#[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone)]
+pub struct ValidationIssue {
+    /// Name of the `PartialGame` field at fault
+    pub field: String,
+    pub message: String,
+}
+
+/// Games whose denormalized columns `FlashpointArchive::rebuild_denormalized` recomputes
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone)]
+pub enum RebuildScope {
+    All,
+    GameIds(Vec<String>),
+}
+
+/// Number of games whose column actually changed, per rebuilt column
+#[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RebuildReport {
+    pub games_checked: i64,
+    pub tags_str: i64,
+    pub platforms_str: i64,
+    /// platformName and platformId, counted once per game
+    pub platform_name: i64,
+    pub active_data_on_disk: i64,
+}
+
+impl RebuildReport {
+    pub fn add(&mut self, other: &RebuildReport) {
+        self.games_checked += other.games_checked;
+        self.tags_str += other.tags_str;
+        self.platforms_str += other.platforms_str;
+        self.platform_name += other.platform_name;
+        self.active_data_on_disk += other.active_data_on_disk;
+    }
+}
+
+/// Recomputes tagsStr, platformsStr, platformName, platformId and activeDataOnDisk from the relations of the given games.
+/// Names are joined in relation order, the order saves add them in. A stored list holding the same names in another order is kept.
+///
+/// platformName becomes the primary name of the related platform it's an alias of. Failing that, it's restored from the
+/// related platform platformId points to, or else the first related platform. An empty platformName is only restored from platformId.
+pub fn rebuild_denormalized(conn: &Connection, ids: &[String]) -> Result<RebuildReport> {
+    // Allow use of rarray() in SQL queries
+    rusqlite::vtab::array::load_module(conn)?;
+
+    let ids_vec = SqlVec(ids.to_vec());
+    let mut report = RebuildReport {
+        games_checked: ids.len() as i64,
+        ..Default::default()
+    };
+
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT gtt.gameId, ta.name FROM game_tags_tag gtt
+        JOIN tag t ON gtt.tagId = t.id
+        JOIN tag_alias ta ON t.primaryAliasId = ta.id
+        WHERE gtt.gameId IN rarray(?) ORDER BY gtt.rowid")?;
+    let tag_iter = stmt.query_map(params![ids_vec], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+    for tag in tag_iter {
+        let (game_id, name) = tag?;
+        tags.entry(game_id).or_default().push(name);
+    }
+
+    let mut platforms: HashMap<String, Vec<(i64, String)>> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT gpp.gameId, p.id, pa.name FROM game_platforms_platform gpp
+        JOIN platform p ON gpp.platformId = p.id
+        JOIN platform_alias pa ON p.primaryAliasId = pa.id
+        WHERE gpp.gameId IN rarray(?) ORDER BY gpp.rowid")?;
+    let platform_iter = stmt.query_map(params![ids_vec], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(2)?)))?;
+    for platform in platform_iter {
+        let (game_id, platform_id, name) = platform?;
+        platforms.entry(game_id).or_default().push((platform_id, name));
+    }
+
+    let mut stmt = conn.prepare("SELECT id, tagsStr, IFNULL(platformsStr, ''), IFNULL(platformName, ''), platformId
+        FROM game WHERE id IN rarray(?)")?;
+    let stored = stmt.query_map(params![ids_vec], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, String>(3)?, row.get::<_, Option<i64>>(4)?))
+    })?
+    .collect::<Result<Vec<_>>>()?;
+
+    let mut alias_stmt = conn.prepare("SELECT platformId FROM platform_alias WHERE name = ?")?;
+    for (id, tags_str, platforms_str, platform_name, platform_id) in stored {
+        let tag_names = tags.remove(&id).unwrap_or_default();
+        if !same_names(&tags_str, &tag_names) {
+            conn.execute("UPDATE game SET tagsStr = ? WHERE id = ?", params![tag_names.join("; "), id])?;
+            report.tags_str += 1;
+        }
+
+        let game_platforms = platforms.remove(&id).unwrap_or_default();
+        let platform_names: Vec<String> = game_platforms.iter().map(|(_, name)| name.clone()).collect();
+        if !same_names(&platforms_str, &platform_names) {
+            conn.execute("UPDATE game SET platformsStr = ? WHERE id = ?", params![platform_names.join("; "), id])?;
+            report.platforms_str += 1;
+        }
+
+        let related = |platform_id: Option<i64>| game_platforms.iter().find(|(id, _)| Some(*id) == platform_id);
+        let alias_of = match platform_name.is_empty() {
+            true => None,
+            false => alias_stmt.query_row(params![platform_name], |row| row.get::<_, Option<i64>>(0)).optional()?.flatten(),
+        };
+        let primary = related(alias_of)
+            .or_else(|| related(platform_id))
+            .or_else(|| match platform_name.is_empty() {
+                true => None,
+                false => game_platforms.first(),
+            });
+        let (primary_id, primary_name) = match primary {
+            Some((primary_id, name)) => (Some(*primary_id), name.clone()),
+            None => (None, String::new()),
+        };
+        if primary_name != platform_name || primary_id != platform_id {
+            conn.execute("UPDATE game SET platformName = ?, platformId = ? WHERE id = ?", params![primary_name, primary_id, id])?;
+            report.platform_name += 1;
+        }
+    }
+
+    report.active_data_on_disk = conn.execute("UPDATE game SET activeDataOnDisk = r.value FROM (
+        SELECT g.id, IFNULL((SELECT gd.presentOnDisk FROM game_data gd WHERE gd.id = g.activeDataId), 0) AS value
+        FROM game g WHERE g.id IN rarray(?)
+    ) r WHERE game.id = r.id AND game.activeDataOnDisk IS NOT r.value", params![ids_vec])? as i64;
+
+    mark_index_dirty(conn)?;
+
+    Ok(report)
+}
+
+/// Whether a stored "; " separated list holds exactly `names`, in any order
+fn same_names(stored: &str, names: &[String]) -> bool {
+    let mut stored: Vec<&str> = stored.split("; ").filter(|name| !name.is_empty()).collect();
+    let mut names: Vec<&str> = names.iter().map(|name| name.as_str()).collect();
+    stored.sort_unstable();
+    names.sort_unstable();
+    stored == names
+}
+
+/// Rewrites the language of the given games as the codes from `util::normalize_languages`, joined with "; ".
+/// The value it replaced is kept in languageRaw. Returns how many games changed.
+pub fn normalize_languages(conn: &Connection, ids: &[String]) -> Result<i64> {
+    // Allow use of rarray() in SQL queries
+    rusqlite::vtab::array::load_module(conn)?;
+
+    let mut stmt = conn.prepare("SELECT id, language FROM game WHERE id IN rarray(?)")?;
+    let languages = stmt.query_map(params![SqlVec(ids.to_vec())], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<Result<Vec<(String, String)>>>()?;
+
+    let mut update_stmt = conn.prepare("UPDATE game SET languageRaw = language, language = ? WHERE id = ?")?;
+    let mut changed = 0;
+    for (id, language) in languages {
+        let normalized = crate::util::normalize_languages(&language).join("; ");
+        if normalized != language {
+            update_stmt.execute(params![normalized, id])?;
+            changed += 1;
+        }
+    }
+
+    Ok(changed)
+}
+
 pub fn find_all_ids(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT id FROM game WHERE deletedAt IS NULL")?;
+
+    let ids = stmt.query_map([], |row| {
+        row.get(0)
+    })?
+    .collect::<Result<Vec<String>>>()?;
+
+    Ok(ids)
+}
+
+/// Like `find_all_ids`, but soft deleted games are included
+pub fn find_all_ids_with_deleted(conn: &Connection) -> Result<Vec<String>> {
     let mut stmt = conn.prepare("SELECT id FROM game")?;
 
     let ids = stmt.query_map([], |row| {
@@ -315,17 +596,245 @@ pub fn find_all_ids(conn: &Connection) -> Result<Vec<String>> {
     Ok(ids)
 }
 
+pub fn find_tag_relations(conn: &Connection, game_ids: Option<Vec<String>>) -> Result<Vec<(String, i64)>> {
+    find_relations(conn, "tag", game_ids)
+}
+
+pub fn find_platform_relations(conn: &Connection, game_ids: Option<Vec<String>>) -> Result<Vec<(String, i64)>> {
+    find_relations(conn, "platform", game_ids)
+}
+
+fn find_relations(conn: &Connection, tag_name: &str, game_ids: Option<Vec<String>>) -> Result<Vec<(String, i64)>> {
+    // Allow use of rarray() in SQL queries
+    rusqlite::vtab::array::load_module(conn)?;
+
+    let relations = match game_ids {
+        Some(ids) => {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT gameId, {}Id FROM game_{}s_{} WHERE gameId IN rarray(?) ORDER BY gameId, {}Id",
+                tag_name, tag_name, tag_name, tag_name
+            ))?;
+            let rows = stmt.query_map(params![SqlVec(ids)], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<Vec<(String, i64)>>>()?;
+            rows
+        }
+        None => {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT gameId, {}Id FROM game_{}s_{} ORDER BY gameId, {}Id",
+                tag_name, tag_name, tag_name, tag_name
+            ))?;
+            let rows = stmt.query_map((), |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<Vec<(String, i64)>>>()?;
+            rows
+        }
+    };
+
+    Ok(relations)
+}
+
+pub fn find_tag_ids(conn: &Connection, game_id: &str) -> Result<Vec<i64>> {
+    let mut stmt = conn.prepare("SELECT tagId FROM game_tags_tag WHERE gameId = ? ORDER BY tagId")?;
+
+    let ids = stmt.query_map(params![game_id], |row| {
+        row.get(0)
+    })?
+    .collect::<Result<Vec<i64>>>()?;
+
+    Ok(ids)
+}
+
+pub fn find_platform_ids(conn: &Connection, game_id: &str) -> Result<Vec<i64>> {
+    let mut stmt = conn.prepare("SELECT platformId FROM game_platforms_platform WHERE gameId = ? ORDER BY platformId")?;
+
+    let ids = stmt.query_map(params![game_id], |row| {
+        row.get(0)
+    })?
+    .collect::<Result<Vec<i64>>>()?;
+
+    Ok(ids)
+}
+
 pub fn find(conn: &Connection, id: &str) -> Result<Option<Game>> {
+    if let Some(mut game) = find_slim(conn, id)? {
+        load_relations(conn, &mut game)?;
+        Ok(Some(game))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Fills every relation `find` returns, so created and saved games come back the same shape
+fn load_relations(conn: &Connection, game: &mut Game) -> Result<()> {
+    load_batch_relations(conn, std::slice::from_mut(game))
+}
+
+/// Fills every relation `find` returns for a batch of games, with one query per relation whatever the batch size
+fn load_batch_relations(conn: &Connection, games: &mut [Game]) -> Result<()> {
+    if games.is_empty() {
+        return Ok(());
+    }
+
+    // Allow use of rarray() in SQL queries
+    rusqlite::vtab::array::load_module(conn)?;
+    let ids = SqlVec(games.iter().map(|game| game.id.clone()).collect::<Vec<String>>());
+
+    let mut platforms = group_by_game(conn, "SELECT gpp.gameId, p.id, p.description, pa.name, p.dateModified
+        FROM platform p
+        INNER JOIN game_platforms_platform gpp ON gpp.platformId = p.id
+        INNER JOIN platform_alias pa ON p.primaryAliasId = pa.id
+        WHERE gpp.gameId IN rarray(?) ORDER BY gpp.rowid", &ids, |row| {
+        Ok(Tag {
+            id: row.get(1)?,
+            description: row.get(2)?,
+            name: row.get(3)?,
+            date_modified: row.get(4)?,
+            category: None,
+            category_color: None,
+            aliases: vec![],
+        })
+    })?;
+    let platform_aliases = group_aliases(conn, "SELECT pa.platformId, pa.name FROM platform_alias pa
+        WHERE pa.platformId IN (SELECT platformId FROM game_platforms_platform WHERE gameId IN rarray(?))
+        ORDER BY pa.id", &ids)?;
+
+    let mut tags = group_by_game(conn, "SELECT gtt.gameId, t.id, t.description, ta.name, t.dateModified, tc.name, tc.color
+        FROM tag t
+        INNER JOIN game_tags_tag gtt ON gtt.tagId = t.id
+        INNER JOIN tag_alias ta ON t.primaryAliasId = ta.id
+        INNER JOIN tag_category tc ON t.categoryId = tc.id
+        WHERE gtt.gameId IN rarray(?) ORDER BY gtt.rowid", &ids, |row| {
+        Ok(Tag {
+            id: row.get(1)?,
+            description: row.get(2)?,
+            name: row.get(3)?,
+            date_modified: row.get(4)?,
+            category: row.get(5)?,
+            category_color: row.get(6)?,
+            aliases: vec![],
+        })
+    })?;
+    let tag_aliases = group_aliases(conn, "SELECT ta.tagId, ta.name FROM tag_alias ta
+        WHERE ta.tagId IN (SELECT tagId FROM game_tags_tag WHERE gameId IN rarray(?))
+        ORDER BY ta.id", &ids)?;
+
+    let mut game_data = group_by_game(conn, "SELECT gameId, id, title, dateAdded, sha256, crc32, presentOnDisk,
+        path, size, parameters, applicationPath, launchCommand
+        FROM game_data WHERE gameId IN rarray(?) ORDER BY id", &ids, |row| {
+        Ok(GameData {
+            id: row.get(1)?,
+            game_id: row.get(0)?,
+            title: row.get(2)?,
+            date_added: row.get(3)?,
+            sha256: row.get(4)?,
+            crc32: row.get(5)?,
+            present_on_disk: row.get(6)?,
+            path: row.get(7)?,
+            size: row.get(8)?,
+            parameters: row.get(9)?,
+            application_path: row.get(10)?,
+            launch_command: row.get(11)?,
+        })
+    })?;
+
+    let mut add_apps = group_by_game(conn, "SELECT parentGameId, id, name, applicationPath, launchCommand, autoRunBefore, waitForExit
+        FROM additional_app WHERE parentGameId IN rarray(?) ORDER BY rowid", &ids, |row| {
+        Ok(AdditionalApp {
+            id: row.get(1)?,
+            parent_game_id: row.get(0)?,
+            name: row.get(2)?,
+            application_path: row.get(3)?,
+            launch_command: row.get(4)?,
+            auto_run_before: row.get(5)?,
+            wait_for_exit: row.get(6)?,
+        })
+    })?;
+
+    let with_aliases = |mut list: Vec<Tag>, aliases: &HashMap<i64, Vec<String>>| {
+        for item in list.iter_mut() {
+            item.aliases = aliases.get(&item.id).cloned().unwrap_or_default();
+        }
+        list
+    };
+    for game in games.iter_mut() {
+        game.detailed_platforms = Some(with_aliases(platforms.remove(&game.id).unwrap_or_default(), &platform_aliases));
+        game.detailed_tags = Some(with_aliases(tags.remove(&game.id).unwrap_or_default(), &tag_aliases));
+        game.game_data = Some(game_data.remove(&game.id).unwrap_or_default());
+        game.add_apps = Some(add_apps.remove(&game.id).unwrap_or_default());
+    }
+
+    Ok(())
+}
+
+/// Runs a query whose first column is a game id, grouping the mapped rows by game in query order
+fn group_by_game<T>(
+    conn: &Connection,
+    query: &str,
+    ids: &SqlVec<String>,
+    map: impl Fn(&rusqlite::Row<'_>) -> Result<T>,
+) -> Result<HashMap<String, Vec<T>>> {
+    let mut stmt = conn.prepare(query)?;
+    let mut rows = stmt.query(params![ids])?;
+    let mut grouped: HashMap<String, Vec<T>> = HashMap::new();
+    while let Some(row) = rows.next()? {
+        grouped.entry(row.get(0)?).or_default().push(map(row)?);
+    }
+    Ok(grouped)
+}
+
+/// Alias names keyed by the tag or platform id in the first column
+fn group_aliases(conn: &Connection, query: &str, ids: &SqlVec<String>) -> Result<HashMap<i64, Vec<String>>> {
+    let mut stmt = conn.prepare(query)?;
+    let mut rows = stmt.query(params![ids])?;
+    let mut grouped: HashMap<i64, Vec<String>> = HashMap::new();
+    while let Some(row) = rows.next()? {
+        grouped.entry(row.get(0)?).or_default().push(row.get(1)?);
+    }
+    Ok(grouped)
+}
+
+/// Games keyed by the requested id, following redirects. Missing ids are left out of the map.
+pub fn find_by_ids(conn: &Connection, ids: &[String]) -> Result<HashMap<String, Game>> {
+    // Allow use of rarray() in SQL queries
+    rusqlite::vtab::array::load_module(conn)?;
+
+    let mut stmt = conn.prepare("SELECT r.value, COALESCE((SELECT id FROM game_redirect WHERE sourceId = r.value), r.value)
+        FROM rarray(?) r")?;
+    let resolved = stmt.query_map(params![SqlVec(ids.to_vec())], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?
+    .collect::<Result<Vec<(String, String)>>>()?;
+
+    let resolved_ids = SqlVec(resolved.iter().map(|(_, id)| id.clone()).collect::<Vec<String>>());
+    let mut stmt = conn.prepare(&format!("{} WHERE game.id IN rarray(?) AND game.deletedAt IS NULL", search::RESULTS_QUERY))?;
+    let game_iter = stmt.query_map(params![resolved_ids], search::full_game_from_row)?;
+
+    let mut games = game_iter.collect::<Result<Vec<Game>>>()?;
+    load_batch_relations(conn, &mut games)?;
+    let games: HashMap<String, Game> = games.into_iter().map(|game| (game.id.clone(), game)).collect();
+
+    Ok(resolved.into_iter()
+        .filter_map(|(requested, id)| games.get(&id).map(|game| (requested, game.clone())))
+        .collect())
+}
+
+/// Whether a game row exists for the id, following redirects. Soft deleted games count.
+pub fn exists_with_deleted(conn: &Connection, id: &str) -> Result<bool> {
+    conn.query_row("SELECT EXISTS (SELECT 1 FROM game WHERE id = COALESCE((SELECT id FROM game_redirect WHERE sourceId = ?), ?))",
+        params![id, id], |row| row.get(0))
+}
+
+/// Finds a game without loading its relations, following redirects like `find`
+pub fn find_slim(conn: &Connection, id: &str) -> Result<Option<Game>> {
     let mut stmt = conn.prepare(
         "SELECT id, title, alternateTitles, series, developer, publisher, platformsStr, \
         platformName, dateAdded, dateModified, broken, extreme, playMode, status, notes, \
         tagsStr, source, applicationPath, launchCommand, releaseDate, version, \
         originalDescription, language, activeDataId, activeDataOnDisk, lastPlayed, playtime, \
-        activeGameConfigId, activeGameConfigOwner, archiveState, library, playCounter, ruffleSupport \
-        FROM game WHERE id = COALESCE((SELECT id FROM game_redirect WHERE sourceId = ?), ?)",
+        activeGameConfigId, activeGameConfigOwner, archiveState, library, playCounter, ruffleSupport, releaseYear \
+        FROM game WHERE id = COALESCE((SELECT id FROM game_redirect WHERE sourceId = ?), ?) AND deletedAt IS NULL",
     )?;
 
-    let game_result = stmt
+    stmt
         .query_row(params![id, id], |row| {
             Ok(Game {
                 id: row.get(0)?,
@@ -365,51 +874,60 @@ pub fn find(conn: &Connection, id: &str) -> Result<Option<Game>> {
                 game_data: None,
                 add_apps: None,
                 ruffle_support: row.get(32)?,
+                release_year: row.get(33)?,
+                matched_fields: None,
             })
         })
-        .optional()?; // Converts rusqlite::Error::QueryReturnedNoRows to None
+        .optional() // Converts rusqlite::Error::QueryReturnedNoRows to None
+}
 
-    if let Some(mut game) = game_result {
-        game.detailed_platforms = Some(get_game_platforms(conn, id)?);
-        game.detailed_tags = Some(get_game_tags(conn, id)?);
-        game.game_data = Some(get_game_data(conn, id)?);
-        game.add_apps = Some(get_game_add_apps(conn, id)?);
-        Ok(Some(game))
-    } else {
-        Ok(None)
+/// Replaces the game's tag and platform names with the primary names they resolve to, creating any that
+/// don't exist yet. Names resolving to the same tag or platform are only kept once.
+///
+/// The primary platform is always one of the resulting platforms: it's replaced by the primary name of the
+/// platform it resolves to, and cleared if it isn't one of the game's platforms (e.g when a later save
+/// overwrote the platforms without it).
+fn resolve_relations(conn: &Connection, game: &mut Game) -> Result<(Vec<Tag>, Vec<Tag>)> {
+    let mut detailed_tags: Vec<Tag> = vec![];
+    for name in game.tags.iter() {
+        let detailed_tag = tag::find_or_create(conn, name)?;
+        if !detailed_tags.iter().any(|t| t.id == detailed_tag.id) {
+            detailed_tags.push(detailed_tag);
+        }
     }
-}
 
-pub fn create(conn: &Connection, partial: &PartialGame) -> Result<Game> {
-    let mut game: Game = partial.into();
+    let primary_name = game.primary_platform.to_lowercase();
+    let mut primary_platform = String::new();
+    let mut detailed_platforms: Vec<Tag> = vec![];
+    for name in game.platforms.iter() {
+        let detailed_platform = platform::find_or_create(conn, name, None)?;
+        if name.to_lowercase() == primary_name {
+            primary_platform = detailed_platform.name.clone();
+        }
+        if !detailed_platforms.iter().any(|p| p.id == detailed_platform.id) {
+            detailed_platforms.push(detailed_platform);
+        }
+    }
 
-    let mut detailed_tags = vec![];
-    let mut detailed_platforms = vec![];
+    game.tags = TagVec(detailed_tags.iter().map(|t| t.name.clone()).collect());
+    game.platforms = TagVec(detailed_platforms.iter().map(|p| p.name.clone()).collect());
+    game.primary_platform = primary_platform;
 
-    let tags_copy = game.tags.clone();
-    let platforms_copy = game.platforms.clone();
-    game.tags = vec![].into();
-    game.platforms = vec![].into();
+    Ok((detailed_tags, detailed_platforms))
+}
 
-    for name in tags_copy {
-        let detailed_tag = tag::find_or_create(conn, &name)?;
-        game.tags.push(detailed_tag.name);
-        detailed_tags.push(detailed_tag.id);
-    }
+pub fn create(conn: &Connection, partial: &PartialGame) -> Result<Game> {
+    let mut game: Game = partial.into();
 
-    for name in platforms_copy {
-        let detailed_platform = platform::find_or_create(conn, &name, None)?;
-        game.platforms.push(detailed_platform.name);
-        detailed_platforms.push(detailed_platform.id);
-    }
+    let (detailed_tags, detailed_platforms) = resolve_relations(conn, &mut game)?;
 
     conn.execute(
         "INSERT INTO game (id, library, title, alternateTitles, series, developer, publisher, \
-         platformName, platformsStr, dateAdded, dateModified, broken, extreme, playMode, status, \
+         platformName, platformId, platformsStr, dateAdded, dateModified, broken, extreme, playMode, status, \
          notes, tagsStr, source, applicationPath, launchCommand, releaseDate, version, \
          originalDescription, language, activeDataId, activeDataOnDisk, lastPlayed, playtime, \
-         activeGameConfigId, activeGameConfigOwner, archiveState, orderTitle, ruffleSupport) VALUES (?, ?, ?, ?, ?, ?, ?, \
-         ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, '', ?)",
+         activeGameConfigId, activeGameConfigOwner, archiveState, orderTitle, ruffleSupport, releaseYear) VALUES (?, ?, ?, ?, ?, ?, ?, \
+         ?, (SELECT platformId FROM platform_alias WHERE name = ?), ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, '', ?, ?)",
         params![
             &game.id,
             &game.library,
@@ -419,6 +937,7 @@ pub fn create(conn: &Connection, partial: &PartialGame) -> Result<Game> {
             &game.developer,
             &game.publisher,
             &game.primary_platform,
+            &game.primary_platform,
             &game.platforms.join("; "),
             &game.date_added,
             &game.date_modified,
@@ -443,128 +962,170 @@ pub fn create(conn: &Connection, partial: &PartialGame) -> Result<Game> {
             &game.active_game_config_owner,
             &game.archive_state,
             &game.ruffle_support,
+            &game.release_year,
         ],
     )?;
 
     for tag in detailed_tags {
-        conn.execute("INSERT OR IGNORE INTO game_tags_tag (gameId, tagId) VALUES (?, ?)", params![game.id, tag])?;
+        conn.execute("INSERT OR IGNORE INTO game_tags_tag (gameId, tagId) VALUES (?, ?)", params![game.id, tag.id])?;
     }
 
     for platform in detailed_platforms {
-        conn.execute("INSERT OR IGNORE INTO game_platforms_platform (gameId, platformId) VALUES (?, ?)", params![game.id, platform])?;
+        conn.execute("INSERT OR IGNORE INTO game_platforms_platform (gameId, platformId) VALUES (?, ?)", params![game.id, platform.id])?;
     }
 
+    load_relations(conn, &mut game)?;
+
     mark_index_dirty(conn)?;
 
     Ok(game)
 }
 
-pub fn save(conn: &Connection, game: &PartialGame) -> Result<Game> {
-    // Allow use of rarray() in SQL queries
-    rusqlite::vtab::array::load_module(conn)?;
+pub fn save(conn: &Connection, game: &PartialGame) -> error::Result<Game> {
+    save_game(conn, game, false)
+}
 
-    let existing_game_result = find(conn, game.id.as_str())?;
-    if let Some(mut existing_game) = existing_game_result {
-        existing_game.apply_partial(game);
+/// Like `save`, but never writes dateModified, whatever the partial holds
+pub fn save_preserving_date_modified(conn: &Connection, game: &PartialGame) -> error::Result<Game> {
+    save_game(conn, game, true)
+}
 
-        // Process  any tag and platform changes
-        let tags_copy = existing_game.tags.clone();
-        let platforms_copy = existing_game.platforms.clone();
-        let mut detailed_tags_copy: Vec<Tag> = vec![];
-        let mut detailed_platforms_copy: Vec<Tag> = vec![];
-        existing_game.tags = vec![].into();
-        existing_game.platforms = vec![].into();
+fn save_game(conn: &Connection, game: &PartialGame, preserve_date_modified: bool) -> error::Result<Game> {
+    let existing_game_result = find(conn, game.id.as_str()).context(error::SqliteSnafu)?;
+    match existing_game_result {
+        Some(existing_game) => apply_save(conn, existing_game, game, preserve_date_modified).context(error::SqliteSnafu),
+        None => error::GameNotFoundSnafu { id: game.id.clone() }.fail(),
+    }
+}
 
-        for name in tags_copy {
-            let detailed_tag = tag::find_or_create(conn, &name)?;
-            detailed_tags_copy.push(detailed_tag.clone());
-            existing_game.tags.push(detailed_tag.name);
+/// Checks a partial against the game it would be saved over. Nothing is written.
+/// Fails with `GameNotFound` when there's no such game, the same as saving would.
+pub fn validate(conn: &Connection, partial: &PartialGame) -> error::Result<Vec<ValidationIssue>> {
+    let mut issues = vec![];
+
+    let dates = [
+        ("date_added", &partial.date_added),
+        ("date_modified", &partial.date_modified),
+        ("last_played", &partial.last_played),
+    ];
+    for (field, value) in dates {
+        if let Some(value) = value {
+            if parse_timestamp(value).is_none() {
+                issues.push(ValidationIssue {
+                    field: field.to_owned(),
+                    message: format!("'{}' is not a valid timestamp", value),
+                });
+            }
         }
+    }
 
-        for name in platforms_copy {
-            let detailed_platform = platform::find_or_create(conn, &name, None)?;
-            detailed_platforms_copy.push(detailed_platform.clone());
-            existing_game.platforms.push(detailed_platform.name);
-        }
+    // Checked before apply_partial, which would quietly add a missing primary platform to the platforms
+    let game = match find(conn, &partial.id).context(error::SqliteSnafu)? {
+        Some(game) => game,
+        None => return error::GameNotFoundSnafu { id: partial.id.clone() }.fail(),
+    };
+    let primary_platform = partial.primary_platform.as_ref().unwrap_or(&game.primary_platform);
+    let platforms = partial.platforms.as_ref().unwrap_or(&game.platforms);
+    if primary_platform != "" && !platforms.iter().any(|p| p.to_lowercase() == primary_platform.to_lowercase()) {
+        issues.push(ValidationIssue {
+            field: "primary_platform".to_owned(),
+            message: format!("'{}' is not one of the game's platforms", primary_platform),
+        });
+    }
 
-        // Update relations in database
-        let tag_ids: Vec<i64> = detailed_tags_copy.iter().map(|t| t.id).collect::<Vec<i64>>();
-        let tag_values = Rc::new(tag_ids.iter().copied().map(Value::from).collect::<Vec<Value>>());
-        let mut stmt = conn.prepare("DELETE FROM game_tags_tag WHERE gameId = ? AND tagId NOT IN rarray(?)")?;
-        stmt.execute(params![existing_game.id.as_str(), tag_values]).map(|changes| changes as usize)?;
-        for tag_id in tag_ids {
-            stmt = conn.prepare("INSERT OR IGNORE INTO game_tags_tag (gameId, tagId) VALUES (?, ?)")?;
-            stmt.execute(params![existing_game.id.as_str(), tag_id])?;
-        }
+    Ok(issues)
+}
 
-        let platform_ids: Vec<i64> = detailed_platforms_copy.iter().map(|t| t.id).collect::<Vec<i64>>();
-        let platform_values = Rc::new(platform_ids.iter().copied().map(Value::from).collect::<Vec<Value>>());
-        let mut stmt = conn.prepare("DELETE FROM game_platforms_platform WHERE gameId = ? AND platformId NOT IN rarray(?)")?;
-        stmt.execute(params![existing_game.id.as_str(), platform_values]).map(|changes| changes as usize)?;
-        for platform_id in platform_ids {
-            stmt = conn.prepare("INSERT OR IGNORE INTO game_platforms_platform (gameId, platformId) VALUES (?, ?)")?;
-            stmt.execute(params![existing_game.id.as_str(), platform_id])?;
-        }
+fn apply_save(conn: &Connection, mut existing_game: Game, game: &PartialGame, preserve_date_modified: bool) -> Result<Game> {
+    // Allow use of rarray() in SQL queries
+    rusqlite::vtab::array::load_module(conn)?;
+
+    let stored_date_modified = existing_game.date_modified.clone();
+    existing_game.apply_partial(game);
+    if preserve_date_modified {
+        existing_game.date_modified = stored_date_modified;
+    }
 
+    // Process  any tag and platform changes
+    let (detailed_tags_copy, detailed_platforms_copy) = resolve_relations(conn, &mut existing_game)?;
+
+    // Update relations in database
+    let tag_ids: Vec<i64> = detailed_tags_copy.iter().map(|t| t.id).collect::<Vec<i64>>();
+    let tag_values = Rc::new(tag_ids.iter().copied().map(Value::from).collect::<Vec<Value>>());
+    let mut stmt = conn.prepare("DELETE FROM game_tags_tag WHERE gameId = ? AND tagId NOT IN rarray(?)")?;
+    stmt.execute(params![existing_game.id.as_str(), tag_values]).map(|changes| changes as usize)?;
+    for tag_id in tag_ids {
+        stmt = conn.prepare("INSERT OR IGNORE INTO game_tags_tag (gameId, tagId) VALUES (?, ?)")?;
+        stmt.execute(params![existing_game.id.as_str(), tag_id])?;
+    }
+
+    let platform_ids: Vec<i64> = detailed_platforms_copy.iter().map(|t| t.id).collect::<Vec<i64>>();
+    let platform_values = Rc::new(platform_ids.iter().copied().map(Value::from).collect::<Vec<Value>>());
+    let mut stmt = conn.prepare("DELETE FROM game_platforms_platform WHERE gameId = ? AND platformId NOT IN rarray(?)")?;
+    stmt.execute(params![existing_game.id.as_str(), platform_values]).map(|changes| changes as usize)?;
+    for platform_id in platform_ids {
+        stmt = conn.prepare("INSERT OR IGNORE INTO game_platforms_platform (gameId, platformId) VALUES (?, ?)")?;
+        stmt.execute(params![existing_game.id.as_str(), platform_id])?;
+    }
 
-        // Write back the changes to the database
-        conn.execute(
-            "UPDATE game SET library = ?, title = ?, alternateTitles = ?, series = ?, developer = ?, publisher = ?, \
-             platformName = ?, platformsStr = ?, dateAdded = ?, dateModified = ?, broken = ?, \
-             extreme = ?, playMode = ?, status = ?, notes = ?, tagsStr = ?, source = ?, \
-             applicationPath = ?, launchCommand = ?, releaseDate = ?, version = ?, \
-             originalDescription = ?, language = ?, activeDataId = ?, activeDataOnDisk = ?, \
-             lastPlayed = ?, playtime = ?, playCounter = ?, activeGameConfigId = ?, activeGameConfigOwner = ?, \
-             archiveState = ?, ruffleSupport = ? WHERE id = ?",
-            params![
-                &existing_game.library,
-                &existing_game.title,
-                &existing_game.alternate_titles,
-                &existing_game.series,
-                &existing_game.developer,
-                &existing_game.publisher,
-                &existing_game.primary_platform,
-                &existing_game.platforms.join("; "),
-                &existing_game.date_added,
-                &existing_game.date_modified,
-                &existing_game.legacy_broken,
-                &existing_game.legacy_extreme,
-                &existing_game.play_mode,
-                &existing_game.status,
-                &existing_game.notes,
-                &existing_game.tags.join("; "),
-                &existing_game.source,
-                &existing_game.legacy_application_path,
-                &existing_game.legacy_launch_command,
-                &existing_game.release_date,
-                &existing_game.version,
-                &existing_game.original_description,
-                &existing_game.language,
-                &existing_game.active_data_id,
-                &existing_game.active_data_on_disk,
-                &existing_game.last_played,
-                &existing_game.playtime,
-                &existing_game.play_counter,
-                &existing_game.active_game_config_id,
-                &existing_game.active_game_config_owner,
-                &existing_game.archive_state,
-                &existing_game.ruffle_support,
-                &existing_game.id,
-            ],
-        )?;
 
+    // Write back the changes to the database
+    conn.execute(
+        "UPDATE game SET library = ?, title = ?, alternateTitles = ?, series = ?, developer = ?, publisher = ?, \
+         platformName = ?, platformId = (SELECT platformId FROM platform_alias WHERE name = ?), platformsStr = ?, \
+         dateAdded = ?, dateModified = IFNULL(?, dateModified), broken = ?, \
+         extreme = ?, playMode = ?, status = ?, notes = ?, tagsStr = ?, source = ?, \
+         applicationPath = ?, launchCommand = ?, releaseDate = ?, version = ?, \
+         originalDescription = ?, language = ?, activeDataId = ?, activeDataOnDisk = ?, \
+         lastPlayed = ?, playtime = ?, playCounter = ?, activeGameConfigId = ?, activeGameConfigOwner = ?, \
+         archiveState = ?, ruffleSupport = ?, releaseYear = ? WHERE id = ?",
+        params![
+            &existing_game.library,
+            &existing_game.title,
+            &existing_game.alternate_titles,
+            &existing_game.series,
+            &existing_game.developer,
+            &existing_game.publisher,
+            &existing_game.primary_platform,
+            &existing_game.primary_platform,
+            &existing_game.platforms.join("; "),
+            &existing_game.date_added,
+            // NULL skips the column
+            (!preserve_date_modified).then_some(&existing_game.date_modified),
+            &existing_game.legacy_broken,
+            &existing_game.legacy_extreme,
+            &existing_game.play_mode,
+            &existing_game.status,
+            &existing_game.notes,
+            &existing_game.tags.join("; "),
+            &existing_game.source,
+            &existing_game.legacy_application_path,
+            &existing_game.legacy_launch_command,
+            &existing_game.release_date,
+            &existing_game.version,
+            &existing_game.original_description,
+            &existing_game.language,
+            &existing_game.active_data_id,
+            &existing_game.active_data_on_disk,
+            &existing_game.last_played,
+            &existing_game.playtime,
+            &existing_game.play_counter,
+            &existing_game.active_game_config_id,
+            &existing_game.active_game_config_owner,
+            &existing_game.archive_state,
+            &existing_game.ruffle_support,
+            &existing_game.release_year,
+            &existing_game.id,
+        ],
+    )?;
 
 
-        existing_game.detailed_platforms = get_game_platforms(conn, &existing_game.id)?.into();
-        existing_game.detailed_tags = get_game_tags(conn, &existing_game.id)?.into();
-        existing_game.game_data = get_game_data(conn, &existing_game.id)?.into();
 
-        mark_index_dirty(conn)?;
+    load_relations(conn, &mut existing_game)?;
 
-        Ok(existing_game)
-    } else {
-        Err(rusqlite::Error::QueryReturnedNoRows)
-    }
+    mark_index_dirty(conn)?;
+
+    Ok(existing_game)
 }
 
 pub fn delete(conn: &Connection, id: &str) -> Result<()> {    
@@ -583,8 +1144,53 @@ pub fn delete(conn: &Connection, id: &str) -> Result<()> {
     Ok(())
 }
 
+/// Hides a game from finds and searches, its relations are left untouched so it can be restored
+pub fn soft_delete(conn: &Connection, id: &str, deleted_at: &str) -> error::Result<()> {
+    let changed = conn.execute("UPDATE game SET deletedAt = ? WHERE id = ? AND deletedAt IS NULL", params![deleted_at, id])
+        .context(error::SqliteSnafu)?;
+    if changed == 0 {
+        return error::GameNotFoundSnafu { id }.fail();
+    }
+    mark_index_dirty(conn).context(error::SqliteSnafu)
+}
+
+pub fn restore(conn: &Connection, id: &str) -> error::Result<()> {
+    let changed = conn.execute("UPDATE game SET deletedAt = NULL WHERE id = ? AND deletedAt IS NOT NULL", params![id])
+        .context(error::SqliteSnafu)?;
+    if changed == 0 {
+        return error::GameNotFoundSnafu { id }.fail();
+    }
+    mark_index_dirty(conn).context(error::SqliteSnafu)
+}
+
+/// Hard deletes games soft deleted before `older_than`, returns the purged ids
+pub fn purge_deleted(conn: &Connection, older_than: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT id FROM game WHERE deletedAt IS NOT NULL AND deletedAt < ?")?;
+    let ids = stmt.query_map(params![older_than], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<String>>>()?;
+
+    for id in &ids {
+        delete(conn, id)?;
+    }
+    mark_index_dirty(conn)?;
+
+    Ok(ids)
+}
+
 pub fn count(conn: &Connection) -> Result<i64> {
-    conn.query_row("SELECT COUNT(*) FROM game", (), |row| row.get::<_, i64>(0))
+    conn.query_row("SELECT COUNT(*) FROM game WHERE deletedAt IS NULL", (), |row| row.get::<_, i64>(0))
+}
+
+/// Number of games in each archive state, as (state, count) pairs
+pub fn count_by_archive_state(conn: &Connection) -> Result<Vec<(i64, i64)>> {
+    let mut stmt = conn.prepare("SELECT archiveState, COUNT(*) FROM game WHERE deletedAt IS NULL GROUP BY archiveState ORDER BY archiveState")?;
+
+    let counts = stmt.query_map([], |row| {
+        Ok((row.get(0)?, row.get(1)?))
+    })?
+    .collect::<Result<Vec<(i64, i64)>>>()?;
+
+    Ok(counts)
 }
 
 fn get_game_platforms(conn: &Connection, id: &str) -> Result<Vec<Tag>> {
@@ -602,6 +1208,7 @@ fn get_game_platforms(conn: &Connection, id: &str) -> Result<Vec<Tag>> {
             name: row.get(2)?,
             date_modified: row.get(3)?,
             category: None,
+            category_color: None,
             aliases: vec![],
         })
     })?;
@@ -631,7 +1238,7 @@ fn get_game_platforms(conn: &Connection, id: &str) -> Result<Vec<Tag>> {
 
 fn get_game_tags(conn: &Connection, id: &str) -> Result<Vec<Tag>> {
     let mut tag_stmt = conn.prepare(
-        "SELECT t.id, t.description, ta.name, t.dateModified, tc.name FROM tag t
+        "SELECT t.id, t.description, ta.name, t.dateModified, tc.name, tc.color FROM tag t
          INNER JOIN game_tags_tag gtt ON gtt.tagId = t.id
          INNER JOIN tag_alias ta ON t.primaryAliasId = ta.id
          INNER JOIN tag_category tc ON t.categoryId = tc.id
@@ -645,6 +1252,7 @@ fn get_game_tags(conn: &Connection, id: &str) -> Result<Vec<Tag>> {
             name: row.get(2)?,
             date_modified: row.get(3)?,
             category: row.get(4)?,
+            category_color: row.get(5)?,
             aliases: vec![],
         })
     })?;
@@ -759,6 +1367,18 @@ pub fn find_game_data_by_id(conn: &Connection, id: i64) -> Result<Option<GameDat
     }).optional()?)
 }
 
+/// The game data row the game's activeDataId points at, following redirects
+pub fn find_active_game_data(conn: &Connection, game_id: &str) -> Result<Option<GameData>> {
+    let active_data_id: Option<i64> = conn.query_row("SELECT activeDataId FROM game
+        WHERE id = COALESCE((SELECT id FROM game_redirect WHERE sourceId = ?1), ?1) AND deletedAt IS NULL",
+        params![game_id], |row| row.get(0)).optional()?.flatten();
+
+    match active_data_id {
+        Some(id) => find_game_data_by_id(conn, id),
+        None => Ok(None),
+    }
+}
+
 pub fn create_game_data(conn: &Connection, partial: &PartialGameData) -> Result<GameData> {
     // Make sure game exists
     let game = find(conn, &partial.game_id)?;
@@ -790,6 +1410,45 @@ pub fn create_game_data(conn: &Connection, partial: &PartialGameData) -> Result<
     Ok(game_data)
 }
 
+/// Creates every game data row, failing with `GameNotFound` before inserting anything if a parent game is missing
+pub fn create_game_data_batch(conn: &Connection, partials: &[PartialGameData]) -> error::Result<Vec<GameData>> {
+    // Allow use of rarray() in SQL queries
+    rusqlite::vtab::array::load_module(conn).context(error::SqliteSnafu)?;
+
+    let game_ids = SqlVec(partials.iter().map(|p| p.game_id.clone()).collect::<Vec<String>>());
+    let missing: Option<String> = conn.query_row("SELECT r.value FROM rarray(?) r
+        WHERE NOT EXISTS (SELECT 1 FROM game WHERE id = r.value)
+        AND NOT EXISTS (SELECT 1 FROM game_redirect WHERE sourceId = r.value) LIMIT 1", params![game_ids], |row| row.get(0))
+        .optional().context(error::SqliteSnafu)?;
+    if let Some(id) = missing {
+        return error::GameNotFoundSnafu { id }.fail();
+    }
+
+    let mut stmt = conn.prepare("INSERT INTO game_data (gameId, title, dateAdded, sha256, crc32, presentOnDisk
+        , path, size, parameters, applicationPath, launchCommand)
+        VALUES (COALESCE((SELECT id FROM game_redirect WHERE sourceId = ?1), ?1), ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+        RETURNING id, gameId").context(error::SqliteSnafu)?;
+    let mut created = Vec::with_capacity(partials.len());
+    for partial in partials {
+        let mut game_data: GameData = partial.into();
+        (game_data.id, game_data.game_id) = stmt.query_row(params![
+            &game_data.game_id,
+            &game_data.title,
+            &game_data.date_added,
+            &game_data.sha256,
+            &game_data.crc32,
+            &game_data.present_on_disk,
+            &game_data.path,
+            &game_data.size,
+            &game_data.parameters,
+            &game_data.application_path,
+            &game_data.launch_command,
+        ], |row| Ok((row.get(0)?, row.get(1)?))).context(error::SqliteSnafu)?;
+        created.push(game_data);
+    }
+    Ok(created)
+}
+
 pub fn save_game_data(conn: &Connection, partial: &PartialGameData) -> Result<GameData> {
     let game_data: GameData = partial.into();
     
@@ -818,21 +1477,167 @@ pub fn save_game_data(conn: &Connection, partial: &PartialGameData) -> Result<Ga
     }
 }
 
-pub fn find_with_tag(conn: &Connection, tag: &str) -> Result<Vec<Game>> {
+/// Finds all games with a tag, matched by name or alias. Loads every relation and returns all games unless given a limit and relations.
+/// `tag_filter` hides games as `GameSearch::with_tag_filter` does, its index must be up to date.
+pub fn find_with_tag(conn: &Connection, tag: &str, limit: Option<i64>, relations: Option<GameSearchRelations>, tag_filter: Option<Vec<String>>) -> Result<Vec<Game>> {
+    let tag = match tag::find_by_name(conn, tag)? {
+        Some(t) => t,
+        None => return Ok(vec![]),
+    };
+
     let mut search = GameSearch::default();
-    search.load_relations = GameSearchRelations {
+    search.load_relations = relations.unwrap_or(GameSearchRelations {
         tags: true,
         platforms: true,
         game_data: true,
         add_apps: true,
-    };
-    search.filter.exact_whitelist.tags = Some(vec![tag.to_owned()]);
-    search.limit = 9999999999;
+    });
+    search.filter.exact_whitelist.tags = Some(vec![tag.name]);
+    search.with_tag_filter = tag_filter;
+    search::search_limited(conn, &search, limit)
+}
+
+/// Games with no primary platform and no platform relations, narrowed by `search` if given
+pub fn find_platformless(conn: &Connection, search: GameSearch) -> Result<Vec<Game>> {
+    let mut stmt = conn.prepare("SELECT id FROM game WHERE IFNULL(platformName, '') = ''
+        AND NOT EXISTS (SELECT 1 FROM game_platforms_platform gpp WHERE gpp.gameId = game.id)")?;
+    let ids = stmt.query_map((), |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<String>>>()?;
+    search_within(conn, search, ids)
+}
+
+/// Slim games listing `language` as one of their "; " separated languages, ignoring case.
+/// Unlike the language filter, "en" doesn't match "eng".
+pub fn find_by_language(conn: &Connection, language: &str, search: GameSearch) -> Result<Vec<Game>> {
+    let mut stmt = conn.prepare("SELECT id FROM game
+        WHERE instr(';' || lower(replace(game.language, ' ', '')) || ';', ';' || lower(?) || ';') > 0")?;
+    let ids = stmt.query_map(params![language.trim()], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<String>>>()?;
+    let mut search = search;
+    search.slim = true;
+    search_within(conn, search, ids)
+}
+
+/// Runs `search` over only the given games
+fn search_within(conn: &Connection, mut search: GameSearch, ids: Vec<String>) -> Result<Vec<Game>> {
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut id_filter = GameFilter::default();
+    id_filter.exact_whitelist.id = Some(ids);
+    search.filter = merge_game_filters(&search.filter, &id_filter);
     search::search(conn, &search)
 }
 
+/// Finds the first standalone run of 4 digits in a free-form release date, e.g. "circa 2001?" gives 2001.
+/// Must agree with the backfill in the release year migration.
+pub fn parse_release_year(release_date: &str) -> Option<i64> {
+    release_date
+        .split(|c: char| !c.is_ascii_digit())
+        .find(|run| run.len() == 4)
+        .and_then(|run| run.parse().ok())
+}
+
+/// Finds all games in a series as slim games. An empty series matches nothing unless `match_empty` is set,
+/// then it finds the games without a series. `tag_filter` works as in `find_with_tag`.
+pub fn find_series_games(conn: &Connection, series: &str, order: GameSearchOrder, match_empty: bool, tag_filter: Option<Vec<String>>) -> Result<Vec<Game>> {
+    if series.is_empty() && !match_empty {
+        return Ok(vec![]);
+    }
+
+    let mut search = GameSearch::default();
+    search.slim = true;
+    search.order = order;
+    search.filter.exact_whitelist.series = Some(vec![series.to_owned()]);
+    search.with_tag_filter = tag_filter;
+    search::search_limited(conn, &search, None)
+}
+
+/// Most recently played games first as slim games, games that have never been played are excluded.
+/// `tag_filter` works as in `find_with_tag`.
+pub fn find_recently_played(conn: &Connection, limit: i64, tag_filter: Option<Vec<String>>) -> Result<Vec<Game>> {
+    let tag_filter_clause = match tag_filter.is_some_and(|tags| !tags.is_empty()) {
+        true => " AND game.id IN (SELECT id FROM tag_filter_index)",
+        false => "",
+    };
+    let fields = [
+        search::GameField::Id,
+        search::GameField::Title,
+        search::GameField::Series,
+        search::GameField::Developer,
+        search::GameField::Publisher,
+        search::GameField::Platforms,
+        search::GameField::PrimaryPlatform,
+        search::GameField::Tags,
+        search::GameField::Library,
+        search::GameField::LastPlayed,
+    ];
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM game
+        WHERE game.lastPlayed IS NOT NULL AND game.deletedAt IS NULL{}
+        ORDER BY game.lastPlayed DESC LIMIT ?",
+        search::slim_selection(&fields),
+        tag_filter_clause
+    ))?;
+
+    let games = stmt.query_map(params![limit], |row| search::slim_game_from_row(row, &fields))?
+        .collect::<Result<Vec<Game>>>()?;
+
+    Ok(games)
+}
+
+/// Stored dates mix formats, so both sides are normalized before comparing
+pub fn changes_since(conn: &Connection, timestamp: &str, limit: i64, offset: i64) -> error::Result<ChangesReport> {
+    if parse_timestamp(timestamp).is_none() {
+        return error::InvalidTimestampSnafu { timestamp }.fail();
+    }
+
+    const NEW: &str = "game.deletedAt IS NULL
+        AND strftime('%Y-%m-%dT%H:%M:%fZ', game.dateAdded) > strftime('%Y-%m-%dT%H:%M:%fZ', ?1)";
+    const UPDATED: &str = "game.deletedAt IS NULL
+        AND strftime('%Y-%m-%dT%H:%M:%fZ', game.dateModified) > strftime('%Y-%m-%dT%H:%M:%fZ', ?1)
+        AND strftime('%Y-%m-%dT%H:%M:%fZ', game.dateAdded) <= strftime('%Y-%m-%dT%H:%M:%fZ', ?1)";
+    const DELETED: &str = "strftime('%Y-%m-%dT%H:%M:%fZ', game.deletedAt) > strftime('%Y-%m-%dT%H:%M:%fZ', ?1)";
+
+    let mut fields = search::DEFAULT_SLIM_FIELDS.to_vec();
+    fields.extend([search::GameField::DateAdded, search::GameField::DateModified]);
+    let selection = search::slim_selection(&fields);
+
+    let count = |clause: &str| -> Result<i64> {
+        conn.query_row(&format!("SELECT COUNT(*) FROM game WHERE {}", clause), params![timestamp], |row| row.get(0))
+    };
+    let slim_games = |clause: &str, order_column: &str| -> Result<Vec<Game>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM game WHERE {} ORDER BY strftime('%Y-%m-%dT%H:%M:%fZ', game.{}) DESC, game.id LIMIT ?2 OFFSET ?3",
+            selection, clause, order_column
+        ))?;
+        let games = stmt.query_map(params![timestamp, limit, offset], |row| search::slim_game_from_row(row, &fields))?
+            .collect::<Result<Vec<Game>>>()?;
+        Ok(games)
+    };
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id FROM game WHERE {} ORDER BY strftime('%Y-%m-%dT%H:%M:%fZ', game.deletedAt) DESC, game.id LIMIT ?2 OFFSET ?3",
+        DELETED
+    )).context(error::SqliteSnafu)?;
+    let deleted_ids = stmt.query_map(params![timestamp, limit, offset], |row| row.get(0))
+        .context(error::SqliteSnafu)?
+        .collect::<Result<Vec<String>>>()
+        .context(error::SqliteSnafu)?;
+
+    Ok(ChangesReport {
+        new_games: slim_games(NEW, "dateAdded").context(error::SqliteSnafu)?,
+        updated_games: slim_games(UPDATED, "dateModified").context(error::SqliteSnafu)?,
+        deleted_ids,
+        total_new: count(NEW).context(error::SqliteSnafu)?,
+        total_updated: count(UPDATED).context(error::SqliteSnafu)?,
+        total_deleted: count(DELETED).context(error::SqliteSnafu)?,
+    })
+}
+
 pub fn find_developers(conn: &Connection) -> Result<Vec<String>> {
-    let mut stmt = conn.prepare("SELECT DISTINCT developer FROM game")?;
+    let mut stmt = conn.prepare("SELECT DISTINCT developer FROM game WHERE deletedAt IS NULL")?;
     let dev_iter = stmt.query_map((), |row| row.get::<_, String>(0))?;
 
     let mut developers_set = HashSet::new();
@@ -850,7 +1655,7 @@ pub fn find_developers(conn: &Connection) -> Result<Vec<String>> {
 }
 
 pub fn find_publishers(conn: &Connection) -> Result<Vec<String>> {
-    let mut stmt = conn.prepare("SELECT DISTINCT publisher FROM game")?;
+    let mut stmt = conn.prepare("SELECT DISTINCT publisher FROM game WHERE deletedAt IS NULL")?;
     let dev_iter = stmt.query_map((), |row| row.get::<_, String>(0))?;
 
     let mut publishers_set = HashSet::new();
@@ -868,7 +1673,7 @@ pub fn find_publishers(conn: &Connection) -> Result<Vec<String>> {
 }
 
 pub fn find_series(conn: &Connection) -> Result<Vec<String>> {
-    let mut stmt = conn.prepare("SELECT DISTINCT series FROM game")?;
+    let mut stmt = conn.prepare("SELECT DISTINCT series FROM game WHERE deletedAt IS NULL")?;
     let series_iter = stmt.query_map((), |row| row.get::<_, String>(0))?;
 
     let mut seriesss = vec![];
@@ -881,7 +1686,7 @@ pub fn find_series(conn: &Connection) -> Result<Vec<String>> {
 }
 
 pub fn find_libraries(conn: &Connection) -> Result<Vec<String>> {
-    let mut stmt = conn.prepare("SELECT DISTINCT library FROM game")?;
+    let mut stmt = conn.prepare("SELECT DISTINCT library FROM game WHERE deletedAt IS NULL")?;
     let libraries_iter = stmt.query_map((), |row| row.get(0))?;
 
     let mut libraries = vec![];
@@ -894,7 +1699,7 @@ pub fn find_libraries(conn: &Connection) -> Result<Vec<String>> {
 }
 
 pub fn find_statuses(conn: &Connection) -> Result<Vec<String>> {
-    let mut stmt = conn.prepare("SELECT DISTINCT status FROM game")?;
+    let mut stmt = conn.prepare("SELECT DISTINCT status FROM game WHERE deletedAt IS NULL")?;
     let status_iter = stmt.query_map((), |row| {
         let value: String = row.get(0)?;
         Ok(value)
@@ -913,7 +1718,7 @@ pub fn find_statuses(conn: &Connection) -> Result<Vec<String>> {
 }
 
 pub fn find_play_modes(conn: &Connection) -> Result<Vec<String>> {
-    let mut stmt = conn.prepare("SELECT DISTINCT playMode FROM game")?;
+    let mut stmt = conn.prepare("SELECT DISTINCT playMode FROM game WHERE deletedAt IS NULL")?;
     let play_modes_iter = stmt.query_map((), |row| {
         let value: String = row.get(0)?;
         Ok(value)
@@ -931,12 +1736,21 @@ pub fn find_play_modes(conn: &Connection) -> Result<Vec<String>> {
     Ok(play_modes.into_iter().collect())
 }
 
+/// Sorted, games without ruffle support set are left out
+pub fn find_ruffle_support(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT DISTINCT ruffleSupport FROM game WHERE ruffleSupport != '' AND deletedAt IS NULL ORDER BY ruffleSupport")?;
+    let ruffle_iter = stmt.query_map((), |row| row.get(0))?;
+
+    ruffle_iter.collect()
+}
+
 pub fn find_application_paths(conn: &Connection) -> Result<Vec<String>> {
     let mut stmt = conn.prepare("
     SELECT COUNT(*) as games_count, applicationPath FROM (
-        SELECT applicationPath FROM game WHERE applicationPath != ''
+        SELECT applicationPath FROM game WHERE applicationPath != '' AND deletedAt IS NULL
         UNION ALL
-        SELECT applicationPath FROM game_data WHERE applicationPath != ''
+        SELECT game_data.applicationPath FROM game_data INNER JOIN game ON game.id = game_data.gameId
+        WHERE game_data.applicationPath != '' AND game.deletedAt IS NULL
     ) GROUP BY applicationPath ORDER BY games_count DESC")?;
     let ap_iter = stmt.query_map((), |row| row.get(1))?;
 
@@ -951,7 +1765,7 @@ pub fn find_application_paths(conn: &Connection) -> Result<Vec<String>> {
 
 pub fn find_platform_app_paths(conn: &Connection) -> Result<HashMap<String, Vec<PlatformAppPath>>> {
     let mut suggestions = HashMap::new();
-    let platforms = platform::find(conn)?;
+    let platforms = platform::find(conn, false)?;
 
     for platform in platforms {
         let mut stmt = conn.prepare("
@@ -1001,6 +1815,113 @@ pub fn find_add_app_by_id(conn: &Connection, id: &str) -> Result<Option<Addition
     }).optional()
 }
 
+pub fn find_by_add_app(conn: &Connection, add_app_id: &str) -> Result<Option<Game>> {
+    match find_add_app_by_id(conn, add_app_id)? {
+        Some(add_app) => find(conn, &add_app.parent_game_id),
+        None => Ok(None),
+    }
+}
+
+/// Sums the size of game data present on disk, game data without a parent game only counts towards the totals
+pub fn disk_usage_report(conn: &Connection, top_n: i64) -> Result<DiskUsageReport> {
+    let (total_size, installed_count) = conn.query_row(
+        "SELECT IFNULL(SUM(game_data.size), 0), COUNT(*) FROM game_data INNER JOIN game ON game.id = game_data.gameId
+        WHERE game_data.presentOnDisk = 1 AND game.deletedAt IS NULL",
+        (),
+        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+    )?;
+
+    let mut stmt = conn.prepare("SELECT game.id, game.title, game.library, SUM(game_data.size) AS installedSize, COUNT(*)
+        FROM game_data INNER JOIN game ON game.id = game_data.gameId
+        WHERE game_data.presentOnDisk = 1 AND game.deletedAt IS NULL
+        GROUP BY game.id ORDER BY installedSize DESC, game.title LIMIT ?")?;
+    let top_games = stmt.query_map(params![top_n], |row| {
+        Ok(GameDiskUsage {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            library: row.get(2)?,
+            size: row.get(3)?,
+            installed_count: row.get(4)?,
+        })
+    })?
+    .collect::<Result<Vec<GameDiskUsage>>>()?;
+
+    let mut stmt = conn.prepare("SELECT game.library, SUM(game_data.size) AS installedSize, COUNT(*)
+        FROM game_data INNER JOIN game ON game.id = game_data.gameId
+        WHERE game_data.presentOnDisk = 1 AND game.deletedAt IS NULL
+        GROUP BY game.library ORDER BY installedSize DESC, game.library")?;
+    let libraries = stmt.query_map((), |row| {
+        Ok(LibraryDiskUsage {
+            library: row.get(0)?,
+            size: row.get(1)?,
+            installed_count: row.get(2)?,
+        })
+    })?
+    .collect::<Result<Vec<LibraryDiskUsage>>>()?;
+
+    Ok(DiskUsageReport {
+        total_size,
+        installed_count,
+        top_games,
+        libraries,
+    })
+}
+
+/// Summed game data size per platform name, largest first. Data of a game on several platforms counts towards each.
+pub fn content_size_by_platform(conn: &Connection, present_on_disk_only: bool) -> Result<Vec<(String, i64)>> {
+    let mut stmt = conn.prepare("SELECT platform_alias.name, SUM(game_data.size) AS contentSize
+        FROM game_data
+        INNER JOIN game ON game.id = game_data.gameId
+        INNER JOIN game_platforms_platform ON game_platforms_platform.gameId = game.id
+        INNER JOIN platform ON platform.id = game_platforms_platform.platformId
+        INNER JOIN platform_alias ON platform_alias.id = platform.primaryAliasId
+        WHERE game.deletedAt IS NULL AND (? = 0 OR game_data.presentOnDisk = 1)
+        GROUP BY platform.id ORDER BY contentSize DESC, platform_alias.name")?;
+    let sizes = stmt.query_map(params![present_on_disk_only], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<(String, i64)>>>()?;
+    Ok(sizes)
+}
+
+/// Every add app regardless of parent, ordered by id for stable paging
+pub fn find_all_add_apps(conn: &Connection, limit: i64, offset: i64) -> Result<Vec<AdditionalApp>> {
+    let mut stmt = conn.prepare("SELECT id, name, applicationPath, launchCommand, autoRunBefore,
+        waitForExit, parentGameId FROM additional_app ORDER BY id LIMIT ? OFFSET ?")?;
+
+    let add_apps = stmt.query_map(params![limit, offset], |row| {
+        Ok(AdditionalApp{
+            id: row.get(0)?,
+            name: row.get(1)?,
+            application_path: row.get(2)?,
+            launch_command: row.get(3)?,
+            auto_run_before: row.get(4)?,
+            wait_for_exit: row.get(5)?,
+            parent_game_id: row.get(6)?
+        })
+    })?
+    .collect::<Result<Vec<AdditionalApp>>>()?;
+
+    Ok(add_apps)
+}
+
+pub fn count_add_apps(conn: &Connection) -> Result<i64> {
+    conn.query_row("SELECT COUNT(*) FROM additional_app aa
+        JOIN game ON game.id = aa.parentGameId
+        WHERE game.deletedAt IS NULL", (), |row| row.get::<_, i64>(0))
+}
+
+pub fn count_game_data(conn: &Connection) -> Result<i64> {
+    conn.query_row("SELECT COUNT(*) FROM game_data gd
+        JOIN game ON game.id = gd.gameId
+        WHERE game.deletedAt IS NULL", (), |row| row.get::<_, i64>(0))
+}
+
+/// Removes add apps whose parent game no longer exists, returns how many were removed
+pub fn delete_orphan_add_apps(conn: &Connection) -> Result<i64> {
+    let deleted = conn.execute("DELETE FROM additional_app
+        WHERE NOT EXISTS (SELECT 1 FROM game WHERE game.id = additional_app.parentGameId)", ())?;
+    Ok(deleted as i64)
+}
+
 pub fn create_add_app(conn: &Connection, add_app: &mut AdditionalApp) -> Result<()> {
     let id = conn.query_row("INSERT INTO additional_app (
         id, applicationPath, launchCommand, name, parentGameId, autoRunBefore, waitForExit
@@ -1010,7 +1931,125 @@ pub fn create_add_app(conn: &Connection, add_app: &mut AdditionalApp) -> Result<
     Ok(())
 }
 
-pub fn add_playtime(conn: &Connection, game_id: &str, seconds: i64) -> Result<()> {
+/// Replaces the game's tags, writing only the tag relations, tagsStr and dateModified
+pub fn set_tags(conn: &Connection, game_id: &str, tags: &[String], date_modified: &str) -> error::Result<Game> {
+    // Follow redirects to the stored game
+    let game_id = match find_slim(conn, game_id).context(error::SqliteSnafu)? {
+        Some(game) => game.id,
+        None => return error::GameNotFoundSnafu { id: game_id }.fail(),
+    };
+
+    let mut detailed_tags: Vec<Tag> = vec![];
+    for name in tags {
+        let detailed_tag = tag::find_or_create(conn, name).context(error::SqliteSnafu)?;
+        if !detailed_tags.iter().any(|t| t.id == detailed_tag.id) {
+            detailed_tags.push(detailed_tag);
+        }
+    }
+
+    replace_relations(conn, "game_tags_tag", "tagId", &game_id, &detailed_tags).context(error::SqliteSnafu)?;
+    let tags_str = detailed_tags.iter().map(|t| t.name.as_str()).collect::<Vec<&str>>().join("; ");
+    conn.execute("UPDATE game SET tagsStr = ?, dateModified = ? WHERE id = ?", params![tags_str, date_modified, game_id])
+        .context(error::SqliteSnafu)?;
+    mark_index_dirty(conn).context(error::SqliteSnafu)?;
+
+    find_game_or_fail(conn, &game_id)
+}
+
+/// Replaces the game's platforms, writing only the platform relations, platformName, platformId, platformsStr and dateModified.
+/// The primary platform is cleared when it isn't one of the new platforms, the same as when saving a game.
+pub fn set_platforms(conn: &Connection, game_id: &str, platforms: &[String], date_modified: &str) -> error::Result<Game> {
+    let game = match find_slim(conn, game_id).context(error::SqliteSnafu)? {
+        Some(game) => game,
+        None => return error::GameNotFoundSnafu { id: game_id }.fail(),
+    };
+
+    let primary_name = game.primary_platform.to_lowercase();
+    let mut primary_platform = String::new();
+    let mut detailed_platforms: Vec<Tag> = vec![];
+    for name in platforms {
+        let detailed_platform = platform::find_or_create(conn, name, None).context(error::SqliteSnafu)?;
+        if name.to_lowercase() == primary_name {
+            primary_platform = detailed_platform.name.clone();
+        }
+        if !detailed_platforms.iter().any(|p| p.id == detailed_platform.id) {
+            detailed_platforms.push(detailed_platform);
+        }
+    }
+
+    replace_relations(conn, "game_platforms_platform", "platformId", &game.id, &detailed_platforms).context(error::SqliteSnafu)?;
+    let platforms_str = detailed_platforms.iter().map(|p| p.name.as_str()).collect::<Vec<&str>>().join("; ");
+    conn.execute("UPDATE game SET platformName = ?, platformId = (SELECT platformId FROM platform_alias WHERE name = ?), platformsStr = ?,
+        dateModified = ? WHERE id = ?",
+        params![primary_platform, primary_platform, platforms_str, date_modified, game.id])
+        .context(error::SqliteSnafu)?;
+    mark_index_dirty(conn).context(error::SqliteSnafu)?;
+
+    find_game_or_fail(conn, &game.id)
+}
+
+fn replace_relations(conn: &Connection, table: &str, column: &str, game_id: &str, related: &[Tag]) -> Result<()> {
+    // Allow use of rarray() in SQL queries
+    rusqlite::vtab::array::load_module(conn)?;
+
+    let ids = Rc::new(related.iter().map(|t| Value::from(t.id)).collect::<Vec<Value>>());
+    conn.execute(&format!("DELETE FROM {} WHERE gameId = ? AND {} NOT IN rarray(?)", table, column), params![game_id, ids])?;
+    let mut stmt = conn.prepare(&format!("INSERT OR IGNORE INTO {} (gameId, {}) VALUES (?, ?)", table, column))?;
+    for t in related {
+        stmt.execute(params![game_id, t.id])?;
+    }
+    Ok(())
+}
+
+fn find_game_or_fail(conn: &Connection, game_id: &str) -> error::Result<Game> {
+    match find(conn, game_id).context(error::SqliteSnafu)? {
+        Some(game) => Ok(game),
+        None => error::GameNotFoundSnafu { id: game_id }.fail(),
+    }
+}
+
+/// Expected to run inside a transaction, the game id is assigned to every data and add app
+pub fn create_full(conn: &Connection, full: &CreateFullGame) -> Result<Game> {
+    let game = create(conn, &full.game)?;
+
+    let mut active_data: Option<GameData> = None;
+    for partial in &full.game_data {
+        let mut partial = partial.clone();
+        partial.game_id = game.id.clone();
+        let data = create_game_data(conn, &partial)?;
+        // Newest data becomes active, same as force_active_data_most_recent
+        let newer = match &active_data {
+            Some(active) => data.date_added > active.date_added,
+            None => true,
+        };
+        if newer {
+            active_data = Some(data);
+        }
+    }
+
+    if let Some(data) = active_data {
+        conn.execute(
+            "UPDATE game SET activeDataId = ?, activeDataOnDisk = ? WHERE id = ?",
+            params![data.id, data.present_on_disk, game.id],
+        )?;
+    }
+
+    for add_app in &full.add_apps {
+        let mut add_app = add_app.clone();
+        add_app.parent_game_id = game.id.clone();
+        if add_app.id.is_empty() {
+            add_app.id = Uuid::new_v4().to_string();
+        }
+        create_add_app(conn, &mut add_app)?;
+    }
+
+    match find(conn, &game.id)? {
+        Some(game) => Ok(game),
+        None => Err(rusqlite::Error::QueryReturnedNoRows),
+    }
+}
+
+pub fn add_playtime(conn: &Connection, game_id: &str, seconds: i64, played_at: &str) -> Result<()> {
     let mut game = match find(conn, game_id)? {
         Some(g) => g,
         None => return Err(rusqlite::Error::QueryReturnedNoRows)
@@ -1018,24 +2057,34 @@ pub fn add_playtime(conn: &Connection, game_id: &str, seconds: i64) -> Result<()
 
     game.play_counter += 1;
     game.playtime += seconds;
-    game.last_played = Some(Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string());
+    game.last_played = Some(played_at.to_owned());
 
-    save(conn, &(game.into()))?;
+    let partial: PartialGame = game.clone().into();
+    apply_save(conn, game, &partial, false)?;
     Ok(())
 }
 
 pub fn clear_playtime_tracking(conn: &Connection) -> Result<()> {
     let mut stmt = conn.prepare("UPDATE game SET playtime = 0, playCounter = 0, lastPlayed = NULL")?;
     stmt.execute(())?;
+    mark_index_dirty(conn)?;
     Ok(())
 }
 
 pub fn clear_playtime_tracking_by_id(conn: &Connection, game_id: &str) -> Result<()> {
     let mut stmt = conn.prepare("UPDATE game SET playtime = 0, playCounter = 0, lastPlayed = NULL WHERE id = ?")?;
     stmt.execute(params![game_id])?;
+    mark_index_dirty(conn)?;
     Ok(())
 }
 
+/// Clears the active config of every game whose config belongs to `owner`, returns how many were cleared
+pub fn clear_active_config_by_owner(conn: &Connection, owner: &str) -> Result<i64> {
+    let cleared = conn.execute("UPDATE game SET activeGameConfigId = NULL, activeGameConfigOwner = NULL
+        WHERE activeGameConfigOwner = ?", params![owner])?;
+    Ok(cleared as i64)
+}
+
 pub fn force_active_data_most_recent(conn: &Connection) -> Result<()> {
     conn.execute("UPDATE game
     SET activeDataId = (SELECT game_data.id FROM game_data WHERE game.id = game_data.gameId ORDER BY game_data.dateAdded DESC LIMIT 1)
@@ -1122,8 +2171,8 @@ impl Default for Game {
             publisher: String::default(),
             primary_platform: String::default(),
             platforms: TagVec::default(),
-            date_added: Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
-            date_modified: Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+            date_added: String::default(),
+            date_modified: String::default(),
             detailed_platforms: None,
             legacy_broken: false,
             legacy_extreme: false,
@@ -1136,6 +2185,7 @@ impl Default for Game {
             legacy_application_path: String::default(),
             legacy_launch_command: String::default(),
             release_date: String::default(),
+            release_year: None,
             version: String::default(),
             original_description: String::default(),
             language: String::default(),
@@ -1150,12 +2200,19 @@ impl Default for Game {
             game_data: None,
             add_apps: None,
             ruffle_support: String::default(),
+            matched_fields: None,
         }
     }
 }
 
+/// Drops names differing only by case from an earlier name, tag and platform names are case insensitive
+fn dedup_names(names: &mut TagVec) {
+    let mut seen = HashSet::new();
+    names.retain(|name| seen.insert(name.to_lowercase()));
+}
+
 impl Game {
-    fn apply_partial(&mut self, source: &PartialGame) {
+    pub(crate) fn apply_partial(&mut self, source: &PartialGame) {
         if source.id == "" {
             self.id = Uuid::new_v4().to_string();
         } else {
@@ -1188,11 +2245,12 @@ impl Game {
 
         if let Some(platforms) = source.platforms.clone() {
             self.platforms = platforms;
+            dedup_names(&mut self.platforms);
         }
     
         if let Some(platform) = source.primary_platform.clone() {
             // Make sure platforms always includes the primary platform
-            if !self.platforms.contains(&platform) {
+            if !self.platforms.iter().any(|p| p.to_lowercase() == platform.to_lowercase()) {
                 self.platforms.push(platform.clone());
             }
 
@@ -1229,6 +2287,7 @@ impl Game {
     
         if let Some(tags) = source.tags.clone() {
             self.tags = tags;
+            dedup_names(&mut self.tags);
         }
     
         if let Some(source) = source.source.clone() {
@@ -1244,6 +2303,7 @@ impl Game {
         }
     
         if let Some(release_date) = source.release_date.clone() {
+            self.release_year = parse_release_year(&release_date);
             self.release_date = release_date;
         }
     
@@ -1267,8 +2327,8 @@ impl Game {
             self.active_data_on_disk = active_data_on_disk;
         }
     
-        if let Some(last_played) = source.last_played.clone() {
-            self.last_played = Some(last_played);
+        if let Some(last_played) = source.last_played.as_deref() {
+            self.last_played = Some(normalize_timestamp(last_played));
         }
     
         if let Some(playtime) = source.playtime {
@@ -1407,7 +2467,7 @@ impl Default for GameData {
             id: -1,
             game_id: "".to_owned(),
             title: "".to_owned(),
-            date_added: Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+            date_added: "".to_owned(),
             sha256: "".to_owned(),
             crc32: 0,
             size: 0,
@@ -0,0 +1,170 @@
+use serde_json::Value;
+
+use crate::error::{self, Result};
+
+use super::{Game, PartialGame, TagVec};
+
+/// Text fields a patch may change, removing an optional one sets it to an empty string
+const EDITABLE_TEXT_FIELDS: [&str; 16] = [
+    "library",
+    "title",
+    "alternate_titles",
+    "series",
+    "developer",
+    "publisher",
+    "primary_platform",
+    "play_mode",
+    "status",
+    "notes",
+    "source",
+    "release_date",
+    "version",
+    "original_description",
+    "language",
+    "ruffle_support",
+];
+
+/// Text fields every game needs, a patch may replace them but not remove them
+const REQUIRED_TEXT_FIELDS: [&str; 3] = ["library", "title", "primary_platform"];
+
+/// List fields a patch may change as a whole or by index, removing one empties it
+const EDITABLE_LIST_FIELDS: [&str; 2] = ["tags", "platforms"];
+
+/// Applies a JSON Patch (RFC 6902) to `game`, returning the partial game to save.
+///
+/// Supports `add`, `replace` and `remove` on the editable text fields and on `tags` / `platforms`,
+/// whole (`/tags`) or by index (`/tags/0`, `/tags/-` to append). Fields the patch doesn't touch are left `None`.
+pub fn apply_patch(game: &Game, patch: &Value) -> Result<PartialGame> {
+    let ops = match patch.as_array() {
+        Some(ops) => ops,
+        None => return invalid("", "patch must be an array of operations"),
+    };
+
+    let mut partial = PartialGame {
+        id: game.id.clone(),
+        ..Default::default()
+    };
+    for op in ops {
+        let path = match op.get("path").and_then(|p| p.as_str()) {
+            Some(path) => path,
+            None => return invalid("", "operation is missing a path"),
+        };
+        let name = op.get("op").and_then(|o| o.as_str()).unwrap_or_default();
+        let value = op.get("value");
+
+        let mut segments = path.strip_prefix('/').unwrap_or(path).split('/').map(|s| s.replace("~1", "/").replace("~0", "~"));
+        let field = segments.next().unwrap_or_default();
+        let index = segments.next();
+        if segments.next().is_some() {
+            return invalid(path, "path is too deep");
+        }
+
+        if EDITABLE_TEXT_FIELDS.contains(&field.as_str()) {
+            if index.is_some() {
+                return invalid(path, "text fields have no elements");
+            }
+            let text = match name {
+                "add" | "replace" => match value.and_then(|v| v.as_str()) {
+                    Some(text) => text.to_owned(),
+                    None => return invalid(path, "value must be a string"),
+                },
+                "remove" if REQUIRED_TEXT_FIELDS.contains(&field.as_str()) => {
+                    return invalid(path, "field is required and can't be removed")
+                }
+                "remove" => String::new(),
+                _ => return invalid(path, &format!("unsupported op '{}'", name)),
+            };
+            set_text(&mut partial, &field, text);
+        } else if EDITABLE_LIST_FIELDS.contains(&field.as_str()) {
+            let current = match (field.as_str(), &partial.tags, &partial.platforms) {
+                ("tags", Some(tags), _) => tags.to_vec(),
+                ("tags", None, _) => game.tags.to_vec(),
+                (_, _, Some(platforms)) => platforms.to_vec(),
+                _ => game.platforms.to_vec(),
+            };
+            let list = patch_list(path, current, name, index.as_deref(), value)?;
+            match field.as_str() {
+                "tags" => partial.tags = Some(TagVec(list)),
+                _ => partial.platforms = Some(TagVec(list)),
+            }
+        } else if field.is_empty() {
+            return invalid(path, "the whole game can't be replaced");
+        } else {
+            return invalid(path, "field is read only or doesn't exist");
+        }
+    }
+
+    Ok(partial)
+}
+
+fn patch_list(path: &str, mut list: Vec<String>, op: &str, index: Option<&str>, value: Option<&Value>) -> Result<Vec<String>> {
+    let position = |len: usize| -> Result<usize> {
+        match index.map(|i| i.parse::<usize>()) {
+            Some(Ok(i)) if i < len => Ok(i),
+            _ => invalid(path, "index out of range"),
+        }
+    };
+    let text = || -> Result<String> {
+        match value.and_then(|v| v.as_str()) {
+            Some(text) => Ok(text.to_owned()),
+            None => invalid(path, "value must be a string"),
+        }
+    };
+
+    match (op, index) {
+        ("add" | "replace", None) => {
+            let values = match value.and_then(|v| v.as_array()) {
+                Some(values) => values,
+                None => return invalid(path, "value must be an array of strings"),
+            };
+            list = match values.iter().map(|v| v.as_str().map(|s| s.to_owned())).collect() {
+                Some(values) => values,
+                None => return invalid(path, "value must be an array of strings"),
+            };
+        }
+        ("remove", None) => list.clear(),
+        ("add", Some("-")) => list.push(text()?),
+        ("add", Some(_)) => {
+            // Adding may also append at the index one past the end
+            let i = position(list.len() + 1)?;
+            list.insert(i, text()?);
+        }
+        ("replace", Some(_)) => {
+            let i = position(list.len())?;
+            list[i] = text()?;
+        }
+        ("remove", Some(_)) => {
+            let i = position(list.len())?;
+            list.remove(i);
+        }
+        _ => return invalid(path, &format!("unsupported op '{}'", op)),
+    }
+    Ok(list)
+}
+
+fn set_text(partial: &mut PartialGame, field: &str, text: String) {
+    let target = match field {
+        "library" => &mut partial.library,
+        "title" => &mut partial.title,
+        "alternate_titles" => &mut partial.alternate_titles,
+        "series" => &mut partial.series,
+        "developer" => &mut partial.developer,
+        "publisher" => &mut partial.publisher,
+        "primary_platform" => &mut partial.primary_platform,
+        "play_mode" => &mut partial.play_mode,
+        "status" => &mut partial.status,
+        "notes" => &mut partial.notes,
+        "source" => &mut partial.source,
+        "release_date" => &mut partial.release_date,
+        "version" => &mut partial.version,
+        "original_description" => &mut partial.original_description,
+        "language" => &mut partial.language,
+        "ruffle_support" => &mut partial.ruffle_support,
+        _ => return,
+    };
+    *target = Some(text);
+}
+
+fn invalid<T>(path: &str, reason: &str) -> Result<T> {
+    error::InvalidPatchSnafu { path, reason }.fail()
+}
@@ -1,4 +1,4 @@
-use std::{fmt::Display, rc::Rc};
+use std::{collections::HashMap, fmt::Display, rc::Rc, str::FromStr};
 
 use fancy_regex::{Captures, Regex};
 use rusqlite::{
@@ -7,18 +7,25 @@ use rusqlite::{
     Connection, OptionalExtension, Result, ToSql,
 };
 
-use crate::{debug_println, game::get_game_add_apps};
+use crate::{debug_println, error, game::get_game_add_apps};
 
-use super::{get_game_data, get_game_platforms, get_game_tags, Game};
+use super::{find_slim, get_game_data, get_game_platforms, get_game_tags, Game};
 
 #[derive(Debug, Clone)]
 pub enum SearchParam {
     Boolean(bool),
     String(String),
-    StringVec(Vec<String>),
+    /// Bound as an rarray, shared so repeating a large list doesn't copy it
+    StringVec(Rc<Vec<Value>>),
     Integer64(i64),
 }
 
+impl SearchParam {
+    fn string_vec(values: &[String]) -> SearchParam {
+        SearchParam::StringVec(Rc::new(values.iter().map(|v| Value::from(v.clone())).collect()))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TagFilterInfo {
     pub key: String,
@@ -30,14 +37,7 @@ impl ToSql for SearchParam {
         match self {
             SearchParam::Boolean(b) => Ok(ToSqlOutput::from(b.clone())),
             SearchParam::String(s) => Ok(ToSqlOutput::from(s.as_str())),
-            SearchParam::StringVec(m) => {
-                let v: Rc<Vec<Value>> = Rc::new(
-                    m.iter()
-                        .map(|v| Value::from(v.clone()))
-                        .collect::<Vec<Value>>(),
-                );
-                Ok(ToSqlOutput::Array(v))
-            }
+            SearchParam::StringVec(m) => Ok(ToSqlOutput::Array(m.clone())),
             SearchParam::Integer64(i) => Ok(ToSqlOutput::from(i.clone())),
         }
     }
@@ -48,13 +48,23 @@ impl Display for SearchParam {
         match self {
             SearchParam::Boolean(b) => f.write_str(b.to_string().as_str()),
             SearchParam::String(s) => f.write_str(s),
-            SearchParam::StringVec(m) => f.write_str(format!("{}", m.join("', '")).as_str()),
+            SearchParam::StringVec(m) => {
+                let values: Vec<String> = m
+                    .iter()
+                    .map(|v| match v {
+                        Value::Text(s) => s.clone(),
+                        _ => String::new(),
+                    })
+                    .collect();
+                f.write_str(&values.join("', '"))
+            }
             SearchParam::Integer64(i) => f.write_str(i.to_string().as_str()),
         }
     }
 }
 
 #[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Debug, Clone)]
 pub struct GameSearch {
     pub filter: GameFilter,
@@ -64,10 +74,23 @@ pub struct GameSearch {
     pub offset: Option<GameSearchOffset>,
     pub limit: i64,
     pub slim: bool,
+    /// Columns loaded when slim is set, defaults to `DEFAULT_SLIM_FIELDS`. Unselected fields keep their default value.
+    pub slim_fields: Vec<GameField>,
+    /// Left as `None`, the archive's global tag blacklist is used instead, if one is set
     pub with_tag_filter: Option<Vec<String>>,
+    /// Ignore the archive's global tag blacklist, e.g. for admin views
+    pub disable_global_tag_filter: bool,
+    /// Include soft deleted games in the results
+    pub include_deleted: bool,
+    /// Fill in `matched_fields` on each result with the columns its free text terms matched
+    pub explain_matches: bool,
+    /// Compare language filters as codes from `util::normalize_languages`, so "English" also finds "en".
+    /// Only matches games rewritten by `normalize_all_languages`.
+    pub normalize_languages: bool,
 }
 
 #[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Debug, Clone)]
 pub struct GameSearchOffset {
     pub value: String,
@@ -76,6 +99,7 @@ pub struct GameSearchOffset {
 }
 
 #[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Debug, Clone)]
 pub struct GameSearchOrder {
     pub column: GameSearchSortable,
@@ -102,13 +126,14 @@ pub enum GameSearchSortable {
 
 #[cfg_attr(feature = "napi", napi)]
 #[cfg_attr(not(feature = "napi"), derive(Clone))]
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum GameSearchDirection {
     ASC,
     DESC,
 }
 
 #[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Debug, Clone)]
 pub struct GameSearchRelations {
     pub tags: bool,
@@ -118,6 +143,7 @@ pub struct GameSearchRelations {
 }
 
 #[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Debug, Clone)]
 pub struct GameFilter {
     pub subfilters: Vec<GameFilter>,
@@ -129,10 +155,16 @@ pub struct GameFilter {
     pub higher_than: SizeFilter,
     pub equal_to: SizeFilter,
     pub bool_comp: BoolFilter,
+    /// Each term must match at least one of title, alternate titles, developer, publisher, series, notes or original description
+    pub any_text: Option<Vec<String>>,
     pub match_any: bool,
+    /// Exact matches in this filter compare case sensitively when true and ignore case when false.
+    /// None keeps each column's own collation, e.g case insensitive tags but case sensitive titles. Not inherited by subfilters.
+    pub case_sensitive: Option<bool>,
 }
 
 #[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Debug, Clone)]
 pub struct FieldFilter {
     pub id: Option<Vec<String>>,
@@ -153,15 +185,25 @@ pub struct FieldFilter {
     pub application_path: Option<Vec<String>>,
     pub launch_command: Option<Vec<String>>,
     pub ruffle_support: Option<Vec<String>>,
+    pub active_game_config_owner: Option<Vec<String>>,
 }
 
 #[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Debug, Clone)]
 pub struct BoolFilter {
     pub installed: Option<bool>,
+    pub favorite: Option<bool>,
+    /// The add app checks are existence checks. True matches games with at least one such add app,
+    /// false matches games with none, not games whose every add app lacks the property.
+    pub add_app_auto_run_before: Option<bool>,
+    pub add_app_wait_for_exit: Option<bool>,
+    /// Add apps named 'Extras'
+    pub add_app_extras: Option<bool>,
 }
 
 #[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Debug, Clone)]
 pub struct SizeFilter {
     pub tags: Option<i64>,
@@ -169,11 +211,15 @@ pub struct SizeFilter {
     pub date_added: Option<String>,
     pub date_modified: Option<String>,
     pub release_date: Option<String>,
+    pub release_year: Option<i64>,
     pub game_data: Option<i64>,
     pub add_apps: Option<i64>,
     pub playtime: Option<i64>,
     pub playcount: Option<i64>,
     pub last_played: Option<String>,
+    pub user_rating: Option<i64>,
+    /// Bytes of game data present on disk
+    pub installed_size: Option<i64>,
 }
 
 #[derive(Debug, Clone)]
@@ -208,6 +254,7 @@ struct ForcedFieldFilter {
     pub application_path: Vec<String>,
     pub launch_command: Vec<String>,
     pub ruffle_support: Vec<String>,
+    pub active_game_config_owner: Vec<String>,
 }
 
 #[cfg_attr(feature = "napi", napi(object))]
@@ -218,6 +265,212 @@ pub struct PageTuple {
     pub title: String,
 }
 
+#[cfg_attr(feature = "napi", napi)]
+#[cfg_attr(not(feature = "napi"), derive(Clone))]
+#[derive(Debug, PartialEq)]
+pub enum GroupableField {
+    Series,
+    Developer,
+    Publisher,
+    Library,
+    PrimaryPlatform,
+}
+
+/// A single column of the game table, used to pick the columns of slim searches
+#[cfg_attr(feature = "napi", napi)]
+#[cfg_attr(not(feature = "napi"), derive(Clone))]
+#[derive(Debug, PartialEq)]
+pub enum GameField {
+    Id,
+    Library,
+    Title,
+    AlternateTitles,
+    Series,
+    Developer,
+    Publisher,
+    PrimaryPlatform,
+    Platforms,
+    DateAdded,
+    DateModified,
+    LegacyBroken,
+    LegacyExtreme,
+    PlayMode,
+    Status,
+    Notes,
+    Tags,
+    Source,
+    LegacyApplicationPath,
+    LegacyLaunchCommand,
+    ReleaseDate,
+    ReleaseYear,
+    Version,
+    OriginalDescription,
+    Language,
+    ActiveDataId,
+    ActiveDataOnDisk,
+    LastPlayed,
+    Playtime,
+    PlayCounter,
+    ActiveGameConfigId,
+    ActiveGameConfigOwner,
+    ArchiveState,
+    RuffleSupport,
+}
+
+pub const DEFAULT_SLIM_FIELDS: [GameField; 9] = [
+    GameField::Id,
+    GameField::Title,
+    GameField::Series,
+    GameField::Developer,
+    GameField::Publisher,
+    GameField::Platforms,
+    GameField::PrimaryPlatform,
+    GameField::Tags,
+    GameField::Library,
+];
+
+/// Implements `Display`, `FromStr`, `all()` and string based serde for a fieldless enum.
+/// Parsing ignores case so the old upper case names still parse.
+macro_rules! string_enum {
+    ($name:ident { $($variant:ident => $value:expr,)* }) => {
+        impl $name {
+            /// Every variant in declaration order, e.g for dropdowns
+            pub fn all() -> Vec<$name> {
+                vec![$($name::$variant),*]
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(match self {
+                    $($name::$variant => $value,)*
+                })
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = String;
+
+            fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+                $(if s.eq_ignore_ascii_case($value) {
+                    return Ok($name::$variant);
+                })*
+                Err(format!("Unknown {} '{}'", stringify!($name), s))
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.serialize_str(&self.to_string())
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                let value = String::deserialize(deserializer)?;
+                value.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+string_enum!(GameSearchSortable {
+    TITLE => "title",
+    DEVELOPER => "developer",
+    PUBLISHER => "publisher",
+    SERIES => "series",
+    PLATFORM => "platform",
+    DATEADDED => "dateAdded",
+    DATEMODIFIED => "dateModified",
+    RELEASEDATE => "releaseDate",
+    LASTPLAYED => "lastPlayed",
+    PLAYTIME => "playtime",
+    RANDOM => "random",
+    CUSTOM => "custom",
+});
+
+string_enum!(GameSearchDirection {
+    ASC => "asc",
+    DESC => "desc",
+});
+
+string_enum!(GroupableField {
+    Series => "series",
+    Developer => "developer",
+    Publisher => "publisher",
+    Library => "library",
+    PrimaryPlatform => "primaryPlatform",
+});
+
+string_enum!(GameField {
+    Id => "id",
+    Library => "library",
+    Title => "title",
+    AlternateTitles => "alternateTitles",
+    Series => "series",
+    Developer => "developer",
+    Publisher => "publisher",
+    PrimaryPlatform => "primaryPlatform",
+    Platforms => "platforms",
+    DateAdded => "dateAdded",
+    DateModified => "dateModified",
+    LegacyBroken => "legacyBroken",
+    LegacyExtreme => "legacyExtreme",
+    PlayMode => "playMode",
+    Status => "status",
+    Notes => "notes",
+    Tags => "tags",
+    Source => "source",
+    LegacyApplicationPath => "legacyApplicationPath",
+    LegacyLaunchCommand => "legacyLaunchCommand",
+    ReleaseDate => "releaseDate",
+    ReleaseYear => "releaseYear",
+    Version => "version",
+    OriginalDescription => "originalDescription",
+    Language => "language",
+    ActiveDataId => "activeDataId",
+    ActiveDataOnDisk => "activeDataOnDisk",
+    LastPlayed => "lastPlayed",
+    Playtime => "playtime",
+    PlayCounter => "playCounter",
+    ActiveGameConfigId => "activeGameConfigId",
+    ActiveGameConfigOwner => "activeGameConfigOwner",
+    ArchiveState => "archiveState",
+    RuffleSupport => "ruffleSupport",
+});
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone)]
+pub struct GameGroup {
+    /// Field value shared by the group, empty values are grouped as "(none)"
+    pub name: String,
+    pub count: i64,
+    /// Ids of the first games in the group, following the search order
+    pub representatives: Vec<String>,
+}
+
+/// Games matched by only one of two searches
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, Default)]
+pub struct SearchDiff {
+    pub only_in_a_count: i64,
+    pub only_in_b_count: i64,
+    /// Up to `limit` example games, by title
+    pub only_in_a: Vec<Game>,
+    pub only_in_b: Vec<Game>,
+}
+
+/// Upper bound on the example games a diff returns across both sides
+pub const DIFF_SEARCHES_MAX: i64 = 1000;
+
 impl Default for GameSearch {
     fn default() -> Self {
         GameSearch {
@@ -231,7 +484,12 @@ impl Default for GameSearch {
             offset: None,
             limit: 1000,
             slim: false,
+            slim_fields: DEFAULT_SLIM_FIELDS.to_vec(),
             with_tag_filter: None,
+            disable_global_tag_filter: false,
+            include_deleted: false,
+            explain_matches: false,
+            normalize_languages: false,
         }
     }
 }
@@ -248,7 +506,9 @@ impl Default for GameFilter {
             higher_than: SizeFilter::default(),
             equal_to: SizeFilter::default(),
             bool_comp: BoolFilter::default(),
+            any_text: None,
             match_any: false,
+            case_sensitive: None,
         }
     }
 }
@@ -285,6 +545,7 @@ impl Default for FieldFilter {
             application_path: None,
             launch_command: None,
             ruffle_support: None,
+            active_game_config_owner: None,
         }
     }
 }
@@ -325,6 +586,7 @@ impl Default for ForcedFieldFilter {
             application_path: vec![],
             launch_command: vec![],
             ruffle_support: vec![],
+            active_game_config_owner: vec![],
         }
     }
 }
@@ -337,257 +599,71 @@ impl Default for SizeFilter {
             date_added: None,
             date_modified: None,
             release_date: None,
+            release_year: None,
             game_data: None,
             add_apps: None,
             playtime: None,
             playcount: None,
             last_played: None,
+            user_rating: None,
+            installed_size: None,
         };
     }
 }
 
 impl Default for BoolFilter {
     fn default() -> Self {
-        return BoolFilter { installed: None };
+        return BoolFilter {
+            installed: None,
+            favorite: None,
+            add_app_auto_run_before: None,
+            add_app_wait_for_exit: None,
+            add_app_extras: None,
+        };
     }
 }
 
-impl From<&ForcedGameFilter> for GameFilter {
-    fn from(value: &ForcedGameFilter) -> Self {
-        let mut search = GameFilter::default();
-
-        // Whitelist
-
-        if value.whitelist.id.len() > 0 {
-            search.whitelist.id = Some(value.whitelist.id.clone());
-        }
-        if value.whitelist.generic.len() > 0 {
-            search.whitelist.generic = Some(value.whitelist.generic.clone());
-        }
-        if value.whitelist.title.len() > 0 {
-            search.whitelist.title = Some(value.whitelist.title.clone());
-        }
-        if value.whitelist.developer.len() > 0 {
-            search.whitelist.developer = Some(value.whitelist.developer.clone());
-        }
-        if value.whitelist.publisher.len() > 0 {
-            search.whitelist.publisher = Some(value.whitelist.publisher.clone());
-        }
-        if value.whitelist.series.len() > 0 {
-            search.whitelist.series = Some(value.whitelist.series.clone());
-        }
-        if value.whitelist.tags.len() > 0 {
-            search.whitelist.tags = Some(value.whitelist.tags.clone());
-        }
-        if value.whitelist.platforms.len() > 0 {
-            search.whitelist.platforms = Some(value.whitelist.platforms.clone());
-        }
-        if value.whitelist.play_mode.len() > 0 {
-            search.whitelist.play_mode = Some(value.whitelist.play_mode.clone());
-        }
-        if value.whitelist.status.len() > 0 {
-            search.whitelist.status = Some(value.whitelist.status.clone());
-        }
-        if value.whitelist.notes.len() > 0 {
-            search.whitelist.notes = Some(value.whitelist.notes.clone());
-        }
-        if value.whitelist.source.len() > 0 {
-            search.whitelist.source = Some(value.whitelist.source.clone());
-        }
-        if value.whitelist.original_description.len() > 0 {
-            search.whitelist.original_description =
-                Some(value.whitelist.original_description.clone());
-        }
-        if value.whitelist.language.len() > 0 {
-            search.whitelist.language = Some(value.whitelist.language.clone());
-        }
-        if value.whitelist.application_path.len() > 0 {
-            search.whitelist.application_path = Some(value.whitelist.application_path.clone());
-        }
-        if value.whitelist.launch_command.len() > 0 {
-            search.whitelist.launch_command = Some(value.whitelist.launch_command.clone());
-        }
-        if value.whitelist.ruffle_support.len() > 0 {
-            search.whitelist.ruffle_support = Some(value.whitelist.ruffle_support.clone());
-        }
-
-        // Blacklist
-
-        if value.blacklist.id.len() > 0 {
-            search.blacklist.id = Some(value.blacklist.id.clone());
-        }
-        if value.blacklist.generic.len() > 0 {
-            search.blacklist.generic = Some(value.blacklist.generic.clone());
-        }
-        if value.blacklist.title.len() > 0 {
-            search.blacklist.title = Some(value.blacklist.title.clone());
-        }
-        if value.blacklist.developer.len() > 0 {
-            search.blacklist.developer = Some(value.blacklist.developer.clone());
-        }
-        if value.blacklist.publisher.len() > 0 {
-            search.blacklist.publisher = Some(value.blacklist.publisher.clone());
-        }
-        if value.blacklist.series.len() > 0 {
-            search.blacklist.series = Some(value.blacklist.series.clone());
-        }
-        if value.blacklist.tags.len() > 0 {
-            search.blacklist.tags = Some(value.blacklist.tags.clone());
-        }
-        if value.blacklist.platforms.len() > 0 {
-            search.blacklist.platforms = Some(value.blacklist.platforms.clone());
-        }
-        if value.blacklist.play_mode.len() > 0 {
-            search.blacklist.play_mode = Some(value.blacklist.play_mode.clone());
-        }
-        if value.blacklist.status.len() > 0 {
-            search.blacklist.status = Some(value.blacklist.status.clone());
-        }
-        if value.blacklist.notes.len() > 0 {
-            search.blacklist.notes = Some(value.blacklist.notes.clone());
-        }
-        if value.blacklist.source.len() > 0 {
-            search.blacklist.source = Some(value.blacklist.source.clone());
-        }
-        if value.blacklist.original_description.len() > 0 {
-            search.blacklist.original_description =
-                Some(value.blacklist.original_description.clone());
-        }
-        if value.blacklist.language.len() > 0 {
-            search.blacklist.language = Some(value.blacklist.language.clone());
-        }
-        if value.blacklist.application_path.len() > 0 {
-            search.blacklist.application_path = Some(value.blacklist.application_path.clone());
-        }
-        if value.blacklist.launch_command.len() > 0 {
-            search.blacklist.launch_command = Some(value.blacklist.launch_command.clone());
-        }
-        if value.blacklist.ruffle_support.len() > 0 {
-            search.blacklist.ruffle_support = Some(value.blacklist.ruffle_support.clone());
-        }
-
-        // Exact whitelist
-
-        if value.exact_whitelist.id.len() > 0 {
-            search.exact_whitelist.id = Some(value.exact_whitelist.id.clone());
-        }
-        if value.exact_whitelist.generic.len() > 0 {
-            search.exact_whitelist.generic = Some(value.exact_whitelist.generic.clone());
-        }
-        if value.exact_whitelist.title.len() > 0 {
-            search.exact_whitelist.title = Some(value.exact_whitelist.title.clone());
-        }
-        if value.exact_whitelist.developer.len() > 0 {
-            search.exact_whitelist.developer = Some(value.exact_whitelist.developer.clone());
-        }
-        if value.exact_whitelist.publisher.len() > 0 {
-            search.exact_whitelist.publisher = Some(value.exact_whitelist.publisher.clone());
-        }
-        if value.exact_whitelist.series.len() > 0 {
-            search.exact_whitelist.series = Some(value.exact_whitelist.series.clone());
-        }
-        if value.exact_whitelist.tags.len() > 0 {
-            search.exact_whitelist.tags = Some(value.exact_whitelist.tags.clone());
-        }
-        if value.exact_whitelist.platforms.len() > 0 {
-            search.exact_whitelist.platforms = Some(value.exact_whitelist.platforms.clone());
-        }
-        if value.exact_whitelist.play_mode.len() > 0 {
-            search.exact_whitelist.play_mode = Some(value.exact_whitelist.play_mode.clone());
-        }
-        if value.exact_whitelist.status.len() > 0 {
-            search.exact_whitelist.status = Some(value.exact_whitelist.status.clone());
-        }
-        if value.exact_whitelist.notes.len() > 0 {
-            search.exact_whitelist.notes = Some(value.exact_whitelist.notes.clone());
-        }
-        if value.exact_whitelist.source.len() > 0 {
-            search.exact_whitelist.source = Some(value.exact_whitelist.source.clone());
-        }
-        if value.exact_whitelist.original_description.len() > 0 {
-            search.exact_whitelist.original_description =
-                Some(value.exact_whitelist.original_description.clone());
-        }
-        if value.exact_whitelist.language.len() > 0 {
-            search.exact_whitelist.language = Some(value.exact_whitelist.language.clone());
-        }
-        if value.exact_whitelist.application_path.len() > 0 {
-            search.exact_whitelist.application_path =
-                Some(value.exact_whitelist.application_path.clone());
-        }
-        if value.exact_whitelist.launch_command.len() > 0 {
-            search.exact_whitelist.launch_command =
-                Some(value.exact_whitelist.launch_command.clone());
-        }
-        if value.exact_whitelist.ruffle_support.len() > 0 {
-            search.exact_whitelist.ruffle_support =
-                Some(value.exact_whitelist.ruffle_support.clone());
+impl From<ForcedGameFilter> for GameFilter {
+    fn from(value: ForcedGameFilter) -> Self {
+        GameFilter {
+            whitelist: value.whitelist.into(),
+            blacklist: value.blacklist.into(),
+            exact_whitelist: value.exact_whitelist.into(),
+            exact_blacklist: value.exact_blacklist.into(),
+            higher_than: value.higher_than,
+            lower_than: value.lower_than,
+            equal_to: value.equal_to,
+            bool_comp: value.bool_comp,
+            ..Default::default()
         }
+    }
+}
 
-        // Exact blacklist
-
-        if value.exact_blacklist.id.len() > 0 {
-            search.exact_blacklist.id = Some(value.exact_blacklist.id.clone());
-        }
-        if value.exact_blacklist.generic.len() > 0 {
-            search.exact_blacklist.generic = Some(value.exact_blacklist.generic.clone());
-        }
-        if value.exact_blacklist.title.len() > 0 {
-            search.exact_blacklist.title = Some(value.exact_blacklist.title.clone());
-        }
-        if value.exact_blacklist.developer.len() > 0 {
-            search.exact_blacklist.developer = Some(value.exact_blacklist.developer.clone());
-        }
-        if value.exact_blacklist.publisher.len() > 0 {
-            search.exact_blacklist.publisher = Some(value.exact_blacklist.publisher.clone());
-        }
-        if value.exact_blacklist.series.len() > 0 {
-            search.exact_blacklist.series = Some(value.exact_blacklist.series.clone());
-        }
-        if value.exact_blacklist.tags.len() > 0 {
-            search.exact_blacklist.tags = Some(value.exact_blacklist.tags.clone());
-        }
-        if value.exact_blacklist.platforms.len() > 0 {
-            search.exact_blacklist.platforms = Some(value.exact_blacklist.platforms.clone());
-        }
-        if value.exact_blacklist.play_mode.len() > 0 {
-            search.exact_blacklist.play_mode = Some(value.exact_blacklist.play_mode.clone());
-        }
-        if value.exact_blacklist.status.len() > 0 {
-            search.exact_blacklist.status = Some(value.exact_blacklist.status.clone());
-        }
-        if value.exact_blacklist.notes.len() > 0 {
-            search.exact_blacklist.notes = Some(value.exact_blacklist.notes.clone());
-        }
-        if value.exact_blacklist.source.len() > 0 {
-            search.exact_blacklist.source = Some(value.exact_blacklist.source.clone());
-        }
-        if value.exact_blacklist.original_description.len() > 0 {
-            search.exact_blacklist.original_description =
-                Some(value.exact_blacklist.original_description.clone());
-        }
-        if value.exact_blacklist.language.len() > 0 {
-            search.exact_blacklist.language = Some(value.exact_blacklist.language.clone());
-        }
-        if value.exact_blacklist.application_path.len() > 0 {
-            search.exact_blacklist.application_path =
-                Some(value.exact_blacklist.application_path.clone());
-        }
-        if value.exact_blacklist.launch_command.len() > 0 {
-            search.exact_blacklist.launch_command =
-                Some(value.exact_blacklist.launch_command.clone());
-        }
-        if value.exact_blacklist.ruffle_support.len() > 0 {
-            search.exact_blacklist.ruffle_support =
-                Some(value.exact_blacklist.ruffle_support.clone());
+impl From<ForcedFieldFilter> for FieldFilter {
+    fn from(value: ForcedFieldFilter) -> Self {
+        // Moves the parsed values, only empty lists are dropped
+        let non_empty = |list: Vec<String>| (!list.is_empty()).then_some(list);
+        FieldFilter {
+            id: non_empty(value.id),
+            generic: non_empty(value.generic),
+            library: non_empty(value.library),
+            title: non_empty(value.title),
+            developer: non_empty(value.developer),
+            publisher: non_empty(value.publisher),
+            series: non_empty(value.series),
+            tags: non_empty(value.tags),
+            platforms: non_empty(value.platforms),
+            play_mode: non_empty(value.play_mode),
+            status: non_empty(value.status),
+            notes: non_empty(value.notes),
+            source: non_empty(value.source),
+            original_description: non_empty(value.original_description),
+            language: non_empty(value.language),
+            application_path: non_empty(value.application_path),
+            launch_command: non_empty(value.launch_command),
+            ruffle_support: non_empty(value.ruffle_support),
+            active_game_config_owner: non_empty(value.active_game_config_owner),
         }
-
-        search.higher_than = value.higher_than.clone();
-        search.lower_than = value.lower_than.clone();
-        search.equal_to = value.equal_to.clone();
-        search.bool_comp = value.bool_comp.clone();
-
-        search
     }
 }
 
@@ -617,21 +693,35 @@ macro_rules! exact_blacklist_clause {
 
 const COUNT_QUERY: &str = "SELECT COUNT(*) FROM game";
 
-const RESULTS_QUERY: &str =
+pub(crate) const RESULTS_QUERY: &str =
     "SELECT game.id, title, alternateTitles, series, developer, publisher, platformsStr, \
 platformName, dateAdded, dateModified, broken, extreme, playMode, status, notes, \
 tagsStr, source, applicationPath, launchCommand, releaseDate, version, \
 originalDescription, language, activeDataId, activeDataOnDisk, lastPlayed, playtime, \
-activeGameConfigId, activeGameConfigOwner, archiveState, library, playCounter, ruffleSupport \
+activeGameConfigId, activeGameConfigOwner, archiveState, library, playCounter, ruffleSupport, releaseYear \
 FROM game";
 
-const SLIM_RESULTS_QUERY: &str =
-    "SELECT game.id, title, series, developer, publisher, platformsStr, 
-platformName, tagsStr, library 
-FROM game";
 
 const TAG_FILTER_INDEX_QUERY: &str = "INSERT INTO tag_filter_index (id) SELECT game.id FROM game";
 
+const ID_QUERY: &str = "SELECT game.id FROM game";
+
+/// Most page tuples `search_index` returns when no limit is given, pages past it are reached by keyset from the last tuple
+pub const INDEX_MAX_PAGES: i64 = 10000;
+
+// Date columns are compared in the canonical format, older rows may have been stored in another one
+const DATE_ADDED_ORDER: &str = "strftime('%Y-%m-%dT%H:%M:%fZ', game.dateAdded)";
+const DATE_MODIFIED_ORDER: &str = "strftime('%Y-%m-%dT%H:%M:%fZ', game.dateModified)";
+const LAST_PLAYED_ORDER: &str = "strftime('%Y-%m-%dT%H:%M:%fZ', game.lastPlayed)";
+// Games missing from a custom order follow the listed ones, by title
+const CUSTOM_ORDER: &str = "IFNULL(OrderedIDs.RowNum, 9223372036854775807)";
+// Room for the joins, offset, ordering and limit around the filter in a built query
+const QUERY_CLAUSES_CAPACITY: usize = 512;
+// Field filter columns that may be NULL, blacklists never exclude games without a value
+const NULLABLE_FILTER_COLUMNS: [&str; 1] = ["activeGameConfigOwner"];
+
+/// The first game of every page after the first, `search.limit` being the page size.
+/// `limit` caps the games covered, otherwise it's capped at `INDEX_MAX_PAGES` pages.
 pub fn search_index(
     conn: &Connection,
     search: &mut GameSearch,
@@ -641,15 +731,7 @@ pub fn search_index(
     rusqlite::vtab::array::load_module(conn)?;
 
     // Update tag filter indexing
-    if let Some(tags) = &search.with_tag_filter {
-        if tags.len() > 0 {
-            let mut filtered_search = GameSearch::default();
-            filtered_search.limit = 999999999;
-            filtered_search.filter.exact_blacklist.tags = Some(tags.to_vec());
-            filtered_search.filter.match_any = true;
-            new_tag_filter_index(conn, &mut filtered_search)?;
-        }
-    }
+    update_tag_filter_index(conn, search)?;
 
     if search.order.column == GameSearchSortable::CUSTOM {
         if let Some(custom_id_order) = &search.custom_id_order {
@@ -665,20 +747,21 @@ pub fn search_index(
         GameSearchSortable::PUBLISHER => "game.publisher",
         GameSearchSortable::SERIES => "game.series",
         GameSearchSortable::PLATFORM => "game.platformName",
-        GameSearchSortable::DATEADDED => "game.dateAdded",
-        GameSearchSortable::DATEMODIFIED => "game.dateModified",
+        GameSearchSortable::DATEADDED => DATE_ADDED_ORDER,
+        GameSearchSortable::DATEMODIFIED => DATE_MODIFIED_ORDER,
         GameSearchSortable::RELEASEDATE => "game.releaseDate",
-        GameSearchSortable::LASTPLAYED => "game.lastPlayed",
+        GameSearchSortable::LASTPLAYED => LAST_PLAYED_ORDER,
         GameSearchSortable::PLAYTIME => "game.playtime",
-        GameSearchSortable::CUSTOM => "RowNum",
+        GameSearchSortable::CUSTOM => CUSTOM_ORDER,
         _ => "unknown",
     };
     let order_direction = match search.order.direction {
         GameSearchDirection::ASC => "ASC",
         GameSearchDirection::DESC => "DESC",
     };
+    // Without a limit only the games covering the first INDEX_MAX_PAGES pages are indexed
     let page_size = search.limit;
-    search.limit = limit.or_else(|| Some(999999999)).unwrap();
+    let index_limit = limit.or_else(|| (page_size > 0).then(|| page_size.saturating_mul(INDEX_MAX_PAGES)));
     let selection = match search.order.column {
         GameSearchSortable::CUSTOM => "
         WITH OrderedIDs AS (
@@ -687,15 +770,15 @@ pub fn search_index(
             ROW_NUMBER() OVER (ORDER BY (SELECT NULL)) AS RowNum
             FROM custom_id_order
         ) 
-        SELECT game.id, OrderedIDs.RowNum, game.title, ROW_NUMBER() OVER (ORDER BY OrderedIDs.RowNum, game.title, game.id) AS rn FROM game".to_owned(),
-        _ => format!("SELECT game.id, {}, game.title, ROW_NUMBER() OVER (ORDER BY {} {}, game.title {}, game.id) AS rn FROM game", order_column, order_column, order_direction, order_direction)
+        SELECT game.id, ".to_owned() + CUSTOM_ORDER + " AS orderVal, game.title, ROW_NUMBER() OVER (ORDER BY " + CUSTOM_ORDER + ", game.title, game.id) AS rn FROM game",
+        _ => format!("SELECT game.id, {} AS orderVal, game.title, ROW_NUMBER() OVER (ORDER BY {} {}, game.title {}, game.id) AS rn FROM game", order_column, order_column, order_direction, order_direction)
     };
-    let (mut query, mut params) = build_search_query(search, &selection);
+    let (mut query, mut params) = build_limited_search_query(search, &selection, index_limit);
 
     // Add the weirdness
     query = format!(
-        "SELECT game.id, {}, game.title FROM ({}) game WHERE rn % ? = 0",
-        order_column, query
+        "SELECT game.id, game.orderVal, game.title FROM ({}) game WHERE rn % ? = 0",
+        query
     );
     params.push(SearchParam::String(page_size.to_string()));
 
@@ -705,7 +788,7 @@ pub fn search_index(
     let mut keyset = vec![];
     debug_println!(
         "search index query - \n{}",
-        format_query(&query, params.clone())
+        query_log(&query, &params)
     );
     let mut stmt = conn.prepare(&query)?;
     let page_tuple_iter = stmt.query_map(params_as_refs.as_slice(), |row| {
@@ -751,7 +834,7 @@ pub fn search_count(conn: &Connection, search: &GameSearch) -> Result<i64> {
     let (query, params) = build_search_query(search, &selection);
     debug_println!(
         "search count query - \n{}",
-        format_query(&query, params.clone())
+        query_log(&query, &params)
     );
 
     let params_as_refs: Vec<&dyn rusqlite::ToSql> =
@@ -769,13 +852,54 @@ pub fn search_count(conn: &Connection, search: &GameSearch) -> Result<i64> {
     }
 }
 
+/// Rejects order and offset combinations that can't produce a well defined page.
+///
+/// | order column | offset                       | custom_id_order |
+/// |--------------|------------------------------|-----------------|
+/// | RANDOM       | not allowed                  | ignored         |
+/// | CUSTOM       | `value` must be a row number | required        |
+/// | any other    | keyset of the order column   | ignored         |
+pub fn validate_search(search: &GameSearch) -> error::Result<()> {
+    let reason = match search.order.column {
+        GameSearchSortable::RANDOM if search.offset.is_some() => {
+            Some("an offset cannot be used with RANDOM order".to_owned())
+        }
+        GameSearchSortable::CUSTOM => match (&search.custom_id_order, &search.offset) {
+            (None, _) => Some("CUSTOM order requires custom_id_order".to_owned()),
+            (Some(_), Some(offset)) if offset.value.parse::<i64>().is_err() => Some(format!(
+                "CUSTOM order offset must be a row number, got '{}'",
+                offset.value
+            )),
+            _ => None,
+        },
+        _ => None,
+    };
+    match reason {
+        Some(reason) => error::InvalidSearchSnafu { reason }.fail(),
+        None => Ok(()),
+    }
+}
+
 // The search function that takes a connection and a GameSearch object
 pub fn search(conn: &Connection, search: &GameSearch) -> Result<Vec<Game>> {
+    search_limited(conn, search, Some(search.limit))
+}
+
+/// `search` with the limit given separately, `None` returns every matching game
+pub fn search_limited(conn: &Connection, search: &GameSearch, limit: Option<i64>) -> Result<Vec<Game>> {
     // Allow use of rarray() in SQL queries
     rusqlite::vtab::array::load_module(conn)?;
 
+    // An empty field list would select nothing, treat it as the default set
+    let slim_fields: &[GameField] = match search.slim_fields.is_empty() {
+        true => &DEFAULT_SLIM_FIELDS,
+        false => &search.slim_fields,
+    };
     let mut selection = match search.slim {
-        true => SLIM_RESULTS_QUERY.to_owned(),
+        true => format!(
+            "SELECT {} FROM game",
+            slim_selection(slim_fields)
+        ),
         false => RESULTS_QUERY.to_owned(),
     };
     if search.order.column == GameSearchSortable::CUSTOM {
@@ -789,8 +913,8 @@ pub fn search(conn: &Connection, search: &GameSearch) -> Result<Vec<Game>> {
             + &selection;
     }
 
-    let (query, params) = build_search_query(search, &selection);
-    debug_println!("search query - \n{}", format_query(&query, params.clone()));
+    let (query, params) = build_limited_search_query(search, &selection, limit);
+    debug_println!("search query - \n{}", query_log(&query, &params));
 
     // Convert the parameters array to something rusqlite understands
     let params_as_refs: Vec<&dyn rusqlite::ToSql> =
@@ -799,62 +923,11 @@ pub fn search(conn: &Connection, search: &GameSearch) -> Result<Vec<Game>> {
     let mut games = Vec::new();
 
     let mut stmt = conn.prepare(query.as_str())?;
-    let game_map_closure = match search.slim {
-        true => |row: &rusqlite::Row<'_>| -> Result<Game> {
-            Ok(Game {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                series: row.get(2)?,
-                developer: row.get(3)?,
-                publisher: row.get(4)?,
-                platforms: row.get(5)?,
-                primary_platform: row.get(6)?,
-                tags: row.get(7)?,
-                library: row.get(8)?,
-                ..Default::default()
-            })
-        },
-        false => |row: &rusqlite::Row<'_>| -> Result<Game> {
-            Ok(Game {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                alternate_titles: row.get(2)?,
-                series: row.get(3)?,
-                developer: row.get(4)?,
-                publisher: row.get(5)?,
-                platforms: row.get(6)?,
-                primary_platform: row.get(7)?,
-                date_added: row.get(8)?,
-                date_modified: row.get(9)?,
-                legacy_broken: row.get(10)?,
-                legacy_extreme: row.get(11)?,
-                play_mode: row.get(12)?,
-                status: row.get(13)?,
-                notes: row.get(14)?,
-                tags: row.get(15)?,
-                source: row.get(16)?,
-                legacy_application_path: row.get(17)?,
-                legacy_launch_command: row.get(18)?,
-                release_date: row.get(19)?,
-                version: row.get(20)?,
-                original_description: row.get(21)?,
-                language: row.get(22)?,
-                active_data_id: row.get(23)?,
-                active_data_on_disk: row.get(24)?,
-                last_played: row.get(25)?,
-                playtime: row.get(26)?,
-                active_game_config_id: row.get(27)?,
-                active_game_config_owner: row.get(28)?,
-                archive_state: row.get(29)?,
-                library: row.get(30)?,
-                play_counter: row.get(31)?,
-                detailed_platforms: None,
-                detailed_tags: None,
-                game_data: None,
-                add_apps: None,
-                ruffle_support: row.get(32)?,
-            })
-        },
+    let game_map_closure = |row: &rusqlite::Row<'_>| -> Result<Game> {
+        match search.slim {
+            true => slim_game_from_row(row, slim_fields),
+            false => full_game_from_row(row),
+        }
     };
 
     let game_iter = stmt.query_map(params_as_refs.as_slice(), game_map_closure)?;
@@ -876,34 +949,379 @@ pub fn search(conn: &Connection, search: &GameSearch) -> Result<Vec<Game>> {
         games.push(game);
     }
 
+    if search.explain_matches {
+        explain_matches(conn, &search.filter, &mut games)?;
+    }
+
     Ok(games)
 }
 
+/// Columns generic terms are filtered on by `build_filter_query`
+const GENERIC_TEXT_FIELDS: [GameField; 5] = [
+    GameField::Title,
+    GameField::AlternateTitles,
+    GameField::Developer,
+    GameField::Publisher,
+    GameField::Series,
+];
+
+/// Columns `any_text` terms are filtered on by `build_filter_query`, also every column an explanation can report
+const ANY_TEXT_FIELDS: [GameField; 7] = [
+    GameField::Title,
+    GameField::AlternateTitles,
+    GameField::Developer,
+    GameField::Publisher,
+    GameField::Series,
+    GameField::Notes,
+    GameField::OriginalDescription,
+];
+
+fn column_names(fields: &[GameField]) -> Vec<&'static str> {
+    fields.iter().map(|field| slim_column(field).trim_start_matches("game.")).collect()
+}
+
+/// LIKE pattern for values containing `value`, for use with `ESCAPE '\'`
+fn like_contains(value: &str) -> String {
+    let escaped = value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// A whitelisted free text term, with the columns and comparison its filter clause used
+struct TextTerm {
+    fields: &'static [GameField],
+    exact: bool,
+    value: String,
+}
+
+fn collect_text_terms(filter: &GameFilter, terms: &mut Vec<TextTerm>) {
+    let sources: [(&'static [GameField], bool, &Option<Vec<String>>); 3] = [
+        (&GENERIC_TEXT_FIELDS, false, &filter.whitelist.generic),
+        (&GENERIC_TEXT_FIELDS, true, &filter.exact_whitelist.generic),
+        (&ANY_TEXT_FIELDS, false, &filter.any_text),
+    ];
+    for (fields, exact, values) in sources {
+        for value in values.iter().flatten() {
+            terms.push(TextTerm { fields, exact, value: value.clone() });
+        }
+    }
+    for subfilter in &filter.subfilters {
+        collect_text_terms(subfilter, terms);
+    }
+}
+
+/// Second pass over a page of results, checking each column against the terms filtered on it in one query
+fn explain_matches(conn: &Connection, filter: &GameFilter, games: &mut [Game]) -> Result<()> {
+    let mut terms = vec![];
+    collect_text_terms(filter, &mut terms);
+
+    if terms.is_empty() || games.is_empty() {
+        for game in games.iter_mut() {
+            game.matched_fields = Some(vec![]);
+        }
+        return Ok(());
+    }
+
+    let mut values = vec![];
+    let mut checks = vec![];
+    for field in ANY_TEXT_FIELDS.iter() {
+        let column = slim_column(field);
+        let mut comparisons = vec![];
+        for term in terms.iter().filter(|term| term.fields.contains(field)) {
+            if term.exact {
+                comparisons.push(format!("{} = ?", column));
+                values.push(term.value.clone());
+            } else {
+                comparisons.push(format!("{} LIKE ? ESCAPE '\\'", column));
+                values.push(like_contains(&term.value));
+            }
+        }
+        checks.push(match comparisons.is_empty() {
+            true => "0".to_owned(),
+            false => format!("({})", comparisons.join(" OR ")),
+        });
+    }
+
+    let ids = SearchParam::StringVec(Rc::new(games.iter().map(|g| Value::from(g.id.clone())).collect()));
+    let mut params: Vec<&dyn ToSql> = values.iter().map(|v| v as &dyn ToSql).collect();
+    params.push(&ids);
+    let query = format!("SELECT game.id, {} FROM game WHERE game.id IN rarray(?)", checks.join(", "));
+
+    let mut stmt = conn.prepare(&query)?;
+    let matched_iter = stmt.query_map(params.as_slice(), |row| {
+        let mut matched = vec![];
+        for (idx, field) in ANY_TEXT_FIELDS.iter().enumerate() {
+            // NULL columns never match
+            if row.get::<_, Option<bool>>(idx + 1)? == Some(true) {
+                matched.push(field.to_string());
+            }
+        }
+        Ok((row.get::<_, String>(0)?, matched))
+    })?;
+    let matched: HashMap<String, Vec<String>> = matched_iter.collect::<Result<_>>()?;
+
+    for game in games.iter_mut() {
+        game.matched_fields = Some(matched.get(&game.id).cloned().unwrap_or_default());
+    }
+
+    Ok(())
+}
+
+pub(crate) fn full_game_from_row(row: &rusqlite::Row<'_>) -> Result<Game> {
+    Ok(Game {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        alternate_titles: row.get(2)?,
+        series: row.get(3)?,
+        developer: row.get(4)?,
+        publisher: row.get(5)?,
+        platforms: row.get(6)?,
+        primary_platform: row.get(7)?,
+        date_added: row.get(8)?,
+        date_modified: row.get(9)?,
+        legacy_broken: row.get(10)?,
+        legacy_extreme: row.get(11)?,
+        play_mode: row.get(12)?,
+        status: row.get(13)?,
+        notes: row.get(14)?,
+        tags: row.get(15)?,
+        source: row.get(16)?,
+        legacy_application_path: row.get(17)?,
+        legacy_launch_command: row.get(18)?,
+        release_date: row.get(19)?,
+        version: row.get(20)?,
+        original_description: row.get(21)?,
+        language: row.get(22)?,
+        active_data_id: row.get(23)?,
+        active_data_on_disk: row.get(24)?,
+        last_played: row.get(25)?,
+        playtime: row.get(26)?,
+        active_game_config_id: row.get(27)?,
+        active_game_config_owner: row.get(28)?,
+        archive_state: row.get(29)?,
+        library: row.get(30)?,
+        play_counter: row.get(31)?,
+        detailed_platforms: None,
+        detailed_tags: None,
+        game_data: None,
+        add_apps: None,
+        ruffle_support: row.get(32)?,
+        release_year: row.get(33)?,
+        matched_fields: None,
+    })
+}
+
+/// Select list for `fields` in order, for reading rows back with `slim_game_from_row`
+pub(crate) fn slim_selection(fields: &[GameField]) -> String {
+    fields.iter().map(slim_column).collect::<Vec<&str>>().join(", ")
+}
+
+fn slim_column(field: &GameField) -> &'static str {
+    match field {
+        GameField::Id => "game.id",
+        GameField::Library => "game.library",
+        GameField::Title => "game.title",
+        GameField::AlternateTitles => "game.alternateTitles",
+        GameField::Series => "game.series",
+        GameField::Developer => "game.developer",
+        GameField::Publisher => "game.publisher",
+        GameField::PrimaryPlatform => "game.platformName",
+        GameField::Platforms => "game.platformsStr",
+        GameField::DateAdded => "game.dateAdded",
+        GameField::DateModified => "game.dateModified",
+        GameField::LegacyBroken => "game.broken",
+        GameField::LegacyExtreme => "game.extreme",
+        GameField::PlayMode => "game.playMode",
+        GameField::Status => "game.status",
+        GameField::Notes => "game.notes",
+        GameField::Tags => "game.tagsStr",
+        GameField::Source => "game.source",
+        GameField::LegacyApplicationPath => "game.applicationPath",
+        GameField::LegacyLaunchCommand => "game.launchCommand",
+        GameField::ReleaseDate => "game.releaseDate",
+        GameField::ReleaseYear => "game.releaseYear",
+        GameField::Version => "game.version",
+        GameField::OriginalDescription => "game.originalDescription",
+        GameField::Language => "game.language",
+        GameField::ActiveDataId => "game.activeDataId",
+        GameField::ActiveDataOnDisk => "game.activeDataOnDisk",
+        GameField::LastPlayed => "game.lastPlayed",
+        GameField::Playtime => "game.playtime",
+        GameField::PlayCounter => "game.playCounter",
+        GameField::ActiveGameConfigId => "game.activeGameConfigId",
+        GameField::ActiveGameConfigOwner => "game.activeGameConfigOwner",
+        GameField::ArchiveState => "game.archiveState",
+        GameField::RuffleSupport => "game.ruffleSupport",
+    }
+}
+
+pub(crate) fn slim_game_from_row(row: &rusqlite::Row<'_>, fields: &[GameField]) -> Result<Game> {
+    let mut game = Game::default();
+    for (idx, field) in fields.iter().enumerate() {
+        match field {
+            GameField::Id => game.id = row.get(idx)?,
+            GameField::Library => game.library = row.get(idx)?,
+            GameField::Title => game.title = row.get(idx)?,
+            GameField::AlternateTitles => game.alternate_titles = row.get(idx)?,
+            GameField::Series => game.series = row.get(idx)?,
+            GameField::Developer => game.developer = row.get(idx)?,
+            GameField::Publisher => game.publisher = row.get(idx)?,
+            GameField::PrimaryPlatform => game.primary_platform = row.get(idx)?,
+            GameField::Platforms => game.platforms = row.get(idx)?,
+            GameField::DateAdded => game.date_added = row.get(idx)?,
+            GameField::DateModified => game.date_modified = row.get(idx)?,
+            GameField::LegacyBroken => game.legacy_broken = row.get(idx)?,
+            GameField::LegacyExtreme => game.legacy_extreme = row.get(idx)?,
+            GameField::PlayMode => game.play_mode = row.get(idx)?,
+            GameField::Status => game.status = row.get(idx)?,
+            GameField::Notes => game.notes = row.get(idx)?,
+            GameField::Tags => game.tags = row.get(idx)?,
+            GameField::Source => game.source = row.get(idx)?,
+            GameField::LegacyApplicationPath => game.legacy_application_path = row.get(idx)?,
+            GameField::LegacyLaunchCommand => game.legacy_launch_command = row.get(idx)?,
+            GameField::ReleaseDate => game.release_date = row.get(idx)?,
+            GameField::ReleaseYear => game.release_year = row.get(idx)?,
+            GameField::Version => game.version = row.get(idx)?,
+            GameField::OriginalDescription => game.original_description = row.get(idx)?,
+            GameField::Language => game.language = row.get(idx)?,
+            GameField::ActiveDataId => game.active_data_id = row.get(idx)?,
+            GameField::ActiveDataOnDisk => game.active_data_on_disk = row.get(idx)?,
+            GameField::LastPlayed => game.last_played = row.get(idx)?,
+            GameField::Playtime => game.playtime = row.get(idx)?,
+            GameField::PlayCounter => game.play_counter = row.get(idx)?,
+            GameField::ActiveGameConfigId => game.active_game_config_id = row.get(idx)?,
+            GameField::ActiveGameConfigOwner => game.active_game_config_owner = row.get(idx)?,
+            GameField::ArchiveState => game.archive_state = row.get(idx)?,
+            GameField::RuffleSupport => game.ruffle_support = row.get(idx)?,
+        }
+    }
+    Ok(game)
+}
+
 pub fn search_random(conn: &Connection, mut s: GameSearch, count: i64) -> Result<Vec<Game>> {
     s.limit = count;
     s.order.column = GameSearchSortable::RANDOM;
+    // Random picks aren't paged
+    s.offset = None;
 
     // Update tag filter indexing
-    if let Some(tags) = &s.with_tag_filter {
-        if tags.len() > 0 {
-            let mut filtered_search = GameSearch::default();
-            filtered_search.limit = 999999999;
-            filtered_search.filter.exact_blacklist.tags = Some(tags.to_vec());
-            filtered_search.filter.match_any = true;
-            new_tag_filter_index(conn, &mut filtered_search)?;
-        }
-    }
+    update_tag_filter_index(conn, &s)?;
 
     search(conn, &s)
 }
 
-fn build_search_query(search: &GameSearch, selection: &str) -> (String, Vec<SearchParam>) {
-    let mut query = String::from(selection);
+pub fn search_grouped(
+    conn: &Connection,
+    search: &GameSearch,
+    group_by: GroupableField,
+    representatives: i64,
+) -> Result<Vec<GameGroup>> {
+    // Allow use of rarray() in SQL queries
+    rusqlite::vtab::array::load_module(conn)?;
 
+    let group_column = match group_by {
+        GroupableField::Series => "game.series",
+        GroupableField::Developer => "game.developer",
+        GroupableField::Publisher => "game.publisher",
+        GroupableField::Library => "game.library",
+        GroupableField::PrimaryPlatform => "game.platformName",
+    };
+    let group_expr = format!(
+        "CASE WHEN IFNULL({}, '') = '' THEN '(none)' ELSE {} END",
+        group_column, group_column
+    );
+    let order_column = match search.order.column {
+        GameSearchSortable::TITLE => "game.title",
+        GameSearchSortable::DEVELOPER => "game.developer",
+        GameSearchSortable::PUBLISHER => "game.publisher",
+        GameSearchSortable::SERIES => "game.series",
+        GameSearchSortable::PLATFORM => "game.platformName",
+        GameSearchSortable::DATEADDED => DATE_ADDED_ORDER,
+        GameSearchSortable::DATEMODIFIED => DATE_MODIFIED_ORDER,
+        GameSearchSortable::RELEASEDATE => "game.releaseDate",
+        GameSearchSortable::LASTPLAYED => LAST_PLAYED_ORDER,
+        GameSearchSortable::PLAYTIME => "game.playtime",
+        GameSearchSortable::CUSTOM => CUSTOM_ORDER,
+        GameSearchSortable::RANDOM => "RANDOM()",
+    };
+    let order_direction = match (&search.order.column, &search.order.direction) {
+        (GameSearchSortable::CUSTOM, _) | (_, GameSearchDirection::ASC) => "ASC",
+        (_, GameSearchDirection::DESC) => "DESC",
+    };
+
+    // Group over the whole filtered set, not just the first page
+    let mut filtered_search = search.clone();
+    filtered_search.offset = None;
+    if filtered_search.order.column == GameSearchSortable::RANDOM {
+        filtered_search.order.column = GameSearchSortable::TITLE;
+    }
+
+    let mut selection = format!(
+        "SELECT game.id, {} AS groupName, ROW_NUMBER() OVER (PARTITION BY {} ORDER BY {} {}, game.title {}, game.id) AS rn FROM game",
+        group_expr, group_expr, order_column, order_direction, order_direction
+    );
     if search.order.column == GameSearchSortable::CUSTOM {
-        query.push_str(" INNER JOIN OrderedIDs ON game.id = OrderedIDs.id");
+        selection = "WITH OrderedIDs AS (
+            SELECT
+            id,
+            ROW_NUMBER() OVER (ORDER BY (SELECT NULL)) AS RowNum
+            FROM custom_id_order
+        ) "
+        .to_owned()
+            + &selection;
     }
+    let (inner_query, params) = build_limited_search_query(&filtered_search, &selection, None);
 
+    let params_as_refs: Vec<&dyn rusqlite::ToSql> =
+        params.iter().map(|s| s as &dyn rusqlite::ToSql).collect();
+
+    // Group counts
+    let query = format!(
+        "SELECT groupName, COUNT(*) FROM ({}) GROUP BY groupName ORDER BY groupName",
+        inner_query
+    );
+    debug_println!("search grouped query - \n{}", query_log(&query, &params));
+    let mut groups = vec![];
+    let mut stmt = conn.prepare(&query)?;
+    let group_iter = stmt.query_map(params_as_refs.as_slice(), |row| {
+        Ok(GameGroup {
+            name: row.get(0)?,
+            count: row.get(1)?,
+            representatives: vec![],
+        })
+    })?;
+    for group in group_iter {
+        groups.push(group?);
+    }
+
+    // First N games of each group
+    if representatives > 0 {
+        let query = format!(
+            "SELECT groupName, id FROM ({}) WHERE rn <= {} ORDER BY groupName, rn",
+            inner_query, representatives
+        );
+        let mut stmt = conn.prepare(&query)?;
+        let rep_iter = stmt.query_map(params_as_refs.as_slice(), |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        for rep in rep_iter {
+            let (name, id) = rep?;
+            if let Some(group) = groups.iter_mut().find(|g| g.name == name) {
+                group.representatives.push(id);
+            }
+        }
+    }
+
+    Ok(groups)
+}
+
+/// Builds the SQL and parameters for `search`, appending the joins, filter, ordering and limit to `selection`.
+/// `selection` must select from `game`, e.g. `SELECT game.id FROM game`.
+pub fn build_search_query(search: &GameSearch, selection: &str) -> (String, Vec<SearchParam>) {
+    build_limited_search_query(search, selection, Some(search.limit))
+}
+
+/// `build_search_query` with the limit given separately, `None` leaves the query unlimited
+fn build_limited_search_query(search: &GameSearch, selection: &str, limit: Option<i64>) -> (String, Vec<SearchParam>) {
     // Ordering
     let order_column = match search.order.column {
         GameSearchSortable::TITLE => "game.title",
@@ -911,12 +1329,12 @@ fn build_search_query(search: &GameSearch, selection: &str) -> (String, Vec<Sear
         GameSearchSortable::PUBLISHER => "game.publisher",
         GameSearchSortable::SERIES => "game.series",
         GameSearchSortable::PLATFORM => "game.platformName",
-        GameSearchSortable::DATEADDED => "game.dateAdded",
-        GameSearchSortable::DATEMODIFIED => "game.dateModified",
+        GameSearchSortable::DATEADDED => DATE_ADDED_ORDER,
+        GameSearchSortable::DATEMODIFIED => DATE_MODIFIED_ORDER,
         GameSearchSortable::RELEASEDATE => "game.releaseDate",
-        GameSearchSortable::LASTPLAYED => "game.lastPlayed",
+        GameSearchSortable::LASTPLAYED => LAST_PLAYED_ORDER,
         GameSearchSortable::PLAYTIME => "game.playtime",
-        GameSearchSortable::CUSTOM => "OrderedIDs.RowNum",
+        GameSearchSortable::CUSTOM => CUSTOM_ORDER,
         _ => "unknown",
     };
     let order_direction = match search.order.direction {
@@ -926,7 +1344,21 @@ fn build_search_query(search: &GameSearch, selection: &str) -> (String, Vec<Sear
 
     // Build the inner WHERE clause
     let mut params: Vec<SearchParam> = vec![];
-    let where_clause = build_filter_query(&search.filter, &mut params);
+    let mut where_clause = build_filter_query(&search.filter, search.normalize_languages, &mut params);
+    if !search.include_deleted {
+        where_clause = match !where_clause.is_empty() && where_clause != "()" {
+            true => format!("game.deletedAt IS NULL AND ({})", where_clause),
+            false => "game.deletedAt IS NULL".to_owned(),
+        };
+    }
+
+    // The filter makes up most of a large query, size for it once up front
+    let mut query = String::with_capacity(selection.len() + where_clause.len() + QUERY_CLAUSES_CAPACITY);
+    query.push_str(selection);
+
+    if search.order.column == GameSearchSortable::CUSTOM {
+        query.push_str(" LEFT JOIN OrderedIDs ON game.id = OrderedIDs.id");
+    }
 
     // Add tag filtering
     if let Some(tags) = &search.with_tag_filter {
@@ -938,16 +1370,33 @@ fn build_search_query(search: &GameSearch, selection: &str) -> (String, Vec<Sear
     // Add offset
     if let Some(offset) = search.offset.clone() {
         if search.order.column == GameSearchSortable::CUSTOM {
-            let offset_clause = format!(" WHERE OrderedIDs.RowNum > ?");
+            // Listed games have unique positions, only the unlisted tail needs the title and id
+            let offset_clause = format!(
+                " WHERE ({} > ? OR (OrderedIDs.RowNum IS NULL AND {} = ? AND (game.title, game.id) > (?, ?)))",
+                CUSTOM_ORDER, CUSTOM_ORDER
+            );
             query.push_str(&offset_clause);
-            params.insert(0, SearchParam::Integer64(coerce_to_i64(&offset.value)));
+
+            // Insert in reverse order
+            let position = coerce_to_i64(&offset.value);
+            params.insert(0, SearchParam::String(offset.game_id.clone()));
+            params.insert(0, SearchParam::String(offset.title.clone()));
+            params.insert(0, SearchParam::Integer64(position));
+            params.insert(0, SearchParam::Integer64(position));
         } else {
+            // Offset values may come straight from a legacy row, normalize them like the column
+            let offset_value = match search.order.column {
+                GameSearchSortable::DATEADDED
+                | GameSearchSortable::DATEMODIFIED
+                | GameSearchSortable::LASTPLAYED => "strftime('%Y-%m-%dT%H:%M:%fZ', ?)",
+                _ => "?",
+            };
             let offset_clause = match search.order.direction {
                 GameSearchDirection::ASC => {
-                    format!(" WHERE ({}, game.title, game.id) > (?, ?, ?)", order_column)
+                    format!(" WHERE ({}, game.title, game.id) > ({}, ?, ?)", order_column, offset_value)
                 }
                 GameSearchDirection::DESC => {
-                    format!(" WHERE ({}, game.title, game.id) < (?, ?, ?)", order_column)
+                    format!(" WHERE ({}, game.title, game.id) < ({}, ?, ?)", order_column, offset_value)
                 }
             };
             query.push_str(&offset_clause);
@@ -960,7 +1409,7 @@ fn build_search_query(search: &GameSearch, selection: &str) -> (String, Vec<Sear
     }
 
     // Combine all where clauses
-    if where_clause.len() > 0 && where_clause != "()" {
+    if !where_clause.is_empty() && where_clause != "()" {
         // Offset will begin WHERE itself, otherwise we're ANDing the offset
         let start_clause = match search.offset {
             Some(_) => " AND (",
@@ -973,11 +1422,9 @@ fn build_search_query(search: &GameSearch, selection: &str) -> (String, Vec<Sear
 
     if search.order.column == GameSearchSortable::RANDOM {
         query.push_str(" ORDER BY RANDOM()");
-        let limit_query = format!(" LIMIT {}", search.limit);
-        query.push_str(&limit_query);
     } else {
         if search.order.column == GameSearchSortable::CUSTOM {
-            query.push_str(" ORDER BY OrderedIDs.RowNum");
+            query.push_str(&format!(" ORDER BY {}, game.title, game.id", CUSTOM_ORDER));
         } else if order_column == "game.title" {
             query.push_str(format!(" ORDER BY game.title {}", order_direction).as_str());
         } else {
@@ -989,28 +1436,40 @@ fn build_search_query(search: &GameSearch, selection: &str) -> (String, Vec<Sear
                 .as_str(),
             );
         }
-        let limit_query = format!(" LIMIT {}", search.limit);
-        query.push_str(&limit_query);
+    }
+    if let Some(limit) = limit {
+        query.push_str(&format!(" LIMIT {}", limit));
     }
 
     (query, params)
 }
 
-fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> String {
+fn build_filter_query(filter: &GameFilter, normalize_languages: bool, params: &mut Vec<SearchParam>) -> String {
     let mut where_clauses = Vec::new();
 
     if filter.subfilters.len() > 0 {
         for subfilter in filter.subfilters.iter() {
-            let new_clause = build_filter_query(subfilter, params);
+            let new_clause = build_filter_query(subfilter, normalize_languages, params);
             if new_clause != "" {
-                where_clauses.push(new_clause);
+                // Keeps an OR joined subfilter together when the parent joins with AND
+                where_clauses.push(format!("({})", new_clause));
             }
         }
     }
 
+    let collation = match filter.case_sensitive {
+        Some(true) => " COLLATE BINARY",
+        Some(false) => " COLLATE NOCASE",
+        None => "",
+    };
+
     let mut add_clause =
         |field_name: &str, values: &Option<Vec<String>>, exact: bool, blacklist: bool| {
             if let Some(value_list) = values {
+                let or_null = |clause: String| match blacklist && NULLABLE_FILTER_COLUMNS.contains(&field_name) {
+                    true => format!("(game.{} IS NULL OR {})", field_name, clause),
+                    false => clause,
+                };
                 let comparator = match (blacklist, exact) {
                     (true, true) => "!=",
                     (true, false) => "NOT LIKE",
@@ -1024,12 +1483,13 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
                         true => "NOT IN",
                         false => "IN",
                     };
-                    where_clauses.push(format!("game.{} {} rarray(?)", field_name, comparator));
-                    params.push(SearchParam::StringVec(value_list.clone()));
+                    where_clauses.push(or_null(format!("game.{}{} {} rarray(?)", field_name, collation, comparator)));
+                    params.push(SearchParam::string_vec(value_list));
                 } else if blacklist {
                     let mut inner_clauses = vec![];
                     for value in value_list {
-                        inner_clauses.push(format!("game.{} {} ?", field_name, comparator));
+                        let collation = if exact { collation } else { "" };
+                        inner_clauses.push(format!("game.{}{} {} ?", field_name, collation, comparator));
                         if exact {
                             params.push(SearchParam::String(value.clone()));
                         } else {
@@ -1037,10 +1497,11 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
                             params.push(SearchParam::String(p));
                         }
                     }
-                    where_clauses.push(format!("({})", inner_clauses.join(" OR ")));
+                    where_clauses.push(or_null(format!("({})", inner_clauses.join(" OR "))));
                 } else {
                     for value in value_list {
-                        where_clauses.push(format!("game.{} {} ?", field_name, comparator));
+                        let collation = if exact { collation } else { "" };
+                        where_clauses.push(format!("game.{}{} {} ?", field_name, collation, comparator));
                         if exact {
                             params.push(SearchParam::String(value.clone()));
                         } else {
@@ -1053,6 +1514,7 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
         };
 
     // exact whitelist
+    exact_whitelist_clause!(add_clause, "title", &filter.exact_whitelist.title);
     exact_whitelist_clause!(add_clause, "library", &filter.exact_whitelist.library);
     exact_whitelist_clause!(add_clause, "developer", &filter.exact_whitelist.developer);
     exact_whitelist_clause!(add_clause, "publisher", &filter.exact_whitelist.publisher);
@@ -1066,14 +1528,22 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
         "originalDescription",
         &filter.exact_whitelist.original_description
     );
-    exact_whitelist_clause!(add_clause, "language", &filter.exact_whitelist.language);
+    if !normalize_languages {
+        exact_whitelist_clause!(add_clause, "language", &filter.exact_whitelist.language);
+    }
     exact_whitelist_clause!(
         add_clause,
         "ruffleSupport",
         &filter.exact_whitelist.ruffle_support
     );
+    exact_whitelist_clause!(
+        add_clause,
+        "activeGameConfigOwner",
+        &filter.exact_whitelist.active_game_config_owner
+    );
 
     // exact blacklist
+    exact_blacklist_clause!(add_clause, "title", &filter.exact_blacklist.title);
     exact_blacklist_clause!(add_clause, "library", &filter.exact_blacklist.library);
     exact_blacklist_clause!(add_clause, "developer", &filter.exact_blacklist.developer);
     exact_blacklist_clause!(add_clause, "publisher", &filter.exact_blacklist.publisher);
@@ -1087,12 +1557,19 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
         "originalDescription",
         &filter.exact_blacklist.original_description
     );
-    exact_blacklist_clause!(add_clause, "language", &filter.exact_blacklist.language);
+    if !normalize_languages {
+        exact_blacklist_clause!(add_clause, "language", &filter.exact_blacklist.language);
+    }
     exact_blacklist_clause!(
         add_clause,
         "ruffleSupport",
         &filter.exact_blacklist.ruffle_support
     );
+    exact_blacklist_clause!(
+        add_clause,
+        "activeGameConfigOwner",
+        &filter.exact_blacklist.active_game_config_owner
+    );
 
     // whitelist
     whitelist_clause!(add_clause, "library", &filter.whitelist.library);
@@ -1108,12 +1585,19 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
         "originalDescription",
         &filter.whitelist.original_description
     );
-    whitelist_clause!(add_clause, "language", &filter.whitelist.language);
+    if !normalize_languages {
+        whitelist_clause!(add_clause, "language", &filter.whitelist.language);
+    }
     whitelist_clause!(
         add_clause,
         "ruffleSupport",
         &filter.whitelist.ruffle_support
     );
+    whitelist_clause!(
+        add_clause,
+        "activeGameConfigOwner",
+        &filter.whitelist.active_game_config_owner
+    );
 
     // blacklist
     blacklist_clause!(add_clause, "library", &filter.blacklist.library);
@@ -1129,12 +1613,51 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
         "originalDescription",
         &filter.blacklist.original_description
     );
-    blacklist_clause!(add_clause, "language", &filter.blacklist.language);
+    if !normalize_languages {
+        blacklist_clause!(add_clause, "language", &filter.blacklist.language);
+    }
     blacklist_clause!(
         add_clause,
         "ruffleSupport",
         &filter.blacklist.ruffle_support
     );
+    blacklist_clause!(
+        add_clause,
+        "activeGameConfigOwner",
+        &filter.blacklist.active_game_config_owner
+    );
+
+    // Normalized languages are stored as "en; ja", match whole codes within the list
+    if normalize_languages {
+        let language_lists = [
+            (&filter.whitelist.language, false),
+            (&filter.exact_whitelist.language, false),
+            (&filter.blacklist.language, true),
+            (&filter.exact_blacklist.language, true),
+        ];
+        for (values, blacklist) in language_lists {
+            if let Some(value_list) = values {
+                let codes: Vec<String> = value_list.iter().flat_map(|v| crate::util::normalize_languages(v)).collect();
+                if codes.is_empty() {
+                    continue;
+                }
+                let comparator = match blacklist {
+                    true => "= 0",
+                    false => "> 0",
+                };
+                let joiner = match blacklist || !filter.match_any {
+                    true => " AND ",
+                    false => " OR ",
+                };
+                let mut code_clauses = vec![];
+                for code in codes {
+                    code_clauses.push(format!("instr('; ' || game.language || ';', ?) {}", comparator));
+                    params.push(SearchParam::String(format!("; {};", code)));
+                }
+                where_clauses.push(format!("({})", code_clauses.join(joiner)));
+            }
+        }
+    }
 
     let mut id_clause = |values: &Option<Vec<String>>, exact: bool, blacklist: bool| {
         if let Some(value_list) = values {
@@ -1145,8 +1668,9 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
                     false => "IN",
                 };
                 where_clauses.push(format!("(game.id {} rarray(?) OR game.id {} (SELECT id FROM game_redirect WHERE sourceId IN rarray(?)))", comparator, comparator));
-                params.push(SearchParam::StringVec(value_list.clone()));
-                params.push(SearchParam::StringVec(value_list.clone()));
+                let ids = SearchParam::string_vec(value_list);
+                params.push(ids.clone());
+                params.push(ids);
             } else {
                 for value in value_list {
                     if value.len() == 36 {
@@ -1164,7 +1688,10 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
                             false => "LIKE",
                         };
                         where_clauses.push(format!("(game.id {} ?)", comparator));
-                        let p = format!("%{}%", value);
+                        let p = match value.strip_suffix('*') {
+                            Some(prefix) => format!("{}%", prefix),
+                            None => format!("%{}%", value),
+                        };
                         params.push(SearchParam::String(p));
                     }
                 }
@@ -1175,11 +1702,14 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
     id_clause(&filter.exact_whitelist.id, true, false);
     id_clause(&filter.exact_blacklist.id, true, true);
     id_clause(&filter.whitelist.id, false, false);
-    id_clause(&filter.blacklist.id, false, false);
+    id_clause(&filter.blacklist.id, false, true);
 
     let mut add_tagged_clause =
         |tag_name: &str, values: &Option<Vec<String>>, exact: bool, blacklist: bool| {
             if let Some(value_list) = values {
+                if value_list.is_empty() {
+                    return;
+                }
                 let comparator = match blacklist {
                     true => "NOT IN",
                     false => "IN",
@@ -1188,12 +1718,12 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
                 // Exact OR - else - Inexact OR / Inexact AND / Exact AND
                 if exact && filter.match_any {
                     // Must be an exact OR
-                    params.push(SearchParam::StringVec(value_list.clone()));
+                    params.push(SearchParam::string_vec(value_list));
 
                     let tag_query = format!(
                         "game.id {} (SELECT gameId FROM game_{}s_{} WHERE {}Id IN (
-                SELECT {}Id FROM {}_alias WHERE name IN rarray(?)))",
-                        comparator, tag_name, tag_name, tag_name, tag_name, tag_name
+                SELECT {}Id FROM {}_alias WHERE name{} IN rarray(?)))",
+                        comparator, tag_name, tag_name, tag_name, tag_name, tag_name, collation
                     );
 
                     where_clauses.push(tag_query);
@@ -1203,12 +1733,12 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
                     // Add parameters
                     if exact {
                         for value in value_list {
-                            inner_tag_queries.push("name = ?");
+                            inner_tag_queries.push(format!("name{} = ?", collation));
                             params.push(SearchParam::String(value.clone()));
                         }
                     } else {
                         for value in value_list {
-                            inner_tag_queries.push("name LIKE ?");
+                            inner_tag_queries.push("name LIKE ?".to_owned());
                             let p = format!("%{}%", value);
                             params.push(SearchParam::String(p));
                         }
@@ -1276,7 +1806,7 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
                         ),
                         (true, true) | (false, true) => format!(
                             "game.id {} (SELECT gameId FROM game_{}s_{} WHERE {}Id IN (
-                    SELECT {}Id FROM {}_alias WHERE name IN {}))",
+                    SELECT {}Id FROM {}_alias WHERE ({})))",
                             comparator,
                             tag_name,
                             tag_name,
@@ -1312,18 +1842,19 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
                     (false, true) => "=",
                     (false, false) => "LIKE",
                 };
+                // Wildcards in the terms match literally
+                let escape = if exact { "" } else { " ESCAPE '\\'" };
 
                 if blacklist {
                     let mut inner_clauses = vec![];
                     for value in value_list {
                         let mut value_clauses = vec![];
                         for field_name in field_names.clone() {
-                            value_clauses.push(format!("game.{} {} ?", field_name, comparator));
+                            value_clauses.push(format!("game.{} {} ?{}", field_name, comparator, escape));
                             if exact {
                                 params.push(SearchParam::String(value.clone()));
                             } else {
-                                let p = format!("%{}%", value);
-                                params.push(SearchParam::String(p));
+                                params.push(SearchParam::String(like_contains(value)));
                             }
                         }
                         inner_clauses.push(format!("({})", &value_clauses.join(" OR ")));
@@ -1333,12 +1864,11 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
                     for value in value_list {
                         let mut value_clauses = vec![];
                         for field_name in field_names.clone() {
-                            value_clauses.push(format!("game.{} {} ?", field_name, comparator));
+                            value_clauses.push(format!("game.{} {} ?{}", field_name, comparator, escape));
                             if exact {
                                 params.push(SearchParam::String(value.clone()));
                             } else {
-                                let p = format!("%{}%", value);
-                                params.push(SearchParam::String(p));
+                                params.push(SearchParam::String(like_contains(value)));
                             }
                         }
                         where_clauses.push(format!("({})", &value_clauses.join(" OR ")));
@@ -1355,17 +1885,17 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
         false,
     );
     add_multi_clause(
-        vec![
-            "title",
-            "alternateTitles",
-            "developer",
-            "publisher",
-            "series",
-        ],
+        column_names(&GENERIC_TEXT_FIELDS),
         &filter.whitelist.generic,
         false,
         false,
     );
+    add_multi_clause(
+        column_names(&GENERIC_TEXT_FIELDS),
+        &filter.exact_whitelist.generic,
+        true,
+        false,
+    );
 
     // blacklist
     add_multi_clause(
@@ -1375,17 +1905,33 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
         true,
     );
     add_multi_clause(
-        vec![
-            "title",
-            "alternateTitles",
-            "developer",
-            "publisher",
-            "series",
-        ],
+        column_names(&GENERIC_TEXT_FIELDS),
         &filter.blacklist.generic,
         false,
         true,
     );
+    add_multi_clause(
+        column_names(&GENERIC_TEXT_FIELDS),
+        &filter.exact_blacklist.generic,
+        true,
+        true,
+    );
+
+    // Terms are always ANDed together, even when the rest of the filter matches any
+    if let Some(terms) = &filter.any_text {
+        let mut term_clauses = vec![];
+        for term in terms {
+            let mut value_clauses = vec![];
+            for field in ANY_TEXT_FIELDS.iter() {
+                value_clauses.push(format!("{} LIKE ? ESCAPE '\\'", slim_column(field)));
+                params.push(SearchParam::String(like_contains(term)));
+            }
+            term_clauses.push(format!("({})", value_clauses.join(" OR ")));
+        }
+        if term_clauses.len() > 0 {
+            where_clauses.push(format!("({})", term_clauses.join(" AND ")));
+        }
+    }
 
     let mut add_joint_game_data_clause =
         |field_name: &str,
@@ -1758,6 +2304,11 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
             }
         };
 
+    // Games without a parseable year have a NULL releaseYear, which never matches a comparison
+    add_compare_counter_clause("releaseYear", KeyChar::LOWER, &filter.lower_than.release_year);
+    add_compare_counter_clause("releaseYear", KeyChar::HIGHER, &filter.higher_than.release_year);
+    add_compare_counter_clause("releaseYear", KeyChar::EQUALS, &filter.equal_to.release_year);
+
     add_compare_counter_clause("playtime", KeyChar::LOWER, &filter.lower_than.playtime);
     add_compare_counter_clause("playtime", KeyChar::HIGHER, &filter.higher_than.playtime);
     add_compare_counter_clause("playtime", KeyChar::EQUALS, &filter.equal_to.playtime);
@@ -1770,6 +2321,35 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
     );
     add_compare_counter_clause("playCounter", KeyChar::EQUALS, &filter.equal_to.playcount);
 
+    // User data lives in its own table, games without a row are unrated
+    let mut add_user_rating_clause = |comparator: &str, filter: &Option<i64>| {
+        if let Some(f) = filter {
+            where_clauses.push(format!(
+                "(SELECT userRating FROM game_user_data WHERE gameId = game.id) {} ?",
+                comparator
+            ));
+            params.push(SearchParam::Integer64(f.clone()));
+        }
+    };
+
+    add_user_rating_clause("<", &filter.lower_than.user_rating);
+    add_user_rating_clause(">", &filter.higher_than.user_rating);
+    add_user_rating_clause("=", &filter.equal_to.user_rating);
+
+    let mut add_installed_size_clause = |comparator: &str, filter: &Option<i64>| {
+        if let Some(f) = filter {
+            where_clauses.push(format!(
+                "(SELECT IFNULL(SUM(size), 0) FROM game_data WHERE gameId = game.id AND presentOnDisk = 1) {} ?",
+                comparator
+            ));
+            params.push(SearchParam::Integer64(f.clone()));
+        }
+    };
+
+    add_installed_size_clause("<", &filter.lower_than.installed_size);
+    add_installed_size_clause(">", &filter.higher_than.installed_size);
+    add_installed_size_clause("=", &filter.equal_to.installed_size);
+
     // Installed clause
     if let Some(val) = filter.bool_comp.installed {
         where_clauses.push(
@@ -1779,6 +2359,31 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
         params.push(SearchParam::Boolean(val));
     }
 
+    // Add app clauses, each checks for at least one matching add app
+    let add_app_conditions = [
+        ("autoRunBefore = 1", filter.bool_comp.add_app_auto_run_before),
+        ("waitForExit = 1", filter.bool_comp.add_app_wait_for_exit),
+        ("name = 'Extras'", filter.bool_comp.add_app_extras),
+    ];
+    for (condition, val) in add_app_conditions {
+        if let Some(val) = val {
+            where_clauses.push(format!(
+                "{}EXISTS (SELECT 1 FROM additional_app WHERE parentGameId = game.id AND {})",
+                if val { "" } else { "NOT " },
+                condition
+            ));
+        }
+    }
+
+    // Favorite clause, games without user data are treated as not favorited
+    if let Some(val) = filter.bool_comp.favorite {
+        where_clauses.push(
+            "IFNULL((SELECT favorite FROM game_user_data WHERE gameId = game.id), 0) = ?"
+                .to_owned(),
+        );
+        params.push(SearchParam::Boolean(val));
+    }
+
     // Remove any cases of "()" from where_clauses
 
     where_clauses = where_clauses.into_iter().filter(|s| s != "()").collect();
@@ -1790,7 +2395,49 @@ fn build_filter_query(filter: &GameFilter, params: &mut Vec<SearchParam>) -> Str
     }
 }
 
-fn format_query(query: &str, substitutions: Vec<SearchParam>) -> String {
+/// Describes a built query for the debug log, see `set_debug_raw_queries` and `set_debug_param_max_length`
+pub fn query_log(query: &str, params: &[SearchParam]) -> String {
+    let max_length = crate::debug_param_max_length();
+    if crate::debug_raw_queries() {
+        let summary: Vec<String> = params.iter().map(|p| summarize_param(p, max_length)).collect();
+        format!("{}\nparams: [{}]", query, summary.join(", "))
+    } else {
+        format_query(query, params, max_length)
+    }
+}
+
+/// Quotes a parameter for a logged query, cutting it to `max_length` characters with a count of what it held
+pub fn summarize_param(param: &SearchParam, max_length: usize) -> String {
+    let (value, count) = match param {
+        SearchParam::StringVec(m) => {
+            // Only render as much of a long list as will be shown
+            let mut value = String::new();
+            for v in m.iter() {
+                if value.chars().count() > max_length {
+                    break;
+                }
+                if !value.is_empty() {
+                    value.push_str("', '");
+                }
+                if let Value::Text(s) = v {
+                    value.push_str(s);
+                }
+            }
+            (value, format!("[{} values]", m.len()))
+        }
+        SearchParam::String(s) => (s.clone(), format!("[{} chars]", s.chars().count())),
+        _ => (param.to_string(), String::new()),
+    };
+    if value.chars().count() > max_length {
+        let cut: String = value.chars().take(max_length).collect();
+        format!("'{}…' {}", cut, count)
+    } else {
+        format!("'{}'", value)
+    }
+}
+
+/// Indents `query` and fills in its placeholders, each cut to `max_length` characters
+pub fn format_query(query: &str, substitutions: &[SearchParam], max_length: usize) -> String {
     let mut formatted_query = String::new();
     let mut trim_mode = false;
     let mut indent = 0;
@@ -1828,8 +2475,7 @@ fn format_query(query: &str, substitutions: Vec<SearchParam>) -> String {
             }
             '?' => {
                 if let Some(subst) = substitution_iter.next() {
-                    let wrapped_subst = format!("'{}'", subst);
-                    formatted_query.push_str(&wrapped_subst);
+                    formatted_query.push_str(&summarize_param(subst, max_length));
                 } else {
                     // If there are no more substitutions, keep the '?' or handle as needed
                     formatted_query.push(ch);
@@ -1876,7 +2522,6 @@ pub fn new_tag_filter_index(conn: &Connection, search: &mut GameSearch) -> Resul
     // Allow use of rarray() in SQL queries
     rusqlite::vtab::array::load_module(conn)?;
 
-    search.limit = 9999999999999999;
     search.filter = GameFilter::default();
     search.filter.match_any = true;
 
@@ -1923,7 +2568,7 @@ pub fn new_tag_filter_index(conn: &Connection, search: &mut GameSearch) -> Resul
 
     conn.execute("DELETE FROM tag_filter_index", ())?; // Empty existing index
 
-    let (query, params) = build_search_query(search, TAG_FILTER_INDEX_QUERY);
+    let (query, params) = build_limited_search_query(search, TAG_FILTER_INDEX_QUERY, None);
 
     // Convert the parameters array to something rusqlite understands
     let params_as_refs: Vec<&dyn rusqlite::ToSql> =
@@ -1931,7 +2576,7 @@ pub fn new_tag_filter_index(conn: &Connection, search: &mut GameSearch) -> Resul
 
     debug_println!(
         "new filtered tag query - \n{}",
-        format_query(&query, params.clone())
+        query_log(&query, &params)
     );
 
     let mut stmt = conn.prepare(query.as_str())?;
@@ -1948,6 +2593,95 @@ pub fn new_tag_filter_index(conn: &Connection, search: &mut GameSearch) -> Resul
     Ok(())
 }
 
+/// Compares the games matched by two searches, without loading either result set.
+/// Paging and order are ignored, `limit` examples are returned from each side, capped by `DIFF_SEARCHES_MAX`.
+pub fn search_diff(conn: &Connection, a: &GameSearch, b: &GameSearch, limit: i64) -> Result<SearchDiff> {
+    // Allow use of rarray() in SQL queries
+    rusqlite::vtab::array::load_module(conn)?;
+
+    let limit = limit.clamp(0, DIFF_SEARCHES_MAX / 2);
+    let (a_query, a_params) = build_limited_search_query(&id_set_search(a), ID_QUERY, None);
+    let (b_query, b_params) = build_limited_search_query(&id_set_search(b), ID_QUERY, None);
+
+    let mut diff = SearchDiff::default();
+    for (first, first_params, second, second_params, only_in_first) in [
+        (&a_query, &a_params, &b_query, &b_params, true),
+        (&b_query, &b_params, &a_query, &a_params, false),
+    ] {
+        let mut params: Vec<SearchParam> = first_params.clone();
+        params.extend(second_params.iter().cloned());
+        let params_as_refs: Vec<&dyn rusqlite::ToSql> =
+            params.iter().map(|s| s as &dyn rusqlite::ToSql).collect();
+        let except = format!("SELECT id FROM ({}) EXCEPT SELECT id FROM ({})", first, second);
+
+        let query = format!("SELECT COUNT(*) FROM ({})", except);
+        debug_println!("search diff count query - \n{}", query_log(&query, &params));
+        let count = conn.query_row(&query, params_as_refs.as_slice(), |row| row.get::<_, i64>(0))?;
+
+        let query = format!(
+            "SELECT game.id FROM game WHERE game.id IN ({}) ORDER BY game.title, game.id LIMIT {}",
+            except, limit
+        );
+        let mut stmt = conn.prepare(&query)?;
+        let ids = stmt
+            .query_map(params_as_refs.as_slice(), |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<String>>>()?;
+        let mut games = vec![];
+        for id in ids {
+            if let Some(game) = find_slim(conn, &id)? {
+                games.push(game);
+            }
+        }
+
+        if only_in_first {
+            diff.only_in_a_count = count;
+            diff.only_in_a = games;
+        } else {
+            diff.only_in_b_count = count;
+            diff.only_in_b = games;
+        }
+    }
+
+    Ok(diff)
+}
+
+/// Unpaged copy of a search selecting every matching game. The tag filter is folded into the filter,
+/// since the two searches of a diff may filter different tags and there's only one tag filter index.
+fn id_set_search(search: &GameSearch) -> GameSearch {
+    let mut id_search = search.clone();
+    id_search.offset = None;
+    id_search.order = GameSearchOrder {
+        column: GameSearchSortable::TITLE,
+        direction: GameSearchDirection::ASC,
+    };
+    if let Some(tags) = id_search.with_tag_filter.take() {
+        if tags.len() > 0 {
+            let mut tag_filter = GameFilter::default();
+            tag_filter.exact_blacklist.tags = Some(tags);
+            tag_filter.match_any = true;
+            let mut filter = GameFilter::default();
+            filter.subfilters = vec![id_search.filter, tag_filter];
+            id_search.filter = filter;
+        }
+    }
+    id_search
+}
+
+/// Rebuilds the tag filter index for the search's `with_tag_filter`, if it has one and the index is stale
+pub fn update_tag_filter_index(conn: &Connection, search: &GameSearch) -> Result<()> {
+    if let Some(tags) = &search.with_tag_filter {
+        if !tags.is_empty() {
+            // new_tag_filter_index builds its own filter from with_tag_filter
+            let mut filtered_search = GameSearch {
+                with_tag_filter: Some(tags.to_vec()),
+                ..GameSearch::default()
+            };
+            new_tag_filter_index(conn, &mut filtered_search)?;
+        }
+    }
+    Ok(())
+}
+
 pub fn mark_index_dirty(conn: &Connection) -> Result<()> {
     conn.execute("UPDATE tag_filter_index_info SET dirty = 1", ())?;
     Ok(())
@@ -1977,11 +2711,32 @@ pub struct ElementPosition {
 pub struct ParsedInput {
     pub search: GameSearch,
     pub positions: Vec<ElementPosition>,
+    pub terms: Vec<ParsedTerm>,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone)]
+pub struct ParsedTerm {
+    /// Filter field the value was applied to, e.g `title` or `tags`, or `generic` for free text
+    pub field: String,
+    /// Value as used in the filter, after quote and prefix handling
+    pub value: String,
+    pub negated: bool,
+    pub exact: bool,
+    /// Byte offsets of the whole term in the input, including any modifiers, key and quotes
+    pub start: i32,
+    pub end: i32,
 }
 
+/// Parses search box input. Terms between standalone `(` and `)` tokens form a subfilter, and an `OR` token
+/// makes its group match any of its terms. Quoted values escape `"` and `\` with a backslash.
 pub fn parse_user_input(input: &str) -> ParsedInput {
     let mut search = GameSearch::default();
     let mut filter = ForcedGameFilter::default();
+    let mut subfilters: Vec<GameFilter> = vec![];
+    let mut match_any = false;
+    // Enclosing groups of the one being parsed, outermost first
+    let mut parents: Vec<ParsedGroup> = vec![];
 
     let mut capturing_quotes = false;
     let mut working_key = String::new();
@@ -1990,12 +2745,50 @@ pub fn parse_user_input(input: &str) -> ParsedInput {
     let mut negative = false;
 
     let mut positions = Vec::new();
+    let mut terms = Vec::new();
     let mut current_pos = 0;
-
-    for raw_token in input.split(" ") {
+    let mut term_start = 0;
+    // Where the key char or value of a quoted value spanning several tokens starts
+    let mut quoted_start: Option<i32> = None;
+    // Whether the working value came from quotes, and so may hold escaped quotes and backslashes
+    let mut quoted = false;
+
+    let token_count = input.split(" ").count();
+    for (token_idx, raw_token) in input.split(" ").enumerate() {
+        // An unterminated quote is closed by the end of the input
+        let last_token = token_idx + 1 == token_count;
         // Value on the same scope as token to append to
         let mut token = raw_token.to_owned();
         let mut token_start = current_pos.try_into().unwrap_or(0);
+        if !capturing_quotes && working_value == "" {
+            term_start = current_pos;
+            quoted = false;
+        }
+
+        // Groups and OR are standalone tokens outside of quotes
+        if !capturing_quotes && working_value == "" && matches!(raw_token, "(" | ")" | "OR") {
+            match raw_token {
+                "(" => {
+                    parents.push(ParsedGroup {
+                        filter: std::mem::take(&mut filter),
+                        subfilters: std::mem::take(&mut subfilters),
+                        match_any: std::mem::take(&mut match_any),
+                    });
+                }
+                ")" => {
+                    close_group(&mut parents, &mut filter, &mut subfilters, &mut match_any);
+                }
+                _ => match_any = true,
+            }
+            positions.push(ElementPosition {
+                element: ElementType::MODIFIER,
+                value: raw_token.to_owned(),
+                start: token_start,
+                end: token_start + raw_token.len().try_into().unwrap_or(0),
+            });
+            current_pos += raw_token.len() + 1;
+            continue;
+        }
         let mut _t = "".to_owned();
         debug_println!("token {}", token);
         // Handle continued value capture if needed
@@ -2063,6 +2856,10 @@ pub fn parse_user_input(input: &str) -> ParsedInput {
             token = token.strip_prefix('"').unwrap().to_owned();
             // Opening quote
             capturing_quotes = true;
+            quoted = true;
+            if working_value == "" {
+                quoted_start = Some(token_start);
+            }
         }
 
         if capturing_quotes {
@@ -2076,7 +2873,7 @@ pub fn parse_user_input(input: &str) -> ParsedInput {
             }
         }
 
-        if token.ends_with('"') && capturing_quotes {
+        if capturing_quotes && closes_quote(&token) {
             // Closing quote
             capturing_quotes = false;
             // Remove quote at end of working value, if doesn't exist then it's a broken quoted value
@@ -2084,9 +2881,12 @@ pub fn parse_user_input(input: &str) -> ParsedInput {
         }
 
         if capturing_quotes {
-            // Still in capture mode, get next token
-            current_pos += raw_token.len() + 1;
-            continue;
+            if !last_token {
+                // Still in capture mode, get next token
+                current_pos += raw_token.len() + 1;
+                continue;
+            }
+            capturing_quotes = false;
         }
 
         if working_value == "" {
@@ -2120,7 +2920,7 @@ pub fn parse_user_input(input: &str) -> ParsedInput {
             }
 
             // Single value, must be value
-            if token.starts_with('"') && token.ends_with('"') {
+            if token.starts_with('"') && closes_quote(&token) {
                 // Special case for empty value
                 if token.len() == 2 {
                     if working_key != "" {
@@ -2129,6 +2929,7 @@ pub fn parse_user_input(input: &str) -> ParsedInput {
                     }
                 } else {
                     // Fully inside quotes
+                    quoted = true;
                     token = token.strip_prefix('"').unwrap_or_else(|| "").to_owned();
                     token = token.strip_suffix('"').unwrap_or_else(|| "").to_owned();
                     working_value = token.to_owned();
@@ -2137,15 +2938,24 @@ pub fn parse_user_input(input: &str) -> ParsedInput {
                 if token.starts_with('"') {
                     // Starts quotes
                     token = token.strip_prefix('"').unwrap().to_owned();
-                    capturing_quotes = true;
-                    working_value = token.to_owned();
-                    continue;
+                    quoted = true;
+                    if !last_token {
+                        capturing_quotes = true;
+                        working_value = token.to_owned();
+                        quoted_start = Some(token_start);
+                        current_pos += raw_token.len() + 1;
+                        continue;
+                    }
                 }
                 working_value = token.to_owned();
             }
         }
 
         if working_value != "" {
+            let raw_value_len = working_value.len();
+            if quoted {
+                working_value = unescape_quoted(&working_value);
+            }
             let mut exact = false;
             if working_key != "" {
                 if working_value == REPLACEMENT {
@@ -2159,6 +2969,10 @@ pub fn parse_user_input(input: &str) -> ParsedInput {
                             _ => (),
                         }
                     }
+                    // Full ids are always exact so redirects resolve, a trailing * searches by prefix
+                    if resolve_string_field(&working_key) == Some("id") && !working_value.ends_with('*') {
+                        exact = true;
+                    }
                 }
             }
 
@@ -2178,6 +2992,10 @@ pub fn parse_user_input(input: &str) -> ParsedInput {
             };
             let value = working_value.clone();
 
+            if let Some(start) = quoted_start.take() {
+                token_start = start;
+            }
+
             if let Some(kc) = &working_key_char {
                 positions.push(ElementPosition {
                     element: ElementType::KEYCHAR,
@@ -2193,11 +3011,13 @@ pub fn parse_user_input(input: &str) -> ParsedInput {
                 element: ElementType::VALUE,
                 value: working_value.clone(),
                 start: token_start,
-                end: token_start + working_value.len().try_into().unwrap_or(0),
+                end: token_start + raw_value_len.try_into().unwrap_or(0),
             });
 
             // Handle boolean comparisons
             let mut processed: bool = true;
+            let mut term_field = String::from("generic");
+            let mut term_value = working_value.clone();
             match working_key.to_lowercase().as_str() {
                 "installed" => {
                     let mut value = !(working_value.to_lowercase() == "no"
@@ -2208,6 +3028,32 @@ pub fn parse_user_input(input: &str) -> ParsedInput {
                     }
 
                     filter.bool_comp.installed = Some(value);
+                    term_field = "installed".to_owned();
+                }
+                "favorite" | "fav" => {
+                    let lower = working_value.to_lowercase();
+                    let mut value = !(lower == "no" || lower == "false" || lower == "0");
+                    if negative {
+                        value = !value;
+                    }
+
+                    filter.bool_comp.favorite = Some(value);
+                    term_field = "favorite".to_owned();
+                }
+                "hasmessage" | "hasextras" => {
+                    let lower = working_value.to_lowercase();
+                    let mut value = !(lower == "no" || lower == "false" || lower == "0");
+                    if negative {
+                        value = !value;
+                    }
+
+                    if working_key.eq_ignore_ascii_case("hasmessage") {
+                        filter.bool_comp.add_app_auto_run_before = Some(value);
+                        term_field = "add_app_auto_run_before".to_owned();
+                    } else {
+                        filter.bool_comp.add_app_extras = Some(value);
+                        term_field = "add_app_extras".to_owned();
+                    }
                 }
                 _ => {
                     processed = false;
@@ -2218,84 +3064,67 @@ pub fn parse_user_input(input: &str) -> ParsedInput {
             if !processed {
                 if let Some(kc) = &working_key_char {
                     processed = true;
-                    match kc {
-                        KeyChar::LOWER => {
-                            let value = coerce_to_i64(&working_value);
-                            match working_key.to_lowercase().as_str() {
-                                "tags" => filter.lower_than.tags = Some(value),
-                                "platforms" => filter.lower_than.platforms = Some(value),
-                                "dateadded" | "da" => {
-                                    filter.lower_than.date_added = Some(working_value.clone())
-                                }
-                                "datemodified" | "dm" => {
-                                    filter.lower_than.date_modified = Some(working_value.clone())
-                                }
-                                "releasedate" | "rd" => {
-                                    filter.lower_than.release_date = Some(working_value.clone())
-                                }
-                                "gamedata" | "gd" => filter.lower_than.game_data = Some(value),
-                                "addapps" | "aa" => filter.lower_than.add_apps = Some(value),
-                                "playtime" | "pt" => filter.lower_than.playtime = Some(value),
-                                "playcount" | "pc" => filter.lower_than.playcount = Some(value),
-                                "lastplayed" | "lp" => {
-                                    filter.lower_than.last_played = Some(working_value.clone())
+                    let size_field = resolve_size_field(&working_key);
+                    let size_filter = match kc {
+                        KeyChar::LOWER => &mut filter.lower_than,
+                        KeyChar::HIGHER => &mut filter.higher_than,
+                        KeyChar::MATCHES | KeyChar::EQUALS => &mut filter.equal_to,
+                    };
+                    let value = coerce_to_i64(&working_value);
+                    // Inclusive year ranges, e.g year:1995..2000, either side can be left open, e.g year:1995..
+                    let year_bound = |value: &str| match value.trim() {
+                        "" => None,
+                        value => Some(coerce_to_i64(value)),
+                    };
+                    let year_range = match (size_field, kc) {
+                        (Some("release_year"), KeyChar::MATCHES) => working_value
+                            .split_once("..")
+                            .map(|(start, end)| (year_bound(start), year_bound(end))),
+                        _ => None,
+                    };
+                    match size_field {
+                        Some("release_year") => match year_range {
+                            Some((start, end)) => {
+                                if let Some(start) = start {
+                                    filter.higher_than.release_year = Some(start.saturating_sub(1));
                                 }
-                                _ => {
-                                    processed = false;
+                                if let Some(end) = end {
+                                    filter.lower_than.release_year = Some(end.saturating_add(1));
                                 }
                             }
+                            None => size_filter.release_year = Some(value),
+                        },
+                        Some("tags") => size_filter.tags = Some(value),
+                        Some("platforms") => size_filter.platforms = Some(value),
+                        Some("date_added") => size_filter.date_added = Some(working_value.clone()),
+                        Some("date_modified") => {
+                            size_filter.date_modified = Some(working_value.clone())
                         }
-                        KeyChar::HIGHER => {
-                            let value = coerce_to_i64(&working_value);
-                            match working_key.to_lowercase().as_str() {
-                                "tags" => filter.higher_than.tags = Some(value),
-                                "platforms" => filter.higher_than.platforms = Some(value),
-                                "dateadded" | "da" => {
-                                    filter.higher_than.date_added = Some(working_value.clone())
-                                }
-                                "datemodified" | "dm" => {
-                                    filter.higher_than.date_modified = Some(working_value.clone())
-                                }
-                                "releasedate" | "rd" => {
-                                    filter.higher_than.release_date = Some(working_value.clone())
-                                }
-                                "gamedata" | "gd" => filter.higher_than.game_data = Some(value),
-                                "addapps" | "aa" => filter.higher_than.add_apps = Some(value),
-                                "playtime" | "pt" => filter.higher_than.playtime = Some(value),
-                                "playcount" | "pc" => filter.higher_than.playcount = Some(value),
-                                "lastplayed" | "lp" => {
-                                    filter.higher_than.last_played = Some(working_value.clone())
-                                }
-                                _ => {
-                                    processed = false;
-                                }
-                            }
+                        Some("release_date") => {
+                            size_filter.release_date = Some(working_value.clone())
                         }
-                        KeyChar::MATCHES | KeyChar::EQUALS => {
-                            let value = coerce_to_i64(&working_value);
-                            match working_key.to_lowercase().as_str() {
-                                "tags" => filter.equal_to.tags = Some(value),
-                                "platforms" => filter.equal_to.platforms = Some(value),
-                                "dateadded" | "da" => {
-                                    filter.equal_to.date_added = Some(working_value.clone())
-                                }
-                                "datemodified" | "dm" => {
-                                    filter.equal_to.date_modified = Some(working_value.clone())
-                                }
-                                "releasedate" | "rd" => {
-                                    filter.equal_to.release_date = Some(working_value.clone())
-                                }
-                                "gamedata" | "gd" => filter.equal_to.game_data = Some(value),
-                                "addapps" | "aa" => filter.equal_to.add_apps = Some(value),
-                                "playtime" | "pt" => filter.equal_to.playtime = Some(value),
-                                "playcount" | "pc" => filter.equal_to.playcount = Some(value),
-                                "lastplayed" | "lp" => {
-                                    filter.equal_to.last_played = Some(working_value.clone())
-                                }
-                                _ => {
-                                    processed = false;
-                                }
-                            }
+                        Some("game_data") => size_filter.game_data = Some(value),
+                        Some("add_apps") => size_filter.add_apps = Some(value),
+                        Some("playtime") => size_filter.playtime = Some(value),
+                        Some("playcount") => size_filter.playcount = Some(value),
+                        Some("last_played") => size_filter.last_played = Some(working_value.clone()),
+                        Some("user_rating") => size_filter.user_rating = Some(value),
+                        Some("installed_size") => {
+                            size_filter.installed_size = Some(coerce_to_bytes(&working_value))
+                        }
+                        _ => {
+                            processed = false;
+                        }
+                    }
+
+                    if processed {
+                        let field = size_field.unwrap_or_default();
+                        term_field = field.to_owned();
+                        match field {
+                            "date_added" | "date_modified" | "release_date" | "last_played" => (),
+                            "release_year" if year_range.is_some() => (),
+                            "installed_size" => term_value = coerce_to_bytes(&working_value).to_string(),
+                            _ => term_value = value.to_string(),
                         }
                     }
                 }
@@ -2304,35 +3133,42 @@ pub fn parse_user_input(input: &str) -> ParsedInput {
             // Handle generics and string matchers
             if !processed {
                 // Has a complete value, add to filter
-                match working_key.to_lowercase().as_str() {
-                    "id" => list.id.push(value),
-                    "lib" | "library" => list.library.push(value),
-                    "title" => list.title.push(value),
-                    "dev" | "developer" => list.developer.push(value),
-                    "pub" | "publisher" => list.publisher.push(value),
-                    "series" => list.series.push(value),
-                    "tag" => list.tags.push(value),
-                    "plat" | "platform" => list.platforms.push(value),
-                    "mode" | "playmode" => list.play_mode.push(value),
-                    "status" => list.status.push(value),
-                    "note" | "notes" => list.notes.push(value),
-                    "src" | "source" => list.source.push(value),
-                    "od" | "desc" | "description" | "originaldescription" => {
-                        list.original_description.push(value)
+                let string_field = resolve_string_field(&working_key);
+                match string_field {
+                    Some("id") => list.id.push(value),
+                    Some("generic") => list.generic.push(value),
+                    Some("library") => list.library.push(value),
+                    Some("title") => list.title.push(value),
+                    Some("developer") => list.developer.push(value),
+                    Some("publisher") => list.publisher.push(value),
+                    Some("series") => list.series.push(value),
+                    Some("tags") => list.tags.push(value),
+                    Some("platforms") => list.platforms.push(value),
+                    Some("play_mode") => list.play_mode.push(value),
+                    Some("status") => list.status.push(value),
+                    Some("notes") => list.notes.push(value),
+                    Some("source") => list.source.push(value),
+                    Some("original_description") => list.original_description.push(value),
+                    Some("language") => list.language.push(value),
+                    Some("application_path") => list.application_path.push(value),
+                    Some("launch_command") => list.launch_command.push(value),
+                    Some("ruffle_support") => {
+                        term_value = value.to_lowercase();
+                        list.ruffle_support.push(value.to_lowercase())
                     }
-                    "lang" | "language" => list.language.push(value),
-                    "ap" | "path" | "app" | "applicationpath" => list.application_path.push(value),
-                    "lc" | "launchcommand" => list.launch_command.push(value),
-                    "ruffle" | "rufflesupport" => list.ruffle_support.push(value.to_lowercase()),
                     _ => match &working_key_char {
                         Some(kc) => {
                             let ks: String = kc.clone().into();
                             let full_value = working_key.clone() + &ks + &value;
+                            term_value = full_value.clone();
                             list.generic.push(full_value);
                         }
                         None => list.generic.push(value),
                     },
                 }
+                if let Some(field) = string_field {
+                    term_field = field.to_owned();
+                }
 
                 match (negative, exact) {
                     (true, false) => filter.blacklist = list,
@@ -2342,16 +3178,293 @@ pub fn parse_user_input(input: &str) -> ParsedInput {
                 }
             }
 
+            terms.push(ParsedTerm {
+                field: term_field,
+                value: term_value,
+                negated: negative,
+                exact,
+                start: term_start.try_into().unwrap_or(0),
+                end: (current_pos + raw_token.len()).try_into().unwrap_or(0),
+            });
+
             negative = false;
             working_value.clear();
             working_key.clear();
+            working_key_char = None;
         }
         current_pos += raw_token.len() + 1;
     }
 
-    search.filter = (&filter).into();
+    // Unclosed groups are closed by the end of the input
+    while close_group(&mut parents, &mut filter, &mut subfilters, &mut match_any) {}
+    search.filter = ParsedGroup { filter, subfilters, match_any }.into();
+
+    ParsedInput {
+        search,
+        positions,
+        terms,
+    }
+}
+
+/// A parenthesized group of terms in user input, the whole input being the outermost group
+struct ParsedGroup {
+    filter: ForcedGameFilter,
+    subfilters: Vec<GameFilter>,
+    match_any: bool,
+}
+
+impl From<ParsedGroup> for GameFilter {
+    fn from(group: ParsedGroup) -> Self {
+        GameFilter {
+            subfilters: group.subfilters,
+            match_any: group.match_any,
+            ..group.filter.into()
+        }
+    }
+}
+
+/// Adds the group being parsed to its parent's subfilters and makes the parent current again.
+/// Returns false when there's no enclosing group to return to.
+fn close_group(
+    parents: &mut Vec<ParsedGroup>,
+    filter: &mut ForcedGameFilter,
+    subfilters: &mut Vec<GameFilter>,
+    match_any: &mut bool,
+) -> bool {
+    match parents.pop() {
+        Some(parent) => {
+            let group = ParsedGroup {
+                filter: std::mem::replace(filter, parent.filter),
+                subfilters: std::mem::replace(subfilters, parent.subfilters),
+                match_any: std::mem::replace(match_any, parent.match_any),
+            };
+            subfilters.push(group.into());
+            true
+        }
+        None => false,
+    }
+}
+
+/// Whether a token ends with a closing quote, rather than an escaped one
+fn closes_quote(token: &str) -> bool {
+    match token.strip_suffix('"') {
+        Some(rest) => (rest.len() - rest.trim_end_matches('\\').len()) % 2 == 0,
+        None => false,
+    }
+}
+
+/// Undoes `\"` and `\\` escapes in a quoted value, other backslashes are kept as typed
+fn unescape_quoted(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match (ch, chars.peek()) {
+            ('\\', Some(&next)) if next == '"' || next == '\\' => {
+                unescaped.push(next);
+                chars.next();
+            }
+            _ => unescaped.push(ch),
+        }
+    }
+    unescaped
+}
+
+/// Resolves a user input key to the name of the `FieldFilter` field it filters on
+fn resolve_string_field(key: &str) -> Option<&'static str> {
+    match key.to_lowercase().as_str() {
+        "id" => Some("id"),
+        "generic" => Some("generic"),
+        "lib" | "library" => Some("library"),
+        "title" => Some("title"),
+        "dev" | "developer" => Some("developer"),
+        "pub" | "publisher" => Some("publisher"),
+        "series" => Some("series"),
+        "tag" => Some("tags"),
+        "plat" | "platform" => Some("platforms"),
+        "mode" | "playmode" => Some("play_mode"),
+        "status" => Some("status"),
+        "note" | "notes" => Some("notes"),
+        "src" | "source" => Some("source"),
+        "od" | "desc" | "description" | "originaldescription" => Some("original_description"),
+        "lang" | "language" => Some("language"),
+        "ap" | "path" | "app" | "applicationpath" => Some("application_path"),
+        "lc" | "launchcommand" => Some("launch_command"),
+        "ruffle" | "rufflesupport" => Some("ruffle_support"),
+        _ => None,
+    }
+}
+
+/// Resolves a user input key to the name of the `SizeFilter` field it compares against
+fn resolve_size_field(key: &str) -> Option<&'static str> {
+    match key.to_lowercase().as_str() {
+        "tags" => Some("tags"),
+        "platforms" => Some("platforms"),
+        "dateadded" | "da" => Some("date_added"),
+        "datemodified" | "dm" => Some("date_modified"),
+        "releasedate" | "rd" => Some("release_date"),
+        "year" | "releaseyear" | "ry" => Some("release_year"),
+        "gamedata" | "gd" => Some("game_data"),
+        "addapps" | "aa" => Some("add_apps"),
+        "playtime" | "pt" => Some("playtime"),
+        "playcount" | "pc" => Some("playcount"),
+        "lastplayed" | "lp" => Some("last_played"),
+        "rating" | "userrating" => Some("user_rating"),
+        "installedsize" | "size" => Some("installed_size"),
+        _ => None,
+    }
+}
+
+/// Serializes a filter back into the syntax accepted by `parse_user_input`, parsing the query gives an equivalent filter.
+///
+/// Subfilters are written as groups between standalone `(` and `)` tokens, their terms joined by `OR` when `match_any`
+/// is set. Fails with `InvalidSearch` when the filter uses something the syntax can't express, e.g `any_text`.
+pub fn filter_to_query(filter: &GameFilter) -> error::Result<String> {
+    let terms = filter_terms(filter)?;
+    Ok(join_terms(&terms, filter.match_any))
+}
+
+fn join_terms(terms: &[String], match_any: bool) -> String {
+    terms.join(if match_any { " OR " } else { " " })
+}
+
+fn unexpressible<T>(what: &str) -> error::Result<T> {
+    error::InvalidSearchSnafu { reason: format!("{} can't be written as a query", what) }.fail()
+}
+
+/// Quotes a value so it's read back verbatim, whatever spaces, key chars or quotes it holds
+fn quote_value(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn filter_terms(filter: &GameFilter) -> error::Result<Vec<String>> {
+    if filter.any_text.as_ref().is_some_and(|terms| !terms.is_empty()) {
+        return unexpressible("any_text");
+    }
+    if filter.case_sensitive.is_some() {
+        return unexpressible("case_sensitive");
+    }
+    if filter.bool_comp.add_app_wait_for_exit.is_some() {
+        return unexpressible("add_app_wait_for_exit");
+    }
+
+    let mut terms = vec![];
+    append_field_terms(&filter.whitelist, "", false, &mut terms)?;
+    append_field_terms(&filter.exact_whitelist, "", true, &mut terms)?;
+    append_field_terms(&filter.blacklist, "-", false, &mut terms)?;
+    append_field_terms(&filter.exact_blacklist, "-", true, &mut terms)?;
+
+    append_size_terms(&filter.higher_than, ">", &mut terms);
+    append_size_terms(&filter.lower_than, "<", &mut terms);
+    append_size_terms(&filter.equal_to, ":", &mut terms);
+
+    if let Some(installed) = filter.bool_comp.installed {
+        terms.push(format!("{}installed:yes", if installed { "" } else { "-" }));
+    }
+    if let Some(favorite) = filter.bool_comp.favorite {
+        terms.push(format!("favorite:{}", if favorite { "yes" } else { "no" }));
+    }
+    if let Some(message) = filter.bool_comp.add_app_auto_run_before {
+        terms.push(format!("hasMessage:{}", message));
+    }
+    if let Some(extras) = filter.bool_comp.add_app_extras {
+        terms.push(format!("hasExtras:{}", extras));
+    }
+
+    for subfilter in &filter.subfilters {
+        let group = filter_terms(subfilter)?;
+        terms.push(match group.is_empty() {
+            true => "( )".to_owned(),
+            false => format!("( {} )", join_terms(&group, subfilter.match_any)),
+        });
+    }
+
+    Ok(terms)
+}
+
+fn append_field_terms(list: &FieldFilter, modifier: &str, exact: bool, terms: &mut Vec<String>) -> error::Result<()> {
+    if list.active_game_config_owner.as_ref().is_some_and(|values| !values.is_empty()) {
+        return unexpressible("active_game_config_owner");
+    }
+
+    for value in list.generic.iter().flatten() {
+        if value.is_empty() {
+            return unexpressible("an empty generic value");
+        }
+        // Quoted generics skip key detection, exact ones need the key to carry the key char
+        terms.push(format!("{}{}{}", modifier, if exact { "generic=" } else { "" }, quote_value(value)));
+    }
+
+    let fields = [
+        ("id", &list.id),
+        ("library", &list.library),
+        ("title", &list.title),
+        ("developer", &list.developer),
+        ("publisher", &list.publisher),
+        ("series", &list.series),
+        ("tag", &list.tags),
+        ("platform", &list.platforms),
+        ("playmode", &list.play_mode),
+        ("status", &list.status),
+        ("notes", &list.notes),
+        ("source", &list.source),
+        ("description", &list.original_description),
+        ("language", &list.language),
+        ("applicationpath", &list.application_path),
+        ("launchcommand", &list.launch_command),
+        ("rufflesupport", &list.ruffle_support),
+    ];
+    for (key, values) in fields {
+        for value in values.iter().flatten() {
+            let key_char = match (exact, value.is_empty()) {
+                // An empty quoted value is always parsed as an exact match
+                (true, true) => ":",
+                (false, true) => return unexpressible(&format!("an empty inexact {} value", key)),
+                (true, false) => "=",
+                (false, false) => ":",
+            };
+            // The parser makes ids exact unless they end with *, and lowercases ruffle support
+            if key == "id" && !exact && !value.ends_with('*') {
+                return unexpressible("an inexact id without a trailing *");
+            }
+            if key == "rufflesupport" && *value != value.to_lowercase() {
+                return unexpressible("a ruffle support value with upper case letters");
+            }
+            terms.push(format!("{}{}{}{}", modifier, key, key_char, quote_value(value)));
+        }
+    }
+
+    Ok(())
+}
+
+fn append_size_terms(size: &SizeFilter, key_char: &str, terms: &mut Vec<String>) {
+    let numbers = [
+        ("tags", size.tags),
+        ("platforms", size.platforms),
+        ("year", size.release_year),
+        ("gamedata", size.game_data),
+        ("addapps", size.add_apps),
+        ("playtime", size.playtime),
+        ("playcount", size.playcount),
+        ("rating", size.user_rating),
+        ("installedsize", size.installed_size),
+    ];
+    for (key, value) in numbers {
+        if let Some(value) = value {
+            terms.push(format!("{}{}{}", key, key_char, value));
+        }
+    }
 
-    ParsedInput { search, positions }
+    let dates = [
+        ("dateadded", &size.date_added),
+        ("datemodified", &size.date_modified),
+        ("releasedate", &size.release_date),
+        ("lastplayed", &size.last_played),
+    ];
+    for (key, value) in dates {
+        if let Some(value) = value {
+            terms.push(format!("{}{}{}", key, key_char, quote_value(value)));
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -2400,6 +3513,23 @@ fn earliest_key_char(s: &str) -> Option<KeyChar> {
     }
 }
 
+/// Converts a size like "1.5g" or "500MB" to bytes, units are powers of 1024
+fn coerce_to_bytes(input: &str) -> i64 {
+    let lower = input.trim().to_lowercase();
+    let lower = lower.strip_suffix('b').unwrap_or(&lower);
+    let (number, multiplier) = match lower.chars().last() {
+        Some('k') => (&lower[..lower.len() - 1], 1024_f64),
+        Some('m') => (&lower[..lower.len() - 1], 1024_f64.powi(2)),
+        Some('g') => (&lower[..lower.len() - 1], 1024_f64.powi(3)),
+        Some('t') => (&lower[..lower.len() - 1], 1024_f64.powi(4)),
+        _ => (lower, 1_f64),
+    };
+    match number.trim().parse::<f64>() {
+        Ok(num) => (num * multiplier) as i64,
+        Err(_) => 0,
+    }
+}
+
 fn coerce_to_i64(input: &str) -> i64 {
     // Substitute known replacements
     /* d - Seconds in a day
@@ -2432,7 +3562,7 @@ fn coerce_to_i64(input: &str) -> i64 {
         processed_input = re
             .replace_all(&processed_input, |caps: &Captures| {
                 let time_value: i64 = caps[1].parse().unwrap_or(0); // Convert the captured group to i64
-                (time_value * seconds).to_string() // Replace with time_value * seconds per unit
+                time_value.saturating_mul(seconds).to_string() // Replace with time_value * seconds per unit
             })
             .to_string();
     }
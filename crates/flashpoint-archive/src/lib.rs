@@ -1,29 +1,40 @@
-use std::{collections::HashMap, sync::{atomic::AtomicBool, mpsc, Arc}};
-use game::{search::{GameFilter, GameSearch, PageTuple}, AdditionalApp, Game, GameRedirect, PartialGame};
+use std::{cell::Cell, collections::HashMap, sync::{atomic::{AtomicBool, AtomicUsize}, mpsc, Arc, Mutex}, time::{Duration, Instant}};
+use game::{search::{GameFilter, GameGroup, GameSearch, GameSearchOrder, GameSearchRelations, GroupableField, PageTuple, SearchDiff}, AdditionalApp, ChangesReport, CreateFullGame, DateModifiedPolicy, DiskUsageReport, Game, GameRedirect, PartialGame, RebuildReport, RebuildScope, ValidationIssue};
 use game_data::{GameData, PartialGameData};
 use platform::PlatformAppPath;
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
-use rusqlite::Connection;
+use rusqlite::{Connection, TransactionBehavior};
 use snafu::ResultExt;
-use tag::{PartialTag, Tag, TagSuggestion};
+use tag::{taxonomy::{ImportMode, ImportReport, TagTaxonomy}, LooseTagAlias, PartialTag, Tag, TagRedirect, TagSuggestion};
 use tag_category::{TagCategory, PartialTagCategory};
-use chrono::Utc;
 use lazy_static::lazy_static;
 use crate::logger::EventManager;
+use clock::{Clock, SystemClock};
+use checkpoint::{CheckpointMode, CheckpointResult};
+use profile::PerformanceProfile;
 
 mod error;
 use error::{Error, Result};
-use update::{RemoteCategory, RemoteDeletedGamesRes, RemoteGamesRes, RemotePlatform, RemoteTag};
+use overlay::{OverlayId, OverlayOperation, OverlayStore};
+use transaction::ArchiveTransaction;
+use user_data::{GameUserData, PartialGameUserData};
+use update::{ApplyPlatformsReport, RemoteCategory, RemoteDeletedGamesRes, RemoteGamesRes, RemotePlatform, RemoteTag};
 use util::ContentTreeNode;
 
+pub mod checkpoint;
 pub mod game;
 pub mod game_data;
+pub mod clock;
 mod migration;
+pub mod overlay;
 pub mod platform;
+pub mod profile;
 pub mod tag;
 pub mod tag_category;
+pub mod transaction;
 pub mod update;
+pub mod user_data;
 pub mod util;
 mod logger;
 
@@ -32,22 +43,141 @@ mod logger;
 extern crate napi_derive;
 
 static DEBUG_ENABLED: AtomicBool = AtomicBool::new(false);
+static DEBUG_RAW_QUERIES: AtomicBool = AtomicBool::new(false);
+static DEBUG_PARAM_MAX_LENGTH: AtomicUsize = AtomicUsize::new(DEFAULT_DEBUG_PARAM_MAX_LENGTH);
+
+/// Characters of each parameter shown in logged queries until `set_debug_param_max_length` is called
+pub const DEFAULT_DEBUG_PARAM_MAX_LENGTH: usize = 200;
+
+/// Games per transaction when rebuilding denormalized columns or normalizing languages
+const REBUILD_BATCH_SIZE: usize = 2000;
+
+/// SQLite VM instructions between query timeout checks
+const QUERY_TIMEOUT_CHECK_OPS: i32 = 1000;
+
+thread_local! {
+    // Operations on this thread's checked out connection that enforce the query timeout, nested ones included
+    static QUERY_CLOCK_DEPTH: Cell<usize> = Cell::new(0);
+    // Start of the statement running on this thread's checked out connection, if any
+    static QUERY_STARTED: Cell<Option<Instant>> = Cell::new(None);
+}
 
 lazy_static! {
     static ref LOGGER: Arc<EventManager> = EventManager::new();
 }
 
 pub struct FlashpointArchive {
-    pool: Option<Pool<SqliteConnectionManager>>
+    pool: Option<Pool<SqliteConnectionManager>>,
+    overlays: Mutex<OverlayStore>,
+    clock: Arc<dyn Clock>,
+    // Queues serialized writes, awaited so waiting writers don't block an executor thread
+    write_lock: tokio::sync::Mutex<()>,
+    write_timeout: Option<Duration>,
+    query_timeout: Option<Duration>,
+    wal_autocheckpoint: Option<u32>,
+    performance_profile: PerformanceProfile,
+    suggestion_min_length: usize,
+    suggestion_limit: i64,
+    global_tag_blacklist: Mutex<Option<Vec<String>>>,
 }
 
 impl FlashpointArchive {
     pub fn new() -> FlashpointArchive {
         FlashpointArchive {
             pool: None,
+            overlays: Mutex::new(OverlayStore::default()),
+            clock: Arc::new(SystemClock),
+            write_lock: tokio::sync::Mutex::new(()),
+            write_timeout: None,
+            query_timeout: None,
+            wal_autocheckpoint: None,
+            performance_profile: PerformanceProfile::Default,
+            suggestion_min_length: tag::DEFAULT_SUGGESTION_MIN_LENGTH,
+            suggestion_limit: tag::DEFAULT_SUGGESTION_LIMIT,
+            global_tag_blacklist: Mutex::new(None),
+        }
+    }
+
+    /// Replace the clock used for every timestamp written to the database, e.g. with a `FixedClock` in tests
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Give up on serialized writes with `Error::WriteBusy` once they have waited `timeout` for another write to finish.
+    /// Writes holding the lock for longer than `timeout` are reported to the logger.
+    pub fn set_write_timeout(&mut self, timeout: Duration) {
+        self.write_timeout = Some(timeout);
+    }
+
+    /// Interrupt statements running longer than `timeout` with `Error::Timeout`, and wait at most as long on a locked database.
+    /// Each statement gets the full timeout, so writes made of many statements can take longer as a whole.
+    /// Applies to databases loaded afterwards. Disabled by default, pass `None` to disable it again.
+    pub fn set_query_timeout(&mut self, timeout: Option<Duration>) {
+        self.query_timeout = timeout;
+    }
+
+    /// Checkpoint the WAL automatically once it reaches `pages` pages, 0 disables automatic checkpoints.
+    /// Applies to databases loaded afterwards, SQLite's default of 1000 pages is used until set.
+    pub fn set_wal_autocheckpoint(&mut self, pages: u32) {
+        self.wal_autocheckpoint = Some(pages);
+    }
+
+    /// Return no tag suggestions for partials shorter than `min_length` characters, and rank at most `limit` matching aliases.
+    /// Defaults to `tag::DEFAULT_SUGGESTION_MIN_LENGTH` and `tag::DEFAULT_SUGGESTION_LIMIT`.
+    pub fn set_suggestion_limits(&mut self, min_length: usize, limit: i64) {
+        self.suggestion_min_length = min_length;
+        self.suggestion_limit = limit;
+    }
+
+    /// Trade memory for read speed on every connection, see `PerformanceProfile`.
+    /// Applies to databases loaded afterwards, `PerformanceProfile::Default` is used until set.
+    pub fn set_performance_profile(&mut self, profile: PerformanceProfile) {
+        self.performance_profile = profile;
+    }
+
+    /// Settings applied to every connection the pool opens, not only the first one
+    fn connection_manager(&self, manager: SqliteConnectionManager) -> SqliteConnectionManager {
+        let query_timeout = self.query_timeout;
+        let wal_autocheckpoint = self.wal_autocheckpoint;
+        let performance_profile = self.performance_profile.clone();
+        manager.with_init(move |conn| {
+            profile::apply(conn, &performance_profile)?;
+            if let Some(timeout) = query_timeout {
+                conn.busy_timeout(timeout)?;
+                conn.trace(Some(restart_query_clock));
+                conn.progress_handler(
+                    QUERY_TIMEOUT_CHECK_OPS,
+                    Some(move || QUERY_STARTED.with(|started| started.get().is_some_and(|s| s.elapsed() > timeout))),
+                );
+            }
+            if let Some(pages) = wal_autocheckpoint {
+                conn.pragma_update(None, "wal_autocheckpoint", pages)?;
+            }
+            Ok(())
+        })
+    }
+
+    async fn lock_writes(&self) -> Result<tokio::sync::MutexGuard<'_, ()>> {
+        match self.write_timeout {
+            Some(timeout) => {
+                let start = Instant::now();
+                match tokio::time::timeout(timeout, self.write_lock.lock()).await {
+                    Ok(guard) => Ok(guard),
+                    Err(_) => error::WriteBusySnafu { waited_ms: start.elapsed().as_millis() as u64 }.fail(),
+                }
+            },
+            None => Ok(self.write_lock.lock().await),
         }
     }
 
+    /// Like `load_database`, but returns `Error::DatabaseNotFound` instead of creating a new database when the file is missing
+    pub fn load_existing_database(&mut self, source: &str) -> Result<()> {
+        if source != ":memory:" && !std::path::Path::new(source).is_file() {
+            return error::DatabaseNotFoundSnafu { path: source }.fail();
+        }
+        self.load_database(source)
+    }
+
     /// Load a new database for Flashpoint. Open databases will close.
     /// 
     /// `source` - Path to database file, or :memory: to open a fresh database in memory
@@ -58,28 +188,129 @@ impl FlashpointArchive {
             SqliteConnectionManager::file(source)
         };
 
-        let pool = r2d2::Pool::new(conn_manager).expect("Failed to open R2D2 conn pool");
-        let mut conn = pool.get().unwrap();
+        if source != ":memory:" {
+            check_database_header(source)?;
+            // r2d2 retries a failing open until its connection timeout, surface the error straight away
+            Connection::open(source).context(error::SqliteSnafu)?;
+        }
+
+        let pool = r2d2::Pool::new(self.connection_manager(conn_manager)).context(error::PoolInitSnafu)?;
+        let mut conn = pool.get().context(error::ConnectionCheckoutSnafu)?;
+
+        // Perform database migrations, the default tag category is guaranteed by one of them
+        migrate(&mut conn, source)?;
+
+        self.pool = Some(pool);
+
+        Ok(())
+    }
+
+    /// Snapshot the open database into a byte buffer in SQLite's on-disk format.
+    ///
+    /// Pair with `load_database_from_bytes` to reset an in-memory database between tests
+    /// without replaying fixture inserts.
+    #[cfg(feature = "serialize")]
+    pub fn serialize_memory(&self) -> Result<Vec<u8>> {
+        match &self.pool {
+            Some(pool) => {
+                let conn = pool.get().context(error::ConnectionCheckoutSnafu)?;
+                let data = conn.serialize(rusqlite::DatabaseName::Main).context(error::SqliteSnafu)?;
+                Ok(data.to_vec())
+            }
+            None => Err(Error::DatabaseNotInitialized),
+        }
+    }
+
+    /// Load a fresh in-memory database from a snapshot taken with `serialize_memory`. Open databases will close.
+    ///
+    /// The bytes are copied, later changes to the database never alter the snapshot.
+    #[cfg(feature = "serialize")]
+    pub fn load_database_from_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        const SOURCE: &str = "<snapshot>";
+
+        if !bytes.starts_with(SQLITE_HEADER) {
+            let header = &bytes[..bytes.len().min(SQLITE_HEADER.len())];
+            let summary = match header.len() {
+                0 => "an empty buffer".to_owned(),
+                _ => format!("\"{}\"", String::from_utf8_lossy(header).escape_debug()),
+            };
+            return error::NotADatabaseSnafu { path: SOURCE, header: summary }.fail();
+        }
+
+        let mut snapshot = Connection::open_in_memory().context(error::SqliteSnafu)?;
+        snapshot
+            .deserialize(rusqlite::DatabaseName::Main, sqlite_owned_data(bytes)?, true)
+            .context(error::SqliteSnafu)?;
 
-        // Perform database migrations
-        migration::up(&mut conn).context(error::DatabaseMigrationSnafu)?;
-        conn.execute("PRAGMA foreign_keys=off;", ()).context(error::SqliteSnafu)?;
-        // Always make there's always a default tag category present 
-        tag_category::find_or_create(&conn, "default", None).context(error::SqliteSnafu)?;
+        // Every pooled connection shares the same in-memory database, restoring through one is enough
+        let pool = r2d2::Pool::new(self.connection_manager(SqliteConnectionManager::memory())).context(error::PoolInitSnafu)?;
+        let mut conn = pool.get().context(error::ConnectionCheckoutSnafu)?;
+        rusqlite::backup::Backup::new(&snapshot, &mut conn)
+            .and_then(|backup| backup.run_to_completion(256, std::time::Duration::ZERO, None))
+            .context(error::SqliteSnafu)?;
+
+        // Snapshots taken by an older build may still be behind on migrations
+        migrate(&mut conn, SOURCE)?;
 
         self.pool = Some(pool);
 
         Ok(())
     }
 
+    /// Copy the WAL back into the database, e.g. when the launcher is idle. TRUNCATE also shrinks the -wal file.
+    pub async fn checkpoint(&self, mode: CheckpointMode) -> Result<CheckpointResult> {
+        with_connection!(&self.pool, |conn| {
+            checkpoint::checkpoint(conn, mode).context(error::SqliteSnafu)
+        })
+    }
+
+    /// Tags hidden from every search that doesn't set its own `with_tag_filter`, unless it sets `disable_global_tag_filter`.
+    /// The tag filter index is keyed on the tag list, so switching it off and back on doesn't rebuild the index.
+    pub fn set_global_tag_blacklist(&self, tags: Option<Vec<String>>) -> Result<()> {
+        *self.global_tag_blacklist.lock().map_err(|_| Error::MutexLockFailed)? = tags;
+        Ok(())
+    }
+
+    /// A copy of the search using the global tag blacklist, if it applies to it
+    fn with_global_tag_filter(&self, search: &GameSearch) -> Result<Option<GameSearch>> {
+        if search.with_tag_filter.is_some() || search.disable_global_tag_filter {
+            return Ok(None);
+        }
+        let global = self.global_tag_blacklist.lock().map_err(|_| Error::MutexLockFailed)?;
+        Ok(global.as_ref().filter(|tags| !tags.is_empty()).map(|tags| GameSearch {
+            with_tag_filter: Some(tags.clone()),
+            ..search.clone()
+        }))
+    }
+
+    /// The global tag blacklist for finds that build their own search, with the tag filter index brought up to date for it
+    fn global_tag_filter(&self, conn: &Connection) -> Result<Option<Vec<String>>> {
+        match self.with_global_tag_filter(&GameSearch::default())? {
+            Some(filtered) => {
+                game::search::update_tag_filter_index(conn, &filtered).context(error::SqliteSnafu)?;
+                Ok(filtered.with_tag_filter)
+            },
+            None => Ok(None),
+        }
+    }
+
     pub async fn search_games(&self, search: &GameSearch) -> Result<Vec<game::Game>> {
+        game::search::validate_search(search)?;
+        let filtered = self.with_global_tag_filter(search)?;
+        let search = filtered.as_ref().unwrap_or(search);
         with_connection!(&self.pool, |conn| {
             debug_println!("Getting search page");
+            if filtered.is_some() {
+                game::search::update_tag_filter_index(conn, search).context(error::SqliteSnafu)?;
+            }
             game::search::search(conn, search).context(error::SqliteSnafu)
         })
     }
 
     pub async fn search_games_index(&self, search: &mut GameSearch, limit: Option<i64>) -> Result<Vec<PageTuple>> {
+        game::search::validate_search(search)?;
+        let mut filtered = self.with_global_tag_filter(search)?;
+        let search = filtered.as_mut().unwrap_or(search);
         with_connection!(&self.pool, |conn| {
             debug_println!("Getting search index");
             game::search::search_index(conn, search, limit).context(error::SqliteSnafu)
@@ -87,1219 +318,5591 @@ impl FlashpointArchive {
     }
 
     pub async fn search_games_total(&self, search: &GameSearch) -> Result<i64> {
+        game::search::validate_search(search)?;
+        let filtered = self.with_global_tag_filter(search)?;
+        let search = filtered.as_ref().unwrap_or(search);
         with_connection!(&self.pool, |conn| {
             debug_println!("Getting search total");
+            if filtered.is_some() {
+                game::search::update_tag_filter_index(conn, search).context(error::SqliteSnafu)?;
+            }
             game::search::search_count(conn, search).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn search_games_with_tag(&self, tag: &str) -> Result<Vec<Game>> {
+    pub async fn search_games_grouped(&self, search: &GameSearch, group_by: GroupableField, representatives: i64) -> Result<Vec<GameGroup>> {
+        game::search::validate_search(search)?;
+        let filtered = self.with_global_tag_filter(search)?;
+        let search = filtered.as_ref().unwrap_or(search);
         with_connection!(&self.pool, |conn| {
-            game::find_with_tag(conn, tag).context(error::SqliteSnafu)
+            debug_println!("Getting grouped search");
+            if filtered.is_some() {
+                game::search::update_tag_filter_index(conn, search).context(error::SqliteSnafu)?;
+            }
+            game::search::search_grouped(conn, search, group_by, representatives).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn search_games_random(&self, search: &GameSearch, count: i64) -> Result<Vec<Game>> {
+    /// Games matched by only one of the two searches, with up to `limit` examples of each
+    pub async fn diff_searches(&self, a: &GameSearch, b: &GameSearch, limit: i64) -> Result<SearchDiff> {
+        game::search::validate_search(a)?;
+        game::search::validate_search(b)?;
+        let a = self.with_global_tag_filter(a)?.unwrap_or_else(|| a.clone());
+        let b = self.with_global_tag_filter(b)?.unwrap_or_else(|| b.clone());
         with_connection!(&self.pool, |conn| {
-            game::search::search_random(conn, search.clone(), count).context(error::SqliteSnafu)
+            game::search::search_diff(conn, &a, &b, limit).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn search_tag_suggestions(&self, partial: &str, blacklist: Vec<String>) -> Result<Vec<TagSuggestion>> {
+    /// New, updated and deleted games since `timestamp`, e.g. to show what a metadata update changed
+    pub async fn changes_since(&self, timestamp: &str, limit: i64, offset: i64) -> Result<ChangesReport> {
         with_connection!(&self.pool, |conn| {
-            tag::search_tag_suggestions(conn, partial, blacklist).context(error::SqliteSnafu)
+            game::changes_since(conn, timestamp, limit, offset)
         })
     }
 
-    pub async fn search_platform_suggestions(&self, partial: &str) -> Result<Vec<TagSuggestion>> {
+    pub async fn find_platformless_games(&self, search: Option<GameSearch>) -> Result<Vec<Game>> {
+        let search = search.unwrap_or_default();
+        let search = self.with_global_tag_filter(&search)?.unwrap_or(search);
+        game::search::validate_search(&search)?;
         with_connection!(&self.pool, |conn| {
-            platform::search_platform_suggestions(conn, partial).context(error::SqliteSnafu)
+            game::search::update_tag_filter_index(conn, &search).context(error::SqliteSnafu)?;
+            game::find_platformless(conn, search).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn find_all_game_ids(&self) -> Result<Vec<String>> {
+    pub async fn find_games_by_language(&self, language: &str, search: Option<GameSearch>) -> Result<Vec<Game>> {
+        let search = search.unwrap_or_default();
+        let search = self.with_global_tag_filter(&search)?.unwrap_or(search);
+        game::search::validate_search(&search)?;
         with_connection!(&self.pool, |conn| {
-            game::find_all_ids(conn).context(error::SqliteSnafu)
+            game::search::update_tag_filter_index(conn, &search).context(error::SqliteSnafu)?;
+            game::find_by_language(conn, language, search).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn find_game(&self, id: &str) -> Result<Option<Game>> {
+    pub async fn search_games_with_tag(&self, tag: &str, limit: Option<i64>, relations: Option<GameSearchRelations>) -> Result<Vec<Game>> {
         with_connection!(&self.pool, |conn| {
-            game::find(conn, id).context(error::SqliteSnafu)
+            let tag_filter = self.global_tag_filter(conn)?;
+            game::find_with_tag(conn, tag, limit, relations, tag_filter).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn create_game(&self, partial_game: &PartialGame) -> Result<game::Game> {
-        with_transaction!(&self.pool, |tx| {
-            game::create(tx, partial_game).context(error::SqliteSnafu)
+    pub async fn search_games_random(&self, search: &GameSearch, count: i64) -> Result<Vec<Game>> {
+        let search = self.with_global_tag_filter(search)?.unwrap_or_else(|| search.clone());
+        with_connection!(&self.pool, |conn| {
+            game::search::search_random(conn, search, count).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn save_game(&self, partial_game: &mut PartialGame) -> Result<Game> {
-        with_transaction!(&self.pool, |tx| {
-            match partial_game.date_modified {
-                Some(_) => (),
-                None => partial_game.date_modified = Some(Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()),
-            }
-            game::save(tx, partial_game).context(error::SqliteSnafu)
+    /// `library` limits the game counts suggestions are ranked by to games in that library
+    pub async fn search_tag_suggestions(&self, partial: &str, blacklist: Vec<String>, include_alias_matches: bool, library: Option<String>) -> Result<Vec<TagSuggestion>> {
+        with_connection!(&self.pool, |conn| {
+            tag::search_tag_suggestions(conn, partial, blacklist, include_alias_matches, library.as_deref(), self.suggestion_min_length, self.suggestion_limit)
+                .context(error::SqliteSnafu)
         })
     }
 
-    pub async fn save_games(&self, partial_games: Vec<&mut PartialGame>) -> Result<()> {
-        with_transaction!(&self.pool, |tx| {
-            for partial_game in partial_games {
-                match partial_game.date_modified {
-                    Some(_) => (),
-                    None => partial_game.date_modified = Some(Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()),
-                }
-                game::save(tx, partial_game).context(error::SqliteSnafu)?;
-            }
-            Ok(())
+    /// `library` limits the game counts suggestions are ranked by to games in that library
+    pub async fn search_platform_suggestions(&self, partial: &str, library: Option<String>) -> Result<Vec<TagSuggestion>> {
+        with_connection!(&self.pool, |conn| {
+            platform::search_platform_suggestions(conn, partial, library.as_deref()).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn delete_game(&self, id: &str) -> Result<()> {
-        with_transaction!(&self.pool, |conn| {
-            game::delete(conn, id).context(error::SqliteSnafu)
+    pub async fn find_all_game_ids(&self) -> Result<Vec<String>> {
+        with_connection!(&self.pool, |conn| {
+            game::find_all_ids(conn).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn count_games(&self) -> Result<i64> {
-        with_connection!(&self.pool, |conn| {
-            game::count(conn).context(error::SqliteSnafu)
-        })
+    /// Recompute the denormalized game columns from their relations, e.g. after editing the database by hand.
+    /// Runs in batches so other writes can get through in between, progress is reported to the logger.
+    pub async fn rebuild_denormalized(&self, scope: RebuildScope) -> Result<RebuildReport> {
+        let ids = match scope {
+            RebuildScope::All => with_connection!(&self.pool, |conn| {
+                game::find_all_ids_with_deleted(conn).context(error::SqliteSnafu)
+            })?,
+            RebuildScope::GameIds(ids) => ids,
+        };
+
+        let mut report = RebuildReport::default();
+        for batch in ids.chunks(REBUILD_BATCH_SIZE) {
+            let batch_report = with_serialized_transaction!(self, "rebuild_denormalized", |tx| {
+                game::rebuild_denormalized(tx, batch).context(error::SqliteSnafu)
+            })?;
+            report.add(&batch_report);
+            LOGGER.dispatch_event(format!("Rebuilt denormalized columns for {} of {} games", report.games_checked, ids.len()));
+        }
+
+        Ok(report)
     }
 
-    pub async fn find_add_app_by_id(&self, id: &str) -> Result<Option<AdditionalApp>> {
-        with_connection!(&self.pool, |conn| {
-            game::find_add_app_by_id(conn, id).context(error::SqliteSnafu)
-        })
+    /// Rewrite every game's language as ISO 639-1 codes, see `util::normalize_languages`. The replaced value is kept in languageRaw.
+    /// Returns how many games changed.
+    pub async fn normalize_all_languages(&self) -> Result<i64> {
+        let ids = with_connection!(&self.pool, |conn| {
+            game::find_all_ids_with_deleted(conn).context(error::SqliteSnafu)
+        })?;
+
+        let mut changed = 0;
+        for batch in ids.chunks(REBUILD_BATCH_SIZE) {
+            changed += with_serialized_transaction!(self, "normalize_all_languages", |tx| {
+                game::normalize_languages(tx, batch).context(error::SqliteSnafu)
+            })?;
+        }
+
+        Ok(changed)
     }
 
-    pub async fn create_add_app(&self, add_app: &mut AdditionalApp) -> Result<()> {
-        with_transaction!(&self.pool, |conn| {
-            game::create_add_app(conn, add_app).context(error::SqliteSnafu)
+    pub async fn find_all_tag_relations(&self) -> Result<Vec<(String, i64)>> {
+        with_connection!(&self.pool, |conn| {
+            game::find_tag_relations(conn, None).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn find_game_data_by_id(&self, game_data_id: i64) -> Result<Option<GameData>> {
+    pub async fn find_tag_relations(&self, game_ids: Vec<String>) -> Result<Vec<(String, i64)>> {
         with_connection!(&self.pool, |conn| {
-            game::find_game_data_by_id(conn, game_data_id).context(error::SqliteSnafu)
+            game::find_tag_relations(conn, Some(game_ids)).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn find_game_data(&self, game_id: &str) -> Result<Vec<GameData>> {
+    pub async fn find_all_platform_relations(&self) -> Result<Vec<(String, i64)>> {
         with_connection!(&self.pool, |conn| {
-            game::get_game_data(conn, game_id).context(error::SqliteSnafu)
+            game::find_platform_relations(conn, None).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn create_game_data(&self, game_data: &PartialGameData) -> Result<GameData> {
+    pub async fn find_platform_relations(&self, game_ids: Vec<String>) -> Result<Vec<(String, i64)>> {
         with_connection!(&self.pool, |conn| {
-            game::create_game_data(conn, game_data).context(error::SqliteSnafu)
+            game::find_platform_relations(conn, Some(game_ids)).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn save_game_data(&self, game_data: &PartialGameData) -> Result<GameData> {
+    pub async fn find_game_tag_ids(&self, game_id: &str) -> Result<Vec<i64>> {
         with_connection!(&self.pool, |conn| {
-            game::save_game_data(conn, game_data).context(error::SqliteSnafu)
+            game::find_tag_ids(conn, game_id).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn delete_game_data(&self, id: i64) -> Result<()> {
+    pub async fn find_game_platform_ids(&self, game_id: &str) -> Result<Vec<i64>> {
         with_connection!(&self.pool, |conn| {
-            game_data::delete(conn, id).context(error::SqliteSnafu)
+            game::find_platform_ids(conn, game_id).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn find_all_tags(&self) -> Result<Vec<Tag>> {
+    pub async fn find_game(&self, id: &str) -> Result<Option<Game>> {
         with_connection!(&self.pool, |conn| {
-            tag::find(conn).context(error::SqliteSnafu)
+            game::find(conn, id).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn find_tag(&self, name: &str) -> Result<Option<Tag>> {
+    /// Find several games at once, keyed by the requested id. Redirects are followed, missing ids are left out.
+    pub async fn find_games_by_ids(&self, ids: Vec<String>) -> Result<HashMap<String, Game>> {
         with_connection!(&self.pool, |conn| {
-            tag::find_by_name(conn, name).context(error::SqliteSnafu)
+            game::find_by_ids(conn, &ids).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn find_tag_by_id(&self, id: i64) -> Result<Option<Tag>> {
+    /// Find a game without loading its tags, platforms, game data or add apps
+    pub async fn find_game_slim(&self, id: &str) -> Result<Option<Game>> {
         with_connection!(&self.pool, |conn| {
-            tag::find_by_id(conn, id).context(error::SqliteSnafu)
+            game::find_slim(conn, id).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn create_tag(&self, name: &str, category: Option<String>, id: Option<i64>) -> Result<Tag> {
-        with_transaction!(&self.pool, |conn| {
-            tag::create(conn, name, category, id).context(error::SqliteSnafu)
+    pub async fn create_game(&self, partial_game: &PartialGame) -> Result<game::Game> {
+        let mut partial_game = partial_game.clone();
+        let now = self.clock.canonical_now();
+        if partial_game.date_added.is_none() {
+            partial_game.date_added = Some(now.clone());
+        }
+        if partial_game.date_modified.is_none() {
+            partial_game.date_modified = Some(now);
+        }
+        with_serialized_transaction!(self, "create_game", |tx| {
+            game::create(tx, &partial_game).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn save_tag(&self, partial: &mut PartialTag) -> Result<Tag> {
-        with_transaction!(&self.pool, |conn| {
-            match partial.date_modified {
-                Some(_) => (),
-                None => partial.date_modified = Some(Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()),
+    /// Creates a game, its data and add apps together, nothing is written if any part fails
+    pub async fn create_full_game(&self, full: &CreateFullGame) -> Result<Game> {
+        let mut full = full.clone();
+        let now = self.clock.canonical_now();
+        if full.game.date_added.is_none() {
+            full.game.date_added = Some(now.clone());
+        }
+        if full.game.date_modified.is_none() {
+            full.game.date_modified = Some(now.clone());
+        }
+        for data in full.game_data.iter_mut() {
+            if data.date_added.is_none() {
+                data.date_added = Some(now.clone());
             }
-            tag::save(conn, &partial).context(error::SqliteSnafu)
+        }
+        with_serialized_transaction!(self, "create_full_game", |tx| {
+            game::create_full(tx, &full).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn delete_tag(&self, name: &str) -> Result<()> {
-        with_transaction!(&self.pool, |conn| {
-            tag::delete(conn, name).context(error::SqliteSnafu)
+    /// Dry run of a save, returns every problem found with the partial without writing anything
+    pub async fn validate_game(&self, partial_game: &PartialGame) -> Result<Vec<ValidationIssue>> {
+        with_connection!(&self.pool, |conn| {
+            game::validate(conn, partial_game)
         })
     }
 
-    pub async fn delete_tag_by_id(&self, id: i64) -> Result<()> {
-        with_transaction!(&self.pool, |conn| {
-            tag::delete_by_id(conn, id).context(error::SqliteSnafu)
-        })
+    pub async fn save_game(&self, partial_game: &mut PartialGame) -> Result<Game> {
+        self.save_game_with_policy(partial_game, DateModifiedPolicy::Auto).await
     }
 
-    pub async fn count_tags(&self) -> Result<i64> {
-        with_connection!(&self.pool, |conn| {
-            tag::count(conn).context(error::SqliteSnafu)
+    pub async fn save_game_with_policy(&self, partial_game: &mut PartialGame, policy: DateModifiedPolicy) -> Result<Game> {
+        with_serialized_transaction!(self, "save_game", |tx| {
+            self.save_with_policy(tx, partial_game, &policy)
         })
     }
 
-    pub async fn merge_tags(&self, name: &str, merged_into: &str) -> Result<Tag> {
-        with_transaction!(&self.pool, |conn| {
-            tag::merge_tag(conn, name, merged_into).context(error::SqliteSnafu)
+    /// Apply a JSON Patch (RFC 6902) to a game, see `game::patch::apply_patch` for the supported fields.
+    /// Unlike a `PartialGame`, a patch can clear a field by removing it.
+    #[cfg(feature = "serde")]
+    pub async fn patch_game(&self, id: &str, patch: serde_json::Value) -> Result<Game> {
+        with_serialized_transaction!(self, "patch_game", |tx| {
+            let game = match game::find(tx, id).context(error::SqliteSnafu)? {
+                Some(game) => game,
+                None => return error::GameNotFoundSnafu { id }.fail(),
+            };
+            let mut partial = game::patch::apply_patch(&game, &patch)?;
+            self.save_with_policy(tx, &mut partial, &DateModifiedPolicy::Auto)
         })
     }
 
-    pub async fn find_all_platforms(&self) -> Result<Vec<Tag>> {
-        with_connection!(&self.pool, |conn| {
-            platform::find(conn).context(error::SqliteSnafu)
+    /// Replaces every tag on a game without rewriting the rest of the game row
+    pub async fn set_game_tags(&self, game_id: &str, tags: Vec<String>) -> Result<Game> {
+        with_serialized_transaction!(self, "set_game_tags", |tx| {
+            game::set_tags(tx, game_id, &tags, &self.clock.canonical_now())
         })
     }
 
-    pub async fn find_platform(&self, name: &str) -> Result<Option<Tag>> {
-        with_connection!(&self.pool, |conn| {
-            platform::find_by_name(conn, name).context(error::SqliteSnafu)
+    /// Replaces every platform on a game without rewriting the rest of the game row
+    pub async fn set_game_platforms(&self, game_id: &str, platforms: Vec<String>) -> Result<Game> {
+        with_serialized_transaction!(self, "set_game_platforms", |tx| {
+            game::set_platforms(tx, game_id, &platforms, &self.clock.canonical_now())
         })
     }
 
-    pub async fn find_platform_by_id(&self, id: i64) -> Result<Option<Tag>> {
-        with_connection!(&self.pool, |conn| {
-            platform::find_by_id(conn, id).context(error::SqliteSnafu)
-        })
+    pub async fn save_games(&self, partial_games: Vec<&mut PartialGame>) -> Result<()> {
+        self.save_games_with_policy(partial_games, DateModifiedPolicy::Auto).await
     }
 
-    pub async fn create_platform(&self, name: &str, id: Option<i64>) -> Result<Tag> {
-        with_transaction!(&self.pool, |conn| {
-            platform::create(conn, name, id).context(error::SqliteSnafu)
+    pub async fn save_games_with_policy(&self, partial_games: Vec<&mut PartialGame>, policy: DateModifiedPolicy) -> Result<()> {
+        with_serialized_transaction!(self, "save_games", |tx| {
+            for partial_game in partial_games {
+                self.save_with_policy(tx, partial_game, &policy)?;
+            }
+            Ok(())
         })
     }
 
-    pub async fn save_platform(&self, partial: &mut PartialTag) -> Result<Tag> {
-        with_transaction!(&self.pool, |conn| {
-            match partial.date_modified {
-                Some(_) => (),
-                None => partial.date_modified = Some(Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()),
+    fn save_with_policy(&self, conn: &Connection, partial_game: &mut PartialGame, policy: &DateModifiedPolicy) -> Result<Game> {
+        match policy {
+            DateModifiedPolicy::Auto => {
+                if partial_game.date_modified.is_none() {
+                    partial_game.date_modified = Some(self.clock.canonical_now());
+                }
+                game::save(conn, partial_game)
             }
-            platform::save(conn, &partial).context(error::SqliteSnafu)
-        })
+            DateModifiedPolicy::Preserve => game::save_preserving_date_modified(conn, partial_game),
+            DateModifiedPolicy::Force(date_modified) => {
+                partial_game.date_modified = Some(date_modified.clone());
+                game::save(conn, partial_game)
+            }
+        }
     }
 
-    pub async fn delete_platform(&self, name: &str) -> Result<()> {
-        with_transaction!(&self.pool, |conn| {
-            platform::delete(conn, name).context(error::SqliteSnafu)
+    /// Run several operations in one transaction. Committed if `body` returns Ok, rolled back if it returns Err.
+    pub async fn transaction<T, F>(&self, body: F) -> Result<T>
+    where
+        F: FnOnce(&ArchiveTransaction) -> Result<T>,
+    {
+        with_serialized_transaction!(self, "transaction", |tx| {
+            body(&ArchiveTransaction::new(tx, self.clock.as_ref()))
         })
     }
 
-    pub async fn count_platforms(&self) -> Result<i64> {
-        with_connection!(&self.pool, |conn| {
-            platform::count(conn).context(error::SqliteSnafu)
-        })
+    /// Begin a new overlay. Edits staged on an overlay are only visible through the overlay
+    /// variants of find and search until the overlay is committed.
+    pub async fn begin_overlay(&self) -> Result<OverlayId> {
+        let mut overlays = self.overlays.lock().map_err(|_| Error::MutexLockFailed)?;
+        Ok(overlays.begin())
     }
 
-    pub async fn find_all_tag_categories(&self) -> Result<Vec<TagCategory>> {
+    /// Stage a game save on an overlay, returns the game as it would look after the save.
+    /// Saves for ids not yet in the database are staged as new games.
+    pub async fn overlay_save_game(&self, overlay: OverlayId, partial_game: &PartialGame) -> Result<Game> {
+        let partial = overlay::prepare_partial(partial_game);
+        let id = partial.id.clone();
+        let mut ops = self.overlays.lock().map_err(|_| Error::MutexLockFailed)?.operations(overlay)?;
+        ops.push(OverlayOperation::SaveGame(partial.clone()));
+        // Only staged once the preview succeeds, so a bad save can't wedge the commit
+        let _write_guard = self.lock_writes().await?;
+        let game = with_connection!(&self.pool, |conn| {
+            match overlay::find(conn, &id, &ops, &self.clock.canonical_now())? {
+                Some(game) => Ok(game),
+                None => error::GameNotFoundSnafu { id }.fail(),
+            }
+        })?;
+        let mut overlays = self.overlays.lock().map_err(|_| Error::MutexLockFailed)?;
+        overlays.stage(overlay, OverlayOperation::SaveGame(partial))?;
+        Ok(game)
+    }
+
+    /// Stage a tag merge on an overlay. Fails without staging anything if the merge couldn't be committed.
+    pub async fn overlay_merge_tags(&self, overlay: OverlayId, name: &str, merged_into: &str) -> Result<()> {
+        let merge = OverlayOperation::MergeTags { name: name.to_owned(), merged_into: merged_into.to_owned() };
+        let mut ops = self.overlays.lock().map_err(|_| Error::MutexLockFailed)?.operations(overlay)?;
+        ops.push(merge.clone());
+        // Only staged once the preview succeeds, so a bad merge can't wedge the commit
+        let _write_guard = self.lock_writes().await?;
         with_connection!(&self.pool, |conn| {
-            tag_category::find(conn).context(error::SqliteSnafu)
-        })
+            overlay::check(conn, &ops, &self.clock.canonical_now())
+        })?;
+        let mut overlays = self.overlays.lock().map_err(|_| Error::MutexLockFailed)?;
+        overlays.stage(overlay, merge)
     }
 
-    pub async fn find_tag_category(&self, name: &str) -> Result<Option<TagCategory>> {
+    pub async fn overlay_find_game(&self, overlay: OverlayId, id: &str) -> Result<Option<Game>> {
+        let ops = self.overlays.lock().map_err(|_| Error::MutexLockFailed)?.operations(overlay)?;
+        // Previews replay the staged writes before rolling them back, so they queue with real writes
+        let _write_guard = self.lock_writes().await?;
         with_connection!(&self.pool, |conn| {
-            tag_category::find_by_name(conn, name).context(error::SqliteSnafu)
+            overlay::find(conn, id, &ops, &self.clock.canonical_now())
         })
     }
 
-    pub async fn find_tag_category_by_id(&self, id: i64) -> Result<Option<TagCategory>> {
+    pub async fn overlay_search_games(&self, overlay: OverlayId, search: &GameSearch) -> Result<Vec<Game>> {
+        game::search::validate_search(search)?;
+        let filtered = self.with_global_tag_filter(search)?;
+        let search = filtered.as_ref().unwrap_or(search);
+        let ops = self.overlays.lock().map_err(|_| Error::MutexLockFailed)?.operations(overlay)?;
+        // Previews replay the staged writes before rolling them back, so they queue with real writes
+        let _write_guard = self.lock_writes().await?;
         with_connection!(&self.pool, |conn| {
-            tag_category::find_by_id(conn, id).context(error::SqliteSnafu)
+            overlay::search(conn, search, &ops, &self.clock.canonical_now())
         })
     }
 
-    pub async fn create_tag_category(&self, partial: &PartialTagCategory) -> Result<TagCategory> {
-        with_connection!(&self.pool, |conn| {
-            tag_category::create(conn, partial).context(error::SqliteSnafu)
+    /// Replay all staged operations in a single transaction. The overlay is kept if the commit fails.
+    /// Operations staged while the commit runs stay on the overlay for a later commit.
+    pub async fn commit_overlay(&self, overlay: OverlayId) -> Result<()> {
+        let ops = self.overlays.lock().map_err(|_| Error::MutexLockFailed)?.take(overlay)?;
+        // Inside a block so every failure, including the lock timing out, puts the operations back
+        let res: Result<()> = async {
+            with_serialized_transaction!(self, "commit_overlay", |tx| {
+                overlay::commit(tx, &ops, &self.clock.canonical_now())
+            })
+        }.await;
+        let mut overlays = self.overlays.lock().map_err(|_| Error::MutexLockFailed)?;
+        match res {
+            Ok(()) => overlays.discard_if_empty(overlay),
+            Err(_) => overlays.restore(overlay, ops),
+        }
+        res
+    }
+
+    pub async fn discard_overlay(&self, overlay: OverlayId) -> Result<()> {
+        let mut overlays = self.overlays.lock().map_err(|_| Error::MutexLockFailed)?;
+        overlays.discard(overlay)
+    }
+
+    pub async fn delete_game(&self, id: &str) -> Result<()> {
+        with_serialized_transaction!(self, "delete_game", |conn| {
+            game::delete(conn, id).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn save_tag_category(&self, partial: &PartialTagCategory) -> Result<TagCategory> {
-        with_connection!(&self.pool, |conn| {
-            tag_category::save(conn, partial).context(error::SqliteSnafu)
+    /// Hide a game from finds and searches until it's restored or purged
+    pub async fn soft_delete_game(&self, id: &str) -> Result<()> {
+        with_serialized_transaction!(self, "soft_delete_game", |conn| {
+            game::soft_delete(conn, id, &self.clock.canonical_now())
         })
     }
 
-    pub async fn new_tag_filter_index(&self, search: &mut GameSearch) -> Result<()> {
-        with_connection!(&self.pool, |conn| {
-            game::search::new_tag_filter_index(conn, search).context(error::SqliteSnafu)
+    pub async fn restore_game(&self, id: &str) -> Result<()> {
+        with_serialized_transaction!(self, "restore_game", |conn| {
+            game::restore(conn, id)
         })
     }
 
-    pub async fn find_all_game_developers(&self) -> Result<Vec<String>> {
-        with_connection!(&self.pool, |conn| {
-            game::find_developers(conn).context(error::SqliteSnafu)
+    /// Permanently delete games soft deleted before `older_than`, returns the purged ids
+    pub async fn purge_deleted_games(&self, older_than: &str) -> Result<Vec<String>> {
+        with_serialized_transaction!(self, "purge_deleted_games", |conn| {
+            game::purge_deleted(conn, older_than).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn find_all_game_publishers(&self) -> Result<Vec<String>> {
+    pub async fn count_games(&self) -> Result<i64> {
         with_connection!(&self.pool, |conn| {
-            game::find_publishers(conn).context(error::SqliteSnafu)
+            game::count(conn).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn find_all_game_series(&self) -> Result<Vec<String>> {
+    pub async fn archive_state_counts(&self) -> Result<Vec<(i64, i64)>> {
         with_connection!(&self.pool, |conn| {
-            game::find_series(conn).context(error::SqliteSnafu)
+            game::count_by_archive_state(conn).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn find_all_game_libraries(&self) -> Result<Vec<String>> {
+    pub async fn find_add_app_by_id(&self, id: &str) -> Result<Option<AdditionalApp>> {
         with_connection!(&self.pool, |conn| {
-            game::find_libraries(conn).context(error::SqliteSnafu)
+            game::find_add_app_by_id(conn, id).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn find_all_game_statuses(&self) -> Result<Vec<String>> {
+    /// Load the parent game of an add app
+    pub async fn find_game_by_add_app(&self, add_app_id: &str) -> Result<Option<Game>> {
         with_connection!(&self.pool, |conn| {
-            game::find_statuses(conn).context(error::SqliteSnafu)
+            game::find_by_add_app(conn, add_app_id).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn find_all_game_play_modes(&self) -> Result<Vec<String>> {
+    pub async fn find_all_add_apps(&self, limit: i64, offset: i64) -> Result<Vec<AdditionalApp>> {
         with_connection!(&self.pool, |conn| {
-            game::find_play_modes(conn).context(error::SqliteSnafu)
+            game::find_all_add_apps(conn, limit, offset).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn find_all_game_application_paths(&self) -> Result<Vec<String>> {
+    pub async fn count_add_apps(&self) -> Result<i64> {
         with_connection!(&self.pool, |conn| {
-            game::find_application_paths(conn).context(error::SqliteSnafu)
+            game::count_add_apps(conn).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn find_platform_app_paths(&self) -> Result<HashMap<String, Vec<PlatformAppPath>>> {
+    pub async fn count_game_data(&self) -> Result<i64> {
         with_connection!(&self.pool, |conn| {
-            game::find_platform_app_paths(conn).context(error::SqliteSnafu)
+            game::count_game_data(conn).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn add_game_playtime(&self, game_id: &str, seconds: i64) -> Result<()> {
-        with_transaction!(&self.pool, |conn| {
-            game::add_playtime(conn, game_id, seconds).context(error::SqliteSnafu)
+    /// Delete add apps left behind by games that no longer exist, returns how many were removed
+    pub async fn delete_orphan_add_apps(&self) -> Result<i64> {
+        with_serialized_transaction!(self, "delete_orphan_add_apps", |conn| {
+            game::delete_orphan_add_apps(conn).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn clear_playtime_tracking_by_id(&self, game_id: &str) -> Result<()> {
-        with_connection!(&self.pool, |conn| {
-            game::clear_playtime_tracking_by_id(conn, game_id).context(error::SqliteSnafu)
+    pub async fn create_add_app(&self, add_app: &mut AdditionalApp) -> Result<()> {
+        with_serialized_transaction!(self, "create_add_app", |conn| {
+            game::create_add_app(conn, add_app).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn clear_playtime_tracking(&self) -> Result<()> {
+    pub async fn find_game_data_by_id(&self, game_data_id: i64) -> Result<Option<GameData>> {
         with_connection!(&self.pool, |conn| {
-            game::clear_playtime_tracking(conn).context(error::SqliteSnafu)
+            game::find_game_data_by_id(conn, game_data_id).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn force_games_active_data_most_recent(&self) -> Result<()> {
+    pub async fn find_game_data(&self, game_id: &str) -> Result<Vec<GameData>> {
         with_connection!(&self.pool, |conn| {
-            game::force_active_data_most_recent(conn).context(error::SqliteSnafu)
+            game::get_game_data(conn, game_id).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn find_game_redirects(&self) -> Result<Vec<GameRedirect>> {
+    /// The game's active game data, None when it has none set
+    pub async fn find_active_game_data(&self, game_id: &str) -> Result<Option<GameData>> {
         with_connection!(&self.pool, |conn| {
-            game::find_redirects(conn).context(error::SqliteSnafu)
+            game::find_active_game_data(conn, game_id).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn create_game_redirect(&self, src_id: &str, dest_id: &str) -> Result<()> {
-        with_transaction!(&self.pool, |conn| {
-            game::create_redirect(conn, src_id, dest_id).context(error::SqliteSnafu)
+    pub async fn create_game_data(&self, game_data: &PartialGameData) -> Result<GameData> {
+        let mut game_data = game_data.clone();
+        if game_data.date_added.is_none() {
+            game_data.date_added = Some(self.clock.canonical_now());
+        }
+        with_serialized_transaction!(self, "create_game_data", |conn| {
+            game::create_game_data(conn, &game_data).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn delete_game_redirect(&self, src_id: &str, dest_id: &str) -> Result<()> {
-        with_transaction!(&self.pool, |conn| {
-            game::delete_redirect(conn, src_id, dest_id).context(error::SqliteSnafu)
+    /// Create many game data rows in one transaction, nothing is created if any parent game is missing
+    pub async fn create_game_data_batch(&self, mut game_data: Vec<PartialGameData>) -> Result<Vec<GameData>> {
+        let now = self.clock.canonical_now();
+        for data in game_data.iter_mut() {
+            if data.date_added.is_none() {
+                data.date_added = Some(now.clone());
+            }
+        }
+        with_serialized_transaction!(self, "create_game_data_batch", |tx| {
+            game::create_game_data_batch(tx, &game_data)
         })
     }
 
-    pub async fn update_apply_categories(&self, cats: Vec<RemoteCategory>) -> Result<()> {
-        with_transaction!(&self.pool, |conn| {
-            update::apply_categories(conn, cats)
+    /// Installed game data sizes, with the `top_n` largest games and a per library breakdown
+    pub async fn disk_usage_report(&self, top_n: i64) -> Result<DiskUsageReport> {
+        with_connection!(&self.pool, |conn| {
+            game::disk_usage_report(conn, top_n).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn update_apply_platforms(&self, platforms: Vec<RemotePlatform>) -> Result<()> {
-        with_transaction!(&self.pool, |conn| {
-            update::apply_platforms(conn, platforms)
+    /// Summed game data size per platform, optionally only counting data present on disk
+    pub async fn content_size_by_platform(&self, present_on_disk_only: bool) -> Result<Vec<(String, i64)>> {
+        with_connection!(&self.pool, |conn| {
+            game::content_size_by_platform(conn, present_on_disk_only).context(error::SqliteSnafu)
         })
     }
-    
-    pub async fn update_apply_tags(&self, tags: Vec<RemoteTag>) -> Result<()> {
-        with_transaction!(&self.pool, |conn| {
-            update::apply_tags(conn, tags)
+
+    pub async fn save_game_data(&self, game_data: &PartialGameData) -> Result<GameData> {
+        with_serialized_transaction!(self, "save_game_data", |conn| {
+            game::save_game_data(conn, game_data).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn update_apply_games(&self, games_res: &RemoteGamesRes) -> Result<()> {
-        with_transaction!(&self.pool, |conn| {
-            update::apply_games(conn, games_res)
+    pub async fn delete_game_data(&self, id: i64) -> Result<()> {
+        with_serialized_transaction!(self, "delete_game_data", |conn| {
+            game_data::delete(conn, id).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn update_delete_games(&self, games_res: &RemoteDeletedGamesRes) -> Result<()> {
-        with_transaction!(&self.pool, |conn| {
-            update::delete_games(conn, games_res)
+    pub async fn find_all_tags(&self) -> Result<Vec<Tag>> {
+        with_connection!(&self.pool, |conn| {
+            tag::find(conn).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn update_apply_redirects(&self, redirects_res: Vec<GameRedirect>) -> Result<()> {
-        with_transaction!(&self.pool, |conn| {
-            update::apply_redirects(conn, redirects_res)
+    pub async fn find_tags_by_category(&self, category: &str) -> Result<Vec<Tag>> {
+        with_connection!(&self.pool, |conn| {
+            tag::find_by_category(conn, category).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn optimize_database(&self) -> Result<()> {
+    pub async fn find_tag(&self, name: &str) -> Result<Option<Tag>> {
         with_connection!(&self.pool, |conn| {
-            optimize_database(conn).context(error::SqliteSnafu)
+            tag::find_by_name(conn, name).context(error::SqliteSnafu)
         })
     }
 
-    pub async fn new_custom_id_order(&self, custom_id_order: Vec<String>) -> Result<()> {
-        with_transaction!(&self.pool, |conn| {
-            game::search::new_custom_id_order(conn, custom_id_order).context(error::SqliteSnafu)
+    /// Find the tag owning `alias`, which may be its primary name or any secondary alias
+    pub async fn find_tag_owner_of_alias(&self, alias: &str) -> Result<Option<Tag>> {
+        with_connection!(&self.pool, |conn| {
+            tag::find_by_name(conn, alias).context(error::SqliteSnafu)
         })
     }
-}
-
-pub fn logger_subscribe() -> (crate::logger::SubscriptionId, mpsc::Receiver<crate::logger::LogEvent>) {
-    LOGGER.subscribe()
-}
 
-pub fn logger_unsubscribe(id: crate::logger::SubscriptionId) {
-    LOGGER.unsubscribe(id)
-}
+    /// Every tag alias with the id of its owning tag, for building autocomplete indexes
+    pub async fn find_all_tag_aliases(&self) -> Result<Vec<LooseTagAlias>> {
+        with_connection!(&self.pool, |conn| {
+            tag::find_all_aliases(conn).context(error::SqliteSnafu)
+        })
+    }
 
-fn optimize_database(conn: &Connection) -> rusqlite::Result<()> {
-    conn.execute("ANALYZE", ())?;
-    conn.execute("REINDEX", ())?;
-    conn.execute("VACUUM", ())?;
-    Ok(())
-}
+    pub async fn find_tag_aliases_page(&self, prefix: Option<String>, limit: i64, offset: i64) -> Result<Vec<LooseTagAlias>> {
+        with_connection!(&self.pool, |conn| {
+            tag::find_aliases_page(conn, prefix.as_deref(), limit, offset).context(error::SqliteSnafu)
+        })
+    }
 
-pub fn generate_content_tree(root: &str) -> Result<ContentTreeNode> {
-    util::gen_content_tree(root).map_err(|_| snafu::NoneError).context(error::ContentTreeSnafu)
-}
+    pub async fn find_tag_by_id(&self, id: i64) -> Result<Option<Tag>> {
+        with_connection!(&self.pool, |conn| {
+            tag::find_by_id(conn, id).context(error::SqliteSnafu)
+        })
+    }
 
-pub fn copy_folder(src: &str, dest: &str) -> Result<u64> {
-    util::copy_folder(src, dest).map_err(|_| snafu::NoneError).context(error::CopyFolderSnafu)
-}
+    pub async fn create_tag(&self, name: &str, category: Option<String>, id: Option<i64>) -> Result<Tag> {
+        with_serialized_transaction!(self, "create_tag", |conn| {
+            tag::create(conn, name, category, id, Some(self.clock.canonical_now())).context(error::SqliteSnafu)
+        })
+    }
 
-pub fn merge_game_filters(a: &GameFilter, b: &GameFilter) -> GameFilter {
-    let mut new_filter = GameFilter::default();
-    new_filter.subfilters = vec![a.clone(), b.clone()];
+    pub async fn save_tag(&self, partial: &mut PartialTag) -> Result<Tag> {
+        with_serialized_transaction!(self, "save_tag", |conn| {
+            match partial.date_modified {
+                Some(_) => (),
+                None => partial.date_modified = Some(self.clock.canonical_now()),
+            }
+            tag::save(conn, &partial).context(error::SqliteSnafu)
+        })
+    }
 
-    if a.match_any && b.match_any {
-        new_filter.match_any = true;
+    pub async fn delete_tag(&self, name: &str) -> Result<()> {
+        with_serialized_transaction!(self, "delete_tag", |conn| {
+            tag::delete(conn, name).context(error::SqliteSnafu)
+        })
     }
 
-    return new_filter;
-}
+    pub async fn delete_tag_by_id(&self, id: i64) -> Result<()> {
+        with_serialized_transaction!(self, "delete_tag_by_id", |conn| {
+            tag::delete_by_id(conn, id).context(error::SqliteSnafu)
+        })
+    }
 
-#[macro_export]
-macro_rules! with_connection {
-    ($pool:expr, $body:expr) => {
-        match $pool {
-            Some(conn) => {
-                let conn = &conn.get().unwrap();
-                conn.execute("PRAGMA foreign_keys=off;", ()).context(error::SqliteSnafu)?;
-                $body(conn)
-            },
-            None => return Err(Error::DatabaseNotInitialized)
-        }
-    };
-}
+    pub async fn delete_tags_by_ids(&self, ids: &[i64]) -> Result<()> {
+        with_serialized_transaction!(self, "delete_tags_by_ids", |conn| {
+            tag::delete_by_ids(conn, ids).context(error::SqliteSnafu)
+        })
+    }
 
-#[macro_export]
-macro_rules! with_transaction {
-    ($pool:expr, $body:expr) => {
-        match $pool {
-            Some(conn) => {
-                let mut conn = conn.get().unwrap();
-                conn.execute("PRAGMA foreign_keys=off;", ()).context(error::SqliteSnafu)?;
-                let tx = conn.transaction().context(error::SqliteSnafu)?;
-                let res = $body(&tx);
-                if res.is_ok() {
-                    tx.commit().context(error::SqliteSnafu)?;
-                    debug_println!("Applied transaction");
-                }
-                res
-            },
-            None => return Err(Error::DatabaseNotInitialized)
-        }
-    };
-}
+    /// Whether `name` is free to use for a new tag or alias
+    pub async fn tag_name_available(&self, name: &str) -> Result<bool> {
+        with_connection!(&self.pool, |conn| {
+            tag::name_available(conn, name).context(error::SqliteSnafu)
+        })
+    }
 
-pub fn enable_debug() {
-    DEBUG_ENABLED.store(true, std::sync::atomic::Ordering::SeqCst);
-}
+    pub async fn count_tags(&self) -> Result<i64> {
+        with_connection!(&self.pool, |conn| {
+            tag::count(conn).context(error::SqliteSnafu)
+        })
+    }
 
-pub fn disable_debug() {
-    DEBUG_ENABLED.store(false, std::sync::atomic::Ordering::SeqCst);
-}
+    pub async fn next_tag_id(&self) -> Result<i64> {
+        with_connection!(&self.pool, |conn| {
+            tag::next_id(conn).context(error::SqliteSnafu)
+        })
+    }
 
-pub fn debug_enabled() -> bool {
-    DEBUG_ENABLED.load(std::sync::atomic::Ordering::SeqCst)
-}
+    /// `record_merge` defaults to true, leaving a redirect so the old tag id resolves to the merged tag
+    pub async fn merge_tags(&self, name: &str, merged_into: &str, record_merge: Option<bool>) -> Result<Tag> {
+        let redirect_added = record_merge.unwrap_or(true).then(|| self.clock.canonical_now());
+        with_serialized_transaction!(self, "merge_tags", |conn| {
+            tag::merge_tag(conn, name, merged_into, redirect_added.as_deref())
+        })
+    }
 
-#[macro_export]
-macro_rules! debug_println {
-    ($($arg:tt)*) => (if $crate::debug_enabled() {
-        ::std::println!($($arg)*);
-        let formatted_message = ::std::format!($($arg)*);
-        $crate::LOGGER.dispatch_event(formatted_message);
-    })
-}
+    pub async fn find_tag_redirects(&self) -> Result<Vec<TagRedirect>> {
+        with_connection!(&self.pool, |conn| {
+            tag::find_redirects(conn).context(error::SqliteSnafu)
+        })
+    }
 
-#[cfg(test)]
-mod tests {
+    pub async fn find_tag_aliases_for(&self, tag_ids: &[i64]) -> Result<HashMap<i64, Vec<String>>> {
+        with_connection!(&self.pool, |conn| {
+            tag::find_aliases_for(conn, tag_ids).context(error::SqliteSnafu)
+        })
+    }
 
-    use crate::game::search::{GameSearchOffset, GameFilter, FieldFilter};
+    pub async fn set_category_for_tags(&self, tag_ids: &[i64], category: &str) -> Result<()> {
+        with_serialized_transaction!(self, "set_category_for_tags", |conn| {
+            tag::set_category_for_tags(conn, tag_ids, category).context(error::SqliteSnafu)
+        })
+    }
 
-    use super::*;
+    pub async fn export_tags(&self) -> Result<TagTaxonomy> {
+        with_connection!(&self.pool, |conn| {
+            tag::taxonomy::export(conn).context(error::SqliteSnafu)
+        })
+    }
 
-    const TEST_DATABASE: &str = "benches/flashpoint.sqlite";
+    pub async fn import_tags(&self, taxonomy: TagTaxonomy, mode: ImportMode) -> Result<ImportReport> {
+        with_serialized_transaction!(self, "import_tags", |conn| {
+            tag::taxonomy::import(conn, &taxonomy, mode).context(error::SqliteSnafu)
+        })
+    }
 
-    #[tokio::test]
-    async fn database_not_initialized() {
-        let flashpoint = FlashpointArchive::new();
-        let result = flashpoint.count_games().await;
-        assert!(result.is_err());
+    pub async fn find_all_platforms(&self) -> Result<Vec<Tag>> {
+        with_connection!(&self.pool, |conn| {
+            platform::find(conn, false).context(error::SqliteSnafu)
+        })
+    }
 
-        let e = result.unwrap_err();
-        assert!(matches!(e, Error::DatabaseNotInitialized {}));
+    pub async fn find_platforms_with_games(&self) -> Result<Vec<Tag>> {
+        with_connection!(&self.pool, |conn| {
+            platform::find(conn, true).context(error::SqliteSnafu)
+        })
     }
 
-    #[tokio::test]
-    async fn migrations_valid() {
-        let migrations = migration::get();
-        assert!(migrations.validate().is_ok());
+    pub async fn find_platform(&self, name: &str) -> Result<Option<Tag>> {
+        with_connection!(&self.pool, |conn| {
+            platform::find_by_name(conn, name).context(error::SqliteSnafu)
+        })
     }
 
-    #[tokio::test]
-    async fn count_games() {
-        let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(TEST_DATABASE);
-        assert!(create.is_ok());
-        let result = flashpoint.count_games().await;
-        assert!(result.is_ok());
+    /// Find the platform owning `alias`, which may be its primary name or any secondary alias
+    pub async fn find_platform_owner_of_alias(&self, alias: &str) -> Result<Option<Tag>> {
+        with_connection!(&self.pool, |conn| {
+            platform::find_by_name(conn, alias).context(error::SqliteSnafu)
+        })
+    }
 
-        let total = result.unwrap();
-        assert_eq!(total, 191150);
+    /// Find a platform by any of its aliases, along with the alias that matched
+    pub async fn find_platform_by_alias(&self, name: &str) -> Result<Option<(Tag, String)>> {
+        with_connection!(&self.pool, |conn| {
+            platform::find_by_alias(conn, name).context(error::SqliteSnafu)
+        })
     }
 
-    #[tokio::test]
-    async fn search_full_scan() {
-        let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(TEST_DATABASE);
-        assert!(create.is_ok());
-        let mut search = game::search::GameSearch::default();
-        search.limit = 99999999999;
-        search.filter.exact_whitelist.library = Some(vec![String::from("arcade")]);
-        let result = flashpoint.search_games(&search).await;
-        assert!(result.is_ok());
-        let games = result.unwrap();
-        assert_eq!(games.len(), 162929);
+    pub async fn find_platform_by_id(&self, id: i64) -> Result<Option<Tag>> {
+        with_connection!(&self.pool, |conn| {
+            platform::find_by_id(conn, id).context(error::SqliteSnafu)
+        })
     }
 
-    #[tokio::test]
-    async fn search_tags_or() {
-        let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(TEST_DATABASE);
-        assert!(create.is_ok());
-        let mut search = game::search::GameSearch::default();
-        search.limit = 99999999999;
-        search.filter.match_any = true;
-        search.filter.exact_whitelist.tags = Some(vec!["Action".to_owned(), "Adventure".to_owned()]);
-        let result = flashpoint.search_games(&search).await;
-        assert!(result.is_ok());
-        let games = result.unwrap();
-        assert_eq!(games.len(), 36724);
+    pub async fn create_platform(&self, name: &str, id: Option<i64>) -> Result<Tag> {
+        with_serialized_transaction!(self, "create_platform", |conn| {
+            platform::create(conn, name, id, Some(self.clock.canonical_now())).context(error::SqliteSnafu)
+        })
     }
 
-    #[tokio::test]
-    async fn search_tags_and() {
-        let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(TEST_DATABASE);
-        assert!(create.is_ok());
-        let mut search = game::search::GameSearch::default();
-        search.limit = 99999999999;
-        search.filter.match_any = false;
-        search.filter.exact_whitelist.tags = Some(vec!["Action".to_owned(), "Adventure".to_owned()]);
-        let result = flashpoint.search_games(&search).await;
-        assert!(result.is_ok());
-        let games = result.unwrap();
-        assert_eq!(games.len(), 397);
+    pub async fn save_platform(&self, partial: &mut PartialTag) -> Result<Tag> {
+        with_serialized_transaction!(self, "save_platform", |conn| {
+            match partial.date_modified {
+                Some(_) => (),
+                None => partial.date_modified = Some(self.clock.canonical_now()),
+            }
+            platform::save(conn, &partial).context(error::SqliteSnafu)
+        })
     }
 
-    #[tokio::test]
-    async fn search_tags_and_or_combined() {
-        // Has 'Action' or 'Adventure', but is missing 'Sonic The Hedgehog'
-        let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(TEST_DATABASE);
-        assert!(create.is_ok());
-        let mut search = game::search::GameSearch::default();
-        let mut inner_filter = game::search::GameFilter::default();
-        // Set page size for index search
-        search.limit = 30000;
-        // Add the OR to an inner filter
-        inner_filter.exact_whitelist.tags = Some(vec!["Action".to_owned(), "Adventure".to_owned()]);
-        inner_filter.match_any = true; // OR
-        // Add the AND to the main filter, with the inner filter
-        search.filter.subfilters = vec![inner_filter];
-        search.filter.exact_blacklist.tags = Some(vec!["Sonic The Hedgehog".to_owned()]);
-        search.filter.match_any = false; // AND
+    pub async fn delete_platform(&self, name: &str) -> Result<()> {
+        with_serialized_transaction!(self, "delete_platform", |conn| {
+            platform::delete(conn, name).context(error::SqliteSnafu)
+        })
+    }
 
-        // Test total results
-        enable_debug();
-        let total_result = flashpoint.search_games_total(&search).await;
-        assert!(total_result.is_ok());
-        let total = total_result.unwrap();
-        assert_eq!(total, 36541);
+    /// Whether `name` is free to use for a new platform or alias
+    pub async fn platform_name_available(&self, name: &str) -> Result<bool> {
+        with_connection!(&self.pool, |conn| {
+            platform::name_available(conn, name).context(error::SqliteSnafu)
+        })
+    }
 
-        // Test first page results
-        let result = flashpoint.search_games(&search).await;
-        assert!(result.is_ok());
+    pub async fn count_platforms(&self) -> Result<i64> {
+        with_connection!(&self.pool, |conn| {
+            platform::count(conn).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn next_platform_id(&self) -> Result<i64> {
+        with_connection!(&self.pool, |conn| {
+            platform::next_id(conn).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn find_all_tag_categories(&self) -> Result<Vec<TagCategory>> {
+        with_connection!(&self.pool, |conn| {
+            tag_category::find(conn).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn find_tag_category(&self, name: &str) -> Result<Option<TagCategory>> {
+        with_connection!(&self.pool, |conn| {
+            tag_category::find_by_name(conn, name).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn find_tag_category_by_id(&self, id: i64) -> Result<Option<TagCategory>> {
+        with_connection!(&self.pool, |conn| {
+            tag_category::find_by_id(conn, id).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn find_tag_categories_by_color(&self, color: &str) -> Result<Vec<TagCategory>> {
+        with_connection!(&self.pool, |conn| {
+            tag_category::find_by_color(conn, color).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn create_tag_category(&self, partial: &PartialTagCategory) -> Result<TagCategory> {
+        with_serialized_transaction!(self, "create_tag_category", |conn| {
+            tag_category::create(conn, partial)
+        })
+    }
+
+    pub async fn create_tag_category_with_id(&self, partial: &PartialTagCategory) -> Result<TagCategory> {
+        with_serialized_transaction!(self, "create_tag_category_with_id", |conn| {
+            tag_category::create_with_id(conn, partial)
+        })
+    }
+
+    pub async fn save_tag_category(&self, partial: &PartialTagCategory) -> Result<TagCategory> {
+        with_serialized_transaction!(self, "save_tag_category", |conn| {
+            tag_category::save(conn, partial)
+        })
+    }
+
+    /// Orders tag categories for display, the first id gets ordering 0 and so on
+    pub async fn reorder_tag_categories(&self, ordered_ids: Vec<i64>) -> Result<()> {
+        with_serialized_transaction!(self, "reorder_tag_categories", |tx| {
+            tag_category::reorder(tx, &ordered_ids).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn new_tag_filter_index(&self, search: &mut GameSearch) -> Result<()> {
+        with_serialized_transaction!(self, "new_tag_filter_index", |conn| {
+            game::search::new_tag_filter_index(conn, search).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn find_all_game_developers(&self) -> Result<Vec<String>> {
+        with_connection!(&self.pool, |conn| {
+            game::find_developers(conn).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn find_all_game_publishers(&self) -> Result<Vec<String>> {
+        with_connection!(&self.pool, |conn| {
+            game::find_publishers(conn).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn find_all_game_series(&self) -> Result<Vec<String>> {
+        with_connection!(&self.pool, |conn| {
+            game::find_series(conn).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn recently_played(&self, limit: i64) -> Result<Vec<Game>> {
+        with_connection!(&self.pool, |conn| {
+            let tag_filter = self.global_tag_filter(conn)?;
+            game::find_recently_played(conn, limit, tag_filter).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn get_user_data(&self, game_id: &str) -> Result<Option<GameUserData>> {
+        with_connection!(&self.pool, |conn| {
+            user_data::get(conn, game_id).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn set_user_data(&self, partial: &PartialGameUserData) -> Result<GameUserData> {
+        with_serialized_transaction!(self, "set_user_data", |tx| {
+            user_data::set(tx, partial, &self.clock.canonical_now()).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn find_favorites(&self) -> Result<Vec<Game>> {
+        with_connection!(&self.pool, |conn| {
+            let tag_filter = self.global_tag_filter(conn)?;
+            user_data::find_favorites(conn, tag_filter).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn find_series_games(&self, series: &str, order: GameSearchOrder, match_empty: bool) -> Result<Vec<Game>> {
+        with_connection!(&self.pool, |conn| {
+            let tag_filter = self.global_tag_filter(conn)?;
+            game::find_series_games(conn, series, order, match_empty, tag_filter).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn find_all_game_libraries(&self) -> Result<Vec<String>> {
+        with_connection!(&self.pool, |conn| {
+            game::find_libraries(conn).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn find_all_game_statuses(&self) -> Result<Vec<String>> {
+        with_connection!(&self.pool, |conn| {
+            game::find_statuses(conn).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn find_all_game_play_modes(&self) -> Result<Vec<String>> {
+        with_connection!(&self.pool, |conn| {
+            game::find_play_modes(conn).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn find_all_ruffle_support(&self) -> Result<Vec<String>> {
+        with_connection!(&self.pool, |conn| {
+            game::find_ruffle_support(conn).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn find_all_game_application_paths(&self) -> Result<Vec<String>> {
+        with_connection!(&self.pool, |conn| {
+            game::find_application_paths(conn).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn find_platform_app_paths(&self) -> Result<HashMap<String, Vec<PlatformAppPath>>> {
+        with_connection!(&self.pool, |conn| {
+            game::find_platform_app_paths(conn).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn add_game_playtime(&self, game_id: &str, seconds: i64) -> Result<()> {
+        with_serialized_transaction!(self, "add_game_playtime", |conn| {
+            game::add_playtime(conn, game_id, seconds, &self.clock.canonical_now()).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn clear_playtime_tracking_by_id(&self, game_id: &str) -> Result<()> {
+        with_serialized_transaction!(self, "clear_playtime_tracking_by_id", |conn| {
+            game::clear_playtime_tracking_by_id(conn, game_id).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn clear_playtime_tracking(&self) -> Result<()> {
+        with_serialized_transaction!(self, "clear_playtime_tracking", |conn| {
+            game::clear_playtime_tracking(conn).context(error::SqliteSnafu)
+        })
+    }
+
+    /// Clear the active config of every game using one owned by `owner`, e.g. an uninstalled extension. Returns how many games were cleared
+    pub async fn clear_active_config_by_owner(&self, owner: &str) -> Result<i64> {
+        with_serialized_transaction!(self, "clear_active_config_by_owner", |conn| {
+            game::clear_active_config_by_owner(conn, owner).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn force_games_active_data_most_recent(&self) -> Result<()> {
+        with_serialized_transaction!(self, "force_games_active_data_most_recent", |conn| {
+            game::force_active_data_most_recent(conn).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn find_game_redirects(&self) -> Result<Vec<GameRedirect>> {
+        with_connection!(&self.pool, |conn| {
+            game::find_redirects(conn).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn create_game_redirect(&self, src_id: &str, dest_id: &str) -> Result<()> {
+        with_serialized_transaction!(self, "create_game_redirect", |conn| {
+            game::create_redirect(conn, src_id, dest_id).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn delete_game_redirect(&self, src_id: &str, dest_id: &str) -> Result<()> {
+        with_serialized_transaction!(self, "delete_game_redirect", |conn| {
+            game::delete_redirect(conn, src_id, dest_id).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn update_apply_categories(&self, cats: Vec<RemoteCategory>) -> Result<()> {
+        with_serialized_transaction!(self, "update_apply_categories", |conn| {
+            update::apply_categories(conn, cats)
+        })
+    }
+
+    pub async fn update_apply_platforms(&self, platforms: Vec<RemotePlatform>) -> Result<ApplyPlatformsReport> {
+        with_serialized_transaction!(self, "update_apply_platforms", |conn| {
+            update::apply_platforms(conn, platforms)
+        })
+    }
+    
+    pub async fn update_apply_tags(&self, tags: Vec<RemoteTag>) -> Result<()> {
+        with_serialized_transaction!(self, "update_apply_tags", |conn| {
+            update::apply_tags(conn, tags)
+        })
+    }
+
+    pub async fn update_apply_games(&self, games_res: &RemoteGamesRes) -> Result<()> {
+        with_serialized_transaction!(self, "update_apply_games", |conn| {
+            update::apply_games(conn, games_res)
+        })
+    }
+
+    pub async fn update_delete_games(&self, games_res: &RemoteDeletedGamesRes) -> Result<()> {
+        with_serialized_transaction!(self, "update_delete_games", |conn| {
+            update::delete_games(conn, games_res)
+        })
+    }
+
+    pub async fn update_apply_redirects(&self, redirects_res: Vec<GameRedirect>) -> Result<()> {
+        with_serialized_transaction!(self, "update_apply_redirects", |conn| {
+            update::apply_redirects(conn, redirects_res)
+        })
+    }
+
+    pub async fn optimize_database(&self) -> Result<()> {
+        // VACUUM can't run inside a transaction, hold the write lock around a plain connection instead
+        let _write_guard = self.lock_writes().await?;
+        with_connection!(&self.pool, |conn| {
+            optimize_database(conn).context(error::SqliteSnafu)
+        })
+    }
+
+    pub async fn new_custom_id_order(&self, custom_id_order: Vec<String>) -> Result<()> {
+        with_serialized_transaction!(self, "new_custom_id_order", |conn| {
+            game::search::new_custom_id_order(conn, custom_id_order).context(error::SqliteSnafu)
+        })
+    }
+}
+
+pub fn logger_subscribe() -> (crate::logger::SubscriptionId, mpsc::Receiver<crate::logger::LogEvent>) {
+    LOGGER.subscribe()
+}
+
+pub fn logger_unsubscribe(id: crate::logger::SubscriptionId) {
+    LOGGER.unsubscribe(id)
+}
+
+fn optimize_database(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute("ANALYZE", ())?;
+    conn.execute("REINDEX", ())?;
+    conn.execute("VACUUM", ())?;
+    Ok(())
+}
+
+pub fn generate_content_tree(root: &str) -> Result<ContentTreeNode> {
+    util::gen_content_tree(root).map_err(|_| snafu::NoneError).context(error::ContentTreeSnafu)
+}
+
+pub fn copy_folder(src: &str, dest: &str) -> Result<u64> {
+    util::copy_folder(src, dest).map_err(|_| snafu::NoneError).context(error::CopyFolderSnafu)
+}
+
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// Catch files that clearly aren't SQLite (HTML error pages, truncated downloads) before SQLite gives a cryptic error.
+/// Missing files are left alone so SQLite can create them.
+fn check_database_header(source: &str) -> Result<()> {
+    use std::io::Read;
+
+    let mut file = match std::fs::File::open(source) {
+        Ok(file) => file,
+        Err(_) => return Ok(()),
+    };
+
+    let mut header = Vec::with_capacity(SQLITE_HEADER.len());
+    let read = (&mut file).take(SQLITE_HEADER.len() as u64).read_to_end(&mut header);
+    if read.is_err() || header.as_slice() != SQLITE_HEADER {
+        let summary = match header.len() {
+            0 => "an empty file".to_owned(),
+            _ => format!("\"{}\"", String::from_utf8_lossy(&header).escape_debug()),
+        };
+        return error::NotADatabaseSnafu { path: source, header: summary }.fail();
+    }
+
+    Ok(())
+}
+
+fn migrate(conn: &mut Connection, source: &str) -> Result<()> {
+    migration::up(conn).map_err(|e| match e {
+        rusqlite_migration::Error::RusqliteError { ref err, .. } if is_locked_error(err) => {
+            Error::DatabaseLocked { path: source.to_owned() }
+        }
+        e => Error::DatabaseMigration { source: e },
+    })
+}
+
+#[cfg(feature = "serialize")]
+fn sqlite_owned_data(bytes: &[u8]) -> Result<rusqlite::serialize::OwnedData> {
+    use rusqlite::ffi;
+
+    // SQLite frees a deserialized buffer itself, so it must come from SQLite's allocator
+    let ptr = unsafe { ffi::sqlite3_malloc64(bytes.len() as u64) } as *mut u8;
+    let ptr = std::ptr::NonNull::new(ptr).ok_or_else(|| Error::SqliteError {
+        source: rusqlite::Error::SqliteFailure(ffi::Error::new(ffi::SQLITE_NOMEM), None),
+    })?;
+    unsafe {
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr.as_ptr(), bytes.len());
+        Ok(rusqlite::serialize::OwnedData::from_raw_nonnull(ptr, bytes.len()))
+    }
+}
+
+fn start_query_clock() {
+    QUERY_CLOCK_DEPTH.with(|depth| depth.set(depth.get() + 1));
+}
+
+/// Traces every statement as it begins, so the timeout covers single statements rather than whole operations
+fn restart_query_clock(_sql: &str) {
+    if QUERY_CLOCK_DEPTH.with(|depth| depth.get()) > 0 {
+        QUERY_STARTED.with(|started| started.set(Some(Instant::now())));
+    }
+}
+
+/// Stops the clock started by `start_query_clock`, turning an interrupted query into `Error::Timeout`.
+/// An operation nested in another leaves the outer one's clock running.
+fn stop_query_clock<T>(res: Result<T>) -> Result<T> {
+    QUERY_CLOCK_DEPTH.with(|depth| {
+        depth.set(depth.get().saturating_sub(1));
+        if depth.get() == 0 {
+            QUERY_STARTED.with(|started| started.set(None));
+        }
+    });
+    res.map_err(|e| match e {
+        Error::SqliteError { source }
+            if source.sqlite_error_code() == Some(rusqlite::ErrorCode::OperationInterrupted) =>
+        {
+            Error::Timeout
+        }
+        e => e,
+    })
+}
+
+fn is_locked_error(err: &rusqlite::Error) -> bool {
+    matches!(
+        err.sqlite_error_code(),
+        Some(rusqlite::ErrorCode::DatabaseBusy) | Some(rusqlite::ErrorCode::DatabaseLocked)
+    )
+}
+
+pub fn merge_game_filters(a: &GameFilter, b: &GameFilter) -> GameFilter {
+    let mut new_filter = GameFilter::default();
+    new_filter.subfilters = vec![a.clone(), b.clone()];
+
+    if a.match_any && b.match_any {
+        new_filter.match_any = true;
+    }
+
+    return new_filter;
+}
+
+#[macro_export]
+macro_rules! with_connection {
+    ($pool:expr, $body:expr) => {
+        match $pool {
+            Some(conn) => {
+                let conn = &conn.get().context(error::ConnectionCheckoutSnafu)?;
+                $crate::start_query_clock();
+                $crate::stop_query_clock($body(conn))
+            },
+            None => return Err(Error::DatabaseNotInitialized)
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! with_transaction {
+    ($pool:expr, $body:expr) => {
+        match $pool {
+            Some(conn) => {
+                let mut conn = conn.get().context(error::ConnectionCheckoutSnafu)?;
+                let tx = conn.transaction().context(error::SqliteSnafu)?;
+                $crate::start_query_clock();
+                let res = $crate::stop_query_clock($body(&tx));
+                if res.is_ok() {
+                    tx.commit().context(error::SqliteSnafu)?;
+                    debug_println!("Applied transaction");
+                }
+                res
+            },
+            None => return Err(Error::DatabaseNotInitialized)
+        }
+    };
+}
+
+/// Like `with_transaction!`, but takes the write lock up front so concurrent writers queue instead of failing part way.
+/// `$op` names the operation in the log event emitted when it holds the lock past the write timeout.
+///
+/// Every archive method that writes goes through this. `optimize_database` takes the lock without a transaction,
+/// and searches rebuilding a stale tag filter index on the way don't take it.
+macro_rules! with_serialized_transaction {
+    ($archive:expr, $op:expr, $body:expr) => {
+        match &$archive.pool {
+            Some(conn) => {
+                let _write_guard = $archive.lock_writes().await?;
+                let started = Instant::now();
+                let mut conn = conn.get().context(error::ConnectionCheckoutSnafu)?;
+                let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate).context(error::SqliteSnafu)?;
+                $crate::start_query_clock();
+                let res = $crate::stop_query_clock($body(&tx));
+                if res.is_ok() {
+                    tx.commit().context(error::SqliteSnafu)?;
+                    debug_println!("Applied serialized transaction");
+                }
+                if let Some(timeout) = $archive.write_timeout {
+                    let held = started.elapsed();
+                    if held > timeout {
+                        $crate::LOGGER.dispatch_event(format!("Slow write: {} held the write lock for {}ms", $op, held.as_millis()));
+                    }
+                }
+                res
+            },
+            None => return Err(Error::DatabaseNotInitialized)
+        }
+    };
+}
+pub(crate) use with_serialized_transaction;
+
+pub fn enable_debug() {
+    DEBUG_ENABLED.store(true, std::sync::atomic::Ordering::SeqCst);
+}
+
+pub fn disable_debug() {
+    DEBUG_ENABLED.store(false, std::sync::atomic::Ordering::SeqCst);
+}
+
+pub fn debug_enabled() -> bool {
+    DEBUG_ENABLED.load(std::sync::atomic::Ordering::SeqCst)
+}
+
+/// Log queries with their placeholders followed by a parameter summary, instead of interpolating the parameters
+pub fn set_debug_raw_queries(enabled: bool) {
+    DEBUG_RAW_QUERIES.store(enabled, std::sync::atomic::Ordering::SeqCst);
+}
+
+pub fn debug_raw_queries() -> bool {
+    DEBUG_RAW_QUERIES.load(std::sync::atomic::Ordering::SeqCst)
+}
+
+/// Cut each parameter of a logged query to `length` characters, so pasted notes or long id lists don't flood the log
+pub fn set_debug_param_max_length(length: usize) {
+    DEBUG_PARAM_MAX_LENGTH.store(length, std::sync::atomic::Ordering::SeqCst);
+}
+
+pub fn debug_param_max_length() -> usize {
+    DEBUG_PARAM_MAX_LENGTH.load(std::sync::atomic::Ordering::SeqCst)
+}
+
+#[macro_export]
+macro_rules! debug_println {
+    ($($arg:tt)*) => (if $crate::debug_enabled() {
+        ::std::println!($($arg)*);
+        let formatted_message = ::std::format!($($arg)*);
+        $crate::LOGGER.dispatch_event(formatted_message);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::game::search::{GameSearchOffset, GameFilter, FieldFilter};
+
+    use super::*;
+
+    /// Fixture database, override with FPA_TEST_DATABASE to run against another copy
+    fn test_database() -> String {
+        std::env::var("FPA_TEST_DATABASE").unwrap_or_else(|_| "benches/flashpoint.sqlite".to_owned())
+    }
+
+    #[tokio::test]
+    async fn database_not_initialized() {
+        let flashpoint = FlashpointArchive::new();
+        let result = flashpoint.count_games().await;
+        assert!(result.is_err());
+
+        let e = result.unwrap_err();
+        assert!(matches!(e, Error::DatabaseNotInitialized {}));
+    }
+
+    #[tokio::test]
+    async fn migrations_valid() {
+        let migrations = migration::get();
+        assert!(migrations.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn count_games() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_existing_database(&test_database());
+        assert!(create.is_ok());
+        let result = flashpoint.count_games().await;
+        assert!(result.is_ok());
+
+        let total = result.unwrap();
+        assert_eq!(total, 191150);
+    }
+
+    #[tokio::test]
+    async fn search_full_scan() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_existing_database(&test_database());
+        assert!(create.is_ok());
+        let mut search = game::search::GameSearch::default();
+        search.limit = 99999999999;
+        search.filter.exact_whitelist.library = Some(vec![String::from("arcade")]);
+        let result = flashpoint.search_games(&search).await;
+        assert!(result.is_ok());
+        let games = result.unwrap();
+        assert_eq!(games.len(), 162929);
+    }
+
+    #[tokio::test]
+    async fn search_tags_or() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_existing_database(&test_database());
+        assert!(create.is_ok());
+        let mut search = game::search::GameSearch::default();
+        search.limit = 99999999999;
+        search.filter.match_any = true;
+        search.filter.exact_whitelist.tags = Some(vec!["Action".to_owned(), "Adventure".to_owned()]);
+        let result = flashpoint.search_games(&search).await;
+        assert!(result.is_ok());
+        let games = result.unwrap();
+        assert_eq!(games.len(), 36724);
+    }
+
+    #[tokio::test]
+    async fn search_tags_and() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_existing_database(&test_database());
+        assert!(create.is_ok());
+        let mut search = game::search::GameSearch::default();
+        search.limit = 99999999999;
+        search.filter.match_any = false;
+        search.filter.exact_whitelist.tags = Some(vec!["Action".to_owned(), "Adventure".to_owned()]);
+        let result = flashpoint.search_games(&search).await;
+        assert!(result.is_ok());
+        let games = result.unwrap();
+        assert_eq!(games.len(), 397);
+    }
+
+    #[tokio::test]
+    async fn search_tags_and_or_combined() {
+        // Has 'Action' or 'Adventure', but is missing 'Sonic The Hedgehog'
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_existing_database(&test_database());
+        assert!(create.is_ok());
+        let mut search = game::search::GameSearch::default();
+        let mut inner_filter = game::search::GameFilter::default();
+        // Set page size for index search
+        search.limit = 30000;
+        // Add the OR to an inner filter
+        inner_filter.exact_whitelist.tags = Some(vec!["Action".to_owned(), "Adventure".to_owned()]);
+        inner_filter.match_any = true; // OR
+        // Add the AND to the main filter, with the inner filter
+        search.filter.subfilters = vec![inner_filter];
+        search.filter.exact_blacklist.tags = Some(vec!["Sonic The Hedgehog".to_owned()]);
+        search.filter.match_any = false; // AND
+
+        // Test total results
+        enable_debug();
+        let total_result = flashpoint.search_games_total(&search).await;
+        assert!(total_result.is_ok());
+        let total = total_result.unwrap();
+        assert_eq!(total, 36541);
+
+        // Test first page results
+        let result = flashpoint.search_games(&search).await;
+        assert!(result.is_ok());
         let games = result.unwrap();
         assert_eq!(games.len(), 30000);
         let page_end_game = games.last().unwrap();
 
-        // Test index
-        let index_result = flashpoint.search_games_index(&mut search, None).await;
-        assert!(index_result.is_ok());
-        let index = index_result.unwrap();
-        assert_eq!(index.len(), 1);
-        assert_eq!(index[0].id, page_end_game.id);
+        // Test index
+        let index_result = flashpoint.search_games_index(&mut search, None).await;
+        assert!(index_result.is_ok());
+        let index = index_result.unwrap();
+        assert_eq!(index.len(), 1);
+        assert_eq!(index[0].id, page_end_game.id);
+
+        // Test last page results
+        search.offset = Some(GameSearchOffset{
+            value: page_end_game.title.clone(),
+            game_id: page_end_game.id.clone(),
+            title: page_end_game.title.clone(),
+        });
+        let last_result = flashpoint.search_games(&search).await;
+        assert!(last_result.is_ok());
+        let last_page = last_result.unwrap();
+        assert_eq!(last_page.len(), 6541);
+    }
+
+    #[tokio::test]
+    async fn search_multiple_subfilters() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_existing_database(&test_database());
+        assert!(create.is_ok());
+        let mut search = GameSearch::default();
+        search.filter.subfilters.push(GameFilter {
+            exact_blacklist: FieldFilter {
+                tags: Some(vec!["Action".to_owned(), "Shooting".to_owned()]),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        search.filter.subfilters.push(GameFilter {
+            exact_blacklist: FieldFilter {
+                tags: Some(vec!["Adventure".to_owned()]),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        search.filter.exact_whitelist.library = Some(vec!["arcade".to_owned()]);
+        search.filter.match_any = false;
+        assert!(flashpoint.search_games_index(&mut search, None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn parse_user_search_input_assorted() {
+        game::search::parse_user_input("test");
+        game::search::parse_user_input(r#"tag:"sonic""#);
+        game::search::parse_user_input(r#"o_%$ dev:"san" disk t:7 potato"#);
+
+        enable_debug();
+
+        // "" should be treated as exact
+        // Allow key characters in quoted text
+        let s = game::search::parse_user_input(r#"title:"" series:"sonic:hedgehog" -developer:"""#).search;
+        assert!(s.filter.exact_whitelist.title.is_some());
+        assert_eq!(s.filter.exact_whitelist.title.unwrap()[0], "");
+        assert!(s.filter.whitelist.series.is_some());
+        assert_eq!(s.filter.whitelist.series.unwrap()[0], "sonic:hedgehog");
+        assert!(s.filter.exact_blacklist.developer.is_some());
+        assert_eq!(s.filter.exact_blacklist.developer.unwrap()[0], "");
+
+        // Make sure the number filters are populated and the time text is processes
+        let s2 = game::search::parse_user_input(r#"playtime>1h30m tags:3 playcount<3"#).search;
+        assert!(s2.filter.higher_than.playtime.is_some());
+        assert_eq!(s2.filter.higher_than.playtime.unwrap(), 60 * 90);
+        assert!(s2.filter.equal_to.tags.is_some());
+        assert_eq!(s2.filter.equal_to.tags.unwrap(), 3);
+        assert!(s2.filter.lower_than.playcount.is_some());
+        assert_eq!(s2.filter.lower_than.playcount.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn parse_user_search_input_sizes() {
+        let search = game::search::parse_user_input("tags>5 addapps=3 gamedata<12 test>generic").search;
+        assert!(search.filter.higher_than.tags.is_some());
+        assert_eq!(search.filter.higher_than.tags.unwrap(), 5);
+        assert!(search.filter.equal_to.add_apps.is_some());
+        assert_eq!(search.filter.equal_to.add_apps.unwrap(), 3);
+        assert!(search.filter.lower_than.game_data.is_some());
+        assert_eq!(search.filter.lower_than.game_data.unwrap(), 12);
+        assert!(search.filter.whitelist.generic.is_some());
+        let generics = search.filter.whitelist.generic.unwrap();
+        assert_eq!(generics.len(), 1);
+        assert_eq!(generics[0], "test>generic");
+    }
+
+    #[tokio::test]
+    async fn find_game() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_existing_database(&test_database());
+        assert!(create.is_ok());
+        let result = flashpoint.find_game("00deff25-5cd2-40d1-a0e7-151d82ce16c5").await;
+        assert!(result.is_ok());
+        let game_opt = result.unwrap();
+        assert!(game_opt.is_some());
+        let game = game_opt.unwrap();
+        assert_eq!(game.title, "Crab Planet");
+        assert!(game.detailed_platforms.is_some());
+        let platforms = game.detailed_platforms.unwrap();
+        assert_eq!(platforms.len(), 1);
+        assert_eq!(platforms[0].name, "Flash");
+    }
+
+    #[tokio::test]
+    async fn game_redirects() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_database(":memory:");
+        assert!(create.is_ok());
+        let partial_game = game::PartialGame {
+            title: Some(String::from("Test Game")),
+            tags: Some(vec!["Action"].into()),
+            ..game::PartialGame::default()
+        };
+        let result = flashpoint.create_game(&partial_game).await;
+        assert!(result.is_ok());
+        let game = result.unwrap();
+
+        let create_redirect_res = flashpoint.create_game_redirect("test", &game.id).await;
+        assert!(create_redirect_res.is_ok());
+
+        // Find game redirect
+        let found_game_res = flashpoint.find_game("test").await;
+        assert!(found_game_res.is_ok());
+        assert!(found_game_res.unwrap().is_some());
+
+        // ID search redirect
+        let mut search = GameSearch::default();
+        search.filter.exact_whitelist.id = Some(vec!["test".to_owned()]);
+        let search_res = flashpoint.search_games(&search).await;
+        assert!(search_res.is_ok());
+        assert_eq!(search_res.unwrap().len(), 1);
+
+        // ID search token resolves redirects
+        let search = game::search::parse_user_input(r#"id:"test""#).search;
+        assert_eq!(search.filter.exact_whitelist.id, Some(vec!["test".to_owned()]));
+        let search_res = flashpoint.search_games(&search).await;
+        assert!(search_res.is_ok());
+        let found = search_res.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, game.id);
+
+        // ID prefix search
+        let search = game::search::parse_user_input(&format!("id:{}*", &game.id[..8])).search;
+        assert!(search.filter.exact_whitelist.id.is_none());
+        let search_res = flashpoint.search_games(&search).await;
+        assert!(search_res.is_ok());
+        assert_eq!(search_res.unwrap().len(), 1);
+
+        // Find redirects
+        let found_redirs = flashpoint.find_game_redirects().await;
+        assert!(found_redirs.is_ok());
+        assert_eq!(found_redirs.unwrap().len(), 1);
+
+        let remove_redirect_res = flashpoint.delete_game_redirect("test", &game.id).await;
+        assert!(remove_redirect_res.is_ok());
+
+        let found_redirs2 = flashpoint.find_game_redirects().await;
+        assert!(found_redirs2.is_ok());
+        assert_eq!(found_redirs2.unwrap().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn tag_categories() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_database(":memory:");
+        assert!(create.is_ok());
+        let partial_tc = tag_category::PartialTagCategory {
+            id: -1,
+            name: "test".to_owned(),
+            color: "#FF00FF".to_owned(),
+            description: Some("test".to_owned()),
+            ordering: None,
+        };
+        assert!(flashpoint.create_tag_category(&partial_tc).await.is_ok());
+        let saved_cat_result = flashpoint.find_tag_category("test").await;
+        assert!(saved_cat_result.is_ok());
+        let saved_cat_opt = saved_cat_result.unwrap();
+        assert!(saved_cat_opt.is_some());
+        let saved_cat = saved_cat_opt.unwrap();
+        assert_eq!(saved_cat.name, "test");
+        assert_eq!(saved_cat.color, "#FF00FF");
+        assert!(saved_cat.description.is_some());
+        assert_eq!(saved_cat.description.unwrap(), "test");
+
+        let all_cats_result = flashpoint.find_all_tag_categories().await;
+        assert!(all_cats_result.is_ok());
+        let all_cats = all_cats_result.unwrap();
+        // Default category always exists
+        assert_eq!(all_cats.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn tag_category_with_id() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_database(":memory:");
+        assert!(create.is_ok());
+        let partial_tc = tag_category::PartialTagCategory {
+            id: 42,
+            name: "imported".to_owned(),
+            color: "#00FF00".to_owned(),
+            description: None,
+            ordering: None,
+        };
+        let created_res = flashpoint.create_tag_category_with_id(&partial_tc).await;
+        assert!(created_res.is_ok());
+        assert_eq!(created_res.unwrap().id, 42);
+        let saved_cat_res = flashpoint.find_tag_category_by_id(42).await;
+        assert!(saved_cat_res.is_ok());
+        let saved_cat_opt = saved_cat_res.unwrap();
+        assert!(saved_cat_opt.is_some());
+        let saved_cat = saved_cat_opt.unwrap();
+        assert_eq!(saved_cat.name, "imported");
+        assert_eq!(saved_cat.color, "#00FF00");
+
+        // Tags can reference the imported category by name
+        let new_tag_res = flashpoint.create_tag("test", Some("imported".to_owned()), None).await;
+        assert!(new_tag_res.is_ok());
+        assert_eq!(new_tag_res.unwrap().category.unwrap(), "imported");
+    }
+
+    #[tokio::test]
+    async fn create_and_save_game() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_database(":memory:");
+        assert!(create.is_ok());
+        let partial_game = game::PartialGame {
+            title: Some(String::from("Test Game")),
+            tags: Some(vec!["Action"].into()),
+            ..game::PartialGame::default()
+        };
+        let result = flashpoint.create_game(&partial_game).await;
+        assert!(result.is_ok());
+        let mut game = result.unwrap();
+        let found_tag_res = flashpoint.find_tag("Action").await;
+        assert!(found_tag_res.is_ok());
+        let found_tag_opt = found_tag_res.unwrap();
+        assert!(found_tag_opt.is_some());
+        let found_game_res = flashpoint.find_game(&game.id).await;
+        assert!(found_game_res.is_ok());
+        let found_game_opt = found_game_res.unwrap();
+        assert!(found_game_opt.is_some());
+        let found_game = found_game_opt.unwrap();
+        assert!(found_game.detailed_tags.is_some());
+        let found_tags = found_game.detailed_tags.unwrap();
+        assert_eq!(found_tags.len(), 1);
+        assert_eq!(game.title, "Test Game");
+        game.developer = String::from("Newgrounds");
+        game.tags = vec!["Action", "Adventure"].into();
+        game.primary_platform = String::from("Flash");
+        let save_result = flashpoint.save_game(&mut game.into()).await;
+        assert!(save_result.is_ok());
+        let saved_game = save_result.unwrap();
+        assert_eq!(saved_game.developer, "Newgrounds");
+        assert_eq!(saved_game.tags.len(), 2);
+        assert_eq!(saved_game.platforms.len(), 1);
+        assert_eq!(saved_game.platforms[0], "Flash");
+        assert_eq!(saved_game.primary_platform, "Flash");
+        assert!(saved_game.detailed_platforms.is_some());
+        let detailed_platforms = saved_game.detailed_platforms.unwrap();
+        assert_eq!(detailed_platforms.len(), 1);
+        assert!(saved_game.detailed_tags.is_some());
+        let detailed_tags = saved_game.detailed_tags.unwrap();
+        assert_eq!(detailed_tags.len(), 2);
+        assert_eq!(detailed_tags[0].name, "Action");
+    }
+
+    #[tokio::test]
+    async fn create_and_save_game_data() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_database(":memory:");
+        assert!(create.is_ok());
+        let partial_game = game::PartialGame {
+            title: Some(String::from("Test Game")),
+            tags: Some(vec!["Action"].into()),
+            ..game::PartialGame::default()
+        };
+        let game_create_res = flashpoint.create_game(&partial_game).await;
+        assert!(game_create_res.is_ok());
+        let game = game_create_res.unwrap();
+        let game_data = PartialGameData { 
+            id: None,
+            game_id: game.id,
+            title: Some("Test".to_owned()),
+            date_added: Some("2023-01-01T01:01:01.000".to_owned()),
+            sha256: Some("123".to_owned()),
+            crc32: Some(0),
+            present_on_disk: Some(false),
+            path: None,
+            size: Some(123),
+            parameters: None,
+            application_path: Some("Test".to_owned()),
+            launch_command: Some("Test".to_owned())
+        };
+
+        let game_data_res = flashpoint.create_game_data(&game_data).await;
+        assert!(game_data_res.is_ok());
+        let mut gd = game_data_res.unwrap();
+        gd.path = Some("Test".to_owned());
+        let save_res = flashpoint.save_game_data(&gd.into()).await;
+        assert!(save_res.is_ok());
+        let new_gd = save_res.unwrap();
+        assert_eq!(new_gd.path.unwrap(), "Test");
+    }
+
+    #[tokio::test]
+    async fn parse_user_search_input() {
+        let input = r#"sonic title:"dog cat" -title:"cat dog" tag:Action -mario installed:true"#;
+        let search = game::search::parse_user_input(input).search;
+        assert!(search.filter.whitelist.generic.is_some());
+        assert_eq!(search.filter.whitelist.generic.unwrap()[0], "sonic");
+        assert!(search.filter.whitelist.title.is_some());
+        assert_eq!(search.filter.whitelist.title.unwrap()[0], "dog cat");
+        assert!(search.filter.blacklist.title.is_some());
+        assert_eq!(search.filter.blacklist.title.unwrap()[0], "cat dog");
+        assert!(search.filter.whitelist.tags.is_some());
+        assert_eq!(search.filter.whitelist.tags.unwrap()[0], "Action");
+        assert!(search.filter.blacklist.generic.is_some());
+        assert_eq!(search.filter.blacklist.generic.unwrap()[0], "mario");
+        assert!(search.filter.bool_comp.installed.is_some());
+        assert_eq!(search.filter.bool_comp.installed.unwrap(), true);
+    }
+
+    #[tokio::test]
+    async fn parse_user_search_input_terms() {
+        let input = r#"sonic -title:"dog cat" #Action series:"sonic:hedgehog" -developer:"" playtime>1h30m test>generic"#;
+        let terms = game::search::parse_user_input(input).terms;
+        assert_eq!(terms.len(), 7);
+
+        let expected = [
+            ("generic", "sonic", false, false, 0, 5),
+            ("title", "dog cat", true, false, 6, 22),
+            ("tags", "Action", false, false, 23, 30),
+            ("series", "sonic:hedgehog", false, false, 31, 54),
+            ("developer", "", true, true, 55, 68),
+            ("playtime", "5400", false, false, 69, 83),
+            ("generic", "test>generic", false, false, 84, 96),
+        ];
+        for (term, (field, value, negated, exact, start, end)) in terms.iter().zip(expected) {
+            assert_eq!(term.field, field);
+            assert_eq!(term.value, value);
+            assert_eq!(term.negated, negated);
+            assert_eq!(term.exact, exact);
+            assert_eq!(term.start, start);
+            assert_eq!(term.end, end);
+        }
+
+        // Spans cover the original input exactly
+        assert_eq!(&input[terms[1].start as usize..terms[1].end as usize], r#"-title:"dog cat""#);
+        assert_eq!(&input[terms[2].start as usize..terms[2].end as usize], "#Action");
+
+        // Quoted values spanning several tokens keep their key char and value at the opening quote
+        let positions = game::search::parse_user_input(input).positions;
+        let spans: Vec<(&str, i32, i32)> = positions.iter().skip(1).take(5).map(|p| (p.value.as_str(), p.start, p.end)).collect();
+        assert_eq!(spans, vec![("-", 6, 7), ("title", 7, 12), (":", 12, 13), ("dog cat", 13, 20), ("#", 23, 24)]);
+    }
+
+    #[tokio::test]
+    async fn parse_user_search_input_whitespace() {
+        let input = r#"series:"紅白Flash合戦  / Red & White Flash Battle 2013""#;
+        let search = game::search::parse_user_input(input).search;
+        assert!(search.filter.whitelist.series.is_some());
+        assert_eq!(search.filter.whitelist.series.unwrap()[0], "紅白Flash合戦  / Red & White Flash Battle 2013");
+    }
+
+    #[tokio::test]
+    async fn parse_user_quick_search_input() {
+        let input = r#"#Action -!Flash @"armor games" !"#;
+        let search = game::search::parse_user_input(input).search;
+        assert!(search.filter.whitelist.tags.is_some());
+        assert_eq!(search.filter.whitelist.tags.unwrap()[0], "Action");
+        assert!(search.filter.blacklist.platforms.is_some());
+        assert_eq!(search.filter.blacklist.platforms.unwrap()[0], "Flash");
+        assert!(search.filter.whitelist.developer.is_some());
+        assert_eq!(search.filter.whitelist.developer.unwrap()[0], "armor games");
+        assert!(search.filter.whitelist.generic.is_some());
+        assert_eq!(search.filter.whitelist.generic.unwrap()[0], "!");
+    }
+
+    #[tokio::test]
+    async fn parse_user_exact_search_input() {
+        let input = r#"!Flash -publisher=Newgrounds =sonic"#;
+        let search = game::search::parse_user_input(input).search;
+        assert!(search.filter.whitelist.platforms.is_some());
+        assert_eq!(search.filter.whitelist.platforms.unwrap()[0], "Flash");
+        assert!(search.filter.exact_blacklist.publisher.is_some());
+        assert_eq!(search.filter.exact_blacklist.publisher.unwrap()[0], "Newgrounds");
+        assert!(search.filter.whitelist.generic.is_some());
+        assert!(search.filter.exact_whitelist.generic.is_none());
+        assert_eq!(search.filter.whitelist.generic.unwrap()[0], "=sonic");
+    }
+
+    #[tokio::test]
+    async fn find_series_games() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        for (title, series, release_date) in [("B", "Sonic", "2005"), ("A", "Sonic", "2003"), ("C", "", "2004")] {
+            let partial = PartialGame {
+                title: Some(title.to_owned()),
+                series: Some(series.to_owned()),
+                release_date: Some(release_date.to_owned()),
+                ..Default::default()
+            };
+            assert!(flashpoint.create_game(&partial).await.is_ok());
+        }
+        let order = game::search::GameSearchOrder {
+            column: game::search::GameSearchSortable::RELEASEDATE,
+            direction: game::search::GameSearchDirection::ASC,
+        };
+        let series_res = flashpoint.find_series_games("Sonic", order.clone(), false).await;
+        assert!(series_res.is_ok());
+        let series_games = series_res.unwrap();
+        assert_eq!(series_games.len(), 2);
+        assert_eq!(series_games[0].title, "A");
+        assert_eq!(series_games[1].title, "B");
+
+        // Empty series only matches games without a series when asked to
+        let empty_res = flashpoint.find_series_games("", order.clone(), false).await;
+        assert!(empty_res.is_ok());
+        assert_eq!(empty_res.unwrap().len(), 0);
+        let empty_res = flashpoint.find_series_games("", order, true).await;
+        assert!(empty_res.is_ok());
+        let empty_games = empty_res.unwrap();
+        assert_eq!(empty_games.len(), 1);
+        assert_eq!(empty_games[0].title, "C");
+    }
+
+    #[tokio::test]
+    async fn find_all_game_libraries() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_existing_database(&test_database());
+        assert!(create.is_ok());
+        let libraries_res = flashpoint.find_all_game_libraries().await;
+        assert!(libraries_res.is_ok());
+        let libraries = libraries_res.unwrap();
+        assert_eq!(libraries.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn create_tag() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let new_tag_res = flashpoint.create_tag("test", None, None).await;
+        assert!(new_tag_res.is_ok());
+        let new_tag = new_tag_res.unwrap();
+        assert!(new_tag.category.is_some());
+        assert_eq!(new_tag.category.unwrap(), "default");
+        assert_eq!(new_tag.name, "test");
+        assert_eq!(new_tag.aliases.len(), 1);
+        assert_eq!(new_tag.aliases[0], "test");
+    }
+
+    #[tokio::test]
+    async fn delete_tag() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let partial = PartialGame {
+            title: Some("test".to_owned()),
+            tags: Some(vec!["Action"].into()),
+            ..Default::default()
+        };
+        let new_game_res = flashpoint.create_game(&partial).await;
+        assert!(new_game_res.is_ok());
+        let saved_game = new_game_res.unwrap();
+        assert_eq!(saved_game.tags.len(), 1);
+        let delete_res = flashpoint.delete_tag("Action").await;
+        assert!(delete_res.is_ok());
+        let modded_game_res = flashpoint.find_game(&saved_game.id).await;
+        assert!(modded_game_res.is_ok());
+        let modded_game_opt = modded_game_res.unwrap();
+        assert!(modded_game_opt.is_some());
+        let modded_game = modded_game_opt.unwrap();
+        assert_eq!(modded_game.tags.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn merge_tags() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let partial = PartialGame {
+            title: Some("test".to_owned()),
+            tags: Some(vec!["Action"].into()),
+            ..Default::default()
+        };
+        let new_game_res = flashpoint.create_game(&partial).await;
+        assert!(new_game_res.is_ok());
+        assert!(flashpoint.create_tag("Adventure", None, None).await.is_ok());
+        let saved_game = new_game_res.unwrap();
+        let merged_tag_res = flashpoint.merge_tags("Action", "Adventure", None).await;
+        assert!(merged_tag_res.is_ok());
+        let merged_tag = merged_tag_res.unwrap();
+        assert_eq!(merged_tag.aliases.len(), 2);
+        let modded_game_res = flashpoint.find_game(&saved_game.id).await;
+        assert!(modded_game_res.is_ok());
+        let modded_game_opt = modded_game_res.unwrap();
+        assert!(modded_game_opt.is_some());
+        let modded_game = modded_game_opt.unwrap();
+        assert_eq!(modded_game.tags.len(), 1);
+        assert_eq!(modded_game.tags[0], "Adventure");
+    }
+
+    #[tokio::test]
+    async fn find_tag() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let partial = PartialGame {
+            title: Some("test".to_owned()),
+            tags: Some(vec!["Action"].into()),
+            ..Default::default()
+        };
+        let new_game_res = flashpoint.create_game(&partial).await;
+        assert!(new_game_res.is_ok());
+        let tag_res = flashpoint.find_tag("Action").await;
+        assert!(tag_res.is_ok());
+        let tag_opt = tag_res.unwrap();
+        assert!(tag_opt.is_some());
+        let tag_id_res = flashpoint.find_tag_by_id(tag_opt.unwrap().id).await;
+        assert!(tag_id_res.is_ok());
+        assert!(tag_id_res.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn delete_platform() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let partial = PartialGame {
+            title: Some("test".to_owned()),
+            platforms: Some(vec!["Flash"].into()),
+            ..Default::default()
+        };
+        let new_game_res = flashpoint.create_game(&partial).await;
+        assert!(new_game_res.is_ok());
+        let saved_game = new_game_res.unwrap();
+        assert_eq!(saved_game.platforms.len(), 1);
+        let delete_res = flashpoint.delete_platform("Flash").await;
+        assert!(delete_res.is_ok());
+        let modded_game_res = flashpoint.find_game(&saved_game.id).await;
+        assert!(modded_game_res.is_ok());
+        let modded_game_opt = modded_game_res.unwrap();
+        assert!(modded_game_opt.is_some());
+        let modded_game = modded_game_opt.unwrap();
+        assert_eq!(modded_game.platforms.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn create_platform() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let new_tag_res = flashpoint.create_platform("test", None).await;
+        assert!(new_tag_res.is_ok());
+        let new_tag = new_tag_res.unwrap();
+        assert!(new_tag.category.is_none());
+        assert_eq!(new_tag.name, "test");
+        assert_eq!(new_tag.aliases.len(), 1);
+        assert_eq!(new_tag.aliases[0], "test");
+    }
+
+    #[tokio::test]
+    async fn search_tag_suggestions() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let new_tag_res = flashpoint.create_tag("Action", None, None).await;
+        assert!(new_tag_res.is_ok());
+        let suggs_res = flashpoint.search_tag_suggestions("Act", vec![], false, None).await;
+        assert!(suggs_res.is_ok());
+        assert_eq!(suggs_res.unwrap().len(), 1);
+        let suggs_bad_res = flashpoint.search_tag_suggestions("Adventure", vec![], false, None).await;
+        assert!(suggs_bad_res.is_ok());
+        assert_eq!(suggs_bad_res.unwrap().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn search_tag_suggestions_ranking() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        for (name, alias) in [("Action", "Act"), ("Acting", "Acta (old)")] {
+            let tag = flashpoint.create_tag(name, None, None).await.unwrap();
+            let mut partial: PartialTag = tag.into();
+            partial.aliases = Some(vec![name.to_owned(), alias.to_owned()]);
+            assert!(flashpoint.save_tag(&mut partial).await.is_ok());
+            assert!(flashpoint.create_game(&PartialGame {
+                title: Some(format!("{} Game", name)),
+                tags: Some(vec![name].into()),
+                ..Default::default()
+            }).await.is_ok());
+        }
+
+        // Exact match first, then primary names above aliases at equal counts
+        let suggs = flashpoint.search_tag_suggestions("Act", vec![], true, None).await.unwrap();
+        let matched: Vec<&str> = suggs.iter().map(|s| s.matched_from.as_str()).collect();
+        assert_eq!(matched, vec!["Act", "Acting", "Action", "Acta (old)"]);
+        assert!(!suggs[0].is_primary_match);
+        assert!(suggs[1].is_primary_match);
+
+        // One row per tag, using its best ranked match
+        let suggs = flashpoint.search_tag_suggestions("Act", vec![], false, None).await.unwrap();
+        assert_eq!(suggs.len(), 2);
+        assert_eq!(suggs[0].name, "Action");
+        assert_eq!(suggs[0].matched_from, "Act");
+        assert_eq!(suggs[1].name, "Acting");
+        assert_eq!(suggs[1].matched_from, "Acting");
+        assert!(suggs[1].is_primary_match);
+    }
+
+    #[tokio::test]
+    async fn update_game_when_platform_changed() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let partial_game = game::PartialGame {
+            title: Some(String::from("Test Game")),
+            tags: Some(vec!["Action"].into()),
+            platforms: Some(vec!["Flash", "HTML5"].into()),
+            primary_platform: Some("HTML5".into()),
+            ..game::PartialGame::default()
+        };
+        let result = flashpoint.create_game(&partial_game).await;
+        assert!(result.is_ok());
+        let old_game = result.unwrap();
+        let mut platform = flashpoint.find_platform("HTML5").await.unwrap().unwrap();
+        platform.name = String::from("Wiggle");
+        let mut partial = PartialTag::from(platform);
+        let save_res = flashpoint.save_platform(&mut partial).await;
+        assert!(save_res.is_ok());
+        assert_eq!(save_res.unwrap().name, "Wiggle");
+        let new_game = flashpoint.find_game(&old_game.id).await.unwrap().unwrap();
+        assert_eq!(new_game.primary_platform, "Wiggle");
+        assert!(new_game.platforms.contains(&"Wiggle".to_string()));
+    }
+
+    #[tokio::test]
+    async fn search_games_random() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_existing_database(&test_database());
+        assert!(create.is_ok());
+
+        let mut search = crate::game::search::parse_user_input("").search;
+        let mut new_filter = GameFilter::default();
+        new_filter.exact_blacklist.tags = Some(vec!["Action".to_owned()]);
+        search.filter.subfilters.push(new_filter);
+
+        let random_res = flashpoint.search_games_random(&search, 5).await;
+        assert!(random_res.is_ok());
+        assert_eq!(random_res.unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn search_games_installed() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_existing_database(&test_database());
+        assert!(create.is_ok());
+
+        let mut search = crate::game::search::parse_user_input("installed:true").search;
+        if let Some(installed) = search.filter.bool_comp.installed.as_ref() {
+            assert_eq!(installed, &true);
+        } else {
+            panic!("Expected 'installed' to be Some(true), but it was None.");
+        }
+
+        search.limit = 200;
+        let games_res = flashpoint.search_games(&search).await;
+        assert!(games_res.is_ok());
+        assert_eq!(games_res.unwrap().len(), 20);
+    }
+
+    #[tokio::test]
+    async fn search_games_index_limited() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_existing_database(&test_database());
+        assert!(create.is_ok());
+
+        let search = &mut GameSearch::default();
+        search.filter.whitelist.title = Some(vec!["Super".into()]);
+        // Set page size
+        search.limit = 200;
+        let index_res = flashpoint.search_games_index(&mut search.clone(), Some(1000)).await;
+        assert!(index_res.is_ok());
+        let index = index_res.unwrap();
+        assert_eq!(index.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn get_tag() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_existing_database(&test_database());
+        assert!(create.is_ok());
+
+        let tag_res = flashpoint.find_tag("Mario Bros.").await;
+        assert!(tag_res.is_ok());
+        let tag = tag_res.unwrap();
+        assert!(tag.is_some());
+        assert_eq!(tag.unwrap().name, "Super Mario");
+    }
+
+    #[tokio::test]
+    async fn get_platform() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_existing_database(&test_database());
+        assert!(create.is_ok());
+
+        let tag_res = flashpoint.find_platform("Jutvision").await;
+        assert!(tag_res.is_ok());
+        let tag = tag_res.unwrap();
+        assert!(tag.is_some());
+        assert_eq!(tag.unwrap().name, "asdadawdaw");
+    }
+
+    #[tokio::test]
+    async fn find_platform_by_alias() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let platform = flashpoint.create_platform("Flash", None).await.unwrap();
+        let mut partial: PartialTag = platform.into();
+        partial.aliases = Some(vec!["Flash".to_owned(), "Shockwave Flash".to_owned()]);
+        assert!(flashpoint.save_platform(&mut partial).await.is_ok());
+
+        let found = flashpoint.find_platform_by_alias("shockwave flash").await.unwrap();
+        assert!(found.is_some());
+        let (platform, matched) = found.unwrap();
+        assert_eq!(platform.name, "Flash");
+        assert_eq!(matched, "Shockwave Flash");
+
+        let (_, matched) = flashpoint.find_platform_by_alias("Flash").await.unwrap().unwrap();
+        assert_eq!(matched, "Flash");
+        assert!(flashpoint.find_platform_by_alias("Java").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn add_playtime() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_database(":memory:");
+        assert!(create.is_ok());
+        let partial_game = game::PartialGame {
+            title: Some(String::from("Test Game")),
+            tags: Some(vec!["Action"].into()),
+            ..game::PartialGame::default()
+        };
+        let result = flashpoint.create_game(&partial_game).await;
+        assert!(result.is_ok());
+        let game_id = result.unwrap().id;
+        let playtime_res = flashpoint.add_game_playtime(&game_id, 30).await;
+        assert!(playtime_res.is_ok());
+        let saved_game_res = flashpoint.find_game(&game_id).await;
+        assert!(saved_game_res.is_ok());
+        let saved_game_opt = saved_game_res.unwrap();
+        assert!(saved_game_opt.is_some());
+        let saved_game = saved_game_opt.unwrap();
+        assert_eq!(saved_game.playtime, 30);
+        assert_eq!(saved_game.play_counter, 1);
+    }
+
+    #[tokio::test]
+    async fn update_tags_clear_existing(    ) {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_database(":memory:");
+        assert!(create.is_ok());
+        let new_tag_res = flashpoint.create_tag("test", None, Some(10)).await;
+        assert!(new_tag_res.is_ok());
+        let tag_update = RemoteTag {
+            id: 10,
+            name: "hello".to_owned(),
+            description: String::new(),
+            category: "default".to_owned(),
+            date_modified: "2024-01-01 12:00:00".to_owned(),
+            aliases: vec!["hello".to_owned()],
+            deleted: false,
+        };
+        let update_res = flashpoint.update_apply_tags(vec![tag_update]).await;
+        assert!(update_res.is_ok());
+        let saved_tag_res = flashpoint.find_tag_by_id(10).await;
+        assert!(saved_tag_res.is_ok());
+        let saved_tag_opt = saved_tag_res.unwrap();
+        assert!(saved_tag_opt.is_some());
+        let saved_tag = saved_tag_opt.unwrap();
+        assert_eq!(saved_tag.aliases.len(), 1);
+        assert_eq!(saved_tag.aliases[0].as_str(), "hello");
+        assert_eq!(saved_tag.name.as_str(), "hello");
+    }
+
+    #[tokio::test]
+    async fn find_relations_after_tag_rename() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let first_res = flashpoint.create_game(&PartialGame {
+            title: Some("First".to_owned()),
+            tags: Some(vec!["Action", "Adventure"].into()),
+            platforms: Some(vec!["Flash"].into()),
+            ..Default::default()
+        }).await;
+        assert!(first_res.is_ok());
+        let first = first_res.unwrap();
+        let second_res = flashpoint.create_game(&PartialGame {
+            title: Some("Second".to_owned()),
+            tags: Some(vec!["Action"].into()),
+            platforms: Some(vec!["Flash", "HTML5"].into()),
+            ..Default::default()
+        }).await;
+        assert!(second_res.is_ok());
+        let second = second_res.unwrap();
+
+        // Rename a tag mid-session, relations must still resolve by id
+        let action = flashpoint.find_tag("Action").await.unwrap().unwrap();
+        let mut partial = PartialTag::from(action.clone());
+        partial.name = "Shooter".to_owned();
+        assert!(flashpoint.save_tag(&mut partial).await.is_ok());
+
+        let tag_relations_res = flashpoint.find_all_tag_relations().await;
+        assert!(tag_relations_res.is_ok());
+        let tag_relations = tag_relations_res.unwrap();
+        assert_eq!(tag_relations.len(), 3);
+        assert_eq!(tag_relations.iter().filter(|(_, tag_id)| *tag_id == action.id).count(), 2);
+
+        let platform_relations_res = flashpoint.find_all_platform_relations().await;
+        assert!(platform_relations_res.is_ok());
+        assert_eq!(platform_relations_res.unwrap().len(), 3);
+
+        let filtered_res = flashpoint.find_tag_relations(vec![second.id.clone()]).await;
+        assert!(filtered_res.is_ok());
+        let filtered = filtered_res.unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0], (second.id.clone(), action.id));
+
+        let tag_ids_res = flashpoint.find_game_tag_ids(&first.id).await;
+        assert!(tag_ids_res.is_ok());
+        assert_eq!(tag_ids_res.unwrap().len(), 2);
+        let platform_ids_res = flashpoint.find_game_platform_ids(&second.id).await;
+        assert!(platform_ids_res.is_ok());
+        assert_eq!(platform_ids_res.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn save_missing_game() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_database(":memory:");
+        assert!(create.is_ok());
+        let mut partial_game = game::PartialGame {
+            id: String::from("not-a-real-id"),
+            title: Some(String::from("Ghost Game")),
+            ..game::PartialGame::default()
+        };
+        let result = flashpoint.save_game(&mut partial_game).await;
+        assert!(result.is_err());
+        match result.unwrap_err() {
+            Error::GameNotFound { id } => assert_eq!(id, "not-a-real-id"),
+            e => panic!("Expected GameNotFound, got {}", e),
+        }
+    }
+
+    #[tokio::test]
+    async fn set_category_for_tags() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let mut tag_ids = vec![];
+        for name in ["Action", "Puzzle", "Racing"] {
+            let tag_res = flashpoint.create_tag(name, None, None).await;
+            assert!(tag_res.is_ok());
+            tag_ids.push(tag_res.unwrap().id);
+        }
+        let untouched_res = flashpoint.create_tag("Shooter", None, None).await;
+        assert!(untouched_res.is_ok());
+        let untouched = untouched_res.unwrap();
+
+        let set_res = flashpoint.set_category_for_tags(&tag_ids, "Genre").await;
+        assert!(set_res.is_ok());
+        assert!(flashpoint.find_tag_category("Genre").await.unwrap().is_some());
+
+        for id in tag_ids {
+            let tag = flashpoint.find_tag_by_id(id).await.unwrap().unwrap();
+            assert_eq!(tag.category, Some("Genre".to_owned()));
+        }
+        let untouched = flashpoint.find_tag_by_id(untouched.id).await.unwrap().unwrap();
+        assert_eq!(untouched.category, Some("default".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn overlay_session() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let partial = PartialGame {
+            title: Some("Original".to_owned()),
+            tags: Some(vec!["Action", "Shooter"].into()),
+            ..Default::default()
+        };
+        let game = flashpoint.create_game(&partial).await.unwrap();
+        assert!(flashpoint.create_tag("Arcade", None, None).await.is_ok());
+
+        let overlay_res = flashpoint.begin_overlay().await;
+        assert!(overlay_res.is_ok());
+        let overlay = overlay_res.unwrap();
+
+        let edit = PartialGame {
+            id: game.id.clone(),
+            title: Some("Edited".to_owned()),
+            ..Default::default()
+        };
+        let preview_res = flashpoint.overlay_save_game(overlay, &edit).await;
+        assert!(preview_res.is_ok());
+        assert_eq!(preview_res.unwrap().title, "Edited");
+        assert!(flashpoint.overlay_merge_tags(overlay, "Shooter", "Arcade").await.is_ok());
+        let new_game = PartialGame {
+            title: Some("Brand New".to_owned()),
+            ..Default::default()
+        };
+        let new_preview = flashpoint.overlay_save_game(overlay, &new_game).await.unwrap();
+
+        // Overlay shows staged edits
+        let overlay_game = flashpoint.overlay_find_game(overlay, &game.id).await.unwrap().unwrap();
+        assert_eq!(overlay_game.title, "Edited");
+        assert!(overlay_game.tags.contains(&"Arcade".to_owned()));
+        assert!(!overlay_game.tags.contains(&"Shooter".to_owned()));
+        let overlay_new = flashpoint.overlay_find_game(overlay, &new_preview.id).await.unwrap();
+        assert!(overlay_new.is_some());
+        let overlay_search = flashpoint.overlay_search_games(overlay, &GameSearch::default()).await.unwrap();
+        assert_eq!(overlay_search.len(), 2);
+
+        // Filters see the staged values, for edited and new games alike
+        let mut search = GameSearch::default();
+        search.filter.exact_whitelist.title = Some(vec!["Original".to_owned()]);
+        assert!(flashpoint.overlay_search_games(overlay, &search).await.unwrap().is_empty());
+        search.filter.exact_whitelist.title = Some(vec!["Edited".to_owned()]);
+        let edited = flashpoint.overlay_search_games(overlay, &search).await.unwrap();
+        assert_eq!(edited.iter().map(|g| g.id.as_str()).collect::<Vec<&str>>(), vec![game.id.as_str()]);
+        search.filter.exact_whitelist.title = None;
+        search.filter.exact_whitelist.tags = Some(vec!["Arcade".to_owned()]);
+        assert_eq!(flashpoint.overlay_search_games(overlay, &search).await.unwrap().len(), 1);
+
+        // Database is untouched
+        let db_game = flashpoint.find_game(&game.id).await.unwrap().unwrap();
+        assert_eq!(db_game.title, "Original");
+        assert!(db_game.tags.contains(&"Shooter".to_owned()));
+        assert!(flashpoint.find_game(&new_preview.id).await.unwrap().is_none());
+        assert!(flashpoint.find_tag("Shooter").await.unwrap().is_some());
+
+        let commit_res = flashpoint.commit_overlay(overlay).await;
+        assert!(commit_res.is_ok());
+        let db_game = flashpoint.find_game(&game.id).await.unwrap().unwrap();
+        assert_eq!(db_game.title, "Edited");
+        assert!(db_game.tags.contains(&"Arcade".to_owned()));
+        assert!(flashpoint.find_game(&new_preview.id).await.unwrap().is_some());
+
+        // Merges staged by alias apply to the detailed tags too
+        let action = flashpoint.find_tag("Action").await.unwrap().unwrap();
+        let mut partial_tag = PartialTag {
+            id: action.id,
+            name: "Action".to_owned(),
+            description: None,
+            date_modified: None,
+            aliases: Some(vec!["Action".to_owned(), "Fighting".to_owned()]),
+            category: None,
+        };
+        assert!(flashpoint.save_tag(&mut partial_tag).await.is_ok());
+        let aliased = flashpoint.begin_overlay().await.unwrap();
+        assert!(flashpoint.overlay_merge_tags(aliased, "Fighting", "Arcade").await.is_ok());
+        let merged = flashpoint.overlay_find_game(aliased, &game.id).await.unwrap().unwrap();
+        assert_eq!(merged.tags.to_vec(), vec!["Arcade"]);
+        let detailed: Vec<String> = merged.detailed_tags.unwrap().into_iter().map(|t| t.name).collect();
+        assert_eq!(detailed, vec!["Arcade"]);
+
+        // Committed overlays are gone
+        let gone_res = flashpoint.overlay_find_game(overlay, &game.id).await;
+        assert!(matches!(gone_res, Err(Error::OverlayNotFound { .. })));
+
+        // Saves for soft deleted games fail instead of recreating them
+        assert!(flashpoint.soft_delete_game(&new_preview.id).await.is_ok());
+        let binned = flashpoint.begin_overlay().await.unwrap();
+        let binned_edit = PartialGame {
+            id: new_preview.id.clone(),
+            title: Some("Revived".to_owned()),
+            ..Default::default()
+        };
+        let binned_res = flashpoint.overlay_save_game(binned, &binned_edit).await;
+        assert!(matches!(binned_res, Err(Error::GameNotFound { .. })));
+        assert!(flashpoint.commit_overlay(binned).await.is_ok());
+        assert!(flashpoint.restore_game(&new_preview.id).await.is_ok());
+        let restored = flashpoint.find_game(&new_preview.id).await.unwrap().unwrap();
+        assert_eq!(restored.title, "Brand New");
+
+        // Bad merges are rejected without being staged
+        let checked = flashpoint.begin_overlay().await.unwrap();
+        let missing_res = flashpoint.overlay_merge_tags(checked, "Missing", "Arcade").await;
+        assert!(matches!(missing_res, Err(Error::SqliteError { .. })));
+        let self_res = flashpoint.overlay_merge_tags(checked, "Arcade", "Arcade").await;
+        assert!(matches!(self_res, Err(Error::MergeIntoSelf { .. })));
+        assert!(flashpoint.overlay_find_game(checked, &game.id).await.unwrap().is_some());
+        assert!(flashpoint.commit_overlay(checked).await.is_ok());
+
+        // Failed commits put the staged operations back
+        assert!(flashpoint.create_tag("Doomed", None, None).await.is_ok());
+        let failing = flashpoint.begin_overlay().await.unwrap();
+        assert!(flashpoint.overlay_merge_tags(failing, "Doomed", "Arcade").await.is_ok());
+        assert!(flashpoint.delete_tag("Doomed").await.is_ok());
+        assert!(flashpoint.commit_overlay(failing).await.is_err());
+        assert!(flashpoint.create_tag("Doomed", None, None).await.is_ok());
+        assert!(flashpoint.commit_overlay(failing).await.is_ok());
+        let gone_res = flashpoint.overlay_find_game(failing, &game.id).await;
+        assert!(matches!(gone_res, Err(Error::OverlayNotFound { .. })));
+
+        let discarded = flashpoint.begin_overlay().await.unwrap();
+        assert!(flashpoint.discard_overlay(discarded).await.is_ok());
+        assert!(flashpoint.discard_overlay(discarded).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn search_games_grouped() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        for (title, series) in [("Alpha 1", "Alpha"), ("Alpha 2", "Alpha"), ("Alpha 3", "Alpha"), ("Beta 1", "Beta"), ("Loner", "")] {
+            let partial = PartialGame {
+                title: Some(title.to_owned()),
+                series: Some(series.to_owned()),
+                ..Default::default()
+            };
+            assert!(flashpoint.create_game(&partial).await.is_ok());
+        }
+
+        let groups_res = flashpoint.search_games_grouped(&GameSearch::default(), GroupableField::Series, 2).await;
+        assert!(groups_res.is_ok());
+        let groups = groups_res.unwrap();
+        assert_eq!(groups.len(), 3);
+        let none = groups.iter().find(|g| g.name == "(none)").unwrap();
+        assert_eq!(none.count, 1);
+        let alpha = groups.iter().find(|g| g.name == "Alpha").unwrap();
+        assert_eq!(alpha.count, 3);
+        assert_eq!(alpha.representatives.len(), 2);
+        let first = flashpoint.find_game(&alpha.representatives[0]).await.unwrap().unwrap();
+        assert_eq!(first.title, "Alpha 1");
+        let beta = groups.iter().find(|g| g.name == "Beta").unwrap();
+        assert_eq!(beta.count, 1);
+        assert_eq!(beta.representatives.len(), 1);
+
+        // Groups respect the search filter
+        let mut search = GameSearch::default();
+        search.filter.whitelist.title = Some(vec!["1".to_owned()]);
+        let filtered = flashpoint.search_games_grouped(&search, GroupableField::Series, 2).await.unwrap();
+        assert_eq!(filtered.len(), 2);
+        assert!(filtered.iter().all(|g| g.count == 1));
+    }
+
+    #[tokio::test]
+    async fn find_platforms_with_games() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let partial = PartialGame {
+            title: Some("test".to_owned()),
+            primary_platform: Some("Flash".to_owned()),
+            platforms: Some(vec!["Flash"].into()),
+            ..Default::default()
+        };
+        assert!(flashpoint.create_game(&partial).await.is_ok());
+        assert!(flashpoint.create_platform("Unused", None).await.is_ok());
+        let binned = flashpoint.create_game(&PartialGame {
+            title: Some("binned".to_owned()),
+            primary_platform: Some("HTML5".to_owned()),
+            platforms: Some(vec!["HTML5"].into()),
+            ..Default::default()
+        }).await.unwrap();
+        assert!(flashpoint.soft_delete_game(&binned.id).await.is_ok());
+
+        let all_res = flashpoint.find_all_platforms().await;
+        assert!(all_res.is_ok());
+        assert_eq!(all_res.unwrap().len(), 3);
+
+        let used_res = flashpoint.find_platforms_with_games().await;
+        assert!(used_res.is_ok());
+        let used = used_res.unwrap();
+        assert_eq!(used.len(), 1);
+        assert_eq!(used[0].name, "Flash");
+    }
+
+    #[tokio::test]
+    async fn fixed_clock_timestamps() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let fixed = chrono::DateTime::parse_from_rfc3339("2024-03-01T12:30:45.123Z").unwrap().with_timezone(&chrono::Utc);
+        flashpoint.set_clock(Arc::new(clock::FixedClock(fixed)));
+
+        let partial = PartialGame {
+            title: Some("test".to_owned()),
+            ..Default::default()
+        };
+        let game = flashpoint.create_game(&partial).await.unwrap();
+        assert_eq!(game.date_added, "2024-03-01T12:30:45.123Z");
+        assert_eq!(game.date_modified, "2024-03-01T12:30:45.123Z");
+
+        let later = chrono::DateTime::parse_from_rfc3339("2024-03-02T08:00:00.000Z").unwrap().with_timezone(&chrono::Utc);
+        flashpoint.set_clock(Arc::new(clock::FixedClock(later)));
+        let mut edit = PartialGame {
+            id: game.id.clone(),
+            title: Some("edited".to_owned()),
+            ..Default::default()
+        };
+        let saved_res = flashpoint.save_game(&mut edit).await;
+        assert!(saved_res.is_ok());
+        assert_eq!(saved_res.unwrap().date_modified, "2024-03-02T08:00:00.000Z");
+        let found = flashpoint.find_game(&game.id).await.unwrap().unwrap();
+        assert_eq!(found.date_modified, "2024-03-02T08:00:00.000Z");
+        assert_eq!(found.date_added, "2024-03-01T12:30:45.123Z");
+
+        let tag_res = flashpoint.create_tag("Action", None, None).await;
+        assert!(tag_res.is_ok());
+        assert_eq!(tag_res.unwrap().date_modified, "2024-03-02T08:00:00.000Z");
+        let platform = flashpoint.create_platform("Flash", None).await.unwrap();
+        assert_eq!(platform.date_modified, "2024-03-02T08:00:00.000Z");
+        let platform = flashpoint.transaction(|tx| tx.create_platform("HTML5", None)).await.unwrap();
+        assert_eq!(platform.date_modified, "2024-03-02T08:00:00.000Z");
+
+        assert!(flashpoint.add_game_playtime(&game.id, 30).await.is_ok());
+        let played = flashpoint.find_game(&game.id).await.unwrap().unwrap();
+        assert_eq!(played.last_played, Some("2024-03-02T08:00:00.000Z".to_owned()));
+
+        let mut game_data = full_game_data("", false);
+        game_data.game_id = game.id.clone();
+        game_data.date_added = None;
+        let created = flashpoint.create_game_data(&game_data).await.unwrap();
+        assert_eq!(created.date_added, "2024-03-02T08:00:00.000Z");
+    }
+
+    async fn taxonomy_test_db() -> FlashpointArchive {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let action = flashpoint.create_tag("Action", Some("genre".to_owned()), None).await.unwrap();
+        let mut partial = PartialTag::from(action);
+        partial.aliases = Some(vec!["Action".to_owned(), "Fighting".to_owned()]);
+        assert!(flashpoint.save_tag(&mut partial).await.is_ok());
+        assert!(flashpoint.create_tag("Unused", None, None).await.is_ok());
+        let game = PartialGame {
+            title: Some("test".to_owned()),
+            tags: Some(vec!["Action", "Puzzle"].into()),
+            ..Default::default()
+        };
+        assert!(flashpoint.create_game(&game).await.is_ok());
+        flashpoint
+    }
+
+    fn incoming_taxonomy() -> TagTaxonomy {
+        TagTaxonomy {
+            categories: vec![tag::taxonomy::TaxonomyCategory {
+                name: "theme".to_owned(),
+                color: "#00FF00".to_owned(),
+                description: None,
+            }],
+            tags: vec![
+                tag::taxonomy::TaxonomyTag {
+                    name: "Action".to_owned(),
+                    description: "Incoming".to_owned(),
+                    category: "theme".to_owned(),
+                    aliases: vec!["Fighting".to_owned(), "Combat".to_owned()],
+                },
+                tag::taxonomy::TaxonomyTag {
+                    name: "Racing".to_owned(),
+                    description: "".to_owned(),
+                    category: "genre".to_owned(),
+                    aliases: vec![],
+                },
+            ],
+        }
+    }
+
+    #[tokio::test]
+    async fn export_tags() {
+        let flashpoint = taxonomy_test_db().await;
+        let taxonomy_res = flashpoint.export_tags().await;
+        assert!(taxonomy_res.is_ok());
+        let taxonomy = taxonomy_res.unwrap();
+        assert!(taxonomy.categories.iter().any(|c| c.name == "genre"));
+        assert_eq!(taxonomy.tags.len(), 3);
+        let action = taxonomy.tags.iter().find(|t| t.name == "Action").unwrap();
+        assert_eq!(action.category, "genre");
+        assert_eq!(action.aliases, vec!["Fighting".to_owned()]);
+    }
+
+    #[tokio::test]
+    async fn import_tags_keep_local() {
+        let flashpoint = taxonomy_test_db().await;
+        let report_res = flashpoint.import_tags(incoming_taxonomy(), ImportMode::MergeKeepLocal).await;
+        assert!(report_res.is_ok());
+        let report = report_res.unwrap();
+        assert_eq!(report.conflicts, vec!["Action".to_owned()]);
+        assert_eq!(report.created_tags, vec!["Racing".to_owned()]);
+        assert!(report.updated_tags.is_empty());
+
+        let action = flashpoint.find_tag("Action").await.unwrap().unwrap();
+        assert_eq!(action.category, Some("genre".to_owned()));
+        assert_eq!(action.description, "");
+        assert!(action.aliases.contains(&"Combat".to_owned()));
+        assert!(flashpoint.find_tag("Unused").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn import_tags_take_incoming() {
+        let flashpoint = taxonomy_test_db().await;
+        let report_res = flashpoint.import_tags(incoming_taxonomy(), ImportMode::MergeTakeIncoming).await;
+        assert!(report_res.is_ok());
+        let report = report_res.unwrap();
+        assert_eq!(report.updated_tags, vec!["Action".to_owned()]);
+
+        let action = flashpoint.find_tag("Action").await.unwrap().unwrap();
+        assert_eq!(action.category, Some("theme".to_owned()));
+        assert_eq!(action.description, "Incoming");
+        assert!(flashpoint.find_tag("Racing").await.unwrap().is_some());
+        assert!(flashpoint.find_tag("Unused").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn import_tags_replace_all() {
+        let flashpoint = taxonomy_test_db().await;
+        let report_res = flashpoint.import_tags(incoming_taxonomy(), ImportMode::ReplaceAll).await;
+        assert!(report_res.is_ok());
+        let report = report_res.unwrap();
+        assert_eq!(report.deleted_tags, vec!["Unused".to_owned()]);
+        assert_eq!(report.retained_tags, vec!["Puzzle".to_owned()]);
+
+        let action = flashpoint.find_tag("Action").await.unwrap().unwrap();
+        assert_eq!(action.category, Some("theme".to_owned()));
+        assert!(flashpoint.find_tag("Unused").await.unwrap().is_none());
+        assert!(flashpoint.find_tag("Puzzle").await.unwrap().is_some());
+        let games = flashpoint.search_games_with_tag("Action", None, None).await.unwrap();
+        assert_eq!(games.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn archive_state_counts() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        for (title, state) in [("first", 0), ("second", 2), ("third", 2)] {
+            let partial = PartialGame {
+                title: Some(title.to_owned()),
+                archive_state: Some(state),
+                ..Default::default()
+            };
+            assert!(flashpoint.create_game(&partial).await.is_ok());
+        }
+
+        let counts_res = flashpoint.archive_state_counts().await;
+        assert!(counts_res.is_ok());
+        let counts = counts_res.unwrap();
+        assert_eq!(counts, vec![(0, 1), (2, 2)]);
+        assert_eq!(game::ArchiveState::from_i64(counts[1].0), Some(game::ArchiveState::Available));
+        assert_eq!(game::ArchiveState::from_i64(counts[0].0).unwrap().label(), "Not Archived");
+    }
+
+    #[tokio::test]
+    async fn recently_played() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let mut ids = vec![];
+        for title in ["first", "second", "never"] {
+            let partial = PartialGame {
+                title: Some(title.to_owned()),
+                ..Default::default()
+            };
+            ids.push(flashpoint.create_game(&partial).await.unwrap().id);
+        }
+
+        let earlier = chrono::DateTime::parse_from_rfc3339("2024-01-01T00:00:00.000Z").unwrap().with_timezone(&chrono::Utc);
+        flashpoint.set_clock(Arc::new(clock::FixedClock(earlier)));
+        assert!(flashpoint.add_game_playtime(&ids[0], 60).await.is_ok());
+        let later = chrono::DateTime::parse_from_rfc3339("2024-02-01T00:00:00.000Z").unwrap().with_timezone(&chrono::Utc);
+        flashpoint.set_clock(Arc::new(clock::FixedClock(later)));
+        assert!(flashpoint.add_game_playtime(&ids[1], 60).await.is_ok());
+
+        let recent_res = flashpoint.recently_played(10).await;
+        assert!(recent_res.is_ok());
+        let recent = recent_res.unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].id, ids[1]);
+        assert_eq!(recent[1].id, ids[0]);
+
+        let limited = flashpoint.recently_played(1).await.unwrap();
+        assert_eq!(limited.len(), 1);
+        assert_eq!(limited[0].id, ids[1]);
+    }
+
+    #[tokio::test]
+    async fn search_games_with_tag_alias() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        for i in 0..7 {
+            let partial = PartialGame {
+                title: Some(format!("Game {}", i)),
+                tags: Some(vec!["Action"].into()),
+                ..Default::default()
+            };
+            assert!(flashpoint.create_game(&partial).await.is_ok());
+        }
+        let action = flashpoint.find_tag("Action").await.unwrap().unwrap();
+        let mut partial = PartialTag::from(action);
+        partial.aliases = Some(vec!["Action".to_owned(), "Fighting".to_owned()]);
+        assert!(flashpoint.save_tag(&mut partial).await.is_ok());
+
+        let relations = GameSearchRelations {
+            tags: false,
+            platforms: false,
+            game_data: false,
+            add_apps: false,
+        };
+        let games_res = flashpoint.search_games_with_tag("Fighting", Some(5), Some(relations)).await;
+        assert!(games_res.is_ok());
+        let games = games_res.unwrap();
+        assert_eq!(games.len(), 5);
+        assert!(games[0].detailed_tags.is_none());
+
+        let all_games = flashpoint.search_games_with_tag("Fighting", None, None).await.unwrap();
+        assert_eq!(all_games.len(), 7);
+        assert!(all_games[0].detailed_tags.is_some());
+
+        let missing = flashpoint.search_games_with_tag("Missing", None, None).await.unwrap();
+        assert!(missing.is_empty());
+    }
+
+
+    #[tokio::test]
+    async fn user_data_survives_sync() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let game = flashpoint.create_game(&PartialGame {
+            title: Some("Synced".to_owned()),
+            ..Default::default()
+        }).await.unwrap();
+        let other = flashpoint.create_game(&PartialGame {
+            title: Some("Untouched".to_owned()),
+            ..Default::default()
+        }).await.unwrap();
+
+        let set_res = flashpoint.set_user_data(&PartialGameUserData {
+            game_id: game.id.clone(),
+            favorite: Some(true),
+            user_notes: Some("Great game".to_owned()),
+            user_rating: Some(5),
+            ..Default::default()
+        }).await;
+        assert!(set_res.is_ok());
+
+        let remote_game = update::RemoteGame {
+            id: game.id.clone(),
+            title: "Synced (Updated)".to_owned(),
+            alternate_titles: "".to_owned(),
+            series: "".to_owned(),
+            developer: "".to_owned(),
+            publisher: "".to_owned(),
+            date_added: game.date_added.clone(),
+            date_modified: game.date_modified.clone(),
+            play_mode: "".to_owned(),
+            status: "".to_owned(),
+            notes: "".to_owned(),
+            source: "".to_owned(),
+            application_path: "".to_owned(),
+            launch_command: "".to_owned(),
+            release_date: "".to_owned(),
+            version: "".to_owned(),
+            original_description: "".to_owned(),
+            language: "".to_owned(),
+            library: "arcade".to_owned(),
+            platform_name: "".to_owned(),
+            archive_state: 0,
+            ruffle_support: "".to_owned(),
+        };
+        assert!(flashpoint.update_apply_games(&RemoteGamesRes {
+            games: vec![remote_game],
+            add_apps: vec![],
+            game_data: vec![],
+            tag_relations: vec![],
+            platform_relations: vec![],
+        }).await.is_ok());
+        assert_eq!(flashpoint.find_game(&game.id).await.unwrap().unwrap().title, "Synced (Updated)");
+
+        let data = flashpoint.get_user_data(&game.id).await.unwrap().unwrap();
+        assert!(data.favorite);
+        assert_eq!(data.user_notes, "Great game");
+        assert_eq!(data.user_rating, Some(5));
+
+        // Partial updates keep the other fields
+        assert!(flashpoint.set_user_data(&PartialGameUserData {
+            game_id: game.id.clone(),
+            user_rating: Some(3),
+            ..Default::default()
+        }).await.is_ok());
+        let data = flashpoint.get_user_data(&game.id).await.unwrap().unwrap();
+        assert!(data.favorite);
+        assert_eq!(data.user_rating, Some(3));
+
+        let favorites = flashpoint.find_favorites().await.unwrap();
+        assert_eq!(favorites.len(), 1);
+        assert_eq!(favorites[0].id, game.id);
+
+        // Games without user data are treated as not favorited
+        let mut search = game::search::parse_user_input("favorite:false").search;
+        search.limit = 100;
+        let not_favorites = flashpoint.search_games(&search).await.unwrap();
+        assert_eq!(not_favorites.len(), 1);
+        assert_eq!(not_favorites[0].id, other.id);
+
+        let mut search = game::search::parse_user_input("rating>2").search;
+        search.limit = 100;
+        let rated = flashpoint.search_games(&search).await.unwrap();
+        assert_eq!(rated.len(), 1);
+        assert_eq!(rated[0].id, game.id);
+
+        // A rated game can go back to unrated
+        assert!(flashpoint.set_user_data(&PartialGameUserData {
+            game_id: game.id.clone(),
+            user_rating: Some(4),
+            clear_user_rating: Some(true),
+            ..Default::default()
+        }).await.is_ok());
+        let data = flashpoint.get_user_data(&game.id).await.unwrap().unwrap();
+        assert!(data.favorite);
+        assert_eq!(data.user_rating, None);
+        assert!(flashpoint.search_games(&search).await.unwrap().is_empty());
+    }
+
+
+    fn full_game_data(date_added: &str, present_on_disk: bool) -> PartialGameData {
+        PartialGameData {
+            id: None,
+            game_id: "".to_owned(),
+            title: Some("Data".to_owned()),
+            date_added: Some(date_added.to_owned()),
+            sha256: Some("123".to_owned()),
+            crc32: Some(0),
+            present_on_disk: Some(present_on_disk),
+            path: None,
+            size: Some(123),
+            parameters: None,
+            application_path: Some("Test".to_owned()),
+            launch_command: Some("Test".to_owned()),
+        }
+    }
+
+    fn full_game_add_app(id: &str) -> AdditionalApp {
+        AdditionalApp {
+            id: id.to_owned(),
+            name: "Extras".to_owned(),
+            application_path: ":extras:".to_owned(),
+            launch_command: "extras".to_owned(),
+            auto_run_before: false,
+            wait_for_exit: false,
+            parent_game_id: "".to_owned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn create_full_game() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let full = CreateFullGame {
+            game: PartialGame {
+                title: Some("Full Game".to_owned()),
+                ..Default::default()
+            },
+            add_apps: vec![full_game_add_app("")],
+            game_data: vec![full_game_data("2023-01-01T01:01:01.000Z", true)],
+        };
+
+        let create_res = flashpoint.create_full_game(&full).await;
+        assert!(create_res.is_ok());
+        let game = create_res.unwrap();
+
+        let game_data = flashpoint.find_game_data(&game.id).await.unwrap();
+        assert_eq!(game_data.len(), 1);
+        assert_eq!(game.active_data_id, Some(game_data[0].id));
+        assert!(game.active_data_on_disk);
+
+        let add_apps = game.add_apps.unwrap();
+        assert_eq!(add_apps.len(), 1);
+        assert_eq!(add_apps[0].parent_game_id, game.id);
+        assert!(!add_apps[0].id.is_empty());
+    }
+
+    #[tokio::test]
+    async fn create_full_game_rollback() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        // Duplicate add app ids fail on the second insert, after the game and data are written
+        let full = CreateFullGame {
+            game: PartialGame {
+                id: "full-game-rollback".to_owned(),
+                title: Some("Full Game".to_owned()),
+                tags: Some(vec!["Rollback"].into()),
+                ..Default::default()
+            },
+            add_apps: vec![full_game_add_app("duplicate"), full_game_add_app("duplicate")],
+            game_data: vec![full_game_data("2023-01-01T01:01:01.000Z", false)],
+        };
+
+        assert!(flashpoint.create_full_game(&full).await.is_err());
+        assert!(flashpoint.find_game("full-game-rollback").await.unwrap().is_none());
+        assert_eq!(flashpoint.find_game_data("full-game-rollback").await.unwrap().len(), 0);
+        assert!(flashpoint.find_add_app_by_id("duplicate").await.unwrap().is_none());
+        assert_eq!(flashpoint.count_games().await.unwrap(), 0);
+    }
+
+
+    #[tokio::test]
+    async fn load_database_current_schema() {
+        let path = std::env::temp_dir().join(format!("fpa-load-{}.sqlite", uuid::Uuid::new_v4()));
+        let source = path.to_str().unwrap();
+
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(source).is_ok());
+        assert!(flashpoint.find_tag_category("default").await.unwrap().is_some());
+        drop(flashpoint);
+
+        // Second open takes the fast path and must see the same schema
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(source).is_ok());
+        assert!(flashpoint.find_tag_category("default").await.unwrap().is_some());
+        assert!(flashpoint.create_game(&PartialGame {
+            title: Some("After reopen".to_owned()),
+            ..Default::default()
+        }).await.is_ok());
+        drop(flashpoint);
+
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(path.with_extension("sqlite-wal"));
+        let _ = std::fs::remove_file(path.with_extension("sqlite-shm"));
+    }
+
+
+    #[tokio::test]
+    async fn search_any_text() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let by_notes = flashpoint.create_game(&PartialGame {
+            title: Some("First".to_owned()),
+            notes: Some("Has a hidden starfish level".to_owned()),
+            ..Default::default()
+        }).await.unwrap();
+        let by_series = flashpoint.create_game(&PartialGame {
+            title: Some("Second".to_owned()),
+            series: Some("Starfish Adventures".to_owned()),
+            ..Default::default()
+        }).await.unwrap();
+        assert!(flashpoint.create_game(&PartialGame {
+            title: Some("Third".to_owned()),
+            ..Default::default()
+        }).await.is_ok());
+
+        let mut search = GameSearch::default();
+        search.filter.any_text = Some(vec!["starfish".to_owned()]);
+        let games = flashpoint.search_games(&search).await.unwrap();
+        assert_eq!(games.len(), 2);
+        assert!(games.iter().any(|g| g.id == by_notes.id));
+        assert!(games.iter().any(|g| g.id == by_series.id));
+
+        // Every term has to match somewhere
+        search.filter.any_text = Some(vec!["starfish".to_owned(), "hidden".to_owned()]);
+        let games = flashpoint.search_games(&search).await.unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].id, by_notes.id);
+    }
+
+
+    #[tokio::test]
+    async fn transaction_rollback() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+
+        let commit_res = flashpoint.transaction(|tx| {
+            let game = tx.create_game(&PartialGame {
+                id: "kept".to_owned(),
+                title: Some("Kept".to_owned()),
+                ..Default::default()
+            })?;
+            tx.create_game_redirect("kept-old", &game.id)
+        }).await;
+        assert!(commit_res.is_ok());
+        assert!(flashpoint.find_game("kept-old").await.unwrap().is_some());
+
+        let rollback_res = flashpoint.transaction(|tx| {
+            let game = tx.create_game(&PartialGame {
+                id: "rolled-back".to_owned(),
+                title: Some("Rolled Back".to_owned()),
+                tags: Some(vec!["Rollback"].into()),
+                ..Default::default()
+            })?;
+            tx.create_game_redirect("rolled-back-old", &game.id)?;
+            // Missing game fails the closure after the writes above
+            tx.save_game(&mut PartialGame {
+                id: "missing".to_owned(),
+                ..Default::default()
+            })?;
+            Ok(())
+        }).await;
+        assert!(matches!(rollback_res, Err(Error::GameNotFound { .. })));
+        assert!(flashpoint.find_game("rolled-back").await.unwrap().is_none());
+        assert!(flashpoint.find_game("rolled-back-old").await.unwrap().is_none());
+        assert!(flashpoint.find_tag("Rollback").await.unwrap().is_none());
+        assert_eq!(flashpoint.count_games().await.unwrap(), 1);
+    }
+
+
+    #[tokio::test]
+    async fn soft_delete_games() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let deleted_at = chrono::DateTime::parse_from_rfc3339("2024-01-01T00:00:00.000Z").unwrap().with_timezone(&chrono::Utc);
+        flashpoint.set_clock(Arc::new(clock::FixedClock(deleted_at)));
+        let kept = flashpoint.create_game(&PartialGame {
+            title: Some("Kept".to_owned()),
+            ..Default::default()
+        }).await.unwrap();
+        let binned = flashpoint.create_game(&PartialGame {
+            title: Some("Binned".to_owned()),
+            developer: Some("Binned Dev".to_owned()),
+            tags: Some(vec!["Action"].into()),
+            archive_state: Some(1),
+            ruffle_support: Some("standalone".to_owned()),
+            ..Default::default()
+        }).await.unwrap();
+
+        assert!(flashpoint.soft_delete_game(&binned.id).await.is_ok());
+        assert!(flashpoint.find_game(&binned.id).await.unwrap().is_none());
+        assert_eq!(flashpoint.count_games().await.unwrap(), 1);
+        assert_eq!(flashpoint.find_all_game_ids().await.unwrap(), vec![kept.id.clone()]);
+        assert!(!flashpoint.find_all_game_developers().await.unwrap().contains(&"Binned Dev".to_owned()));
+        assert_eq!(flashpoint.archive_state_counts().await.unwrap(), vec![(kept.archive_state, 1)]);
+        assert!(flashpoint.find_all_ruffle_support().await.unwrap().is_empty());
+        let games = flashpoint.search_games(&GameSearch::default()).await.unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].id, kept.id);
+        let mut search = GameSearch::default();
+        search.include_deleted = true;
+        assert_eq!(flashpoint.search_games(&search).await.unwrap().len(), 2);
+
+        // Relations survive a soft delete
+        assert!(flashpoint.restore_game(&binned.id).await.is_ok());
+        let restored = flashpoint.find_game(&binned.id).await.unwrap().unwrap();
+        assert_eq!(restored.tags.len(), 1);
+        assert_eq!(flashpoint.search_games(&GameSearch::default()).await.unwrap().len(), 2);
+        assert!(flashpoint.restore_game(&binned.id).await.is_err());
+
+        assert!(flashpoint.soft_delete_game(&binned.id).await.is_ok());
+        let purged = flashpoint.purge_deleted_games("2023-06-01T00:00:00.000Z").await.unwrap();
+        assert_eq!(purged.len(), 0);
+        let purged = flashpoint.purge_deleted_games("2024-06-01T00:00:00.000Z").await.unwrap();
+        assert_eq!(purged, vec![binned.id.clone()]);
+        search.include_deleted = true;
+        let games = flashpoint.search_games(&search).await.unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].id, kept.id);
+    }
+
+
+    #[tokio::test]
+    async fn load_database_not_sqlite() {
+        let path = std::env::temp_dir().join(format!("fpa-not-db-{}.sqlite", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "<!DOCTYPE html><html><body>404 Not Found</body></html>").unwrap();
+        let mut flashpoint = FlashpointArchive::new();
+        let res = flashpoint.load_database(path.to_str().unwrap());
+        assert!(matches!(res, Err(Error::NotADatabase { .. })));
+        assert!(res.unwrap_err().to_string().contains("<!DOCTYPE html>"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn load_database_empty_file() {
+        let path = std::env::temp_dir().join(format!("fpa-empty-{}.sqlite", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "").unwrap();
+        let mut flashpoint = FlashpointArchive::new();
+        let res = flashpoint.load_database(path.to_str().unwrap());
+        assert!(matches!(res, Err(Error::NotADatabase { .. })));
+        assert!(res.unwrap_err().to_string().contains("an empty file"));
+        assert!(matches!(flashpoint.count_games().await, Err(Error::DatabaseNotInitialized)));
+        let _ = std::fs::remove_file(&path);
+    }
+
+
+    #[tokio::test]
+    async fn load_existing_database_missing() {
+        let path = std::env::temp_dir().join(format!("fpa-missing-{}.sqlite", uuid::Uuid::new_v4()));
+        let mut flashpoint = FlashpointArchive::new();
+        let res = flashpoint.load_existing_database(path.to_str().unwrap());
+        assert!(matches!(res, Err(Error::DatabaseNotFound { .. })));
+        assert!(!path.exists());
+        assert!(flashpoint.load_existing_database(":memory:").is_ok());
+    }
+
+
+    #[tokio::test]
+    async fn delete_orphan_add_apps() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let game = flashpoint.create_game(&PartialGame {
+            title: Some("Parent".to_owned()),
+            ..Default::default()
+        }).await.unwrap();
+        let mut add_app = AdditionalApp {
+            id: "kept-add-app".to_owned(),
+            name: "Extras".to_owned(),
+            application_path: ":extras:".to_owned(),
+            launch_command: "extras".to_owned(),
+            auto_run_before: false,
+            wait_for_exit: false,
+            parent_game_id: game.id.clone(),
+        };
+        assert!(flashpoint.create_add_app(&mut add_app).await.is_ok());
+        {
+            let conn = flashpoint.pool.as_ref().unwrap().get().unwrap();
+            conn.execute("INSERT INTO additional_app (id, applicationPath, launchCommand, name, parentGameId, autoRunBefore, waitForExit)
+                VALUES ('orphan-add-app', ':message:', 'gone', 'Orphan', 'missing-game', false, false)", ()).unwrap();
+        }
+        assert_eq!(flashpoint.count_add_apps().await.unwrap(), 2);
+        let page = flashpoint.find_all_add_apps(1, 1).await.unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].id, "orphan-add-app");
+
+        let deleted_res = flashpoint.delete_orphan_add_apps().await;
+        assert!(deleted_res.is_ok());
+        assert_eq!(deleted_res.unwrap(), 1);
+        let remaining = flashpoint.find_all_add_apps(10, 0).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, "kept-add-app");
+        assert_eq!(flashpoint.delete_orphan_add_apps().await.unwrap(), 0);
+    }
+
+
+    #[tokio::test]
+    async fn last_played_order_mixed_formats() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let mut ids = vec![];
+        // Legacy rows written before timestamps were normalized
+        for (title, last_played) in [
+            ("Morning", "2024-01-01 08:00:00"),
+            ("Midday", "2024-01-01T09:00:00.000Z"),
+            ("Evening", "2024-01-01 10:00:00.500"),
+        ] {
+            let game = flashpoint.create_game(&PartialGame {
+                title: Some(title.to_owned()),
+                ..Default::default()
+            }).await.unwrap();
+            let conn = flashpoint.pool.as_ref().unwrap().get().unwrap();
+            conn.execute("UPDATE game SET lastPlayed = ? WHERE id = ?", rusqlite::params![last_played, game.id]).unwrap();
+            ids.push(game.id);
+        }
+
+        // Page one game at a time, offsets taken straight from the returned legacy values
+        let mut search = GameSearch::default();
+        search.limit = 1;
+        search.order = GameSearchOrder {
+            column: game::search::GameSearchSortable::LASTPLAYED,
+            direction: game::search::GameSearchDirection::DESC,
+        };
+        let mut seen = vec![];
+        for _ in 0..5 {
+            let page = flashpoint.search_games(&search).await.unwrap();
+            if page.is_empty() {
+                break;
+            }
+            let last = &page[0];
+            seen.push(last.id.clone());
+            search.offset = Some(GameSearchOffset {
+                value: last.last_played.clone().unwrap(),
+                title: last.title.clone(),
+                game_id: last.id.clone(),
+            });
+        }
+        assert_eq!(seen, vec![ids[2].clone(), ids[1].clone(), ids[0].clone()]);
+
+        // New writes are stored in the canonical format
+        let mut partial = PartialGame {
+            id: ids[0].clone(),
+            last_played: Some("2024-01-02 12:00:00".to_owned()),
+            ..Default::default()
+        };
+        let saved = flashpoint.save_game(&mut partial).await.unwrap();
+        assert_eq!(saved.last_played.unwrap(), "2024-01-02T12:00:00.000Z");
+    }
+
+
+    #[tokio::test]
+    async fn load_database_unopenable_path() {
+        let path = std::env::temp_dir()
+            .join(format!("fpa-no-dir-{}", uuid::Uuid::new_v4()))
+            .join("flashpoint.sqlite");
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(path.to_str().unwrap()).is_err());
+        assert!(matches!(flashpoint.count_games().await, Err(Error::DatabaseNotInitialized)));
+    }
+
+
+    #[tokio::test]
+    async fn find_game_by_add_app() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let game = flashpoint.create_game(&PartialGame {
+            title: Some("Parent".to_owned()),
+            ..Default::default()
+        }).await.unwrap();
+        let mut add_app = AdditionalApp {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: "Extras".to_owned(),
+            application_path: ":extras:".to_owned(),
+            launch_command: "extras".to_owned(),
+            auto_run_before: false,
+            wait_for_exit: false,
+            parent_game_id: game.id.clone(),
+        };
+        assert!(flashpoint.create_add_app(&mut add_app).await.is_ok());
+
+        let found_res = flashpoint.find_game_by_add_app(&add_app.id).await;
+        assert!(found_res.is_ok());
+        let found = found_res.unwrap();
+        assert!(found.is_some());
+        let found = found.unwrap();
+        assert_eq!(found.id, game.id);
+        assert_eq!(found.add_apps.unwrap().len(), 1);
+        assert!(flashpoint.find_game_by_add_app("missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn tag_category_color_validation() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_database(":memory:");
+        assert!(create.is_ok());
+
+        let accepted = [("#abc", "#AABBCC"), ("#a1b2c3", "#A1B2C3"), ("#FF00FF", "#FF00FF")];
+        for (idx, (color, expected)) in accepted.iter().enumerate() {
+            let partial_tc = tag_category::PartialTagCategory {
+                id: -1,
+                name: format!("accepted{}", idx),
+                color: color.to_string(),
+                description: None,
+                ordering: None,
+            };
+            let tc = flashpoint.create_tag_category(&partial_tc).await.unwrap();
+            assert_eq!(&tc.color, expected);
+        }
+
+        for color in ["", "red", "#12345", "#GGGGGG", "AABBCC"] {
+            let partial_tc = tag_category::PartialTagCategory {
+                id: -1,
+                name: "rejected".to_owned(),
+                color: color.to_owned(),
+                description: None,
+                ordering: None,
+            };
+            let res = flashpoint.create_tag_category(&partial_tc).await;
+            assert!(matches!(res, Err(Error::InvalidColor { .. })), "{} should be rejected", color);
+        }
+        assert!(flashpoint.find_tag_category("rejected").await.unwrap().is_none());
+
+        // Saving validates too
+        let saved = flashpoint.find_tag_category("accepted0").await.unwrap().unwrap();
+        let mut partial_tc = tag_category::PartialTagCategory {
+            id: saved.id,
+            name: saved.name,
+            color: "red".to_owned(),
+            description: None,
+            ordering: None,
+        };
+        let res = flashpoint.save_tag_category(&partial_tc).await;
+        assert!(matches!(res, Err(Error::InvalidColor { .. })));
+        partial_tc.color = "#0f0".to_owned();
+        assert_eq!(flashpoint.save_tag_category(&partial_tc).await.unwrap().color, "#00FF00");
+
+        let by_color = flashpoint.find_tag_categories_by_color("#a1b2c3").await.unwrap();
+        assert_eq!(by_color.len(), 1);
+        assert_eq!(by_color[0].name, "accepted1");
+
+        // Remote categories are normalized, invalid colors fall back to white
+        let remote = vec![
+            RemoteCategory { id: saved.id, name: "accepted0".to_owned(), color: "#abc".to_owned(), description: "".to_owned() },
+            RemoteCategory { id: saved.id + 100, name: "remote".to_owned(), color: "blue".to_owned(), description: "".to_owned() },
+        ];
+        flashpoint.update_apply_categories(remote).await.unwrap();
+        assert_eq!(flashpoint.find_tag_category("accepted0").await.unwrap().unwrap().color, "#AABBCC");
+        assert_eq!(flashpoint.find_tag_category("remote").await.unwrap().unwrap().color, "#FFFFFF");
+    }
+
+    #[tokio::test]
+    async fn tag_category_color_on_tags() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_database(":memory:");
+        assert!(create.is_ok());
+        let partial_tc = tag_category::PartialTagCategory {
+            id: -1,
+            name: "genre".to_owned(),
+            color: "#f00".to_owned(),
+            description: None,
+            ordering: None,
+        };
+        flashpoint.create_tag_category(&partial_tc).await.unwrap();
+        flashpoint.create_tag("Action", Some("genre".to_owned()), None).await.unwrap();
+
+        let tag = flashpoint.find_tag("Action").await.unwrap().unwrap();
+        assert_eq!(tag.category_color.unwrap(), "#FF0000");
+
+        let partial_game = game::PartialGame {
+            title: Some("Test".to_owned()),
+            tags: Some(vec!["Action"].into()),
+            ..game::PartialGame::default()
+        };
+        let game = flashpoint.create_game(&partial_game).await.unwrap();
+        let game = flashpoint.find_game(&game.id).await.unwrap().unwrap();
+        let detailed_tags = game.detailed_tags.unwrap();
+        assert_eq!(detailed_tags[0].category_color.as_deref(), Some("#FF0000"));
+    }
+
+    #[tokio::test]
+    async fn search_release_year() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_database(":memory:");
+        assert!(create.is_ok());
+        let release_dates = [
+            ("A", "2003"),
+            ("B", "2003-04"),
+            ("C", "circa 2001?"),
+            ("D", "12/05/1998"),
+            ("E", "unknown"),
+            ("F", ""),
+        ];
+        for (title, release_date) in release_dates {
+            let partial_game = game::PartialGame {
+                title: Some(title.to_owned()),
+                release_date: Some(release_date.to_owned()),
+                ..game::PartialGame::default()
+            };
+            flashpoint.create_game(&partial_game).await.unwrap();
+        }
+
+        let titles = |games: Vec<Game>| {
+            let mut titles: Vec<String> = games.into_iter().map(|g| g.title).collect();
+            titles.sort();
+            titles
+        };
+
+        let all = flashpoint.search_games(&GameSearch::default()).await.unwrap();
+        for game in &all {
+            let expected = match game.title.as_str() {
+                "A" | "B" => Some(2003),
+                "C" => Some(2001),
+                "D" => Some(1998),
+                _ => None,
+            };
+            assert_eq!(game.release_year, expected, "{}", game.release_date);
+        }
+
+        let search = game::search::parse_user_input("year:2003").search;
+        assert_eq!(titles(flashpoint.search_games(&search).await.unwrap()), vec!["A", "B"]);
+
+        // Games without a year never match a comparison
+        let search = game::search::parse_user_input("year>1999").search;
+        assert_eq!(titles(flashpoint.search_games(&search).await.unwrap()), vec!["A", "B", "C"]);
+        let search = game::search::parse_user_input("year<2002").search;
+        assert_eq!(titles(flashpoint.search_games(&search).await.unwrap()), vec!["C", "D"]);
+
+        let search = game::search::parse_user_input("year:1998..2001").search;
+        assert_eq!(titles(flashpoint.search_games(&search).await.unwrap()), vec!["C", "D"]);
+        let search = game::search::parse_user_input("year:2001..").search;
+        assert_eq!(titles(flashpoint.search_games(&search).await.unwrap()), vec!["A", "B", "C"]);
+        let search = game::search::parse_user_input("year:..2001").search;
+        assert_eq!(titles(flashpoint.search_games(&search).await.unwrap()), vec!["C", "D"]);
+
+        // Raw string lookups are unchanged
+        let search = game::search::parse_user_input("releaseDate=2003-04").search;
+        assert_eq!(titles(flashpoint.search_games(&search).await.unwrap()), vec!["B"]);
+
+        // Saving a new release date updates the year
+        let game_c = all.iter().find(|g| g.title == "C").unwrap();
+        let mut partial = game::PartialGame {
+            id: game_c.id.clone(),
+            release_date: Some("n/a".to_owned()),
+            ..game::PartialGame::default()
+        };
+        let saved = flashpoint.save_game(&mut partial).await.unwrap();
+        assert_eq!(saved.release_year, None);
+        let search = game::search::parse_user_input("year>1999").search;
+        assert_eq!(titles(flashpoint.search_games(&search).await.unwrap()), vec!["A", "B"]);
+    }
+
+    #[tokio::test]
+    async fn find_tags_by_category() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_database(":memory:");
+        assert!(create.is_ok());
+        for (name, category) in [("Action", "genre"), ("Puzzle", "genre"), ("Cat", "theme")] {
+            assert!(flashpoint.create_tag(name, Some(category.to_owned()), None).await.is_ok());
+        }
+
+        let genre_tags = flashpoint.find_tags_by_category("genre").await.unwrap();
+        let names: Vec<String> = genre_tags.iter().map(|t| t.name.clone()).collect();
+        assert_eq!(names, vec!["Action", "Puzzle"]);
+        assert!(genre_tags.iter().all(|t| t.category.as_deref() == Some("genre")));
+
+        let theme_tags = flashpoint.find_tags_by_category("theme").await.unwrap();
+        assert_eq!(theme_tags.len(), 1);
+        assert_eq!(theme_tags[0].name, "Cat");
+
+        assert!(flashpoint.find_tags_by_category("missing").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn search_slim_fields() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_database(":memory:");
+        assert!(create.is_ok());
+        let partial_game = game::PartialGame {
+            title: Some("Test Game".to_owned()),
+            developer: Some("Dev".to_owned()),
+            series: Some("Series".to_owned()),
+            tags: Some(vec!["Action"].into()),
+            ..game::PartialGame::default()
+        };
+        let created = flashpoint.create_game(&partial_game).await.unwrap();
+
+        let mut search = GameSearch::default();
+        search.slim = true;
+        let games = flashpoint.search_games(&search).await.unwrap();
+        assert_eq!(games[0].developer, "Dev");
+        assert_eq!(games[0].tags.len(), 1);
+
+        search.slim_fields = vec![game::search::GameField::Id, game::search::GameField::Title];
+        let games = flashpoint.search_games(&search).await.unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].id, created.id);
+        assert_eq!(games[0].title, "Test Game");
+        assert_eq!(games[0].developer, "");
+        assert_eq!(games[0].series, "");
+        assert!(games[0].tags.is_empty());
+        assert_eq!(games[0].release_year, None);
+    }
+
+    /// Parses the input and checks the resulting search query prepares, panics from the parser fail the caller
+    fn assert_search_input_prepares(conn: &Connection, input: &str) {
+        let search = game::search::parse_user_input(input).search;
+        let (query, _) = game::search::build_search_query(&search, "SELECT game.id FROM game");
+        if let Err(err) = conn.prepare(&query) {
+            panic!("{:?} produced a query that fails to prepare: {}\n{}", input, err, query);
+        }
+    }
+
+    fn search_fuzz_connection() -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migration::up(&mut conn).unwrap();
+        rusqlite::vtab::array::load_module(&conn).unwrap();
+        conn
+    }
+
+    /// Search input built from the pieces the parser cares about, arbitrary strings rarely hit them
+    fn search_input_strategy() -> impl proptest::strategy::Strategy<Value = String> {
+        use proptest::strategy::Strategy;
+        let piece = proptest::prop_oneof![
+            proptest::strategy::Just(" ".to_owned()),
+            proptest::strategy::Just("\"".to_owned()),
+            proptest::strategy::Just("\\".to_owned()),
+            proptest::strategy::Just("-".to_owned()),
+            proptest::strategy::Just("#".to_owned()),
+            proptest::strategy::Just("!".to_owned()),
+            proptest::strategy::Just("@".to_owned()),
+            proptest::strategy::Just("..".to_owned()),
+            proptest::strategy::Just("*".to_owned()),
+            proptest::sample::select(vec![":", "<", ">", "="]).prop_map(|s| s.to_owned()),
+            proptest::sample::select(vec![
+                "tag", "plat", "dev", "title", "id", "year", "rd", "da", "pt", "pc", "tags",
+                "addapps", "gamedata", "installed", "fav", "rating", "ruffle", "🎮",
+            ]).prop_map(|s| s.to_owned()),
+            "[0-9]{1,25}[yMwdhms]?",
+            "\\PC{0,12}",
+        ];
+        proptest::collection::vec(piece, 0..24).prop_map(|pieces| pieces.concat())
+    }
+
+    #[test]
+    fn search_input_regressions() {
+        let conn = search_fuzz_connection();
+        for input in include_str!("game/search_regressions.txt").lines() {
+            assert_search_input_prepares(&conn, input);
+        }
+        assert_search_input_prepares(&conn, &"a".repeat(100_000));
+        assert_search_input_prepares(&conn, &format!("title:\"{}", "b ".repeat(10_000)));
+
+        // Unterminated quotes are closed by the end of the input
+        let search = game::search::parse_user_input(r#"title:"abc def"#).search;
+        assert_eq!(search.filter.whitelist.title.unwrap()[0], "abc def");
+        let search = game::search::parse_user_input(r#"sonic "open"#).search;
+        assert_eq!(search.filter.whitelist.generic.unwrap(), vec!["sonic", "open"]);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn search_input_fuzz(input in search_input_strategy()) {
+            let conn = search_fuzz_connection();
+            assert_search_input_prepares(&conn, &input);
+        }
+
+        #[test]
+        fn search_input_fuzz_arbitrary(input in "\\PC*") {
+            let conn = search_fuzz_connection();
+            assert_search_input_prepares(&conn, &input);
+        }
+    }
+
+    #[tokio::test]
+    async fn disk_usage_report() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        // (title, library, [(size, present on disk)])
+        let games = [
+            ("Small", "arcade", vec![(100, true)]),
+            ("Large", "arcade", vec![(3000, true), (500, false)]),
+            ("Theatre", "theatre", vec![(1000, true), (2000, true)]),
+            ("Missing", "arcade", vec![(9000, false)]),
+        ];
+        for (title, library, data) in games {
+            let game = flashpoint.create_game(&PartialGame {
+                title: Some(title.to_owned()),
+                library: Some(library.to_owned()),
+                ..Default::default()
+            }).await.unwrap();
+            // Data of one game needs distinct dates
+            for (idx, (size, present)) in data.into_iter().enumerate() {
+                let mut partial = full_game_data(&format!("2023-01-01T01:01:{:02}.000", idx), present);
+                partial.game_id = game.id.clone();
+                partial.size = Some(size);
+                flashpoint.create_game_data(&partial).await.unwrap();
+            }
+        }
+
+        let report = flashpoint.disk_usage_report(2).await.unwrap();
+        assert_eq!(report.total_size, 6100);
+        assert_eq!(report.installed_count, 4);
+        let top: Vec<(&str, i64)> = report.top_games.iter().map(|g| (g.title.as_str(), g.size)).collect();
+        assert_eq!(top, vec![("Large", 3000), ("Theatre", 3000)]);
+        assert_eq!(report.top_games[1].installed_count, 2);
+        let libraries: Vec<(&str, i64, i64)> = report.libraries.iter()
+            .map(|l| (l.library.as_str(), l.size, l.installed_count)).collect();
+        assert_eq!(libraries, vec![("arcade", 3100, 2), ("theatre", 3000, 2)]);
+
+        let titles = |games: Vec<Game>| {
+            let mut titles: Vec<String> = games.into_iter().map(|g| g.title).collect();
+            titles.sort();
+            titles
+        };
+        let search = game::search::parse_user_input("installedSize>2k").search;
+        assert_eq!(search.filter.higher_than.installed_size, Some(2048));
+        assert_eq!(titles(flashpoint.search_games(&search).await.unwrap()), vec!["Large", "Theatre"]);
+        // Absent data doesn't count, games without installed data have a size of 0
+        let search = game::search::parse_user_input("installedSize<1kb").search;
+        assert_eq!(titles(flashpoint.search_games(&search).await.unwrap()), vec!["Missing", "Small"]);
+        let search = game::search::parse_user_input("installedSize>1g").search;
+        assert_eq!(search.filter.higher_than.installed_size, Some(1024 * 1024 * 1024));
+        assert!(flashpoint.search_games(&search).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn find_tag_aliases_for() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let action = flashpoint.create_tag("Action", None, None).await.unwrap();
+        let puzzle = flashpoint.create_tag("Puzzle", None, None).await.unwrap();
+        let other = flashpoint.create_tag("Other", None, None).await.unwrap();
+        let mut partial: PartialTag = action.clone().into();
+        partial.aliases = Some(vec!["Action".to_owned(), "Shooter".to_owned()]);
+        flashpoint.save_tag(&mut partial).await.unwrap();
+
+        let aliases = flashpoint.find_tag_aliases_for(&[action.id, puzzle.id]).await.unwrap();
+        assert_eq!(aliases.len(), 2);
+        assert_eq!(aliases[&action.id], vec!["Action", "Shooter"]);
+        assert_eq!(aliases[&puzzle.id], vec!["Puzzle"]);
+        assert!(!aliases.contains_key(&other.id));
+    }
+
+    #[test]
+    fn search_enum_string_round_trip() {
+        use game::search::{GameField, GameSearchDirection, GameSearchSortable};
+        for variant in GameSearchSortable::all() {
+            assert_eq!(variant.to_string().parse::<GameSearchSortable>().unwrap(), variant);
+        }
+        for variant in GameSearchDirection::all() {
+            assert_eq!(variant.to_string().parse::<GameSearchDirection>().unwrap(), variant);
+        }
+        for variant in GroupableField::all() {
+            assert_eq!(variant.to_string().parse::<GroupableField>().unwrap(), variant);
+        }
+        for variant in GameField::all() {
+            assert_eq!(variant.to_string().parse::<GameField>().unwrap(), variant);
+        }
+        assert_eq!(GameSearchSortable::all().len(), 12);
+
+        assert_eq!(GameSearchSortable::DATEADDED.to_string(), "dateAdded");
+        assert_eq!("dateadded".parse::<GameSearchSortable>().unwrap(), GameSearchSortable::DATEADDED);
+        assert_eq!("DESC".parse::<GameSearchDirection>().unwrap(), GameSearchDirection::DESC);
+        assert!("sideways".parse::<GameSearchDirection>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn search_serde_round_trip() {
+        use game::search::{GameSearchDirection, GameSearchSortable};
+        let mut search = game::search::parse_user_input("tag:Action year>2000").search;
+        search.order.column = GameSearchSortable::LASTPLAYED;
+        search.order.direction = game::search::GameSearchDirection::DESC;
+
+        let json = serde_json::to_value(&search).unwrap();
+        assert_eq!(json["order"]["column"], "lastPlayed");
+        assert_eq!(json["order"]["direction"], "desc");
+
+        let parsed: GameSearch = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.order.column, GameSearchSortable::LASTPLAYED);
+        assert_eq!(parsed.order.direction, GameSearchDirection::DESC);
+        assert_eq!(parsed.filter.whitelist.tags, Some(vec!["Action".to_owned()]));
+        assert_eq!(parsed.filter.higher_than.release_year, Some(2000));
+
+        let order: game::search::GameSearchOrder =
+            serde_json::from_str(r#"{"column": "dateAdded", "direction": "asc"}"#).unwrap();
+        assert_eq!(order.column, GameSearchSortable::DATEADDED);
+        assert!(serde_json::from_str::<game::search::GameSearchOrder>(r#"{"column": "nope", "direction": "asc"}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn next_tag_and_platform_id() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        assert_eq!(flashpoint.next_tag_id().await.unwrap(), 1);
+        assert_eq!(flashpoint.next_platform_id().await.unwrap(), 1);
+
+        let tag = flashpoint.create_tag("Action", None, None).await.unwrap();
+        assert_eq!(flashpoint.next_tag_id().await.unwrap(), tag.id + 1);
+        flashpoint.create_tag("Imported", None, Some(50)).await.unwrap();
+        assert_eq!(flashpoint.next_tag_id().await.unwrap(), 51);
+
+        let platform = flashpoint.create_platform("Flash", None).await.unwrap();
+        assert_eq!(flashpoint.next_platform_id().await.unwrap(), platform.id + 1);
+        flashpoint.create_platform("HTML5", Some(20)).await.unwrap();
+        assert_eq!(flashpoint.next_platform_id().await.unwrap(), 21);
+    }
+
+    #[tokio::test]
+    async fn validate_game() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let game = flashpoint.create_game(&PartialGame {
+            title: Some("Test".to_owned()),
+            primary_platform: Some("Flash".to_owned()),
+            platforms: Some(vec!["Flash"].into()),
+            ..Default::default()
+        }).await.unwrap();
+
+        let valid = PartialGame {
+            id: game.id.clone(),
+            date_modified: Some("2024-01-01T00:00:00.000Z".to_owned()),
+            ..Default::default()
+        };
+        assert!(flashpoint.validate_game(&valid).await.unwrap().is_empty());
+
+        let invalid = PartialGame {
+            id: game.id.clone(),
+            title: Some("Changed".to_owned()),
+            date_added: Some("yesterday".to_owned()),
+            primary_platform: Some("HTML5".to_owned()),
+            ..Default::default()
+        };
+        let issues = flashpoint.validate_game(&invalid).await.unwrap();
+        let fields: Vec<&str> = issues.iter().map(|i| i.field.as_str()).collect();
+        assert_eq!(fields, vec!["date_added", "primary_platform"]);
+
+        // Nothing was written
+        let unchanged = flashpoint.find_game(&game.id).await.unwrap().unwrap();
+        assert_eq!(unchanged.title, "Test");
+        assert_eq!(unchanged.primary_platform, "Flash");
+
+        let missing = PartialGame {
+            id: "missing".to_owned(),
+            ..Default::default()
+        };
+        let res = flashpoint.validate_game(&missing).await;
+        assert!(matches!(res, Err(Error::GameNotFound { id }) if id == "missing"));
+    }
+
+    #[cfg(feature = "serialize")]
+    #[tokio::test]
+    async fn snapshot_restore_memory_database() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        for title in ["Alpha", "Beta"] {
+            let partial_game = game::PartialGame {
+                title: Some(String::from(title)),
+                tags: Some(vec!["Action", "Puzzle"].into()),
+                ..game::PartialGame::default()
+            };
+            assert!(flashpoint.create_game(&partial_game).await.is_ok());
+        }
+        let snapshot = flashpoint.serialize_memory().unwrap();
+
+        let mut restored = FlashpointArchive::new();
+        assert!(restored.load_database_from_bytes(&snapshot).is_ok());
+        assert_eq!(restored.count_games().await.unwrap(), 2);
+        assert_eq!(restored.count_tags().await.unwrap(), flashpoint.count_tags().await.unwrap());
+        assert_eq!(restored.count_platforms().await.unwrap(), flashpoint.count_platforms().await.unwrap());
+
+        // Mutating either database leaves the snapshot bytes untouched
+        let partial_game = game::PartialGame {
+            title: Some(String::from("Gamma")),
+            ..game::PartialGame::default()
+        };
+        assert!(restored.create_game(&partial_game).await.is_ok());
+        assert!(flashpoint.create_game(&partial_game).await.is_ok());
+        let mut again = FlashpointArchive::new();
+        assert!(again.load_database_from_bytes(&snapshot).is_ok());
+        assert_eq!(again.count_games().await.unwrap(), 2);
+        assert_eq!(restored.count_games().await.unwrap(), 3);
+    }
+
+    #[cfg(feature = "serialize")]
+    #[tokio::test]
+    async fn snapshot_restore_rejects_garbage() {
+        let mut flashpoint = FlashpointArchive::new();
+        let res = flashpoint.load_database_from_bytes(b"not a database");
+        assert!(matches!(res, Err(Error::NotADatabase { .. })));
+        let res = flashpoint.load_database_from_bytes(&[]);
+        assert!(matches!(res, Err(Error::NotADatabase { .. })));
+        assert!(matches!(flashpoint.serialize_memory(), Err(Error::DatabaseNotInitialized)));
+    }
+
+    #[tokio::test]
+    async fn delete_tags_by_ids() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let first = flashpoint.create_game(&PartialGame {
+            title: Some("First".to_owned()),
+            tags: Some(vec!["Action", "Puzzle", "Keep"].into()),
+            ..Default::default()
+        }).await.unwrap();
+        let second = flashpoint.create_game(&PartialGame {
+            title: Some("Second".to_owned()),
+            tags: Some(vec!["Arcade", "Keep"].into()),
+            ..Default::default()
+        }).await.unwrap();
+        let mut ids = vec![];
+        for name in ["Action", "Puzzle", "Arcade"] {
+            ids.push(flashpoint.find_tag(name).await.unwrap().unwrap().id);
+        }
+        assert!(flashpoint.delete_tags_by_ids(&ids).await.is_ok());
+        for name in ["Action", "Puzzle", "Arcade"] {
+            assert!(flashpoint.find_tag(name).await.unwrap().is_none());
+        }
+        for id in [&first.id, &second.id] {
+            let game = flashpoint.find_game(id).await.unwrap().unwrap();
+            assert_eq!(game.tags.len(), 1);
+            assert_eq!(game.tags[0], "Keep");
+            assert_eq!(game.detailed_tags.unwrap().len(), 1);
+        }
+        assert_eq!(flashpoint.count_tags().await.unwrap(), 1);
+
+        // Redirects to or from deleted tags go with them
+        let old = flashpoint.create_tag("Platformer", None, None).await.unwrap();
+        let merged = flashpoint.create_tag("Jumper", None, None).await.unwrap();
+        assert!(flashpoint.merge_tags("Platformer", "Jumper", None).await.is_ok());
+        assert!(flashpoint.delete_tag_by_id(merged.id).await.is_ok());
+        assert!(flashpoint.find_tag_by_id(old.id).await.unwrap().is_none());
+        assert!(flashpoint.find_tag_redirects().await.unwrap().is_empty());
+
+        let source = flashpoint.create_tag("Shmup", None, None).await.unwrap();
+        let target = flashpoint.create_tag("Shooter", None, None).await.unwrap();
+        assert!(flashpoint.merge_tags("Shmup", "Shooter", None).await.is_ok());
+        assert!(flashpoint.delete_tags_by_ids(&[source.id]).await.is_ok());
+        assert!(flashpoint.find_tag_redirects().await.unwrap().is_empty());
+        assert_eq!(flashpoint.find_tag_by_id(target.id).await.unwrap().unwrap().name, "Shooter");
+    }
+
+    #[tokio::test]
+    async fn find_game_slim() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let game = flashpoint.create_game(&PartialGame {
+            title: Some("Slim".to_owned()),
+            developer: Some("Dev".to_owned()),
+            tags: Some(vec!["Action"].into()),
+            platforms: Some(vec!["Flash"].into()),
+            primary_platform: Some("Flash".to_owned()),
+            ..Default::default()
+        }).await.unwrap();
+        assert!(flashpoint.create_game_redirect("old-id", &game.id).await.is_ok());
+
+        for id in [game.id.as_str(), "old-id"] {
+            let slim = flashpoint.find_game_slim(id).await.unwrap().unwrap();
+            assert_eq!(slim.id, game.id);
+            assert_eq!(slim.title, "Slim");
+            assert_eq!(slim.developer, "Dev");
+            assert_eq!(slim.tags.len(), 1);
+            assert!(slim.detailed_tags.is_none());
+            assert!(slim.detailed_platforms.is_none());
+            assert!(slim.game_data.is_none());
+            assert!(slim.add_apps.is_none());
+        }
+        assert!(flashpoint.find_game_slim("missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn find_owner_of_alias() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let tag = flashpoint.create_tag("Action", None, None).await.unwrap();
+        let mut partial: PartialTag = tag.into();
+        partial.aliases = Some(vec!["Action".to_owned(), "Fighting".to_owned()]);
+        assert!(flashpoint.save_tag(&mut partial).await.is_ok());
+        let platform = flashpoint.create_platform("Flash", None).await.unwrap();
+        let mut partial: PartialTag = platform.into();
+        partial.aliases = Some(vec!["Flash".to_owned(), "Shockwave Flash".to_owned()]);
+        assert!(flashpoint.save_platform(&mut partial).await.is_ok());
+
+        for alias in ["Action", "Fighting"] {
+            let owner = flashpoint.find_tag_owner_of_alias(alias).await.unwrap().unwrap();
+            assert_eq!(owner.name, "Action");
+            assert_eq!(owner.aliases.len(), 2);
+        }
+        for alias in ["Flash", "Shockwave Flash"] {
+            let owner = flashpoint.find_platform_owner_of_alias(alias).await.unwrap().unwrap();
+            assert_eq!(owner.name, "Flash");
+        }
+        assert!(flashpoint.find_tag_owner_of_alias("Flash").await.unwrap().is_none());
+        assert!(flashpoint.find_platform_owner_of_alias("Fighting").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn find_tag_aliases_paged() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let tag = flashpoint.create_tag("Action", None, None).await.unwrap();
+        let action_id = tag.id;
+        let mut partial: PartialTag = tag.into();
+        partial.aliases = Some(vec!["Action".to_owned(), "Action_RPG".to_owned(), "Fighting".to_owned()]);
+        assert!(flashpoint.save_tag(&mut partial).await.is_ok());
+        let puzzle = flashpoint.create_tag("Actionless Puzzle", None, None).await.unwrap();
+
+        let all = flashpoint.find_all_tag_aliases().await.unwrap();
+        let names: Vec<&str> = all.iter().map(|a| a.value.as_str()).collect();
+        assert_eq!(names, vec!["Action", "Action_RPG", "Actionless Puzzle", "Fighting"]);
+        assert_eq!(all[3].id, action_id);
+        assert_eq!(all[2].id, puzzle.id);
+
+        let first = flashpoint.find_tag_aliases_page(Some("act".to_owned()), 2, 0).await.unwrap();
+        let second = flashpoint.find_tag_aliases_page(Some("act".to_owned()), 2, 2).await.unwrap();
+        assert_eq!(first.iter().map(|a| a.value.as_str()).collect::<Vec<_>>(), vec!["Action", "Action_RPG"]);
+        assert_eq!(second.iter().map(|a| a.value.as_str()).collect::<Vec<_>>(), vec!["Actionless Puzzle"]);
+
+        // Wildcards in the prefix match literally
+        let literal = flashpoint.find_tag_aliases_page(Some("Action_".to_owned()), 10, 0).await.unwrap();
+        assert_eq!(literal.len(), 1);
+        assert_eq!(literal[0].value, "Action_RPG");
+
+        let unfiltered = flashpoint.find_tag_aliases_page(None, 10, 1).await.unwrap();
+        assert_eq!(unfiltered.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn serialized_write_times_out() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        flashpoint.set_write_timeout(Duration::from_millis(50));
+        let mut partial = PartialGame {
+            title: Some("Queued".to_owned()),
+            ..Default::default()
+        };
+        let game = flashpoint.create_game(&partial).await.unwrap();
+        partial.id = game.id.clone();
+        let (log_id, log_rx) = logger_subscribe();
+
+        // Waiting on a held lock yields to the runtime, so this runs on a single thread
+        let held = flashpoint.write_lock.lock().await;
+        let res = flashpoint.save_game(&mut partial).await;
+        match res {
+            Err(Error::WriteBusy { waited_ms }) => assert!(waited_ms >= 50),
+            other => panic!("expected WriteBusy, got {:?}", other),
+        }
+        drop(held);
+
+        // Once the lock is released the save goes through
+        assert!(flashpoint.save_game(&mut partial).await.is_ok());
+
+        let slow = flashpoint.transaction(|_| {
+            std::thread::sleep(Duration::from_millis(60));
+            Ok(())
+        }).await;
+        assert!(slow.is_ok());
+        let events: Vec<String> = log_rx.try_iter().collect();
+        logger_unsubscribe(log_id);
+        assert!(events.iter().any(|e| e.starts_with("Slow write: transaction held the write lock")));
+    }
+
+    #[tokio::test]
+    async fn merge_tag_into_itself() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let game = flashpoint.create_game(&PartialGame {
+            title: Some("test".to_owned()),
+            tags: Some(vec!["Action"].into()),
+            ..Default::default()
+        }).await.unwrap();
+        let tag = flashpoint.find_tag("Action").await.unwrap().unwrap();
+        let mut partial: PartialTag = tag.clone().into();
+        partial.aliases = Some(vec!["Action".to_owned(), "Fighting".to_owned()]);
+        assert!(flashpoint.save_tag(&mut partial).await.is_ok());
+
+        for (name, merged_into) in [("Action", "Action"), ("Fighting", "Action")] {
+            let res = flashpoint.merge_tags(name, merged_into, None).await;
+            assert!(matches!(res, Err(Error::MergeIntoSelf { .. })));
+        }
+
+        let unchanged = flashpoint.find_tag_by_id(tag.id).await.unwrap().unwrap();
+        assert_eq!(unchanged.name, "Action");
+        assert_eq!(unchanged.aliases.len(), 2);
+        let game = flashpoint.find_game(&game.id).await.unwrap().unwrap();
+        assert_eq!(game.tags.len(), 1);
+        assert_eq!(game.detailed_tags.unwrap()[0].id, tag.id);
+    }
 
-        // Test last page results
-        search.offset = Some(GameSearchOffset{
-            value: page_end_game.title.clone(),
-            game_id: page_end_game.id.clone(),
-            title: page_end_game.title.clone(),
-        });
-        let last_result = flashpoint.search_games(&search).await;
-        assert!(last_result.is_ok());
-        let last_page = last_result.unwrap();
-        assert_eq!(last_page.len(), 6541);
+    #[tokio::test]
+    async fn search_explain_matches() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let in_notes = flashpoint.create_game(&PartialGame {
+            title: Some("Unrelated".to_owned()),
+            notes: Some("Has a secret level".to_owned()),
+            ..Default::default()
+        }).await.unwrap();
+        let in_title = flashpoint.create_game(&PartialGame {
+            title: Some("Secret Agent".to_owned()),
+            developer: Some("Secretive Studio".to_owned()),
+            ..Default::default()
+        }).await.unwrap();
+        let in_description = flashpoint.create_game(&PartialGame {
+            title: Some("Another".to_owned()),
+            original_description: Some("A secret admirer".to_owned()),
+            ..Default::default()
+        }).await.unwrap();
+
+        let mut search = game::search::GameSearch::default();
+        search.filter.any_text = Some(vec!["secret".to_owned()]);
+        let games = flashpoint.search_games(&search).await.unwrap();
+        assert_eq!(games.len(), 3);
+        assert!(games.iter().all(|g| g.matched_fields.is_none()));
+
+        search.explain_matches = true;
+        let games = flashpoint.search_games(&search).await.unwrap();
+        assert_eq!(games.len(), 3);
+        let find = |id: &str| games.iter().find(|g| g.id == id).unwrap().matched_fields.clone().unwrap();
+        assert_eq!(find(&in_notes.id), vec!["notes"]);
+        assert_eq!(find(&in_title.id), vec!["title", "developer"]);
+        assert_eq!(find(&in_description.id), vec!["originalDescription"]);
+
+        // Generic terms only report the columns the generic filter searches
+        search.filter.any_text = None;
+        search.filter.whitelist.generic = Some(vec!["secret".to_owned()]);
+        let games = flashpoint.search_games(&search).await.unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].matched_fields.clone().unwrap(), vec!["title", "developer"]);
+
+        // Exact terms compare the whole value
+        search.filter.whitelist.generic = None;
+        search.filter.exact_whitelist.generic = Some(vec!["Secret Agent".to_owned()]);
+        let games = flashpoint.search_games(&search).await.unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].matched_fields.clone().unwrap(), vec!["title"]);
+
+        // Wildcards in terms match literally
+        search.filter.exact_whitelist.generic = None;
+        search.filter.whitelist.generic = Some(vec!["Secret_Agent".to_owned()]);
+        assert!(flashpoint.search_games(&search).await.unwrap().is_empty());
     }
 
     #[tokio::test]
-    async fn search_multiple_subfilters() {
+    async fn merge_tags_records_redirect() {
         let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(TEST_DATABASE);
-        assert!(create.is_ok());
-        let mut search = GameSearch::default();
-        search.filter.subfilters.push(GameFilter {
-            exact_blacklist: FieldFilter {
-                tags: Some(vec!["Action".to_owned(), "Shooting".to_owned()]),
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let old = flashpoint.create_tag("Platformer", None, None).await.unwrap();
+        let merged = flashpoint.create_tag("Platform Game", None, None).await.unwrap();
+        let kept = flashpoint.create_tag("Jumping", None, None).await.unwrap();
+        assert!(flashpoint.merge_tags("Platformer", "Platform Game", None).await.is_ok());
+        assert!(flashpoint.merge_tags("Jumping", "Platform Game", Some(false)).await.is_ok());
+
+        let found = flashpoint.find_tag_by_id(old.id).await.unwrap().unwrap();
+        assert_eq!(found.id, merged.id);
+        assert_eq!(found.name, "Platform Game");
+        assert_eq!(flashpoint.find_tag("Platformer").await.unwrap().unwrap().id, merged.id);
+        assert!(flashpoint.find_tag_by_id(kept.id).await.unwrap().is_none());
+
+        let redirects = flashpoint.find_tag_redirects().await.unwrap();
+        assert_eq!(redirects.len(), 1);
+        assert_eq!(redirects[0].old_tag_id, old.id);
+        assert_eq!(redirects[0].new_tag_id, merged.id);
+
+        // Merging the merged tag again keeps the first redirect pointing at a live tag
+        flashpoint.create_tag("Side Scroller", None, None).await.unwrap();
+        let last = flashpoint.merge_tags("Platform Game", "Side Scroller", None).await.unwrap();
+        assert_eq!(flashpoint.find_tag_by_id(old.id).await.unwrap().unwrap().id, last.id);
+        assert_eq!(flashpoint.find_tag_by_id(merged.id).await.unwrap().unwrap().id, last.id);
+    }
+
+    #[tokio::test]
+    async fn update_remaps_merged_tags() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let game = flashpoint.create_game(&PartialGame {
+            title: Some("Jumper".to_owned()),
+            ..Default::default()
+        }).await.unwrap();
+        let old = flashpoint.create_tag("Platformer", None, Some(10)).await.unwrap();
+        let merged = flashpoint.create_tag("Platform Game", None, Some(20)).await.unwrap();
+        assert!(flashpoint.merge_tags("Platformer", "Platform Game", None).await.is_ok());
+        let tag_count = flashpoint.count_tags().await.unwrap();
+
+        let tag_update = RemoteTag {
+            id: old.id,
+            name: "Platformer".to_owned(),
+            description: String::new(),
+            category: "default".to_owned(),
+            date_modified: "2024-01-01 12:00:00".to_owned(),
+            aliases: vec!["Platformer".to_owned(), "Platforming".to_owned()],
+            deleted: false,
+        };
+        assert!(flashpoint.update_apply_tags(vec![tag_update]).await.is_ok());
+        assert_eq!(flashpoint.count_tags().await.unwrap(), tag_count);
+        let found = flashpoint.find_tag_by_id(old.id).await.unwrap().unwrap();
+        assert_eq!(found.id, merged.id);
+        assert_eq!(found.name, "Platform Game");
+        assert!(found.aliases.contains(&"Platforming".to_owned()));
+
+        let remote_game = update::RemoteGame {
+            id: game.id.clone(),
+            title: "Jumper".to_owned(),
+            alternate_titles: "".to_owned(),
+            series: "".to_owned(),
+            developer: "".to_owned(),
+            publisher: "".to_owned(),
+            date_added: game.date_added.clone(),
+            date_modified: game.date_modified.clone(),
+            play_mode: "".to_owned(),
+            status: "".to_owned(),
+            notes: "".to_owned(),
+            source: "".to_owned(),
+            application_path: "".to_owned(),
+            launch_command: "".to_owned(),
+            release_date: "".to_owned(),
+            version: "".to_owned(),
+            original_description: "".to_owned(),
+            language: "".to_owned(),
+            library: "arcade".to_owned(),
+            platform_name: "".to_owned(),
+            archive_state: 0,
+            ruffle_support: "".to_owned(),
+        };
+        assert!(flashpoint.update_apply_games(&RemoteGamesRes {
+            games: vec![remote_game],
+            add_apps: vec![],
+            game_data: vec![],
+            tag_relations: vec![
+                vec![game.id.clone(), old.id.to_string()],
+                vec![game.id.clone(), merged.id.to_string()],
+            ],
+            platform_relations: vec![],
+        }).await.is_ok());
+        let game = flashpoint.find_game(&game.id).await.unwrap().unwrap();
+        let detailed_tags = game.detailed_tags.unwrap();
+        assert_eq!(detailed_tags.len(), 1);
+        assert_eq!(detailed_tags[0].id, merged.id);
+        assert_eq!(game.tags.to_vec(), vec!["Platform Game".to_owned()]);
+    }
+
+    #[tokio::test]
+    async fn find_all_ruffle_support() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        for (title, ruffle) in [("A", "standalone"), ("B", "embedded"), ("C", "standalone"), ("D", "")] {
+            assert!(flashpoint.create_game(&PartialGame {
+                title: Some(title.to_owned()),
+                ruffle_support: Some(ruffle.to_owned()),
+                ..Default::default()
+            }).await.is_ok());
+        }
+        let values = flashpoint.find_all_ruffle_support().await.unwrap();
+        assert_eq!(values, vec!["embedded", "standalone"]);
+    }
+
+    #[tokio::test]
+    async fn rebuild_denormalized_columns() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let platform = flashpoint.create_platform("Flash", None).await.unwrap();
+        let flash_id = platform.id;
+        let mut partial: PartialTag = platform.into();
+        partial.aliases = Some(vec!["Flash".to_owned(), "Shockwave Flash".to_owned()]);
+        assert!(flashpoint.save_platform(&mut partial).await.is_ok());
+        let game = flashpoint.create_full_game(&CreateFullGame {
+            game: PartialGame {
+                title: Some("Rebuilt".to_owned()),
+                tags: Some(vec!["Action"].into()),
+                platforms: Some(vec!["Flash"].into()),
+                primary_platform: Some("Flash".to_owned()),
                 ..Default::default()
             },
+            add_apps: vec![],
+            game_data: vec![full_game_data("2023-01-01T01:01:01.000Z", true)],
+        }).await.unwrap();
+        let untouched = flashpoint.create_game(&PartialGame {
+            title: Some("Untouched".to_owned()),
+            tags: Some(vec!["Puzzle"].into()),
+            ..Default::default()
+        }).await.unwrap();
+        let reordered = flashpoint.create_game(&PartialGame {
+            title: Some("Reordered".to_owned()),
+            tags: Some(vec!["Action", "Puzzle"].into()),
+            platforms: Some(vec!["Flash", "HTML5"].into()),
+            primary_platform: Some("HTML5".to_owned()),
+            ..Default::default()
+        }).await.unwrap();
+        let lost = flashpoint.create_game(&PartialGame {
+            title: Some("Lost".to_owned()),
+            platforms: Some(vec!["HTML5", "Flash"].into()),
+            primary_platform: Some("Flash".to_owned()),
             ..Default::default()
+        }).await.unwrap();
+        let html5 = flashpoint.find_platform("HTML5").await.unwrap().unwrap();
+
+        let platform_id = |id: &str| {
+            let conn = flashpoint.pool.as_ref().unwrap().get().unwrap();
+            conn.query_row("SELECT platformId FROM game WHERE id = ?", [id], |row| row.get::<_, Option<i64>>(0)).unwrap()
+        };
+        assert_eq!(platform_id(&game.id), Some(flash_id));
+        assert_eq!(platform_id(&reordered.id), Some(html5.id));
+
+        {
+            let conn = flashpoint.pool.as_ref().unwrap().get().unwrap();
+            conn.execute("UPDATE game SET tagsStr = 'Stale', platformsStr = '', platformName = 'shockwave flash', platformId = NULL,
+                activeDataOnDisk = false WHERE id = ?", rusqlite::params![game.id]).unwrap();
+            // Same tags in another order, the platform id still knows the primary platform
+            conn.execute("UPDATE game SET tagsStr = 'Puzzle; Action', platformName = 'Garbage' WHERE id = ?", rusqlite::params![reordered.id]).unwrap();
+            conn.execute("UPDATE game SET platformName = 'Garbage', platformId = NULL WHERE id = ?", rusqlite::params![lost.id]).unwrap();
+        }
+
+        let report = flashpoint.rebuild_denormalized(RebuildScope::All).await.unwrap();
+        assert_eq!(report, RebuildReport {
+            games_checked: 4,
+            tags_str: 1,
+            platforms_str: 1,
+            platform_name: 3,
+            active_data_on_disk: 1,
         });
-        search.filter.subfilters.push(GameFilter {
-            exact_blacklist: FieldFilter {
-                tags: Some(vec!["Adventure".to_owned()]),
+
+        let rebuilt = flashpoint.find_game(&game.id).await.unwrap().unwrap();
+        assert_eq!(rebuilt.tags.to_vec(), vec!["Action".to_owned()]);
+        assert_eq!(rebuilt.platforms.to_vec(), vec!["Flash".to_owned()]);
+        assert_eq!(rebuilt.primary_platform, "Flash");
+        assert_eq!(platform_id(&game.id), Some(flash_id));
+        assert!(rebuilt.active_data_on_disk);
+        let other = flashpoint.find_game(&untouched.id).await.unwrap().unwrap();
+        assert_eq!(other.tags.to_vec(), vec!["Puzzle".to_owned()]);
+        let rebuilt = flashpoint.find_game(&reordered.id).await.unwrap().unwrap();
+        assert_eq!(rebuilt.tags.to_vec(), vec!["Puzzle".to_owned(), "Action".to_owned()]);
+        assert_eq!(rebuilt.primary_platform, "HTML5");
+        // Nothing left to point at the primary platform, the first one takes its place
+        let rebuilt = flashpoint.find_game(&lost.id).await.unwrap().unwrap();
+        assert_eq!(rebuilt.primary_platform, "HTML5");
+        assert_eq!(platform_id(&lost.id), Some(html5.id));
+
+        // Nothing left to fix on a second pass
+        let report = flashpoint.rebuild_denormalized(RebuildScope::All).await.unwrap();
+        assert_eq!(report, RebuildReport { games_checked: 4, ..Default::default() });
+    }
+
+    #[tokio::test]
+    async fn find_games_by_ids() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let first = flashpoint.create_game(&PartialGame {
+            title: Some("First".to_owned()),
+            tags: Some(vec!["Action"].into()),
+            ..Default::default()
+        }).await.unwrap();
+        let second = flashpoint.create_game(&PartialGame {
+            title: Some("Second".to_owned()),
+            ..Default::default()
+        }).await.unwrap();
+        assert!(flashpoint.create_game_redirect("old-id", &second.id).await.is_ok());
+
+        let games = flashpoint.find_games_by_ids(vec![first.id.clone(), "old-id".to_owned(), "missing".to_owned()]).await.unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[&first.id].title, "First");
+        assert_eq!(games[&first.id].detailed_tags.as_ref().unwrap().len(), 1);
+        assert_eq!(games["old-id"].id, second.id);
+        assert_eq!(games["old-id"].title, "Second");
+        assert!(!games.contains_key("missing"));
+    }
+
+    #[tokio::test]
+    async fn clear_playtime_by_id_updates_search() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let mut ids = vec![];
+        for title in ["Played", "Cleared"] {
+            let game = flashpoint.create_game(&PartialGame {
+                title: Some(title.to_owned()),
                 ..Default::default()
-            },
+            }).await.unwrap();
+            assert!(flashpoint.add_game_playtime(&game.id, 60).await.is_ok());
+            ids.push(game.id);
+        }
+        let search = game::search::parse_user_input("lastplayed>2000").search;
+        assert_eq!(flashpoint.search_games(&search).await.unwrap().len(), 2);
+
+        assert!(flashpoint.clear_playtime_tracking_by_id(&ids[1]).await.is_ok());
+        let games = flashpoint.search_games(&search).await.unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].id, ids[0]);
+        let cleared = flashpoint.find_game(&ids[1]).await.unwrap().unwrap();
+        assert_eq!(cleared.playtime, 0);
+        assert!(cleared.last_played.is_none());
+    }
+
+    #[tokio::test]
+    async fn save_game_dedupes_relations() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let platform = flashpoint.create_platform("Flash", None).await.unwrap();
+        let mut partial: PartialTag = platform.into();
+        partial.aliases = Some(vec!["Flash".to_owned(), "Shockwave Flash".to_owned()]);
+        assert!(flashpoint.save_platform(&mut partial).await.is_ok());
+
+        let game = flashpoint.create_game(&PartialGame {
+            title: Some("Dupes".to_owned()),
+            platforms: Some(vec!["Flash", "flash", "Shockwave Flash"].into()),
+            primary_platform: Some("shockwave flash".to_owned()),
+            tags: Some(vec!["Action", "action"].into()),
             ..Default::default()
-        });
-        search.filter.exact_whitelist.library = Some(vec!["arcade".to_owned()]);
-        search.filter.match_any = false;
-        assert!(flashpoint.search_games_index(&mut search, None).await.is_ok());
+        }).await.unwrap();
+        assert_eq!(game.platforms.to_vec(), vec!["Flash".to_owned()]);
+        assert_eq!(game.primary_platform, "Flash");
+        assert_eq!(game.tags.to_vec(), vec!["Action".to_owned()]);
+
+        let found = flashpoint.find_game(&game.id).await.unwrap().unwrap();
+        assert_eq!(found.detailed_platforms.unwrap().len(), 1);
+        assert_eq!(found.detailed_tags.unwrap().len(), 1);
+        assert_eq!(found.platforms.to_vec(), vec!["Flash".to_owned()]);
     }
 
     #[tokio::test]
-    async fn parse_user_search_input_assorted() {
-        game::search::parse_user_input("test");
-        game::search::parse_user_input(r#"tag:"sonic""#);
-        game::search::parse_user_input(r#"o_%$ dev:"san" disk t:7 potato"#);
+    async fn save_game_clears_dropped_primary_platform() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let game = flashpoint.create_game(&PartialGame {
+            title: Some("Ported".to_owned()),
+            platforms: Some(vec!["Flash", "HTML5"].into()),
+            primary_platform: Some("Flash".to_owned()),
+            ..Default::default()
+        }).await.unwrap();
+        assert_eq!(game.primary_platform, "Flash");
 
-        enable_debug();
+        // A later save replaces the platforms without the primary one
+        let mut partial = PartialGame {
+            id: game.id.clone(),
+            platforms: Some(vec!["HTML5"].into()),
+            ..Default::default()
+        };
+        let saved = flashpoint.save_game(&mut partial).await.unwrap();
+        assert_eq!(saved.platforms.to_vec(), vec!["HTML5".to_owned()]);
+        assert_eq!(saved.primary_platform, "");
 
-        // "" should be treated as exact
-        // Allow key characters in quoted text
-        let s = game::search::parse_user_input(r#"title:"" series:"sonic:hedgehog" -developer:"""#).search;
-        assert!(s.filter.exact_whitelist.title.is_some());
-        assert_eq!(s.filter.exact_whitelist.title.unwrap()[0], "");
-        assert!(s.filter.whitelist.series.is_some());
-        assert_eq!(s.filter.whitelist.series.unwrap()[0], "sonic:hedgehog");
-        assert!(s.filter.exact_blacklist.developer.is_some());
-        assert_eq!(s.filter.exact_blacklist.developer.unwrap()[0], "");
+        let found = flashpoint.find_game(&game.id).await.unwrap().unwrap();
+        assert_eq!(found.primary_platform, "");
+        let detailed_platforms = found.detailed_platforms.unwrap();
+        assert_eq!(detailed_platforms.len(), 1);
+        assert_eq!(detailed_platforms[0].name, "HTML5");
+    }
 
-        // Make sure the number filters are populated and the time text is processes
-        let s2 = game::search::parse_user_input(r#"playtime>1h30m tags:3 playcount<3"#).search;
-        assert!(s2.filter.higher_than.playtime.is_some());
-        assert_eq!(s2.filter.higher_than.playtime.unwrap(), 60 * 90);
-        assert!(s2.filter.equal_to.tags.is_some());
-        assert_eq!(s2.filter.equal_to.tags.unwrap(), 3);
-        assert!(s2.filter.lower_than.playcount.is_some());
-        assert_eq!(s2.filter.lower_than.playcount.unwrap(), 3);
+    #[test]
+    fn content_tree_rolls_up_sizes() {
+        let root = std::env::temp_dir().join(format!("fpa-content-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("htdocs").join("nested")).unwrap();
+        std::fs::write(root.join("top.txt"), vec![0u8; 3]).unwrap();
+        std::fs::write(root.join("htdocs").join("index.html"), vec![0u8; 10]).unwrap();
+        std::fs::write(root.join("htdocs").join("nested").join("game.swf"), vec![0u8; 25]).unwrap();
+
+        let tree = generate_content_tree(root.to_str().unwrap()).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(tree.size, 38);
+        assert_eq!(tree.total_size, Some(38));
+        assert_eq!(tree.count, 5);
+        let htdocs = tree.children.iter().find(|n| n.name == "htdocs").unwrap();
+        assert_eq!(htdocs.size, 35);
+        assert_eq!(htdocs.total_size, None);
+        assert_eq!(htdocs.children.iter().find(|n| n.name == "nested").unwrap().size, 25);
+
+        let mut files = util::flatten_content_tree(&tree);
+        files.sort();
+        assert_eq!(files, vec![
+            ("htdocs/index.html".to_owned(), 10),
+            ("htdocs/nested/game.swf".to_owned(), 25),
+            ("top.txt".to_owned(), 3),
+        ]);
+
+        // Names carrying Windows separators are still emitted with '/'
+        let windows = ContentTreeNode {
+            name: "content".to_owned(),
+            expanded: true,
+            size: 7,
+            node_type: "directory".to_owned(),
+            count: 2,
+            total_size: Some(7),
+            children: vec![ContentTreeNode {
+                name: "htdocs\\example.com".to_owned(),
+                expanded: true,
+                size: 7,
+                node_type: "directory".to_owned(),
+                count: 1,
+                total_size: None,
+                children: vec![ContentTreeNode {
+                    name: "index.html".to_owned(),
+                    expanded: true,
+                    size: 7,
+                    node_type: "file".to_owned(),
+                    count: 0,
+                    total_size: None,
+                    children: vec![],
+                }],
+            }],
+        };
+        assert_eq!(util::flatten_content_tree(&windows), vec![("htdocs/example.com/index.html".to_owned(), 7)]);
     }
 
     #[tokio::test]
-    async fn parse_user_search_input_sizes() {
-        let search = game::search::parse_user_input("tags>5 addapps=3 gamedata<12 test>generic").search;
-        assert!(search.filter.higher_than.tags.is_some());
-        assert_eq!(search.filter.higher_than.tags.unwrap(), 5);
-        assert!(search.filter.equal_to.add_apps.is_some());
-        assert_eq!(search.filter.equal_to.add_apps.unwrap(), 3);
-        assert!(search.filter.lower_than.game_data.is_some());
-        assert_eq!(search.filter.lower_than.game_data.unwrap(), 12);
-        assert!(search.filter.whitelist.generic.is_some());
-        let generics = search.filter.whitelist.generic.unwrap();
-        assert_eq!(generics.len(), 1);
-        assert_eq!(generics[0], "test>generic");
+    async fn filter_to_query_round_trip() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        for (title, developer, tags, year) in [
+            ("Sonic Racing", "SEGA", vec!["Action", "Racing"], "1996"),
+            ("Sonic Puzzle", "SEGA", vec!["Puzzle"], "1998"),
+            ("Sonic Fan Game", "Fan", vec!["Action"], "2003"),
+            ("Mario Kart", "Nintendo", vec!["Racing"], "1997"),
+        ] {
+            let partial = PartialGame {
+                title: Some(title.to_owned()),
+                developer: Some(developer.to_owned()),
+                tags: Some(tags.into()),
+                release_date: Some(year.to_owned()),
+                ..Default::default()
+            };
+            assert!(flashpoint.create_game(&partial).await.is_ok());
+        }
+
+        let input = r#"sonic -tag:"Puzzle" dev="SEGA" -title:"fan game" notes:"" tags>0 year:1990..2000 fav:no"#;
+        let parsed = game::search::parse_user_input(input).search;
+        let query = game::search::filter_to_query(&parsed.filter).unwrap();
+        assert_eq!(
+            query,
+            r#""sonic" developer="SEGA" notes:"" -title:"fan game" -tag:"Puzzle" tags>0 year>1989 year<2001 favorite:no"#
+        );
+        let reparsed = game::search::parse_user_input(&query).search;
+        assert_eq!(game::search::filter_to_query(&reparsed.filter).unwrap(), query);
+
+        let games: Vec<String> = flashpoint.search_games(&parsed).await.unwrap().into_iter().map(|g| g.id).collect();
+        let regames: Vec<String> = flashpoint.search_games(&reparsed).await.unwrap().into_iter().map(|g| g.id).collect();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games, regames);
+
+        // Quoted generics containing a key char stay generic after a keyed term
+        let search = game::search::parse_user_input(r#"tag:Action "tag:like""#).search;
+        assert_eq!(search.filter.whitelist.generic.unwrap(), vec!["tag:like".to_owned()]);
+
+        // OR subfilters keep their group
+        let parsed = game::search::parse_user_input(r#"sonic ( tag:Racing OR dev="Fan" )"#).search;
+        assert!(parsed.filter.subfilters[0].match_any);
+        let query = game::search::filter_to_query(&parsed.filter).unwrap();
+        assert_eq!(query, r#""sonic" ( tag:"Racing" OR developer="Fan" )"#);
+        let reparsed = game::search::parse_user_input(&query).search;
+        assert_eq!(game::search::filter_to_query(&reparsed.filter).unwrap(), query);
+        let games: Vec<String> = flashpoint.search_games(&parsed).await.unwrap().into_iter().map(|g| g.id).collect();
+        let regames: Vec<String> = flashpoint.search_games(&reparsed).await.unwrap().into_iter().map(|g| g.id).collect();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games, regames);
+
+        // Quotes and backslashes are escaped, exact generics stay exact
+        let mut filter = GameFilter::default();
+        filter.whitelist.title = Some(vec![r#"say "hi" \o/"#.to_owned()]);
+        filter.exact_whitelist.generic = Some(vec!["Mario Kart".to_owned()]);
+        let query = game::search::filter_to_query(&filter).unwrap();
+        assert_eq!(query, r#"title:"say \"hi\" \\o/" generic="Mario Kart""#);
+        let reparsed = game::search::parse_user_input(&query).search.filter;
+        assert_eq!(reparsed.whitelist.title, filter.whitelist.title);
+        assert_eq!(reparsed.exact_whitelist.generic, filter.exact_whitelist.generic);
+        assert!(reparsed.whitelist.generic.is_none());
+
+        // Filters the syntax can't express are rejected
+        filter.any_text = Some(vec!["sonic".to_owned()]);
+        let res = game::search::filter_to_query(&filter);
+        assert!(matches!(res, Err(Error::InvalidSearch { .. })));
     }
 
     #[tokio::test]
-    async fn find_game() {
+    async fn search_rejects_invalid_order_combinations() {
         let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(TEST_DATABASE);
-        assert!(create.is_ok());
-        let result = flashpoint.find_game("00deff25-5cd2-40d1-a0e7-151d82ce16c5").await;
-        assert!(result.is_ok());
-        let game_opt = result.unwrap();
-        assert!(game_opt.is_some());
-        let game = game_opt.unwrap();
-        assert_eq!(game.title, "Crab Planet");
-        assert!(game.detailed_platforms.is_some());
-        let platforms = game.detailed_platforms.unwrap();
-        assert_eq!(platforms.len(), 1);
-        assert_eq!(platforms[0].name, "Flash");
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let mut ids = vec![];
+        for title in ["A", "B", "C"] {
+            let partial = PartialGame {
+                title: Some(title.to_owned()),
+                ..Default::default()
+            };
+            ids.push(flashpoint.create_game(&partial).await.unwrap().id);
+        }
+        let offset = GameSearchOffset {
+            value: "1".to_owned(),
+            title: "A".to_owned(),
+            game_id: ids[0].clone(),
+        };
+
+        let mut search = GameSearch::default();
+        search.order.column = game::search::GameSearchSortable::RANDOM;
+        search.offset = Some(offset.clone());
+        assert!(matches!(flashpoint.search_games(&search).await, Err(Error::InvalidSearch { .. })));
+        assert!(matches!(flashpoint.search_games_index(&mut search.clone(), None).await, Err(Error::InvalidSearch { .. })));
+        assert!(matches!(flashpoint.search_games_total(&search).await, Err(Error::InvalidSearch { .. })));
+        let grouped_res = flashpoint.search_games_grouped(&search, GroupableField::Series, 1).await;
+        assert!(matches!(grouped_res, Err(Error::InvalidSearch { .. })));
+
+        let mut search = GameSearch::default();
+        search.order.column = game::search::GameSearchSortable::CUSTOM;
+        let res = flashpoint.search_games(&search).await;
+        assert!(matches!(&res, Err(Error::InvalidSearch { reason }) if reason.contains("custom_id_order")));
+
+        search.custom_id_order = Some(vec![ids[2].clone(), ids[0].clone(), ids[1].clone()]);
+        search.offset = Some(GameSearchOffset { value: "C".to_owned(), ..offset.clone() });
+        assert!(matches!(flashpoint.search_games(&search).await, Err(Error::InvalidSearch { .. })));
+
+        // Valid combinations still page as before
+        search.offset = None;
+        assert!(flashpoint.search_games_index(&mut search.clone(), None).await.is_ok());
+        search.offset = Some(offset.clone());
+        let page: Vec<String> = flashpoint.search_games(&search).await.unwrap().into_iter().map(|g| g.id).collect();
+        assert_eq!(page, vec![ids[0].clone(), ids[1].clone()]);
+
+        let mut search = GameSearch::default();
+        search.order.column = game::search::GameSearchSortable::RANDOM;
+        assert_eq!(flashpoint.search_games(&search).await.unwrap().len(), 3);
+        search.offset = Some(offset);
+        assert_eq!(flashpoint.search_games_random(&search, 2).await.unwrap().len(), 2);
     }
 
     #[tokio::test]
-    async fn game_redirects() {
+    async fn query_timeout_interrupts_long_queries() {
+        let path = std::env::temp_dir().join(format!("fpa-timeout-{}.sqlite", uuid::Uuid::new_v4()));
+        let path = path.to_str().unwrap().to_owned();
+        {
+            let mut flashpoint = FlashpointArchive::new();
+            assert!(flashpoint.load_database(&path).is_ok());
+            let partial = PartialGame {
+                title: Some("Sonic".to_owned()),
+                ..Default::default()
+            };
+            assert!(flashpoint.create_game(&partial).await.is_ok());
+            let conn = flashpoint.pool.as_ref().unwrap().get().unwrap();
+            conn.execute_batch("CREATE TEMP TABLE seed AS SELECT * FROM game").unwrap();
+            for _ in 0..12 {
+                conn.execute_batch(
+                    "INSERT INTO seed SELECT * FROM seed;
+                     UPDATE seed SET id = lower(hex(randomblob(16)));
+                     INSERT INTO game SELECT * FROM seed;",
+                )
+                .unwrap();
+            }
+        }
+
+        let mut search = GameSearch::default();
+        search.filter.whitelist.generic = Some(vec!["sonic".to_owned()]);
+
         let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(":memory:");
-        assert!(create.is_ok());
-        let partial_game = game::PartialGame {
-            title: Some(String::from("Test Game")),
-            tags: Some(vec!["Action"].into()),
-            ..game::PartialGame::default()
+        flashpoint.set_query_timeout(Some(Duration::from_nanos(1)));
+        assert!(flashpoint.load_database(&path).is_ok());
+        assert!(matches!(flashpoint.search_games(&search).await, Err(Error::Timeout)));
+
+        // Disabled again for databases loaded afterwards
+        flashpoint.set_query_timeout(None);
+        assert!(flashpoint.load_database(&path).is_ok());
+        assert_eq!(flashpoint.search_games(&search).await.unwrap().len(), 1000);
+
+        drop(flashpoint);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn query_timeout_applies_per_statement() {
+        let mut flashpoint = FlashpointArchive::new();
+        flashpoint.set_query_timeout(Some(Duration::from_millis(100)));
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let conn = flashpoint.pool.as_ref().unwrap().get().unwrap();
+        let count_up = |conn: &Connection, to: i64| {
+            conn.query_row(
+                "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c WHERE x < ?) SELECT COUNT(*) FROM c",
+                [to],
+                |row| row.get::<_, i64>(0),
+            ).context(error::SqliteSnafu)
         };
-        let result = flashpoint.create_game(&partial_game).await;
-        assert!(result.is_ok());
-        let game = result.unwrap();
 
-        let create_redirect_res = flashpoint.create_game_redirect("test", &game.id).await;
-        assert!(create_redirect_res.is_ok());
+        // The operation as a whole outlasts the timeout, none of its statements do
+        start_query_clock();
+        let mut res = Ok(0);
+        for _ in 0..3 {
+            std::thread::sleep(Duration::from_millis(60));
+            res = count_up(&conn, 10_000);
+            if res.is_err() {
+                break;
+            }
+        }
+        assert_eq!(stop_query_clock(res).unwrap(), 10_000);
 
-        // Find game redirect
-        let found_game_res = flashpoint.find_game("test").await;
-        assert!(found_game_res.is_ok());
-        assert!(found_game_res.unwrap().is_some());
+        // A nested operation leaves the outer clock running
+        start_query_clock();
+        start_query_clock();
+        assert!(stop_query_clock(count_up(&conn, 10)).is_ok());
+        assert!(matches!(stop_query_clock(count_up(&conn, 100_000_000)), Err(Error::Timeout)));
+    }
 
-        // ID search redirect
-        let mut search = GameSearch::default();
-        search.filter.exact_whitelist.id = Some(vec!["test".to_owned()]);
-        let search_res = flashpoint.search_games(&search).await;
-        assert!(search_res.is_ok());
-        assert_eq!(search_res.unwrap().len(), 1);
+    #[tokio::test]
+    async fn create_game_data_batch() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let mut game_ids = vec![];
+        for title in ["A", "B"] {
+            let partial = PartialGame {
+                title: Some(title.to_owned()),
+                ..Default::default()
+            };
+            game_ids.push(flashpoint.create_game(&partial).await.unwrap().id);
+        }
+        let game_data = |game_id: &str, idx: i64| PartialGameData {
+            id: None,
+            game_id: game_id.to_owned(),
+            title: Some(format!("Data {}", idx)),
+            date_added: Some(format!("2023-01-01T01:01:{:02}.000", idx)),
+            sha256: Some(format!("sha{}", idx)),
+            crc32: Some(0),
+            present_on_disk: Some(false),
+            path: None,
+            size: Some(idx),
+            parameters: None,
+            application_path: Some("Test".to_owned()),
+            launch_command: Some("Test".to_owned()),
+        };
 
-        // Find redirects
-        let found_redirs = flashpoint.find_game_redirects().await;
-        assert!(found_redirs.is_ok());
-        assert_eq!(found_redirs.unwrap().len(), 1);
+        let batch: Vec<PartialGameData> = (0..5).map(|idx| game_data(&game_ids[(idx % 2) as usize], idx)).collect();
+        let created = flashpoint.create_game_data_batch(batch).await.unwrap();
+        assert_eq!(created.len(), 5);
+        assert!(created.iter().all(|gd| gd.id > 0));
+        assert_eq!(created[4].sha256, "sha4");
+        assert_eq!(flashpoint.find_game_data(&game_ids[0]).await.unwrap().len(), 3);
+        assert_eq!(flashpoint.find_game_data(&game_ids[1]).await.unwrap().len(), 2);
+
+        // Redirected ids are stored against the game they point to
+        assert!(flashpoint.create_game_redirect("old-id", &game_ids[1]).await.is_ok());
+        let created = flashpoint.create_game_data_batch(vec![game_data("old-id", 7)]).await.unwrap();
+        assert_eq!(created[0].game_id, game_ids[1]);
+        assert_eq!(flashpoint.find_game_data(&game_ids[1]).await.unwrap().len(), 3);
+
+        // A missing parent fails the whole batch
+        let batch = vec![game_data(&game_ids[0], 5), game_data("not-a-real-id", 6)];
+        let res = flashpoint.create_game_data_batch(batch).await;
+        assert!(matches!(res, Err(Error::GameNotFound { id }) if id == "not-a-real-id"));
+        assert_eq!(flashpoint.find_game_data(&game_ids[0]).await.unwrap().len(), 3);
+    }
 
-        let remove_redirect_res = flashpoint.delete_game_redirect("test", &game.id).await;
-        assert!(remove_redirect_res.is_ok());
+    #[tokio::test]
+    async fn library_restricted_suggestion_counts() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        for (library, tags) in [
+            ("arcade", vec!["Action"]),
+            ("arcade", vec!["Action"]),
+            ("arcade", vec!["Action", "Adventure"]),
+            ("theatre", vec!["Action"]),
+            ("theatre", vec!["Adventure"]),
+        ] {
+            let partial = PartialGame {
+                title: Some("Game".to_owned()),
+                library: Some(library.to_owned()),
+                tags: Some(tags.into()),
+                platforms: Some(vec!["Flash"].into()),
+                ..Default::default()
+            };
+            assert!(flashpoint.create_game(&partial).await.is_ok());
+        }
 
-        let found_redirs2 = flashpoint.find_game_redirects().await;
-        assert!(found_redirs2.is_ok());
-        assert_eq!(found_redirs2.unwrap().len(), 0);
+        // Single character prefixes are below the default minimum
+        flashpoint.set_suggestion_limits(1, tag::DEFAULT_SUGGESTION_LIMIT);
+        let counts = |suggs: Vec<TagSuggestion>| suggs.into_iter().map(|s| (s.name, s.games_count)).collect::<Vec<(String, i64)>>();
+        let all = flashpoint.search_tag_suggestions("A", vec![], false, None).await.unwrap();
+        assert_eq!(counts(all), vec![("Action".to_owned(), 4), ("Adventure".to_owned(), 2)]);
+        let theatre = flashpoint.search_tag_suggestions("A", vec![], false, Some("theatre".to_owned())).await.unwrap();
+        assert_eq!(counts(theatre), vec![("Action".to_owned(), 1), ("Adventure".to_owned(), 1)]);
+
+        let platforms = flashpoint.search_platform_suggestions("Fl", None).await.unwrap();
+        assert_eq!(counts(platforms), vec![("Flash".to_owned(), 5)]);
+        let platforms = flashpoint.search_platform_suggestions("Fl", Some("theatre".to_owned())).await.unwrap();
+        assert_eq!(counts(platforms), vec![("Flash".to_owned(), 2)]);
+    }
+
+    #[tokio::test]
+    async fn find_active_game_data() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let partial = PartialGame {
+            title: Some("Test".to_owned()),
+            ..Default::default()
+        };
+        let game = flashpoint.create_game(&partial).await.unwrap();
+        assert!(flashpoint.find_active_game_data(&game.id).await.unwrap().is_none());
+
+        let mut created = vec![];
+        for (idx, sha256) in ["first", "second"].into_iter().enumerate() {
+            let game_data = PartialGameData {
+                id: None,
+                game_id: game.id.clone(),
+                title: Some("Test".to_owned()),
+                date_added: Some(format!("2023-01-01T01:01:{:02}.000", idx)),
+                sha256: Some(sha256.to_owned()),
+                crc32: Some(0),
+                present_on_disk: Some(false),
+                path: None,
+                size: Some(123),
+                parameters: None,
+                application_path: Some("Test".to_owned()),
+                launch_command: Some("Test".to_owned()),
+            };
+            created.push(flashpoint.create_game_data(&game_data).await.unwrap());
+        }
+
+        let mut partial: PartialGame = game.into();
+        partial.active_data_id = Some(created[1].id);
+        let game = flashpoint.save_game(&mut partial).await.unwrap();
+
+        let active = flashpoint.find_active_game_data(&game.id).await.unwrap().unwrap();
+        assert_eq!(active.id, created[1].id);
+        assert_eq!(active.sha256, "second");
+
+        assert!(flashpoint.soft_delete_game(&game.id).await.is_ok());
+        assert!(flashpoint.find_active_game_data(&game.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn checkpoint_truncates_wal() {
+        let path = std::env::temp_dir().join(format!("fpa-checkpoint-{}.sqlite", uuid::Uuid::new_v4()));
+        let wal_path = path.with_extension("sqlite-wal");
+        let mut flashpoint = FlashpointArchive::new();
+        flashpoint.set_wal_autocheckpoint(0);
+        assert!(flashpoint.load_database(path.to_str().unwrap()).is_ok());
+
+        // Every pooled connection is configured, not only the first
+        {
+            let pool = flashpoint.pool.as_ref().unwrap();
+            let first = pool.get().unwrap();
+            let second = pool.get().unwrap();
+            for conn in [&first, &second] {
+                let pages: i64 = conn.pragma_query_value(None, "wal_autocheckpoint", |row| row.get(0)).unwrap();
+                assert_eq!(pages, 0);
+            }
+        }
+
+        for idx in 0..50 {
+            let partial = PartialGame {
+                title: Some(format!("Game {}", idx)),
+                ..Default::default()
+            };
+            assert!(flashpoint.create_game(&partial).await.is_ok());
+        }
+        assert!(std::fs::metadata(&wal_path).unwrap().len() > 0);
+
+        let passive = flashpoint.checkpoint(checkpoint::CheckpointMode::PASSIVE).await.unwrap();
+        assert!(!passive.busy);
+        assert!(passive.log_frames > 0);
+        assert_eq!(passive.checkpointed_frames, passive.log_frames);
+
+        let truncate = flashpoint.checkpoint(checkpoint::CheckpointMode::TRUNCATE).await.unwrap();
+        assert!(!truncate.busy);
+        assert_eq!(truncate.log_frames, 0);
+        assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), 0);
+
+        drop(flashpoint);
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&wal_path);
+        let _ = std::fs::remove_file(path.with_extension("sqlite-shm"));
     }
 
     #[tokio::test]
-    async fn tag_categories() {
+    async fn search_exact_case_sensitivity() {
         let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(":memory:");
-        assert!(create.is_ok());
-        let partial_tc = tag_category::PartialTagCategory {
-            id: -1,
-            name: "test".to_owned(),
-            color: "#FF00FF".to_owned(),
-            description: Some("test".to_owned()),
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let partial = PartialGame {
+            title: Some("Sonic".to_owned()),
+            tags: Some(vec!["Action"].into()),
+            ..Default::default()
         };
-        assert!(flashpoint.create_tag_category(&partial_tc).await.is_ok());
-        let saved_cat_result = flashpoint.find_tag_category("test").await;
-        assert!(saved_cat_result.is_ok());
-        let saved_cat_opt = saved_cat_result.unwrap();
-        assert!(saved_cat_opt.is_some());
-        let saved_cat = saved_cat_opt.unwrap();
-        assert_eq!(saved_cat.name, "test");
-        assert_eq!(saved_cat.color, "#FF00FF");
-        assert!(saved_cat.description.is_some());
-        assert_eq!(saved_cat.description.unwrap(), "test");
+        assert!(flashpoint.create_game(&partial).await.is_ok());
 
-        let all_cats_result = flashpoint.find_all_tag_categories().await;
-        assert!(all_cats_result.is_ok());
-        let all_cats = all_cats_result.unwrap();
-        // Default category always exists
-        assert_eq!(all_cats.len(), 2);
+        let mut search = GameSearch::default();
+        search.filter.exact_whitelist.title = Some(vec!["sonic".to_owned()]);
+        assert_eq!(flashpoint.search_games(&search).await.unwrap().len(), 0);
+        search.filter.case_sensitive = Some(false);
+        assert_eq!(flashpoint.search_games(&search).await.unwrap().len(), 1);
+        search.filter.case_sensitive = Some(true);
+        assert_eq!(flashpoint.search_games(&search).await.unwrap().len(), 0);
+        search.filter.exact_whitelist.title = Some(vec!["Sonic".to_owned()]);
+        assert_eq!(flashpoint.search_games(&search).await.unwrap().len(), 1);
+
+        // Tag names ignore case unless asked not to
+        let mut search = GameSearch::default();
+        search.filter.exact_whitelist.tags = Some(vec!["action".to_owned()]);
+        assert_eq!(flashpoint.search_games(&search).await.unwrap().len(), 1);
+        search.filter.case_sensitive = Some(true);
+        assert_eq!(flashpoint.search_games(&search).await.unwrap().len(), 0);
     }
 
     #[tokio::test]
-    async fn create_and_save_game() {
+    async fn search_add_app_flags() {
         let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(":memory:");
-        assert!(create.is_ok());
-        let partial_game = game::PartialGame {
-            title: Some(String::from("Test Game")),
-            tags: Some(vec!["Action"].into()),
-            ..game::PartialGame::default()
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let mut ids = HashMap::new();
+        for title in ["Message", "Extras", "None"] {
+            let partial = PartialGame {
+                title: Some(title.to_owned()),
+                ..Default::default()
+            };
+            ids.insert(title, flashpoint.create_game(&partial).await.unwrap().id);
+        }
+        let mut message = full_game_add_app(&uuid::Uuid::new_v4().to_string());
+        message.name = "Message".to_owned();
+        message.application_path = ":message:".to_owned();
+        message.auto_run_before = true;
+        message.wait_for_exit = true;
+        message.parent_game_id = ids["Message"].clone();
+        assert!(flashpoint.create_add_app(&mut message).await.is_ok());
+        let mut extras = full_game_add_app(&uuid::Uuid::new_v4().to_string());
+        extras.parent_game_id = ids["Extras"].clone();
+        assert!(flashpoint.create_add_app(&mut extras).await.is_ok());
+
+        let titles = |games: Vec<Game>| {
+            let mut titles: Vec<String> = games.into_iter().map(|g| g.title).collect();
+            titles.sort();
+            titles
         };
-        let result = flashpoint.create_game(&partial_game).await;
-        assert!(result.is_ok());
-        let mut game = result.unwrap();
-        let found_tag_res = flashpoint.find_tag("Action").await;
-        assert!(found_tag_res.is_ok());
-        let found_tag_opt = found_tag_res.unwrap();
-        assert!(found_tag_opt.is_some());
-        let found_game_res = flashpoint.find_game(&game.id).await;
-        assert!(found_game_res.is_ok());
-        let found_game_opt = found_game_res.unwrap();
-        assert!(found_game_opt.is_some());
-        let found_game = found_game_opt.unwrap();
-        assert!(found_game.detailed_tags.is_some());
-        let found_tags = found_game.detailed_tags.unwrap();
-        assert_eq!(found_tags.len(), 1);
-        assert_eq!(game.title, "Test Game");
-        game.developer = String::from("Newgrounds");
-        game.tags = vec!["Action", "Adventure"].into();
-        game.primary_platform = String::from("Flash");
-        let save_result = flashpoint.save_game(&mut game.into()).await;
-        assert!(save_result.is_ok());
-        let saved_game = save_result.unwrap();
-        assert_eq!(saved_game.developer, "Newgrounds");
-        assert_eq!(saved_game.tags.len(), 2);
-        assert_eq!(saved_game.platforms.len(), 1);
-        assert_eq!(saved_game.platforms[0], "Flash");
-        assert_eq!(saved_game.primary_platform, "Flash");
-        assert!(saved_game.detailed_platforms.is_some());
-        let detailed_platforms = saved_game.detailed_platforms.unwrap();
-        assert_eq!(detailed_platforms.len(), 1);
-        assert!(saved_game.detailed_tags.is_some());
-        let detailed_tags = saved_game.detailed_tags.unwrap();
-        assert_eq!(detailed_tags.len(), 2);
-        assert_eq!(detailed_tags[0].name, "Action");
+        for (input, expected) in [
+            ("hasMessage:true", vec!["Message"]),
+            ("hasExtras:true", vec!["Extras"]),
+            ("-hasMessage:true", vec!["Extras", "None"]),
+            ("hasExtras:false", vec!["Message", "None"]),
+        ] {
+            let search = game::search::parse_user_input(input).search;
+            assert_eq!(titles(flashpoint.search_games(&search).await.unwrap()), expected, "{}", input);
+        }
+
+        let mut search = GameSearch::default();
+        search.filter.bool_comp.add_app_wait_for_exit = Some(true);
+        assert_eq!(titles(flashpoint.search_games(&search).await.unwrap()), vec!["Message"]);
     }
 
     #[tokio::test]
-    async fn create_and_save_game_data() {
+    async fn save_game_date_modified_policy() {
         let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(":memory:");
-        assert!(create.is_ok());
-        let partial_game = game::PartialGame {
-            title: Some(String::from("Test Game")),
-            tags: Some(vec!["Action"].into()),
-            ..game::PartialGame::default()
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let created = chrono::DateTime::parse_from_rfc3339("2024-03-01T12:00:00.000Z").unwrap().with_timezone(&chrono::Utc);
+        flashpoint.set_clock(Arc::new(clock::FixedClock(created)));
+        let partial = PartialGame {
+            title: Some("test".to_owned()),
+            ..Default::default()
         };
-        let game_create_res = flashpoint.create_game(&partial_game).await;
-        assert!(game_create_res.is_ok());
-        let game = game_create_res.unwrap();
-        let game_data = PartialGameData { 
-            id: None,
-            game_id: game.id,
-            title: Some("Test".to_owned()),
-            date_added: Some("2023-01-01T01:01:01.000".to_owned()),
-            sha256: Some("123".to_owned()),
-            crc32: Some(0),
-            present_on_disk: Some(false),
-            path: None,
-            size: Some(123),
-            parameters: None,
-            application_path: Some("Test".to_owned()),
-            launch_command: Some("Test".to_owned())
+        let game = flashpoint.create_game(&partial).await.unwrap();
+        let later = chrono::DateTime::parse_from_rfc3339("2024-03-02T08:00:00.000Z").unwrap().with_timezone(&chrono::Utc);
+        flashpoint.set_clock(Arc::new(clock::FixedClock(later)));
+        let stored = |flashpoint: &FlashpointArchive, id: &str| {
+            let conn = flashpoint.pool.as_ref().unwrap().get().unwrap();
+            conn.query_row("SELECT dateModified FROM game WHERE id = ?", [id], |row| row.get::<_, String>(0)).unwrap()
         };
 
-        let game_data_res = flashpoint.create_game_data(&game_data).await;
-        assert!(game_data_res.is_ok());
-        let mut gd = game_data_res.unwrap();
-        gd.path = Some("Test".to_owned());
-        let save_res = flashpoint.save_game_data(&gd.into()).await;
-        assert!(save_res.is_ok());
-        let new_gd = save_res.unwrap();
-        assert_eq!(new_gd.path.unwrap(), "Test");
+        // Preserve ignores the partial's value too
+        let mut edit = PartialGame {
+            id: game.id.clone(),
+            title: Some("repaired".to_owned()),
+            date_modified: Some("2030-01-01T00:00:00.000Z".to_owned()),
+            ..Default::default()
+        };
+        let saved = flashpoint.save_game_with_policy(&mut edit, DateModifiedPolicy::Preserve).await.unwrap();
+        assert_eq!(saved.title, "repaired");
+        assert_eq!(saved.date_modified, "2024-03-01T12:00:00.000Z");
+        assert_eq!(stored(&flashpoint, &game.id), "2024-03-01T12:00:00.000Z");
+
+        let mut edit = PartialGame {
+            id: game.id.clone(),
+            ..Default::default()
+        };
+        let forced = DateModifiedPolicy::Force("2020-05-05T05:05:05.000Z".to_owned());
+        assert!(flashpoint.save_games_with_policy(vec![&mut edit], forced).await.is_ok());
+        assert_eq!(stored(&flashpoint, &game.id), "2020-05-05T05:05:05.000Z");
+
+        let mut edit = PartialGame {
+            id: game.id.clone(),
+            ..Default::default()
+        };
+        assert!(flashpoint.save_game(&mut edit).await.is_ok());
+        assert_eq!(stored(&flashpoint, &game.id), "2024-03-02T08:00:00.000Z");
     }
 
     #[tokio::test]
-    async fn parse_user_search_input() {
-        let input = r#"sonic title:"dog cat" -title:"cat dog" tag:Action -mario installed:true"#;
-        let search = game::search::parse_user_input(input).search;
-        assert!(search.filter.whitelist.generic.is_some());
-        assert_eq!(search.filter.whitelist.generic.unwrap()[0], "sonic");
-        assert!(search.filter.whitelist.title.is_some());
-        assert_eq!(search.filter.whitelist.title.unwrap()[0], "dog cat");
-        assert!(search.filter.blacklist.title.is_some());
-        assert_eq!(search.filter.blacklist.title.unwrap()[0], "cat dog");
-        assert!(search.filter.whitelist.tags.is_some());
-        assert_eq!(search.filter.whitelist.tags.unwrap()[0], "Action");
-        assert!(search.filter.blacklist.generic.is_some());
-        assert_eq!(search.filter.blacklist.generic.unwrap()[0], "mario");
-        assert!(search.filter.bool_comp.installed.is_some());
-        assert_eq!(search.filter.bool_comp.installed.unwrap(), true);
+    async fn search_custom_order_appends_unlisted_games() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let mut ids = HashMap::new();
+        for title in ["D", "B", "C", "A"] {
+            let partial = PartialGame {
+                title: Some(title.to_owned()),
+                ..Default::default()
+            };
+            ids.insert(title, flashpoint.create_game(&partial).await.unwrap().id);
+        }
+
+        let mut search = GameSearch::default();
+        search.order.column = game::search::GameSearchSortable::CUSTOM;
+        search.order.direction = game::search::GameSearchDirection::DESC;
+        search.custom_id_order = Some(vec![ids["C"].clone(), ids["D"].clone()]);
+        assert!(flashpoint.search_games_index(&mut search.clone(), None).await.is_ok());
+        let titles: Vec<String> = flashpoint.search_games(&search).await.unwrap().into_iter().map(|g| g.title).collect();
+        assert_eq!(titles, vec!["C", "D", "A", "B"]);
+
+        // Paging from a listed game and from within the unlisted tail
+        search.offset = Some(GameSearchOffset {
+            value: "1".to_owned(),
+            title: "C".to_owned(),
+            game_id: ids["C"].clone(),
+        });
+        let titles: Vec<String> = flashpoint.search_games(&search).await.unwrap().into_iter().map(|g| g.title).collect();
+        assert_eq!(titles, vec!["D", "A", "B"]);
+        search.offset = Some(GameSearchOffset {
+            value: i64::MAX.to_string(),
+            title: "A".to_owned(),
+            game_id: ids["A"].clone(),
+        });
+        let titles: Vec<String> = flashpoint.search_games(&search).await.unwrap().into_iter().map(|g| g.title).collect();
+        assert_eq!(titles, vec!["B"]);
     }
 
     #[tokio::test]
-    async fn parse_user_search_input_whitespace() {
-        let input = r#"series:"紅白Flash合戦  / Red & White Flash Battle 2013""#;
-        let search = game::search::parse_user_input(input).search;
-        assert!(search.filter.whitelist.series.is_some());
-        assert_eq!(search.filter.whitelist.series.unwrap()[0], "紅白Flash合戦  / Red & White Flash Battle 2013");
+    async fn performance_profile_pragmas() {
+        let path = std::env::temp_dir().join(format!("fpa-profile-{}.sqlite", uuid::Uuid::new_v4()));
+        let path = path.to_str().unwrap().to_owned();
+        let pragma = |conn: &Connection, name: &str| -> i64 {
+            conn.pragma_query_value(None, name, |row| row.get(0)).unwrap()
+        };
+
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(&path).is_ok());
+        {
+            let conn = flashpoint.pool.as_ref().unwrap().get().unwrap();
+            assert_eq!(pragma(&conn, "foreign_keys"), 0);
+            // FULL, SQLite's default
+            assert_eq!(pragma(&conn, "synchronous"), 2);
+            assert_eq!(pragma(&conn, "temp_store"), 0);
+        }
+
+        flashpoint.set_performance_profile(PerformanceProfile::HighPerformance);
+        assert!(flashpoint.load_database(&path).is_ok());
+        {
+            let pool = flashpoint.pool.as_ref().unwrap();
+            let first = pool.get().unwrap();
+            let second = pool.get().unwrap();
+            for conn in [&first, &second] {
+                assert_eq!(pragma(conn, "foreign_keys"), 0);
+                assert_eq!(pragma(conn, "synchronous"), 1);
+                assert_eq!(pragma(conn, "cache_size"), -64000);
+                assert_eq!(pragma(conn, "mmap_size"), 268435456);
+                assert_eq!(pragma(conn, "temp_store"), 2);
+            }
+        }
+
+        flashpoint.set_performance_profile(PerformanceProfile::LowMemory);
+        assert!(flashpoint.load_database(&path).is_ok());
+        {
+            let conn = flashpoint.pool.as_ref().unwrap().get().unwrap();
+            assert_eq!(pragma(&conn, "synchronous"), 1);
+            assert_eq!(pragma(&conn, "cache_size"), -512);
+            assert_eq!(pragma(&conn, "mmap_size"), 0);
+            assert_eq!(pragma(&conn, "temp_store"), 1);
+        }
+        // Reads still work with foreign keys off on every checkout
+        assert!(flashpoint.search_games(&GameSearch::default()).await.is_ok());
+
+        drop(flashpoint);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path, suffix));
+        }
     }
 
     #[tokio::test]
-    async fn parse_user_quick_search_input() {
-        let input = r#"#Action -!Flash @"armor games" !"#;
-        let search = game::search::parse_user_input(input).search;
-        assert!(search.filter.whitelist.tags.is_some());
-        assert_eq!(search.filter.whitelist.tags.unwrap()[0], "Action");
-        assert!(search.filter.blacklist.platforms.is_some());
-        assert_eq!(search.filter.blacklist.platforms.unwrap()[0], "Flash");
-        assert!(search.filter.whitelist.developer.is_some());
-        assert_eq!(search.filter.whitelist.developer.unwrap()[0], "armor games");
-        assert!(search.filter.whitelist.generic.is_some());
-        assert_eq!(search.filter.whitelist.generic.unwrap()[0], "!");
+    async fn search_active_game_config_owner() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        for (title, owner) in [("A", Some("alice")), ("B", Some("bob")), ("C", None)] {
+            let partial = PartialGame {
+                title: Some(title.to_owned()),
+                active_game_config_id: owner.map(|_| 1),
+                active_game_config_owner: owner.map(|o| o.to_owned()),
+                ..Default::default()
+            };
+            assert!(flashpoint.create_game(&partial).await.is_ok());
+        }
+        let titles = |games: Vec<Game>| -> Vec<String> {
+            let mut titles: Vec<String> = games.into_iter().map(|g| g.title).collect();
+            titles.sort();
+            titles
+        };
+
+        let mut search = GameSearch::default();
+        search.filter.exact_whitelist.active_game_config_owner = Some(vec!["alice".to_owned()]);
+        assert_eq!(titles(flashpoint.search_games(&search).await.unwrap()), vec!["A"]);
+
+        // Games without an active config are kept when excluding another owner
+        let mut search = GameSearch::default();
+        search.filter.exact_blacklist.active_game_config_owner = Some(vec!["bob".to_owned()]);
+        assert_eq!(titles(flashpoint.search_games(&search).await.unwrap()), vec!["A", "C"]);
+        search.filter.match_any = true;
+        assert_eq!(titles(flashpoint.search_games(&search).await.unwrap()), vec!["A", "C"]);
+
+        let mut search = GameSearch::default();
+        search.filter.blacklist.active_game_config_owner = Some(vec!["ali".to_owned()]);
+        assert_eq!(titles(flashpoint.search_games(&search).await.unwrap()), vec!["B", "C"]);
     }
 
     #[tokio::test]
-    async fn parse_user_exact_search_input() {
-        let input = r#"!Flash -publisher=Newgrounds =sonic"#;
-        let search = game::search::parse_user_input(input).search;
-        assert!(search.filter.whitelist.platforms.is_some());
-        assert_eq!(search.filter.whitelist.platforms.unwrap()[0], "Flash");
-        assert!(search.filter.exact_blacklist.publisher.is_some());
-        assert_eq!(search.filter.exact_blacklist.publisher.unwrap()[0], "Newgrounds");
-        assert!(search.filter.whitelist.generic.is_some());
-        assert!(search.filter.exact_whitelist.generic.is_none());
-        assert_eq!(search.filter.whitelist.generic.unwrap()[0], "=sonic");
+    async fn clear_active_config_by_owner() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let mut ids = vec![];
+        for owner in ["alice", "bob", "alice"] {
+            let partial = PartialGame {
+                title: Some("Game".to_owned()),
+                active_game_config_id: Some(5),
+                active_game_config_owner: Some(owner.to_owned()),
+                ..Default::default()
+            };
+            ids.push(flashpoint.create_game(&partial).await.unwrap().id);
+        }
+
+        assert_eq!(flashpoint.clear_active_config_by_owner("alice").await.unwrap(), 2);
+        assert_eq!(flashpoint.clear_active_config_by_owner("alice").await.unwrap(), 0);
+        for (idx, id) in ids.iter().enumerate() {
+            let game = flashpoint.find_game(id).await.unwrap().unwrap();
+            if idx == 1 {
+                assert_eq!(game.active_game_config_id, Some(5));
+                assert_eq!(game.active_game_config_owner, Some("bob".to_owned()));
+            } else {
+                assert_eq!(game.active_game_config_id, None);
+                assert_eq!(game.active_game_config_owner, None);
+            }
+        }
     }
 
     #[tokio::test]
-    async fn find_all_game_libraries() {
-        let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(TEST_DATABASE);
-        assert!(create.is_ok());
-        let libraries_res = flashpoint.find_all_game_libraries().await;
-        assert!(libraries_res.is_ok());
-        let libraries = libraries_res.unwrap();
-        assert_eq!(libraries.len(), 2);
+    async fn build_search_query_golden() {
+        let ids: Vec<String> = (0..10000).map(|idx| format!("id-{}", idx)).collect();
+        let mut search = GameSearch::default();
+        search.filter.exact_whitelist.id = Some(ids.clone());
+        search.filter.whitelist.developer = Some(vec!["sega".to_owned()]);
+        search.filter.exact_blacklist.library = Some(vec!["flash".to_owned()]);
+
+        let (query, params) = game::search::build_search_query(&search, "SELECT game.id FROM game");
+        assert_eq!(
+            query,
+            "SELECT game.id FROM game WHERE (game.deletedAt IS NULL AND ((game.library != ?) AND game.developer LIKE ? AND \
+(game.id IN rarray(?) OR game.id IN (SELECT id FROM game_redirect WHERE sourceId IN rarray(?))))) ORDER BY game.title ASC LIMIT 1000"
+        );
+        assert_eq!(params.len(), 4);
+        assert_eq!(params[0].to_string(), "flash");
+        assert_eq!(params[1].to_string(), "%sega%");
+        assert_eq!(params[2].to_string(), ids.join("', '"));
+
+        // Both id lists bind the same converted values
+        match (&params[2], &params[3]) {
+            (game::search::SearchParam::StringVec(a), game::search::SearchParam::StringVec(b)) => assert!(std::rc::Rc::ptr_eq(a, b)),
+            _ => panic!("id whitelist should bind string arrays"),
+        }
+
+        // Parsed filters keep every field, library included
+        let parsed = game::search::parse_user_input("library:arcade developer:sega").search;
+        assert_eq!(parsed.filter.whitelist.library, Some(vec!["arcade".to_owned()]));
+        assert_eq!(parsed.filter.whitelist.developer, Some(vec!["sega".to_owned()]));
+        assert_eq!(parsed.filter.whitelist.title, None);
     }
 
     #[tokio::test]
-    async fn create_tag() {
+    async fn normalize_languages() {
+        let cases: [(&str, &[&str]); 12] = [
+            ("en", &["en"]),
+            ("English", &["en"]),
+            ("en; ja", &["en", "ja"]),
+            ("Japanese, English", &["ja", "en"]),
+            ("pt-BR", &["pt"]),
+            ("English (US)", &["en"]),
+            ("English and French", &["en", "fr"]),
+            ("日本語/中文", &["ja", "zh"]),
+            ("EN; en; English", &["en"]),
+            ("Deutsch & Español", &["de", "es"]),
+            ("Klingon", &["klingon"]),
+            ("", &[]),
+        ];
+        for (raw, codes) in cases {
+            assert_eq!(util::normalize_languages(raw), codes, "{:?}", raw);
+        }
+
         let mut flashpoint = FlashpointArchive::new();
         assert!(flashpoint.load_database(":memory:").is_ok());
-        let new_tag_res = flashpoint.create_tag("test", None, None).await;
-        assert!(new_tag_res.is_ok());
-        let new_tag = new_tag_res.unwrap();
-        assert!(new_tag.category.is_some());
-        assert_eq!(new_tag.category.unwrap(), "default");
-        assert_eq!(new_tag.name, "test");
-        assert_eq!(new_tag.aliases.len(), 1);
-        assert_eq!(new_tag.aliases[0], "test");
+        for (title, language) in [("A", "English"), ("B", "en"), ("C", "Japanese, English"), ("D", "fr")] {
+            let partial = PartialGame {
+                title: Some(title.to_owned()),
+                language: Some(language.to_owned()),
+                ..Default::default()
+            };
+            assert!(flashpoint.create_game(&partial).await.is_ok());
+        }
+
+        assert_eq!(flashpoint.normalize_all_languages().await.unwrap(), 2);
+        assert_eq!(flashpoint.normalize_all_languages().await.unwrap(), 0);
+        {
+            let conn = flashpoint.pool.as_ref().unwrap().get().unwrap();
+            let (language, raw): (String, Option<String>) = conn
+                .query_row("SELECT language, languageRaw FROM game WHERE title = 'C'", (), |row| Ok((row.get(0)?, row.get(1)?)))
+                .unwrap();
+            assert_eq!(language, "ja; en");
+            assert_eq!(raw, Some("Japanese, English".to_owned()));
+        }
+
+        let titles = |games: Vec<Game>| -> Vec<String> { games.into_iter().map(|g| g.title).collect() };
+        for value in ["English", "en"] {
+            let mut search = GameSearch::default();
+            search.normalize_languages = true;
+            search.filter.whitelist.language = Some(vec![value.to_owned()]);
+            assert_eq!(titles(flashpoint.search_games(&search).await.unwrap()), vec!["A", "B", "C"]);
+        }
+
+        let mut search = GameSearch::default();
+        search.normalize_languages = true;
+        search.filter.exact_blacklist.language = Some(vec!["English".to_owned()]);
+        assert_eq!(titles(flashpoint.search_games(&search).await.unwrap()), vec!["D"]);
     }
 
     #[tokio::test]
-    async fn delete_tag() {
+    async fn content_size_by_platform() {
         let mut flashpoint = FlashpointArchive::new();
         assert!(flashpoint.load_database(":memory:").is_ok());
-        let partial = PartialGame {
-            title: Some("test".to_owned()),
-            tags: Some(vec!["Action"].into()),
-            ..Default::default()
-        };
-        let new_game_res = flashpoint.create_game(&partial).await;
-        assert!(new_game_res.is_ok());
-        let saved_game = new_game_res.unwrap();
-        assert_eq!(saved_game.tags.len(), 1);
-        let delete_res = flashpoint.delete_tag("Action").await;
-        assert!(delete_res.is_ok());
-        let modded_game_res = flashpoint.find_game(&saved_game.id).await;
-        assert!(modded_game_res.is_ok());
-        let modded_game_opt = modded_game_res.unwrap();
-        assert!(modded_game_opt.is_some());
-        let modded_game = modded_game_opt.unwrap();
-        assert_eq!(modded_game.tags.len(), 0);
+        // A game on both platforms counts towards each, deleted games count towards neither
+        let games = [
+            (vec!["Flash"], vec![(100, true), (50, false)]),
+            (vec!["Shockwave"], vec![(1000, true)]),
+            (vec!["Flash", "Shockwave"], vec![(10, false)]),
+            (vec!["Flash"], vec![(5000, true)]),
+        ];
+        let mut game_ids = vec![];
+        for (platforms, sizes) in games {
+            let game = flashpoint.create_game(&PartialGame {
+                title: Some(platforms.join(" and ")),
+                platforms: Some(platforms.into()),
+                ..Default::default()
+            }).await.unwrap();
+            for (idx, (size, present)) in sizes.into_iter().enumerate() {
+                let mut game_data = full_game_data(&format!("2023-02-01T00:00:{:02}.000", idx), present);
+                game_data.game_id = game.id.clone();
+                game_data.size = Some(size);
+                flashpoint.create_game_data(&game_data).await.unwrap();
+            }
+            game_ids.push(game.id);
+        }
+        flashpoint.soft_delete_game(&game_ids[3]).await.unwrap();
+
+        let sizes = flashpoint.content_size_by_platform(false).await.unwrap();
+        assert_eq!(sizes, vec![("Shockwave".to_owned(), 1010), ("Flash".to_owned(), 160)]);
+        let sizes = flashpoint.content_size_by_platform(true).await.unwrap();
+        assert_eq!(sizes, vec![("Shockwave".to_owned(), 1000), ("Flash".to_owned(), 100)]);
+    }
+
+    #[tokio::test]
+    async fn search_tag_suggestions_limits() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        for name in ["Action", "Acting", "Adventure", "Arcade"] {
+            assert!(flashpoint.create_tag(name, None, None).await.is_ok());
+        }
+
+        // Prefixes under the minimum length skip the scan
+        assert!(flashpoint.search_tag_suggestions("A", vec![], false, None).await.unwrap().is_empty());
+        assert_eq!(flashpoint.search_tag_suggestions("Ac", vec![], false, None).await.unwrap().len(), 2);
+
+        flashpoint.set_suggestion_limits(1, 3);
+        assert_eq!(flashpoint.search_tag_suggestions("A", vec![], false, None).await.unwrap().len(), 3);
+        assert_eq!(flashpoint.search_tag_suggestions("Ac", vec![], false, None).await.unwrap().len(), 2);
     }
 
     #[tokio::test]
-    async fn merge_tags() {
+    async fn query_log_caps_parameters() {
+        let ids: Vec<String> = (0..10000).map(|_| uuid::Uuid::new_v4().to_string()).collect();
+        let mut search = GameSearch::default();
+        search.filter.exact_whitelist.id = Some(ids.clone());
+        search.filter.whitelist.notes = Some(vec!["pasted ".repeat(1000)]);
+        let (query, params) = game::search::build_search_query(&search, "SELECT game.id FROM game");
+
+        // Room for indentation, the id list alone would be over 380k characters
+        let cap = query.len() * 2 + params.len() * (DEFAULT_DEBUG_PARAM_MAX_LENGTH + 64);
+        let line = game::search::format_query(&query, &params, DEFAULT_DEBUG_PARAM_MAX_LENGTH);
+        assert!(line.len() < cap, "{} >= {}", line.len(), cap);
+        assert!(line.contains("[10000 values]"));
+        assert!(line.contains("[7002 chars]"));
+
+        // Raw mode logs the placeholders and a compact summary
+        set_debug_raw_queries(true);
+        let line = game::search::query_log(&query, &params);
+        set_debug_raw_queries(false);
+        assert!(line.starts_with(&query));
+        assert!(line.len() < cap, "{} >= {}", line.len(), cap);
+        assert!(line.contains("[10000 values]"));
+
+        // Short parameters are logged whole
+        let short = game::search::SearchParam::String("sonic".to_owned());
+        assert_eq!(game::search::summarize_param(&short, DEFAULT_DEBUG_PARAM_MAX_LENGTH), "'sonic'");
+    }
+
+    #[tokio::test]
+    async fn tag_and_platform_name_available() {
         let mut flashpoint = FlashpointArchive::new();
         assert!(flashpoint.load_database(":memory:").is_ok());
-        let partial = PartialGame {
-            title: Some("test".to_owned()),
+        let tag = flashpoint.create_tag("Action", None, None).await.unwrap();
+        let mut partial: PartialTag = tag.into();
+        partial.aliases = Some(vec!["Action".to_owned(), "Act".to_owned()]);
+        assert!(flashpoint.save_tag(&mut partial).await.is_ok());
+        assert!(flashpoint.create_platform("Flash", None).await.is_ok());
+
+        assert!(!flashpoint.tag_name_available("Action").await.unwrap());
+        assert!(!flashpoint.tag_name_available("act").await.unwrap());
+        assert!(flashpoint.tag_name_available("Adventure").await.unwrap());
+        // Tags and platforms have separate names
+        assert!(flashpoint.tag_name_available("Flash").await.unwrap());
+
+        assert!(!flashpoint.platform_name_available("flash").await.unwrap());
+        assert!(flashpoint.platform_name_available("HTML5").await.unwrap());
+        assert!(flashpoint.platform_name_available("Action").await.unwrap());
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn patch_game() {
+        let mut flashpoint = FlashpointArchive::new();
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let game = flashpoint.create_game(&PartialGame {
+            title: Some("Sonic".to_owned()),
+            series: Some("Sonic the Hedgehog".to_owned()),
+            developer: Some("Sega".to_owned()),
             tags: Some(vec!["Action"].into()),
             ..Default::default()
+        }).await.unwrap();
+
+        // Clearing a field, adding a single tag
+        let patch = serde_json::json!([
+            { "op": "remove", "path": "/series" },
+            { "op": "add", "path": "/tags/-", "value": "Platformer" },
+        ]);
+        let patched = flashpoint.patch_game(&game.id, patch).await.unwrap();
+        assert_eq!(patched.series, "");
+        assert_eq!(patched.developer, "Sega");
+        assert_eq!(patched.tags.to_vec(), vec!["Action", "Platformer"]);
+        let saved = flashpoint.find_game(&game.id).await.unwrap().unwrap();
+        assert_eq!(saved.series, "");
+        assert_eq!(saved.tags.to_vec(), vec!["Action", "Platformer"]);
+
+        // Read only fields are rejected by path, and nothing is saved
+        let patch = serde_json::json!([
+            { "op": "replace", "path": "/title", "value": "Sonic 2" },
+            { "op": "replace", "path": "/date_added", "value": "2000-01-01T00:00:00.000Z" },
+        ]);
+        let res = flashpoint.patch_game(&game.id, patch).await;
+        assert!(matches!(&res, Err(Error::InvalidPatch { path, .. }) if path == "/date_added"));
+        assert_eq!(flashpoint.find_game(&game.id).await.unwrap().unwrap().title, "Sonic");
+
+        let patch = serde_json::json!([{ "op": "remove", "path": "/tags/5" }]);
+        let res = flashpoint.patch_game(&game.id, patch).await;
+        assert!(matches!(&res, Err(Error::InvalidPatch { path, .. }) if path == "/tags/5"));
+
+        // Required fields can be replaced but not removed
+        for path in ["/library", "/title", "/primary_platform"] {
+            let patch = serde_json::json!([{ "op": "remove", "path": path }]);
+            let res = flashpoint.patch_game(&game.id, patch).await;
+            assert!(matches!(&res, Err(Error::InvalidPatch { path: p, .. }) if p == path));
+        }
+        let saved = flashpoint.find_game(&game.id).await.unwrap().unwrap();
+        assert_eq!(saved.library, game.library);
+        assert_eq!(saved.title, "Sonic");
+
+        let patch = serde_json::json!([{ "op": "remove", "path": "/series" }]);
+        assert!(matches!(flashpoint.patch_game("missing", patch).await, Err(Error::GameNotFound { .. })));
+    }
+
+
+    #[tokio::test]
+    async fn reorder_tag_categories() {
+        let mut flashpoint = FlashpointArchive::new();
+        let create = flashpoint.load_database(":memory:");
+        assert!(create.is_ok());
+        let mut ids = vec![];
+        for name in ["genre", "theme", "warnings"] {
+            let partial_tc = tag_category::PartialTagCategory {
+                id: -1,
+                name: name.to_owned(),
+                color: "#FFFFFF".to_owned(),
+                description: None,
+                ordering: None,
+            };
+            ids.push(flashpoint.create_tag_category(&partial_tc).await.unwrap().id);
+        }
+        let default_id = flashpoint.find_tag_category("default").await.unwrap().unwrap().id;
+
+        // Warnings last, default between the others
+        flashpoint.reorder_tag_categories(vec![ids[1], ids[0], default_id, ids[2]]).await.unwrap();
+        let names: Vec<String> = flashpoint.find_all_tag_categories().await.unwrap().into_iter().map(|tc| tc.name).collect();
+        assert_eq!(names, vec!["theme", "genre", "default", "warnings"]);
+        let warnings = flashpoint.find_tag_category("warnings").await.unwrap().unwrap();
+        assert_eq!(warnings.ordering, 3);
+
+        // Saving without an ordering keeps the current one
+        let partial_tc = tag_category::PartialTagCategory {
+            id: warnings.id,
+            name: "warnings".to_owned(),
+            color: "#FF0000".to_owned(),
+            description: None,
+            ordering: None,
         };
-        let new_game_res = flashpoint.create_game(&partial).await;
-        assert!(new_game_res.is_ok());
-        assert!(flashpoint.create_tag("Adventure", None, None).await.is_ok());
-        let saved_game = new_game_res.unwrap();
-        let merged_tag_res = flashpoint.merge_tags("Action", "Adventure").await;
-        assert!(merged_tag_res.is_ok());
-        let merged_tag = merged_tag_res.unwrap();
-        assert_eq!(merged_tag.aliases.len(), 2);
-        let modded_game_res = flashpoint.find_game(&saved_game.id).await;
-        assert!(modded_game_res.is_ok());
-        let modded_game_opt = modded_game_res.unwrap();
-        assert!(modded_game_opt.is_some());
-        let modded_game = modded_game_opt.unwrap();
-        assert_eq!(modded_game.tags.len(), 1);
-        assert_eq!(modded_game.tags[0], "Adventure");
+        assert_eq!(flashpoint.save_tag_category(&partial_tc).await.unwrap().ordering, 3);
+
+        // Remote updates don't carry ordering, local ordering survives them
+        let remote: Vec<RemoteCategory> = ["genre", "theme", "warnings"].iter().zip(ids.iter())
+            .map(|(name, id)| RemoteCategory {
+                id: *id,
+                name: name.to_string(),
+                color: "#000000".to_owned(),
+                description: "remote".to_owned(),
+            })
+            .collect();
+        flashpoint.update_apply_categories(remote).await.unwrap();
+        let cats = flashpoint.find_all_tag_categories().await.unwrap();
+        let names: Vec<&str> = cats.iter().map(|tc| tc.name.as_str()).collect();
+        assert_eq!(names, vec!["theme", "genre", "default", "warnings"]);
+        assert_eq!(cats[0].description.as_deref(), Some("remote"));
     }
 
+
     #[tokio::test]
-    async fn find_tag() {
+    async fn set_game_tags_and_platforms() {
         let mut flashpoint = FlashpointArchive::new();
         assert!(flashpoint.load_database(":memory:").is_ok());
+        let fixed = chrono::DateTime::parse_from_rfc3339("2024-03-01T12:00:00.000Z").unwrap().with_timezone(&chrono::Utc);
+        flashpoint.set_clock(Arc::new(clock::FixedClock(fixed)));
+
         let partial = PartialGame {
-            title: Some("test".to_owned()),
-            tags: Some(vec!["Action"].into()),
+            title: Some("Test".to_owned()),
+            notes: Some("Some notes\nover two lines".to_owned()),
+            tags: Some(vec!["Action", "Puzzle"].into()),
+            platforms: Some(vec!["Flash", "HTML5"].into()),
+            primary_platform: Some("Flash".to_owned()),
             ..Default::default()
         };
-        let new_game_res = flashpoint.create_game(&partial).await;
-        assert!(new_game_res.is_ok());
-        let tag_res = flashpoint.find_tag("Action").await;
-        assert!(tag_res.is_ok());
-        let tag_opt = tag_res.unwrap();
-        assert!(tag_opt.is_some());
-        let tag_id_res = flashpoint.find_tag_by_id(tag_opt.unwrap().id).await;
-        assert!(tag_id_res.is_ok());
-        assert!(tag_id_res.unwrap().is_some());
+        let game = flashpoint.create_game(&partial).await.unwrap();
+        flashpoint.add_game_playtime(&game.id, 30).await.unwrap();
+
+        fn read_row(flashpoint: &FlashpointArchive, id: &str) -> (String, String, i64, i64, Option<String>, String, String, String) {
+            let conn = flashpoint.pool.as_ref().unwrap().get().unwrap();
+            conn.query_row("SELECT title, notes, playtime, playCounter, lastPlayed, platformName, tagsStr, platformsStr FROM game WHERE id = ?", [id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?, row.get(6)?, row.get(7)?))
+            }).unwrap()
+        }
+        let before = read_row(&flashpoint, &game.id);
+
+        let later = chrono::DateTime::parse_from_rfc3339("2024-03-02T08:00:00.000Z").unwrap().with_timezone(&chrono::Utc);
+        flashpoint.set_clock(Arc::new(clock::FixedClock(later)));
+        let tagged = flashpoint.set_game_tags(&game.id, vec!["Puzzle".to_owned(), "Adventure".to_owned(), "Puzzle".to_owned()]).await.unwrap();
+        assert_eq!(tagged.tags.to_vec(), vec!["Puzzle", "Adventure"]);
+        assert_eq!(tagged.date_modified, "2024-03-02T08:00:00.000Z");
+        let detailed: Vec<String> = tagged.detailed_tags.unwrap().into_iter().map(|t| t.name).collect();
+        assert_eq!(detailed.len(), 2);
+        assert!(detailed.contains(&"Adventure".to_owned()));
+
+        let after = read_row(&flashpoint, &game.id);
+        assert_eq!(after.6, "Puzzle; Adventure");
+        assert_eq!((&before.0, &before.1, before.2, before.3, &before.4, &before.5, &before.7), (&after.0, &after.1, after.2, after.3, &after.4, &after.5, &after.7));
+
+        // Redirected ids write to the game they point to, and a dropped primary platform is cleared
+        assert!(flashpoint.create_game_redirect("old-id", &game.id).await.is_ok());
+        let platformed = flashpoint.set_game_platforms("old-id", vec!["HTML5".to_owned()]).await.unwrap();
+        assert_eq!(platformed.id, game.id);
+        assert_eq!(platformed.platforms.to_vec(), vec!["HTML5"]);
+        assert_eq!(platformed.primary_platform, "");
+        let after_platforms = read_row(&flashpoint, &game.id);
+        assert_eq!((after_platforms.5.as_str(), after_platforms.7.as_str()), ("", "HTML5"));
+        assert_eq!((&after.0, &after.1, after.2, after.3, &after.4, &after.6), (&after_platforms.0, &after_platforms.1, after_platforms.2, after_platforms.3, &after_platforms.4, &after_platforms.6));
+
+        let res = flashpoint.set_game_tags("missing", vec![]).await;
+        assert!(matches!(res, Err(Error::GameNotFound { .. })));
     }
 
+
     #[tokio::test]
-    async fn delete_platform() {
+    async fn save_returns_same_relations_as_find() {
         let mut flashpoint = FlashpointArchive::new();
         assert!(flashpoint.load_database(":memory:").is_ok());
         let partial = PartialGame {
-            title: Some("test".to_owned()),
+            title: Some("Test".to_owned()),
+            tags: Some(vec!["Action"].into()),
             platforms: Some(vec!["Flash"].into()),
             ..Default::default()
         };
-        let new_game_res = flashpoint.create_game(&partial).await;
-        assert!(new_game_res.is_ok());
-        let saved_game = new_game_res.unwrap();
-        assert_eq!(saved_game.platforms.len(), 1);
-        let delete_res = flashpoint.delete_platform("Flash").await;
-        assert!(delete_res.is_ok());
-        let modded_game_res = flashpoint.find_game(&saved_game.id).await;
-        assert!(modded_game_res.is_ok());
-        let modded_game_opt = modded_game_res.unwrap();
-        assert!(modded_game_opt.is_some());
-        let modded_game = modded_game_opt.unwrap();
-        assert_eq!(modded_game.platforms.len(), 0);
+        let created = flashpoint.create_game(&partial).await.unwrap();
+        let mut add_app = game::AdditionalApp {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: "Extra".to_owned(),
+            application_path: "extra.exe".to_owned(),
+            launch_command: "".to_owned(),
+            auto_run_before: false,
+            wait_for_exit: false,
+            parent_game_id: created.id.clone(),
+        };
+        flashpoint.create_add_app(&mut add_app).await.unwrap();
+
+        let mut edit = PartialGame {
+            id: created.id.clone(),
+            notes: Some("edited".to_owned()),
+            ..Default::default()
+        };
+        let saved = flashpoint.save_game(&mut edit).await.unwrap();
+        let found = flashpoint.find_game(&created.id).await.unwrap().unwrap();
+
+        let populated = |g: &Game| (g.detailed_tags.is_some(), g.detailed_platforms.is_some(), g.game_data.is_some(), g.add_apps.is_some());
+        assert_eq!(populated(&created), populated(&found));
+        assert_eq!(populated(&saved), populated(&found));
+        assert_eq!(populated(&found), (true, true, true, true));
+        assert_eq!(saved.add_apps.unwrap().len(), 1);
+        assert_eq!(found.add_apps.unwrap().len(), 1);
     }
 
+
     #[tokio::test]
-    async fn create_platform() {
+    async fn global_tag_blacklist() {
         let mut flashpoint = FlashpointArchive::new();
         assert!(flashpoint.load_database(":memory:").is_ok());
-        let new_tag_res = flashpoint.create_platform("test", None).await;
-        assert!(new_tag_res.is_ok());
-        let new_tag = new_tag_res.unwrap();
-        assert!(new_tag.category.is_none());
-        assert_eq!(new_tag.name, "test");
-        assert_eq!(new_tag.aliases.len(), 1);
-        assert_eq!(new_tag.aliases[0], "test");
+        for (title, tags) in [("Safe", vec!["Action"]), ("Also Safe", vec![]), ("Hidden", vec!["Action", "Extreme"])] {
+            let partial = PartialGame {
+                title: Some(title.to_owned()),
+                tags: Some(tags.into()),
+                ..Default::default()
+            };
+            let game = flashpoint.create_game(&partial).await.unwrap();
+            flashpoint.set_user_data(&PartialGameUserData {
+                game_id: game.id,
+                favorite: Some(true),
+                ..Default::default()
+            }).await.unwrap();
+        }
+
+        let search = GameSearch::default();
+        assert_eq!(flashpoint.search_games(&search).await.unwrap().len(), 3);
+
+        flashpoint.set_global_tag_blacklist(Some(vec!["Extreme".to_owned()])).unwrap();
+        let games = flashpoint.search_games(&search).await.unwrap();
+        assert_eq!(games.len(), 2);
+        assert!(games.iter().all(|g| g.title != "Hidden"));
+        assert_eq!(flashpoint.search_games_total(&search).await.unwrap(), 2);
+        let mut index_search = search.clone();
+        index_search.limit = 1;
+        assert_eq!(flashpoint.search_games_index(&mut index_search, None).await.unwrap().len(), 2);
+        assert_eq!(flashpoint.search_games_random(&search, 10).await.unwrap().len(), 2);
+
+        // Finds building their own search are filtered too
+        let tagged = flashpoint.search_games_with_tag("Action", None, None).await.unwrap();
+        assert_eq!(tagged.len(), 1);
+        assert_eq!(tagged[0].title, "Safe");
+        let favorites = flashpoint.find_favorites().await.unwrap();
+        assert_eq!(favorites.len(), 2);
+        assert!(favorites.iter().all(|g| g.title != "Hidden"));
+        let overlay = flashpoint.begin_overlay().await.unwrap();
+        assert_eq!(flashpoint.overlay_search_games(overlay, &search).await.unwrap().len(), 2);
+
+        // Admin views opt out, a search's own filter replaces the global one
+        let mut unfiltered = GameSearch::default();
+        unfiltered.disable_global_tag_filter = true;
+        assert_eq!(flashpoint.search_games(&unfiltered).await.unwrap().len(), 3);
+        let mut own_filter = GameSearch::default();
+        own_filter.with_tag_filter = Some(vec![]);
+        assert_eq!(flashpoint.search_games(&own_filter).await.unwrap().len(), 3);
+
+        // Toggling the global list leaves the index built for it clean
+        flashpoint.set_global_tag_blacklist(None).unwrap();
+        assert_eq!(flashpoint.search_games(&search).await.unwrap().len(), 3);
+        flashpoint.set_global_tag_blacklist(Some(vec!["Extreme".to_owned()])).unwrap();
+        assert_eq!(flashpoint.search_games(&search).await.unwrap().len(), 2);
+        let conn = flashpoint.pool.as_ref().unwrap().get().unwrap();
+        let (key, dirty): (String, bool) = conn.query_row("SELECT key, dirty FROM tag_filter_index_info", (), |row| Ok((row.get(0)?, row.get(1)?))).unwrap();
+        assert_eq!(key, "Extreme");
+        assert!(!dirty);
     }
 
+
     #[tokio::test]
-    async fn search_tag_suggestions() {
+    async fn count_add_apps_and_game_data() {
         let mut flashpoint = FlashpointArchive::new();
         assert!(flashpoint.load_database(":memory:").is_ok());
-        let new_tag_res = flashpoint.create_tag("Action", None, None).await;
-        assert!(new_tag_res.is_ok());
-        let suggs_res = flashpoint.search_tag_suggestions("Act", vec![]).await;
-        assert!(suggs_res.is_ok());
-        assert_eq!(suggs_res.unwrap().len(), 1);
-        let suggs_bad_res = flashpoint.search_tag_suggestions("Adventure", vec![]).await;
-        assert!(suggs_bad_res.is_ok());
-        assert_eq!(suggs_bad_res.unwrap().len(), 0);
+        assert_eq!(flashpoint.count_add_apps().await.unwrap(), 0);
+        assert_eq!(flashpoint.count_game_data().await.unwrap(), 0);
+
+        let mut games = vec![];
+        for (idx, (add_apps, data)) in [(2, 1), (0, 3), (1, 0)].into_iter().enumerate() {
+            let full = CreateFullGame {
+                game: PartialGame {
+                    title: Some(format!("Game {}", idx)),
+                    ..Default::default()
+                },
+                add_apps: (0..add_apps).map(|n| full_game_add_app(&format!("add-app-{}-{}", idx, n))).collect(),
+                game_data: (0..data).map(|n| full_game_data(&format!("2024-01-01T00:00:{:02}.000Z", n), false)).collect(),
+            };
+            games.push(flashpoint.create_full_game(&full).await.unwrap());
+        }
+
+        assert_eq!(flashpoint.count_add_apps().await.unwrap(), 3);
+        assert_eq!(flashpoint.count_game_data().await.unwrap(), 4);
+
+        // Soft deleted games keep their rows but leave the counts
+        assert!(flashpoint.soft_delete_game(&games[0].id).await.is_ok());
+        assert_eq!(flashpoint.count_add_apps().await.unwrap(), 1);
+        assert_eq!(flashpoint.count_game_data().await.unwrap(), 3);
     }
 
+
     #[tokio::test]
-    async fn update_game_when_platform_changed() {
+    async fn apply_platforms_merges_alias_collisions() {
         let mut flashpoint = FlashpointArchive::new();
         assert!(flashpoint.load_database(":memory:").is_ok());
-        let partial_game = game::PartialGame {
-            title: Some(String::from("Test Game")),
-            tags: Some(vec!["Action"].into()),
-            platforms: Some(vec!["Flash", "HTML5"].into()),
-            primary_platform: Some("HTML5".into()),
-            ..game::PartialGame::default()
+        let partial = PartialGame {
+            title: Some("Test".to_owned()),
+            platforms: Some(vec!["HTML5"].into()),
+            primary_platform: Some("HTML5".to_owned()),
+            ..Default::default()
         };
-        let result = flashpoint.create_game(&partial_game).await;
-        assert!(result.is_ok());
-        let old_game = result.unwrap();
-        let mut platform = flashpoint.find_platform("HTML5").await.unwrap().unwrap();
-        platform.name = String::from("Wiggle");
-        let mut partial = PartialTag::from(platform);
-        let save_res = flashpoint.save_platform(&mut partial).await;
-        assert!(save_res.is_ok());
-        assert_eq!(save_res.unwrap().name, "Wiggle");
-        let new_game = flashpoint.find_game(&old_game.id).await.unwrap().unwrap();
-        assert_eq!(new_game.primary_platform, "Wiggle");
-        assert!(new_game.platforms.contains(&"Wiggle".to_string()));
-    }
-
-    #[tokio::test]
-    async fn search_games_random() {
-        let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(TEST_DATABASE);
-        assert!(create.is_ok());
-
-        let mut search = crate::game::search::parse_user_input("").search;
-        let mut new_filter = GameFilter::default();
-        new_filter.exact_blacklist.tags = Some(vec!["Action".to_owned()]);
-        search.filter.subfilters.push(new_filter);
+        let game = flashpoint.create_game(&partial).await.unwrap();
+        let local = flashpoint.find_platform("HTML5").await.unwrap().unwrap();
+        let remote_id = local.id + 100;
+
+        let report = flashpoint.update_apply_platforms(vec![RemotePlatform {
+            id: remote_id,
+            name: "HTML5".to_owned(),
+            description: "Canonical".to_owned(),
+            date_modified: "2024-01-01T00:00:00.000Z".to_owned(),
+            aliases: vec!["HTML5".to_owned(), "html".to_owned()],
+            deleted: false,
+        }]).await.unwrap();
+        assert_eq!(report.merged_platforms.len(), 1);
+        assert_eq!(report.merged_platforms[0].local_id, local.id);
+        assert_eq!(report.merged_platforms[0].remote_id, remote_id);
 
-        let random_res = flashpoint.search_games_random(&search, 5).await;
-        assert!(random_res.is_ok());
-        assert_eq!(random_res.unwrap().len(), 5);
+        let platforms = flashpoint.find_all_platforms().await.unwrap();
+        assert_eq!(platforms.len(), 1);
+        assert_eq!(platforms[0].id, remote_id);
+        assert_eq!(platforms[0].name, "HTML5");
+        assert!(flashpoint.find_platform_by_id(local.id).await.unwrap().is_none());
+
+        let found = flashpoint.find_game(&game.id).await.unwrap().unwrap();
+        assert_eq!(found.platforms.to_vec(), vec!["HTML5"]);
+        assert_eq!(found.primary_platform, "HTML5");
+        let detailed = found.detailed_platforms.unwrap();
+        assert_eq!(detailed.len(), 1);
+        assert_eq!(detailed[0].id, remote_id);
+
+        // Applying again finds nothing left to merge
+        let report = flashpoint.update_apply_platforms(vec![RemotePlatform {
+            id: remote_id,
+            name: "HTML5".to_owned(),
+            description: "Canonical".to_owned(),
+            date_modified: "2024-01-02T00:00:00.000Z".to_owned(),
+            aliases: vec!["HTML5".to_owned(), "html".to_owned()],
+            deleted: false,
+        }]).await.unwrap();
+        assert!(report.merged_platforms.is_empty());
     }
 
+
     #[tokio::test]
-    async fn search_games_installed() {
+    async fn diff_searches() {
         let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(TEST_DATABASE);
-        assert!(create.is_ok());
-
-        let mut search = crate::game::search::parse_user_input("installed:true").search;
-        if let Some(installed) = search.filter.bool_comp.installed.as_ref() {
-            assert_eq!(installed, &true);
-        } else {
-            panic!("Expected 'installed' to be Some(true), but it was None.");
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        for (title, tags) in [("Alpha", vec!["Action"]), ("Bravo", vec!["Action", "Extreme"]), ("Charlie", vec!["Extreme", "Puzzle"]), ("Delta", vec![])] {
+            let partial = PartialGame {
+                title: Some(title.to_owned()),
+                tags: Some(tags.into()),
+                ..Default::default()
+            };
+            flashpoint.create_game(&partial).await.unwrap();
         }
 
-        search.limit = 200;
-        let games_res = flashpoint.search_games(&search).await;
-        assert!(games_res.is_ok());
-        assert_eq!(games_res.unwrap().len(), 20);
+        let a = GameSearch::default();
+        let mut b = GameSearch::default();
+        b.with_tag_filter = Some(vec!["Extreme".to_owned()]);
+        let diff = flashpoint.diff_searches(&a, &b, 10).await.unwrap();
+        assert_eq!(diff.only_in_a_count, 2);
+        assert_eq!(diff.only_in_b_count, 0);
+        let titles: Vec<&str> = diff.only_in_a.iter().map(|g| g.title.as_str()).collect();
+        assert_eq!(titles, vec!["Bravo", "Charlie"]);
+        assert!(diff.only_in_b.is_empty());
+
+        // Both sides can filter different tags, examples are limited but counts aren't
+        let mut a = GameSearch::default();
+        a.with_tag_filter = Some(vec!["Puzzle".to_owned()]);
+        let diff = flashpoint.diff_searches(&a, &b, 1).await.unwrap();
+        assert_eq!(diff.only_in_a_count, 1);
+        assert_eq!(diff.only_in_a[0].title, "Bravo");
+        assert_eq!(diff.only_in_b_count, 0);
+        let diff = flashpoint.diff_searches(&b, &a, 1).await.unwrap();
+        assert_eq!(diff.only_in_b_count, 1);
+        assert_eq!(diff.only_in_b.len(), 1);
     }
 
+
     #[tokio::test]
-    async fn search_games_index_limited() {
+    async fn find_platformless_games() {
         let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(TEST_DATABASE);
-        assert!(create.is_ok());
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let platformless = flashpoint.create_game(&PartialGame {
+            title: Some("No Platform".to_owned()),
+            ..Default::default()
+        }).await.unwrap();
+        flashpoint.create_game(&PartialGame {
+            title: Some("Flash Game".to_owned()),
+            platforms: Some(vec!["Flash"].into()),
+            primary_platform: Some("Flash".to_owned()),
+            ..Default::default()
+        }).await.unwrap();
 
-        let search = &mut GameSearch::default();
-        search.filter.whitelist.title = Some(vec!["Super".into()]);
-        // Set page size
-        search.limit = 200;
-        let index_res = flashpoint.search_games_index(&mut search.clone(), Some(1000)).await;
-        assert!(index_res.is_ok());
-        let index = index_res.unwrap();
-        assert_eq!(index.len(), 5);
+        let games = flashpoint.find_platformless_games(None).await.unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].id, platformless.id);
+
+        // The search narrows the results further
+        let mut search = GameSearch::default();
+        search.filter.whitelist.title = Some(vec!["Flash".to_owned()]);
+        assert!(flashpoint.find_platformless_games(Some(search)).await.unwrap().is_empty());
     }
 
+
     #[tokio::test]
-    async fn get_tag() {
+    async fn changes_since() {
         let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(TEST_DATABASE);
-        assert!(create.is_ok());
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        let seeds = [
+            ("Old", "2024-01-01T00:00:00.000Z", "2024-01-02T00:00:00.000Z"),
+            ("Updated", "2024-01-01T00:00:00.000Z", "2024-03-05T10:00:00.000Z"),
+            // Legacy format, the same instant as the cutoff written differently
+            ("Updated Legacy", "2024-01-01 00:00:00", "2024-03-05 09:00:00"),
+            ("New", "2024-03-02T00:00:00.000Z", "2024-03-02T00:00:00.000Z"),
+            ("At Cutoff", "2024-03-01 00:00:00", "2024-03-01 00:00:00"),
+            ("Deleted", "2024-01-01T00:00:00.000Z", "2024-01-01T00:00:00.000Z"),
+        ];
+        let mut ids = HashMap::new();
+        for (title, added, modified) in seeds {
+            let partial = PartialGame {
+                title: Some(title.to_owned()),
+                date_added: Some(added.to_owned()),
+                date_modified: Some(modified.to_owned()),
+                ..Default::default()
+            };
+            ids.insert(title, flashpoint.create_game(&partial).await.unwrap().id);
+        }
+        {
+            // Raw writes keep the legacy formats that create_game would normalize
+            let conn = flashpoint.pool.as_ref().unwrap().get().unwrap();
+            for (title, added, modified) in seeds {
+                conn.execute("UPDATE game SET dateAdded = ?, dateModified = ? WHERE id = ?", rusqlite::params![added, modified, ids[title]]).unwrap();
+            }
+            conn.execute("UPDATE game SET deletedAt = '2024-03-03T00:00:00.000Z' WHERE id = ?", rusqlite::params![ids["Deleted"]]).unwrap();
+        }
 
-        let tag_res = flashpoint.find_tag("Mario Bros.").await;
-        assert!(tag_res.is_ok());
-        let tag = tag_res.unwrap();
-        assert!(tag.is_some());
-        assert_eq!(tag.unwrap().name, "Super Mario");
+        let report = flashpoint.changes_since("2024-03-01T00:00:00.000Z", 10, 0).await.unwrap();
+        let titles = |games: &Vec<Game>| games.iter().map(|g| g.title.clone()).collect::<Vec<String>>();
+        assert_eq!(titles(&report.new_games), vec!["New"]);
+        assert_eq!(titles(&report.updated_games), vec!["Updated", "Updated Legacy"]);
+        assert_eq!(report.deleted_ids, vec![ids["Deleted"].clone()]);
+        assert_eq!((report.total_new, report.total_updated, report.total_deleted), (1, 2, 1));
+
+        // Paging applies to each list, totals don't change
+        let report = flashpoint.changes_since("2024-03-01T00:00:00.000Z", 1, 1).await.unwrap();
+        assert_eq!(titles(&report.updated_games), vec!["Updated Legacy"]);
+        assert!(report.new_games.is_empty());
+        assert_eq!(report.total_updated, 2);
+        assert_eq!(report.updated_games[0].date_modified, "2024-03-05 09:00:00");
+
+        let res = flashpoint.changes_since("last tuesday", 10, 0).await;
+        assert!(matches!(res, Err(Error::InvalidTimestamp { timestamp }) if timestamp == "last tuesday"));
     }
 
+
     #[tokio::test]
-    async fn get_platform() {
+    async fn search_index_page_size() {
         let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(TEST_DATABASE);
-        assert!(create.is_ok());
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        for idx in 0..7 {
+            let partial = PartialGame {
+                title: Some(format!("Game {}", idx)),
+                ..Default::default()
+            };
+            flashpoint.create_game(&partial).await.unwrap();
+        }
 
-        let tag_res = flashpoint.find_platform("Jutvision").await;
-        assert!(tag_res.is_ok());
-        let tag = tag_res.unwrap();
-        assert!(tag.is_some());
-        assert_eq!(tag.unwrap().name, "asdadawdaw");
+        let mut search = GameSearch::default();
+        search.limit = 2;
+        let index = flashpoint.search_games_index(&mut search, None).await.unwrap();
+        let titles: Vec<&str> = index.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, vec!["Game 1", "Game 3", "Game 5"]);
+        // The page size is left alone for the page queries that follow
+        assert_eq!(search.limit, 2);
+
+        let index = flashpoint.search_games_index(&mut search, Some(4)).await.unwrap();
+        assert_eq!(index.len(), 2);
     }
 
-    #[tokio::test]
-    async fn add_playtime() {
-        let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(":memory:");
-        assert!(create.is_ok());
-        let partial_game = game::PartialGame {
-            title: Some(String::from("Test Game")),
-            tags: Some(vec!["Action"].into()),
-            ..game::PartialGame::default()
-        };
-        let result = flashpoint.create_game(&partial_game).await;
-        assert!(result.is_ok());
-        let game_id = result.unwrap().id;
-        let playtime_res = flashpoint.add_game_playtime(&game_id, 30).await;
-        assert!(playtime_res.is_ok());
-        let saved_game_res = flashpoint.find_game(&game_id).await;
-        assert!(saved_game_res.is_ok());
-        let saved_game_opt = saved_game_res.unwrap();
-        assert!(saved_game_opt.is_some());
-        let saved_game = saved_game_opt.unwrap();
-        assert_eq!(saved_game.playtime, 30);
-        assert_eq!(saved_game.play_counter, 1);
-    }
 
     #[tokio::test]
-    async fn update_tags_clear_existing(    ) {
+    async fn find_games_by_language() {
         let mut flashpoint = FlashpointArchive::new();
-        let create = flashpoint.load_database(":memory:");
-        assert!(create.is_ok());
-        let new_tag_res = flashpoint.create_tag("test", None, Some(10)).await;
-        assert!(new_tag_res.is_ok());
-        let tag_update = RemoteTag {
-            id: 10,
-            name: "hello".to_owned(),
-            description: String::new(),
-            category: "default".to_owned(),
-            date_modified: "2024-01-01 12:00:00".to_owned(),
-            aliases: vec!["hello".to_owned()],
-            deleted: false,
-        };
-        let update_res = flashpoint.update_apply_tags(vec![tag_update]).await;
-        assert!(update_res.is_ok());
-        let saved_tag_res = flashpoint.find_tag_by_id(10).await;
-        assert!(saved_tag_res.is_ok());
-        let saved_tag_opt = saved_tag_res.unwrap();
-        assert!(saved_tag_opt.is_some());
-        let saved_tag = saved_tag_opt.unwrap();
-        assert_eq!(saved_tag.aliases.len(), 1);
-        assert_eq!(saved_tag.aliases[0].as_str(), "hello");
-        assert_eq!(saved_tag.name.as_str(), "hello");
+        assert!(flashpoint.load_database(":memory:").is_ok());
+        for (title, language) in [("English", "en"), ("Bilingual", "en; ja"), ("Other", "jav")] {
+            let partial = PartialGame {
+                title: Some(title.to_owned()),
+                language: Some(language.to_owned()),
+                ..Default::default()
+            };
+            flashpoint.create_game(&partial).await.unwrap();
+        }
+
+        let games = flashpoint.find_games_by_language("ja", None).await.unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].title, "Bilingual");
+
+        let mut search = GameSearch::default();
+        search.order.direction = game::search::GameSearchDirection::DESC;
+        let games = flashpoint.find_games_by_language("EN", Some(search)).await.unwrap();
+        let titles: Vec<&str> = games.iter().map(|g| g.title.as_str()).collect();
+        assert_eq!(titles, vec!["English", "Bilingual"]);
     }
-}
+}
\ No newline at end of file
@@ -2,7 +2,11 @@ use rusqlite::Connection;
 use rusqlite_migration::{M, Migrations, Result};
 
 pub fn get() -> Migrations<'static> {
-    let migrations = Migrations::new(vec![
+    Migrations::new(list())
+}
+
+fn list() -> Vec<M<'static>> {
+    vec![
         M::up(r#"
             CREATE TABLE IF NOT EXISTS "tag_category" (
                 "id"	integer NOT NULL,
@@ -281,15 +285,85 @@ pub fn get() -> Migrations<'static> {
         M::up(r#"
             ALTER TABLE "game" ADD COLUMN "ruffleSupport" varchar NOT NULL DEFAULT '';
         "#),
-    ]);
-
-    migrations
+        M::up(r#"
+            -- No foreign key to game, user data must outlive the game being removed by a metadata sync
+            CREATE TABLE "game_user_data" (
+                "gameId"	varchar NOT NULL,
+                "favorite"	boolean NOT NULL DEFAULT 0,
+                "userNotes"	text NOT NULL DEFAULT '',
+                "userRating"	integer,
+                "updatedAt"	datetime NOT NULL,
+                PRIMARY KEY("gameId")
+            );
+        "#),
+        M::up(r#"
+            INSERT OR IGNORE INTO "tag_category" ("name", "color") VALUES ('default', '#FFFFFF');
+        "#),
+        M::up(r#"
+            ALTER TABLE "game" ADD COLUMN "deletedAt" datetime;
+            CREATE INDEX "IDX_game_deletedAt" ON "game" ("deletedAt");
+        "#),
+        M::up(r#"
+            UPDATE "game" SET "lastPlayed" = strftime('%Y-%m-%dT%H:%M:%fZ', "lastPlayed")
+            WHERE "lastPlayed" IS NOT NULL AND strftime('%Y-%m-%dT%H:%M:%fZ', "lastPlayed") IS NOT NULL;
+        "#),
+        M::up(r#"
+            UPDATE "tag_category" SET "color" = CASE
+                WHEN "color" GLOB '#[0-9A-Fa-f][0-9A-Fa-f][0-9A-Fa-f][0-9A-Fa-f][0-9A-Fa-f][0-9A-Fa-f]' THEN upper("color")
+                WHEN "color" GLOB '#[0-9A-Fa-f][0-9A-Fa-f][0-9A-Fa-f]' THEN upper('#'
+                    || substr("color", 2, 1) || substr("color", 2, 1)
+                    || substr("color", 3, 1) || substr("color", 3, 1)
+                    || substr("color", 4, 1) || substr("color", 4, 1))
+                ELSE '#FFFFFF'
+            END;
+        "#),
+        M::up(r#"
+            ALTER TABLE "game" ADD COLUMN "releaseYear" integer;
+            CREATE INDEX IF NOT EXISTS "IDX_game_releaseYear" ON "game" ("releaseYear");
+            UPDATE "game" SET "releaseYear" = (
+                WITH RECURSIVE pos(i) AS (
+                    SELECT 1 UNION ALL SELECT i + 1 FROM pos WHERE i < length("game"."releaseDate") - 3
+                )
+                SELECT CAST(substr("game"."releaseDate", i, 4) AS INTEGER) FROM pos
+                WHERE substr("game"."releaseDate", i, 4) GLOB '[0-9][0-9][0-9][0-9]'
+                    AND (i = 1 OR substr("game"."releaseDate", i - 1, 1) NOT GLOB '[0-9]')
+                    AND substr("game"."releaseDate", i + 4, 1) NOT GLOB '[0-9]'
+                ORDER BY i LIMIT 1
+            ) WHERE "releaseDate" GLOB '*[0-9][0-9][0-9][0-9]*';
+        "#),
+        M::up(r#"
+            CREATE TABLE IF NOT EXISTS "tag_redirect" (
+                "oldTagId" integer PRIMARY KEY NOT NULL,
+                "newTagId" integer NOT NULL,
+                "dateAdded" datetime NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS "IDX_tag_redirect_newTagId" ON "tag_redirect" ("newTagId");
+        "#),
+        M::up(r#"
+            ALTER TABLE "game" ADD COLUMN "languageRaw" varchar;
+        "#),
+        M::up(r#"
+            ALTER TABLE "tag_category" ADD COLUMN "ordering" integer NOT NULL DEFAULT 0;
+        "#),
+    ]
 }
 
 pub fn up(conn: &mut Connection) -> Result<()> {
-    let migrations = get();
+    let migrations = list();
+
+    let journal_mode: String = conn.pragma_query_value(None, "journal_mode", |row| row.get(0))
+        .map_err(|err| rusqlite_migration::Error::RusqliteError { query: "PRAGMA journal_mode".to_owned(), err })?;
+    if !journal_mode.eq_ignore_ascii_case("wal") {
+        conn.pragma_update(None, "journal_mode", &"WAL")
+            .map_err(|err| rusqlite_migration::Error::RusqliteError { query: "PRAGMA journal_mode = WAL".to_owned(), err })?;
+    }
 
-    conn.pragma_update(None, "journal_mode", &"WAL").unwrap();
+    // rusqlite_migration stores the number of applied migrations in user_version
+    let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))
+        .map_err(|err| rusqlite_migration::Error::RusqliteError { query: "PRAGMA user_version".to_owned(), err })?;
+    if version == migrations.len() as i64 {
+        return Ok(());
+    }
 
-    migrations.to_latest(conn)
+    Migrations::new(migrations).to_latest(conn)
 }
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use rusqlite::Connection;
+use snafu::ResultExt;
+use uuid::Uuid;
+
+use crate::error;
+use crate::game::{self, search::GameSearch, Game, PartialGame};
+use crate::tag;
+
+pub type OverlayId = u32;
+
+/// Maximum number of staged operations a single overlay can hold
+pub const MAX_OVERLAY_OPERATIONS: usize = 1000;
+
+#[derive(Debug, Clone)]
+pub enum OverlayOperation {
+    SaveGame(PartialGame),
+    MergeTags { name: String, merged_into: String },
+}
+
+/// In memory store of staged operations, keyed by overlay id. Nothing here touches the database.
+#[derive(Debug, Default)]
+pub struct OverlayStore {
+    next_id: OverlayId,
+    sessions: HashMap<OverlayId, Vec<OverlayOperation>>,
+}
+
+impl OverlayStore {
+    pub fn begin(&mut self) -> OverlayId {
+        self.next_id += 1;
+        self.sessions.insert(self.next_id, vec![]);
+        self.next_id
+    }
+
+    pub fn stage(&mut self, id: OverlayId, operation: OverlayOperation) -> error::Result<()> {
+        let ops = self.get_mut(id)?;
+        if ops.len() >= MAX_OVERLAY_OPERATIONS {
+            return error::OverlayFullSnafu { id, limit: MAX_OVERLAY_OPERATIONS }.fail();
+        }
+        ops.push(operation);
+        Ok(())
+    }
+
+    pub fn operations(&self, id: OverlayId) -> error::Result<Vec<OverlayOperation>> {
+        match self.sessions.get(&id) {
+            Some(ops) => Ok(ops.clone()),
+            None => error::OverlayNotFoundSnafu { id }.fail(),
+        }
+    }
+
+    /// Takes the staged operations out of an overlay, leaving it open and empty for anything staged meanwhile
+    pub fn take(&mut self, id: OverlayId) -> error::Result<Vec<OverlayOperation>> {
+        Ok(std::mem::take(self.get_mut(id)?))
+    }
+
+    /// Puts taken operations back ahead of anything staged since. Does nothing if the overlay was discarded meanwhile.
+    pub fn restore(&mut self, id: OverlayId, mut ops: Vec<OverlayOperation>) {
+        if let Some(staged) = self.sessions.get_mut(&id) {
+            ops.append(staged);
+            *staged = ops;
+        }
+    }
+
+    /// Discards the overlay once it has nothing left to commit
+    pub fn discard_if_empty(&mut self, id: OverlayId) {
+        if self.sessions.get(&id).is_some_and(|ops| ops.is_empty()) {
+            self.sessions.remove(&id);
+        }
+    }
+
+    pub fn discard(&mut self, id: OverlayId) -> error::Result<()> {
+        match self.sessions.remove(&id) {
+            Some(_) => Ok(()),
+            None => error::OverlayNotFoundSnafu { id }.fail(),
+        }
+    }
+
+    fn get_mut(&mut self, id: OverlayId) -> error::Result<&mut Vec<OverlayOperation>> {
+        match self.sessions.get_mut(&id) {
+            Some(ops) => Ok(ops),
+            None => error::OverlayNotFoundSnafu { id }.fail(),
+        }
+    }
+}
+
+/// Gives a staged partial a stable id so later finds and the final commit agree on it
+pub fn prepare_partial(partial: &PartialGame) -> PartialGame {
+    let mut partial = partial.clone();
+    if partial.id.is_empty() {
+        partial.id = Uuid::new_v4().to_string();
+    }
+    partial
+}
+
+pub fn find(conn: &Connection, id: &str, ops: &[OverlayOperation], now: &str) -> error::Result<Option<Game>> {
+    with_operations_applied(conn, ops, now, |conn| game::find(conn, id).context(error::SqliteSnafu))
+}
+
+/// Replays the staged operations and rolls them back, failing wherever a commit would
+pub fn check(conn: &Connection, ops: &[OverlayOperation], now: &str) -> error::Result<()> {
+    with_operations_applied(conn, ops, now, |_| Ok(()))
+}
+
+/// The tag filter index is rebuilt with the staged operations applied when `search` has a tag filter
+pub fn search(conn: &Connection, search: &GameSearch, ops: &[OverlayOperation], now: &str) -> error::Result<Vec<Game>> {
+    with_operations_applied(conn, ops, now, |conn| {
+        game::search::update_tag_filter_index(conn, search).context(error::SqliteSnafu)?;
+        game::search::search(conn, search).context(error::SqliteSnafu)
+    })
+}
+
+/// Replays the staged operations in order, expected to run inside a transaction
+pub fn commit(conn: &Connection, ops: &[OverlayOperation], now: &str) -> error::Result<()> {
+    for op in ops {
+        match op {
+            OverlayOperation::SaveGame(partial) => {
+                let mut partial = partial.clone();
+                if partial.date_modified.is_none() {
+                    partial.date_modified = Some(now.to_owned());
+                }
+                // Soft deleted games fail with GameNotFound like a direct save, rather than being recreated
+                if game::exists_with_deleted(conn, &partial.id).context(error::SqliteSnafu)? {
+                    game::save(conn, &partial)?;
+                } else {
+                    if partial.date_added.is_none() {
+                        partial.date_added = Some(now.to_owned());
+                    }
+                    game::create(conn, &partial).context(error::SqliteSnafu)?;
+                }
+            },
+            OverlayOperation::MergeTags { name, merged_into } => {
+                tag::merge_tag(conn, name, merged_into, Some(now))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs `read` with the staged operations replayed inside a savepoint that's always rolled back, so filters,
+/// redirects and tag aliases apply to the overlay exactly as they would after a commit
+fn with_operations_applied<T>(
+    conn: &Connection,
+    ops: &[OverlayOperation],
+    now: &str,
+    read: impl FnOnce(&Connection) -> error::Result<T>,
+) -> error::Result<T> {
+    conn.execute_batch("SAVEPOINT overlay_preview").context(error::SqliteSnafu)?;
+    let res = commit(conn, ops, now).and_then(|_| read(conn));
+    conn.execute_batch("ROLLBACK TO overlay_preview; RELEASE overlay_preview").context(error::SqliteSnafu)?;
+    res
+}
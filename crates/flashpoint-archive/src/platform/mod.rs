@@ -18,11 +18,39 @@ pub fn count(conn: &Connection) -> Result<i64> {
     })
 }
 
-pub fn find(conn: &Connection) -> Result<Vec<Tag>> {
-    let mut stmt = conn.prepare(
-        "SELECT p.id, pa.name, p.description, p.dateModified FROM platform_alias pa
-        INNER JOIN platform p ON p.id = pa.platformId
-        WHERE pa.id == p.primaryAliasId")?;
+/// Whether no platform uses `name` as its name or an alias, ignoring case like the alias table
+pub fn name_available(conn: &Connection, name: &str) -> Result<bool> {
+    conn.query_row("SELECT NOT EXISTS (SELECT 1 FROM platform_alias WHERE name = ?)", params![name], |row| {
+        row.get::<_, bool>(0)
+    })
+}
+
+/// One past the highest platform id, for importers assigning explicit ids
+pub fn next_id(conn: &Connection) -> Result<i64> {
+    conn.query_row("SELECT IFNULL(MAX(id), 0) + 1 FROM platform", (), |row| {
+        row.get::<_, i64>(0)
+    })
+}
+
+/// `only_with_games` - Exclude platforms with no games that aren't soft deleted, ordered by game count descending
+pub fn find(conn: &Connection, only_with_games: bool) -> Result<Vec<Tag>> {
+    let mut stmt = match only_with_games {
+        true => conn.prepare(
+            "SELECT p.id, pa.name, p.description, p.dateModified FROM platform_alias pa
+            INNER JOIN platform p ON p.id = pa.platformId
+            INNER JOIN (
+                SELECT gpp.platformId, COUNT(*) AS gameCount FROM game_platforms_platform gpp
+                INNER JOIN game ON game.id = gpp.gameId
+                WHERE game.deletedAt IS NULL
+                GROUP BY gpp.platformId
+            ) gpp ON gpp.platformId = p.id
+            WHERE pa.id == p.primaryAliasId
+            ORDER BY gpp.gameCount DESC, pa.name")?,
+        false => conn.prepare(
+            "SELECT p.id, pa.name, p.description, p.dateModified FROM platform_alias pa
+            INNER JOIN platform p ON p.id = pa.platformId
+            WHERE pa.id == p.primaryAliasId")?,
+    };
 
     let platform_iter = stmt.query_map((), |row| {
         Ok(Tag {
@@ -32,6 +60,7 @@ pub fn find(conn: &Connection) -> Result<Vec<Tag>> {
             date_modified: row.get(3)?,
             aliases: vec![],
             category: None,
+            category_color: None,
         })
     })?;
 
@@ -52,30 +81,32 @@ pub fn find(conn: &Connection) -> Result<Vec<Tag>> {
     Ok(platforms)
 }
 
-pub fn create(conn: &Connection, name: &str, id: Option<i64>) -> Result<Tag> {
+pub fn create(conn: &Connection, name: &str, id: Option<i64>, date_modified: Option<String>) -> Result<Tag> {
     // Create the alias
     let mut stmt = "INSERT INTO platform_alias (name, platformId) VALUES(?, ?) RETURNING id";    
 
     // Create a new tag
     let alias_id: i64 = conn.query_row(stmt, params![name, -1], |row| row.get(0))?;
 
-    match id {
+    let platform_id = match id {
         Some(id) => {
             stmt = "INSERT INTO platform (id, primaryAliasId, description) VALUES (?, ?, ?)";
             conn.execute(stmt, params![id, alias_id, ""])?;
-        
-            // Update tag alias with the new tag id
-            stmt = "UPDATE platform_alias SET platformId = ? WHERE id = ?";
-            conn.execute(stmt, params![id, alias_id])?;
+            id
         }
         None => {
             stmt = "INSERT INTO platform (primaryAliasId, description) VALUES (?, ?) RETURNING id";
-            let tag_id: i64 = conn.query_row(stmt, params![alias_id, ""], |row| row.get(0))?;
-        
-            // Update tag alias with the new tag id
-            stmt = "UPDATE platform_alias SET platformId = ? WHERE id = ?";
-            conn.execute(stmt, params![tag_id, alias_id])?;
+            conn.query_row(stmt, params![alias_id, ""], |row| row.get(0))?
         }
+    };
+
+    // Update tag alias with the new tag id
+    stmt = "UPDATE platform_alias SET platformId = ? WHERE id = ?";
+    conn.execute(stmt, params![platform_id, alias_id])?;
+
+    if let Some(date_modified) = date_modified {
+        stmt = "UPDATE platform SET dateModified = ? WHERE id = ?";
+        conn.execute(stmt, params![date_modified, platform_id])?;
     }
 
 
@@ -94,7 +125,7 @@ pub fn find_or_create(conn: &Connection, name: &str, id: Option<i64>) -> Result<
     } else {
         // Clear a lingering alias
         conn.execute("DELETE FROM platform_alias WHERE name = ?", params![name])?;
-        create(conn, name, id)
+        create(conn, name, id, None)
     }
 }
 
@@ -112,6 +143,7 @@ pub fn find_by_name(conn: &Connection, name: &str) -> Result<Option<Tag>> {
             description: row.get(2)?,
             date_modified: row.get(3)?,
             category: None,
+            category_color: None,
             aliases: vec![],
         })
     });
@@ -133,6 +165,20 @@ pub fn find_by_name(conn: &Connection, name: &str) -> Result<Option<Tag>> {
     }
 }
 
+/// Like `find_by_name`, but also returns the alias that matched, as stored
+pub fn find_by_alias(conn: &Connection, name: &str) -> Result<Option<(Tag, String)>> {
+    let alias_result = conn.query_row(
+        "SELECT platformId, name FROM platform_alias WHERE name = ?",
+        params![name],
+        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+    ).optional()?;
+
+    match alias_result {
+        Some((platform_id, matched_alias)) => Ok(find_by_id(conn, platform_id)?.map(|platform| (platform, matched_alias))),
+        None => Ok(None),
+    }
+}
+
 pub fn  find_by_id(conn: &Connection, id: i64) -> Result<Option<Tag>> {
     let mut stmt = conn.prepare(
         "SELECT p.id, pa.name, p.description, p.dateModified FROM platform_alias pa
@@ -146,6 +192,7 @@ pub fn  find_by_id(conn: &Connection, id: i64) -> Result<Option<Tag>> {
             description: row.get(2)?,
             date_modified: row.get(3)?,
             category: None,
+            category_color: None,
             aliases: vec![],
         })
     });
@@ -287,14 +334,22 @@ pub fn delete(conn: &Connection, name: &str) -> Result<()> {
 pub fn search_platform_suggestions(
     conn: &Connection,
     partial: &str,
+    library: Option<&str>,
 ) -> Result<Vec<TagSuggestion>> {
     let mut suggestions = vec![];
 
-    let query = "SELECT sugg.tagId, sugg.matched_alias, count(game_tag.gameId) as gameCount, sugg.primary_alias FROM (
+    // Only count games in the library when one is given
+    let game_platforms = match library {
+        Some(_) => "(SELECT gpp.platformId, gpp.gameId FROM game_platforms_platform gpp JOIN game ON game.id = gpp.gameId WHERE game.library = ?1)",
+        None => "game_platforms_platform",
+    };
+
+    let query = format!("SELECT sugg.tagId, sugg.matched_alias, count(game_tag.gameId) as gameCount, sugg.primary_alias, sugg.is_primary FROM (
         SELECT 
 			ta1.platformId as tagId,
 			ta1.name AS matched_alias,
-			ta2.name AS primary_alias
+			ta2.name AS primary_alias,
+            ta1.id = t.primaryAliasId AS is_primary
 		FROM 
 			platform_alias ta1
 		JOIN 
@@ -302,22 +357,23 @@ pub fn search_platform_suggestions(
 		JOIN 
         platform_alias ta2 ON t.primaryAliasId = ta2.id
 		WHERE 
-			ta1.name LIKE ?
+			ta1.name LIKE ?2
     ) sugg
-    LEFT JOIN game_platforms_platform game_tag ON game_tag.platformId = sugg.tagId
+    LEFT JOIN {} game_tag ON game_tag.platformId = sugg.tagId
     GROUP BY sugg.matched_alias
-    ORDER BY COUNT(game_tag.gameId) DESC, sugg.matched_alias ASC";
+    ORDER BY COUNT(game_tag.gameId) DESC, sugg.matched_alias ASC", game_platforms);
 
     let mut stmt = conn.prepare(&query)?;
     let mut likeable = String::from(partial);
     likeable.push_str("%");
-    let results = stmt.query_map(params![&likeable], |row| {
+    let results = stmt.query_map(params![library, &likeable], |row| {
         Ok(TagSuggestion {
             id: row.get(0)?,
             matched_from: row.get(1)?,
             games_count: row.get(2)?,
             name: row.get(3)?,
             category: None,
+            is_primary_match: row.get(4)?,
         })
     })?;
 
@@ -0,0 +1,38 @@
+use rusqlite::{Connection, Result};
+
+#[cfg_attr(feature = "napi", napi)]
+#[cfg_attr(not(feature = "napi"), derive(Clone))]
+#[derive(Debug, PartialEq)]
+pub enum PerformanceProfile {
+    /// SQLite's own sync, cache and temp storage settings
+    Default,
+    /// Smallest page cache, no memory mapping, temp tables kept on disk and `synchronous=NORMAL`
+    LowMemory,
+    /// 64MB page cache, 256MB memory mapped, temp tables kept in memory and `synchronous=NORMAL`
+    HighPerformance,
+}
+
+/// Apply the per-connection pragmas of `profile`. Run once per pooled connection, pragmas don't carry over between connections.
+pub fn apply(conn: &Connection, profile: &PerformanceProfile) -> Result<()> {
+    conn.pragma_update(None, "foreign_keys", false)?;
+
+    if *profile != PerformanceProfile::Default {
+        // Safe under WAL, a power loss can only roll back the last commits
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+    }
+
+    match profile {
+        PerformanceProfile::Default => (),
+        PerformanceProfile::LowMemory => {
+            conn.pragma_update(None, "cache_size", -512)?;
+            conn.pragma_update(None, "mmap_size", 0)?;
+            conn.pragma_update(None, "temp_store", "FILE")?;
+        }
+        PerformanceProfile::HighPerformance => {
+            conn.pragma_update(None, "cache_size", -64000)?;
+            conn.pragma_update(None, "mmap_size", 268435456)?;
+            conn.pragma_update(None, "temp_store", "MEMORY")?;
+        }
+    }
+    Ok(())
+}
@@ -1,12 +1,16 @@
-use std::rc::Rc;
+use std::{collections::{HashMap, HashSet}, rc::Rc};
 
 use rusqlite::{params, types::Value, Connection, OptionalExtension, Result};
+use snafu::ResultExt;
 
 use crate::{
+    error,
     game::search::mark_index_dirty,
     tag_category, update::SqlVec,
 };
 
+pub mod taxonomy;
+
 #[cfg_attr(feature = "napi", napi(object))]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Debug, Clone)]
@@ -17,6 +21,8 @@ pub struct Tag {
     pub date_modified: String,
     pub aliases: Vec<String>,
     pub category: Option<String>,
+    /// Color of the tag's category, as #RRGGBB
+    pub category_color: Option<String>,
 }
 
 #[cfg_attr(feature = "napi", napi(object))]
@@ -40,6 +46,18 @@ pub struct TagSuggestion {
     pub matched_from: String,
     pub games_count: i64,
     pub category: Option<String>,
+    /// Whether `matched_from` is the primary name rather than a secondary alias
+    pub is_primary_match: bool,
+}
+
+/// Left behind by a merge so the merged away tag id still resolves to the tag it was merged into
+#[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone)]
+pub struct TagRedirect {
+    pub old_tag_id: i64,
+    pub new_tag_id: i64,
+    pub date_added: String,
 }
 
 #[cfg_attr(feature = "napi", napi(object))]
@@ -103,7 +121,7 @@ impl From<Tag> for PartialTag {
 
 pub fn find(conn: &Connection) -> Result<Vec<Tag>> {
     let mut stmt = conn.prepare(
-        "SELECT t.id, ta.name, t.description, t.dateModified, tc.name FROM tag t
+        "SELECT t.id, ta.name, t.description, t.dateModified, tc.name, tc.color FROM tag t
         INNER JOIN tag_alias ta ON ta.id = t.primaryAliasId
         INNER JOIN tag_category tc ON t.categoryId = tc.id
         ORDER BY tc.name, ta.name",
@@ -117,6 +135,7 @@ pub fn find(conn: &Connection) -> Result<Vec<Tag>> {
             date_modified: row.get(3)?,
             aliases: vec![],
             category: row.get(4)?,
+            category_color: row.get(5)?,
         })
     })?;
 
@@ -137,11 +156,50 @@ pub fn find(conn: &Connection) -> Result<Vec<Tag>> {
     Ok(tags)
 }
 
+pub fn find_by_category(conn: &Connection, category: &str) -> Result<Vec<Tag>> {
+    let mut stmt = conn.prepare(
+        "SELECT t.id, ta.name, t.description, t.dateModified, tc.name, tc.color FROM tag t
+        INNER JOIN tag_alias ta ON ta.id = t.primaryAliasId
+        INNER JOIN tag_category tc ON t.categoryId = tc.id
+        WHERE tc.name = ?
+        ORDER BY ta.name",
+    )?;
+
+    let tag_iter = stmt.query_map(params![category], |row| {
+        Ok(Tag {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            date_modified: row.get(3)?,
+            aliases: vec![],
+            category: row.get(4)?,
+            category_color: row.get(5)?,
+        })
+    })?;
+
+    let mut tags = vec![];
+
+    for tag in tag_iter {
+        let mut tag = tag?;
+        let mut tag_alias_stmt =
+            conn.prepare("SELECT ta.name FROM tag_alias ta WHERE ta.tagId = ?")?;
+        let tag_alias_iter = tag_alias_stmt.query_map(params![&tag.id], |row| row.get(0))?;
+
+        for alias in tag_alias_iter {
+            tag.aliases.push(alias?);
+        }
+        tags.push(tag);
+    }
+
+    Ok(tags)
+}
+
 pub fn create(
     conn: &Connection,
     name: &str,
     category: Option<String>,
     id: Option<i64>,
+    date_modified: Option<String>,
 ) -> Result<Tag> {
     // Create the alias
     let mut stmt = "INSERT INTO tag_alias (name, tagId) VALUES(?, ?) RETURNING id";
@@ -154,25 +212,26 @@ pub fn create(
     // Create a new tag
     let alias_id: i64 = conn.query_row(stmt, params![name, -1], |row| row.get(0))?;
 
-    match id {
+    let tag_id = match id {
         Some(id) => {
             stmt =
                 "INSERT INTO tag (id, primaryAliasId, description, categoryId) VALUES (?, ?, ?, ?)";
             conn.execute(stmt, params![id, alias_id, "", category.id])?;
-
-            // Update tag alias with the new tag id
-            stmt = "UPDATE tag_alias SET tagId = ? WHERE id = ?";
-            conn.execute(stmt, params![id, alias_id])?;
+            id
         }
         None => {
             stmt = "INSERT INTO tag (primaryAliasId, description, categoryId) VALUES (?, ?, ?) RETURNING id";
-            let tag_id: i64 =
-                conn.query_row(stmt, params![alias_id, "", category.id], |row| row.get(0))?;
-
-            // Update tag alias with the new tag id
-            stmt = "UPDATE tag_alias SET tagId = ? WHERE id = ?";
-            conn.execute(stmt, params![tag_id, alias_id])?;
+            conn.query_row(stmt, params![alias_id, "", category.id], |row| row.get(0))?
         }
+    };
+
+    // Update tag alias with the new tag id
+    stmt = "UPDATE tag_alias SET tagId = ? WHERE id = ?";
+    conn.execute(stmt, params![tag_id, alias_id])?;
+
+    if let Some(date_modified) = date_modified {
+        stmt = "UPDATE tag SET dateModified = ? WHERE id = ?";
+        conn.execute(stmt, params![date_modified, tag_id])?;
     }
 
     mark_index_dirty(conn)?;
@@ -192,13 +251,13 @@ pub fn find_or_create(conn: &Connection, name: &str) -> Result<Tag> {
     } else {
         // Clear a lingering alias
         conn.execute("DELETE FROM tag_alias WHERE name = ?", params![name])?;
-        create(conn, name, None, None)
+        create(conn, name, None, None, None)
     }
 }
 
 pub fn find_by_name(conn: &Connection, name: &str) -> Result<Option<Tag>> {
     let mut stmt = conn.prepare(
-        "SELECT t.id, ta.name, t.description, t.dateModified, tc.name FROM tag t
+        "SELECT t.id, ta.name, t.description, t.dateModified, tc.name, tc.color FROM tag t
         INNER JOIN tag_alias ta ON t.id = ta.tagId
         INNER JOIN tag_category tc ON t.categoryId = tc.id
         WHERE t.id IN (SELECT alias.tagId FROM tag_alias alias WHERE alias.name = ?)
@@ -213,6 +272,7 @@ pub fn find_by_name(conn: &Connection, name: &str) -> Result<Option<Tag>> {
                 description: row.get(2)?,
                 date_modified: row.get(3)?,
                 category: row.get(4)?,
+                category_color: row.get(5)?,
                 aliases: vec![],
             })
         })
@@ -235,10 +295,10 @@ pub fn find_by_name(conn: &Connection, name: &str) -> Result<Option<Tag>> {
 
 pub fn find_by_id(conn: &Connection, id: i64) -> Result<Option<Tag>> {
     let mut stmt = conn.prepare(
-        "SELECT t.id, ta.name, t.description, t.dateModified, tc.name FROM tag t
+        "SELECT t.id, ta.name, t.description, t.dateModified, tc.name, tc.color FROM tag t
         INNER JOIN tag_alias ta ON t.id = ta.tagId
         INNER JOIN tag_category tc ON t.categoryId = tc.id
-        WHERE t.id = ? AND t.primaryAliasId == ta.id",
+        WHERE t.id = COALESCE((SELECT newTagId FROM tag_redirect WHERE oldTagId = ?1), ?1) AND t.primaryAliasId == ta.id",
     )?;
 
     let tag_result = stmt
@@ -249,6 +309,7 @@ pub fn find_by_id(conn: &Connection, id: i64) -> Result<Option<Tag>> {
                 description: row.get(2)?,
                 date_modified: row.get(3)?,
                 category: row.get(4)?,
+                category_color: row.get(5)?,
                 aliases: vec![],
             })
         })
@@ -273,48 +334,46 @@ pub fn count(conn: &Connection) -> Result<i64> {
     conn.query_row("SELECT COUNT(*) FROM tag", (), |row| row.get::<_, i64>(0))
 }
 
+/// Whether no tag uses `name` as its name or an alias, ignoring case like the alias table
+pub fn name_available(conn: &Connection, name: &str) -> Result<bool> {
+    conn.query_row("SELECT NOT EXISTS (SELECT 1 FROM tag_alias WHERE name = ?)", params![name], |row| row.get::<_, bool>(0))
+}
+
+/// One past the highest tag id, for importers assigning explicit ids
+pub fn next_id(conn: &Connection) -> Result<i64> {
+    conn.query_row("SELECT IFNULL(MAX(id), 0) + 1 FROM tag", (), |row| row.get::<_, i64>(0))
+}
+
 pub fn delete(conn: &Connection, name: &str) -> Result<()> {
     let tag = find_by_name(conn, name)?;
     match tag {
-        Some(tag) => {
-            let mut stmt = "DELETE FROM tag_alias WHERE tagId = ?";
-            conn.execute(stmt, params![tag.id])?;
-
-            stmt = "DELETE FROM tag WHERE id = ?";
-            conn.execute(stmt, params![tag.id])?;
-
-            // Update game tagsStr
-            stmt = "UPDATE game
-            SET tagsStr = (
-                SELECT IFNULL(string_agg(ta.name, '; '), '')
-                FROM game_tags_tag gtt
-                JOIN tag t ON gtt.tagId = t.id
-                JOIN tag_alias ta ON t.primaryAliasId = ta.id
-                WHERE gtt.gameId = game.id
-            ) WHERE game.id IN (
-                SELECT gameId FROM game_tags_tag WHERE tagId = ?   
-            )";
-            conn.execute(stmt, params![tag.id])?;
-
-            stmt = "DELETE FROM game_tags_tag WHERE tagId = ?";
-            conn.execute(stmt, params![tag.id])?;
-
-            mark_index_dirty(conn)?;
-
-            Ok(())
-        }
+        Some(tag) => delete_by_id(conn, tag.id),
         None => Err(rusqlite::Error::QueryReturnedNoRows),
     }
 }
 
 pub fn delete_by_id(conn: &Connection, id: i64) -> Result<()> {
-    let mut stmt = "DELETE FROM tag_alias WHERE tagId = ?";
-    conn.execute(stmt, params![id])?;
+    delete_by_ids(conn, &[id])
+}
 
-    stmt = "DELETE FROM tag WHERE id = ?";
-    conn.execute(stmt, params![id])?;
+/// Also removes redirects to or from the deleted ids, so no redirect resolves to a missing tag
+pub fn delete_by_ids(conn: &Connection, ids: &[i64]) -> Result<()> {
+    if ids.is_empty() {
+        return Ok(());
+    }
 
-    // Update game tagsStr
+    // Allow use of rarray() in SQL queries
+    rusqlite::vtab::array::load_module(conn)?;
+
+    let ids = SqlVec(ids.to_vec());
+
+    let mut stmt = "DELETE FROM tag_alias WHERE tagId IN rarray(?)";
+    conn.execute(stmt, params![ids])?;
+
+    stmt = "DELETE FROM tag WHERE id IN rarray(?)";
+    conn.execute(stmt, params![ids])?;
+
+    // Update game tagsStr once for every affected game
     stmt = "UPDATE game
     SET tagsStr = (
         SELECT IFNULL(string_agg(ta.name, '; '), '')
@@ -323,28 +382,40 @@ pub fn delete_by_id(conn: &Connection, id: i64) -> Result<()> {
         JOIN tag_alias ta ON t.primaryAliasId = ta.id
         WHERE gtt.gameId = game.id
     ) WHERE game.id IN (
-        SELECT gameId FROM game_tags_tag WHERE tagId = ?   
+        SELECT gameId FROM game_tags_tag WHERE tagId IN rarray(?)
     )";
-    conn.execute(stmt, params![id])?;
+    conn.execute(stmt, params![ids])?;
 
-    stmt = "DELETE FROM game_tags_tag WHERE tagId = ?";
-    conn.execute(stmt, params![id])?;
+    stmt = "DELETE FROM game_tags_tag WHERE tagId IN rarray(?)";
+    conn.execute(stmt, params![ids])?;
+
+    stmt = "DELETE FROM tag_redirect WHERE oldTagId IN rarray(?1) OR newTagId IN rarray(?1)";
+    conn.execute(stmt, params![ids])?;
 
     mark_index_dirty(conn)?;
 
     Ok(())
 }
 
-pub fn merge_tag(conn: &Connection, name: &str, merged_into: &str) -> Result<Tag> {
-    let old_tag = match find_by_name(conn, name)? {
-        Some(tag) => tag,
-        None => return Err(rusqlite::Error::QueryReturnedNoRows),
-    };
-    let merged_tag = match find_by_name(conn, merged_into)? {
-        Some(tag) => tag,
-        None => return Err(rusqlite::Error::QueryReturnedNoRows),
-    };
+/// Moves every alias and game of `name` onto `merged_into` and deletes it.
+/// When `redirect_added` is set, a redirect dated with it keeps the old tag id resolving to the merged tag.
+pub fn merge_tag(conn: &Connection, name: &str, merged_into: &str, redirect_added: Option<&str>) -> error::Result<Tag> {
+    let old_tag = find_by_name(conn, name)
+        .and_then(|tag| tag.ok_or(rusqlite::Error::QueryReturnedNoRows))
+        .context(error::SqliteSnafu)?;
+    let merged_tag = find_by_name(conn, merged_into)
+        .and_then(|tag| tag.ok_or(rusqlite::Error::QueryReturnedNoRows))
+        .context(error::SqliteSnafu)?;
+
+    // Merging a tag into itself would delete its own rows part way through
+    if old_tag.id == merged_tag.id {
+        return error::MergeIntoSelfSnafu { name, merged_into }.fail();
+    }
+
+    merge(conn, old_tag, &merged_tag, redirect_added).context(error::SqliteSnafu)
+}
 
+fn merge(conn: &Connection, old_tag: Tag, merged_tag: &Tag, redirect_added: Option<&str>) -> Result<Tag> {
     // Remove future duplicate relations, add relations for all games with the old tag
     let mut stmt = "DELETE FROM game_tags_tag
     WHERE gameId IN (
@@ -356,6 +427,14 @@ pub fn merge_tag(conn: &Connection, name: &str, merged_into: &str) -> Result<Tag
     stmt = "UPDATE game_tags_tag SET tagId = ? WHERE tagId = ?";
     conn.execute(stmt, params![merged_tag.id, old_tag.id])?;
 
+    // Redirects to the old tag would dangle once it's gone, point them at the merged tag
+    stmt = "UPDATE tag_redirect SET newTagId = ? WHERE newTagId = ?";
+    conn.execute(stmt, params![merged_tag.id, old_tag.id])?;
+    if let Some(date_added) = redirect_added {
+        stmt = "INSERT OR REPLACE INTO tag_redirect (oldTagId, newTagId, dateAdded) VALUES (?, ?, ?)";
+        conn.execute(stmt, params![old_tag.id, merged_tag.id, date_added])?;
+    }
+
     // Remove old tag table entries
     stmt = "DELETE FROM tag WHERE id = ?";
     conn.execute(stmt, params![old_tag.id])?;
@@ -383,12 +462,90 @@ pub fn merge_tag(conn: &Connection, name: &str, merged_into: &str) -> Result<Tag
 
     mark_index_dirty(conn)?;
 
-    match find_by_name(conn, merged_into)? {
+    match find_by_id(conn, merged_tag.id)? {
         Some(tag) => Ok(tag),
         None => Err(rusqlite::Error::QueryReturnedNoRows),
     }
 }
 
+pub fn find_redirects(conn: &Connection) -> Result<Vec<TagRedirect>> {
+    let mut stmt = conn.prepare("SELECT oldTagId, newTagId, dateAdded FROM tag_redirect ORDER BY oldTagId")?;
+    let redirect_iter = stmt.query_map((), |row| {
+        Ok(TagRedirect {
+            old_tag_id: row.get(0)?,
+            new_tag_id: row.get(1)?,
+            date_added: row.get(2)?,
+        })
+    })?;
+
+    redirect_iter.collect()
+}
+
+/// Aliases of each tag in one query, tags without any aliases are left out of the map
+pub fn find_aliases_for(conn: &Connection, ids: &[i64]) -> Result<HashMap<i64, Vec<String>>> {
+    // Allow use of rarray() in SQL queries
+    rusqlite::vtab::array::load_module(conn)?;
+
+    let mut stmt = conn.prepare("SELECT tagId, name FROM tag_alias WHERE tagId IN rarray(?) ORDER BY tagId, name")?;
+    let alias_iter = stmt.query_map(params![SqlVec(ids.to_vec())], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+    })?;
+
+    let mut aliases: HashMap<i64, Vec<String>> = HashMap::new();
+    for alias in alias_iter {
+        let (tag_id, name) = alias?;
+        aliases.entry(tag_id).or_default().push(name);
+    }
+
+    Ok(aliases)
+}
+
+/// Every alias paired with the id of the tag that owns it
+pub fn find_all_aliases(conn: &Connection) -> Result<Vec<LooseTagAlias>> {
+    let mut stmt = conn.prepare("SELECT tagId, name FROM tag_alias ORDER BY name, tagId")?;
+    let alias_iter = stmt.query_map((), |row| {
+        Ok(LooseTagAlias {
+            id: row.get(0)?,
+            value: row.get(1)?,
+        })
+    })?;
+
+    alias_iter.collect()
+}
+
+/// A page of aliases ordered by name, optionally restricted to names starting with `prefix` (case insensitive)
+pub fn find_aliases_page(conn: &Connection, prefix: Option<&str>, limit: i64, offset: i64) -> Result<Vec<LooseTagAlias>> {
+    let pattern = match prefix {
+        Some(prefix) => format!("{}%", prefix.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")),
+        None => "%".to_owned(),
+    };
+
+    let mut stmt = conn.prepare("SELECT tagId, name FROM tag_alias WHERE name LIKE ? ESCAPE '\\'
+        ORDER BY name, tagId LIMIT ? OFFSET ?")?;
+    let alias_iter = stmt.query_map(params![pattern, limit, offset], |row| {
+        Ok(LooseTagAlias {
+            id: row.get(0)?,
+            value: row.get(1)?,
+        })
+    })?;
+
+    alias_iter.collect()
+}
+
+pub fn set_category_for_tags(conn: &Connection, tag_ids: &[i64], category: &str) -> Result<()> {
+    // Allow use of rarray() in SQL queries
+    rusqlite::vtab::array::load_module(conn)?;
+
+    let category = tag_category::find_or_create(conn, category, None)?;
+
+    let stmt = "UPDATE tag SET categoryId = ? WHERE id IN rarray(?)";
+    conn.execute(stmt, params![category.id, SqlVec(tag_ids.to_vec())])?;
+
+    mark_index_dirty(conn)?;
+
+    Ok(())
+}
+
 pub fn save(conn: &Connection, partial: &PartialTag) -> Result<Tag> {
     // Allow use of rarray() in SQL queries
     rusqlite::vtab::array::load_module(conn)?;
@@ -477,11 +634,32 @@ pub fn save(conn: &Connection, partial: &PartialTag) -> Result<Tag> {
     }
 }
 
+/// Shorter partials return no tag suggestions by default, one character matches most aliases
+pub const DEFAULT_SUGGESTION_MIN_LENGTH: usize = 2;
+/// Most ranked alias rows a tag suggestion search returns by default
+pub const DEFAULT_SUGGESTION_LIMIT: i64 = 100;
+
+/// Exact matches rank first, then by games count, with primary names above aliases at equal counts.
+///
+/// `include_alias_matches` - Return a row for every matching alias instead of only the best one per tag
+///
+/// `min_length` - Return nothing for partials of fewer characters instead of scanning every alias
+///
+/// `limit` - Most alias rows kept after grouping and ranking, every matching alias is still scanned.
+/// Fewer suggestions are returned when several kept rows belong to the same tag
 pub fn search_tag_suggestions(
     conn: &Connection,
     partial: &str,
     blacklist: Vec<String>,
+    include_alias_matches: bool,
+    library: Option<&str>,
+    min_length: usize,
+    limit: i64,
 ) -> Result<Vec<TagSuggestion>> {
+    if partial.chars().count() < min_length {
+        return Ok(vec![]);
+    }
+
     // Allow use of rarray() in SQL queries
     rusqlite::vtab::array::load_module(conn)?;
 
@@ -489,12 +667,20 @@ pub fn search_tag_suggestions(
 
     let mut suggestions = vec![];
 
-    let query = "SELECT sugg.tagId, sugg.matched_alias, count(game_tag.gameId) as gameCount, sugg.primary_alias, sugg.category FROM (
+    // Only count games in the library when one is given
+    let game_tags = match library {
+        Some(_) => "(SELECT gtt.tagId, gtt.gameId FROM game_tags_tag gtt JOIN game ON game.id = gtt.gameId WHERE game.library = ?3)",
+        None => "game_tags_tag",
+    };
+
+    let query = format!("SELECT sugg.tagId, sugg.matched_alias, count(game_tag.gameId) as gameCount, sugg.primary_alias, sugg.category, sugg.is_primary FROM (
         SELECT 
 			ta1.tagId as tagId,
 			ta1.name AS matched_alias,
 			ta2.name AS primary_alias,
-            cat.name as category
+            cat.name as category,
+            ta1.id = t.primaryAliasId AS is_primary,
+            ta1.name = ?1 AS is_exact
 		FROM 
 			tag_alias ta1
 		JOIN 
@@ -504,30 +690,37 @@ pub fn search_tag_suggestions(
         JOIN 
             tag_category cat ON t.categoryId = cat.id
 		WHERE 
-			ta1.name LIKE ?
+			ta1.name LIKE ?2
     ) sugg
-    LEFT JOIN game_tags_tag game_tag ON game_tag.tagId = sugg.tagId
+    LEFT JOIN {} game_tag ON game_tag.tagId = sugg.tagId
     WHERE sugg.tagId NOT IN (
-        SELECT tagId FROM tag_alias WHERE name IN rarray(?)
+        SELECT tagId FROM tag_alias WHERE name IN rarray(?4)
     )
     GROUP BY sugg.matched_alias
-    ORDER BY COUNT(game_tag.gameId) DESC, sugg.matched_alias ASC";
+    ORDER BY sugg.is_exact DESC, COUNT(game_tag.gameId) DESC, sugg.is_primary DESC, sugg.matched_alias ASC
+    LIMIT ?5", game_tags);
 
     let mut stmt = conn.prepare(&query)?;
     let mut likeable = String::from(partial);
     likeable.push_str("%");
-    let results = stmt.query_map(params![&likeable, blacklist], |row| {
+    let results = stmt.query_map(params![partial, &likeable, library, blacklist, limit], |row| {
         Ok(TagSuggestion {
             id: row.get(0)?,
             matched_from: row.get(1)?,
             games_count: row.get(2)?,
             name: row.get(3)?,
             category: row.get(4)?,
+            is_primary_match: row.get(5)?,
         })
     })?;
 
+    // Rows are ranked already, so the first one seen for a tag is its best match
+    let mut seen_tags = HashSet::new();
     for sugg in results {
-        suggestions.push(sugg?);
+        let sugg = sugg?;
+        if include_alias_matches || seen_tags.insert(sugg.id) {
+            suggestions.push(sugg);
+        }
     }
 
     Ok(suggestions)
@@ -0,0 +1,263 @@
+use std::collections::HashSet;
+
+use rusqlite::{params, Connection, OptionalExtension, Result};
+
+use crate::{
+    game::search::mark_index_dirty,
+    tag_category::{self, PartialTagCategory},
+    update::SqlVec,
+};
+
+use super::{create, delete_by_id, find, find_by_id};
+
+/// Categories and tags without any game data, for exchanging taxonomy proposals
+#[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone)]
+pub struct TagTaxonomy {
+    pub categories: Vec<TaxonomyCategory>,
+    pub tags: Vec<TaxonomyTag>,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone)]
+pub struct TaxonomyCategory {
+    pub name: String,
+    pub color: String,
+    pub description: Option<String>,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone)]
+pub struct TaxonomyTag {
+    pub name: String,
+    pub description: String,
+    pub category: String,
+    /// Aliases besides the primary name
+    pub aliases: Vec<String>,
+}
+
+#[cfg_attr(feature = "napi", napi)]
+#[cfg_attr(not(feature = "napi"), derive(Clone))]
+#[derive(Debug, PartialEq)]
+pub enum ImportMode {
+    /// Add missing tags and aliases, local values win on disagreement
+    MergeKeepLocal,
+    /// Add missing tags and aliases, incoming values win on disagreement
+    MergeTakeIncoming,
+    /// Make the local taxonomy match the incoming one, tags still used by games are kept
+    ReplaceAll,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    pub created_categories: Vec<String>,
+    pub created_tags: Vec<String>,
+    pub updated_tags: Vec<String>,
+    /// Tags where the local and incoming name, category or description disagreed
+    pub conflicts: Vec<String>,
+    /// Incoming aliases left on the local tag that already owned them
+    pub skipped_aliases: Vec<String>,
+    pub deleted_tags: Vec<String>,
+    /// Tags missing from the incoming taxonomy which were kept because games still use them
+    pub retained_tags: Vec<String>,
+}
+
+pub fn export(conn: &Connection) -> Result<TagTaxonomy> {
+    let categories = tag_category::find(conn)?
+        .into_iter()
+        .map(|tc| TaxonomyCategory {
+            name: tc.name,
+            color: tc.color,
+            description: tc.description,
+        })
+        .collect();
+
+    let tags = find(conn)?
+        .into_iter()
+        .map(|t| TaxonomyTag {
+            aliases: t.aliases.into_iter().filter(|a| a != &t.name).collect(),
+            name: t.name,
+            description: t.description,
+            category: t.category.unwrap_or_else(|| "default".to_owned()),
+        })
+        .collect();
+
+    Ok(TagTaxonomy { categories, tags })
+}
+
+/// Expected to run inside a transaction, a failure part way through leaves a partial import
+pub fn import(conn: &Connection, taxonomy: &TagTaxonomy, mode: ImportMode) -> Result<ImportReport> {
+    // Allow use of rarray() in SQL queries
+    rusqlite::vtab::array::load_module(conn)?;
+
+    let mut report = ImportReport::default();
+    let take_incoming = mode != ImportMode::MergeKeepLocal;
+
+    for category in &taxonomy.categories {
+        // Incoming colors get the same treatment as the migration gave stored ones
+        let color = tag_category::normalize_color(&category.color).unwrap_or_else(|| "#FFFFFF".to_owned());
+        match tag_category::find_by_name(conn, &category.name)? {
+            Some(existing) => {
+                if take_incoming {
+                    tag_category::update(conn, &PartialTagCategory {
+                        id: existing.id,
+                        name: category.name.clone(),
+                        color,
+                        description: category.description.clone(),
+                        ordering: None,
+                    })?;
+                }
+            }
+            None => {
+                tag_category::insert(conn, &PartialTagCategory {
+                    id: -1,
+                    name: category.name.clone(),
+                    color,
+                    description: category.description.clone(),
+                    ordering: None,
+                })?;
+                report.created_categories.push(category.name.clone());
+            }
+        }
+    }
+
+    let mut matched: HashSet<i64> = HashSet::new();
+    let mut affected: Vec<i64> = vec![];
+
+    for incoming in &taxonomy.tags {
+        if tag_category::find_by_name(conn, &incoming.category)?.is_none() {
+            tag_category::find_or_create(conn, &incoming.category, None)?;
+            report.created_categories.push(incoming.category.clone());
+        }
+
+        // Match on the primary name first, then any alias
+        let mut local_id = find_alias_owner(conn, &incoming.name)?;
+        if local_id.is_none() {
+            for alias in &incoming.aliases {
+                local_id = find_alias_owner(conn, alias)?;
+                if local_id.is_some() {
+                    break;
+                }
+            }
+        }
+
+        let tag_id = match local_id {
+            Some(id) => {
+                let local = match find_by_id(conn, id)? {
+                    Some(t) => t,
+                    None => return Err(rusqlite::Error::QueryReturnedNoRows),
+                };
+                let disagrees = local.name != incoming.name
+                    || local.description != incoming.description
+                    || local.category.as_deref() != Some(incoming.category.as_str());
+                if disagrees {
+                    report.conflicts.push(incoming.name.clone());
+                    if take_incoming {
+                        let stmt = "UPDATE tag SET description = ?, categoryId = (SELECT id FROM tag_category WHERE name = ?) WHERE id = ?";
+                        conn.execute(stmt, params![incoming.description, incoming.category, id])?;
+                        report.updated_tags.push(incoming.name.clone());
+                    }
+                }
+                id
+            }
+            None => {
+                let tag = create(conn, &incoming.name, Some(incoming.category.clone()), None, None)?;
+                conn.execute("UPDATE tag SET description = ? WHERE id = ?", params![incoming.description, tag.id])?;
+                report.created_tags.push(incoming.name.clone());
+                tag.id
+            }
+        };
+        matched.insert(tag_id);
+        affected.push(tag_id);
+
+        let mut names = vec![incoming.name.clone()];
+        names.extend(incoming.aliases.iter().cloned());
+        for alias in &names {
+            match find_alias_owner(conn, alias)? {
+                Some(owner_id) if owner_id == tag_id => (),
+                Some(owner_id) => {
+                    // Never take the primary alias of another tag, it would be left nameless
+                    let is_primary: bool = conn.query_row(
+                        "SELECT EXISTS (SELECT 1 FROM tag t JOIN tag_alias ta ON ta.id = t.primaryAliasId WHERE t.id = ? AND ta.name = ?)",
+                        params![owner_id, alias],
+                        |row| row.get(0),
+                    )?;
+                    if take_incoming && !is_primary {
+                        conn.execute("UPDATE tag_alias SET tagId = ? WHERE name = ?", params![tag_id, alias])?;
+                        affected.push(owner_id);
+                    } else {
+                        report.skipped_aliases.push(alias.clone());
+                    }
+                }
+                None => {
+                    conn.execute("INSERT INTO tag_alias (name, tagId) VALUES (?, ?)", params![alias, tag_id])?;
+                }
+            }
+        }
+
+        if take_incoming {
+            let primary_alias_id: Option<i64> = conn
+                .query_row(
+                    "SELECT id FROM tag_alias WHERE name = ? AND tagId = ?",
+                    params![incoming.name, tag_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if let Some(alias_id) = primary_alias_id {
+                conn.execute("UPDATE tag SET primaryAliasId = ? WHERE id = ?", params![alias_id, tag_id])?;
+            }
+        }
+
+        if mode == ImportMode::ReplaceAll {
+            let stmt = "DELETE FROM tag_alias WHERE tagId = ? AND name NOT IN rarray(?)
+            AND id != (SELECT primaryAliasId FROM tag WHERE id = ?)";
+            conn.execute(stmt, params![tag_id, SqlVec(names), tag_id])?;
+        }
+    }
+
+    if mode == ImportMode::ReplaceAll {
+        for tag in find(conn)? {
+            if matched.contains(&tag.id) {
+                continue;
+            }
+            let games_count: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM game_tags_tag WHERE tagId = ?",
+                params![tag.id],
+                |row| row.get(0),
+            )?;
+            if games_count > 0 {
+                report.retained_tags.push(tag.name);
+            } else {
+                delete_by_id(conn, tag.id)?;
+                report.deleted_tags.push(tag.name);
+            }
+        }
+    }
+
+    // Update game tagsStr fields
+    let stmt = "UPDATE game
+    SET tagsStr = (
+        SELECT IFNULL(string_agg(ta.name, '; '), '')
+        FROM game_tags_tag gtt
+        JOIN tag t ON gtt.tagId = t.id
+        JOIN tag_alias ta ON t.primaryAliasId = ta.id
+        WHERE gtt.gameId = game.id
+    ) WHERE game.id IN (
+        SELECT gameId FROM game_tags_tag WHERE tagId IN rarray(?)
+    )";
+    conn.execute(stmt, params![SqlVec(affected)])?;
+
+    mark_index_dirty(conn)?;
+
+    Ok(report)
+}
+
+fn find_alias_owner(conn: &Connection, name: &str) -> Result<Option<i64>> {
+    conn.query_row("SELECT tagId FROM tag_alias WHERE name = ?", params![name], |row| row.get(0))
+        .optional()
+}
@@ -1,4 +1,7 @@
 use rusqlite::{Connection, Result, params, OptionalExtension};
+use snafu::ResultExt;
+
+use crate::error;
 
 #[cfg_attr(feature = "napi", napi(object))]
 #[derive(Debug, Clone)]
@@ -7,6 +10,8 @@ pub struct TagCategory {
     pub name: String,
     pub color: String,
     pub description: Option<String>,
+    /// Display position in tag sidebars, lower values come first
+    pub ordering: i64,
 }
 
 
@@ -16,7 +21,8 @@ pub struct PartialTagCategory {
     pub id: i64,
     pub name: String,
     pub color: String,
-    pub description: Option<String>
+    pub description: Option<String>,
+    pub ordering: Option<i64>,
 }
 
 impl TagCategory {
@@ -27,6 +33,10 @@ impl TagCategory {
         if let Some(description) = partial.description.clone() {
             self.description = Some(description);
         }
+
+        if let Some(ordering) = partial.ordering {
+            self.ordering = ordering;
+        }
     }
 }
 
@@ -36,14 +46,15 @@ impl From<&PartialTagCategory> for TagCategory {
             id: -1,
             name: value.name.clone(),
             color: value.color.clone(),
-            description: value.description.clone()
+            description: value.description.clone(),
+            ordering: value.ordering.unwrap_or_default(),
         }
     }
 }
 
 pub fn find(conn: &Connection) -> Result<Vec<TagCategory>> {
     let mut stmt = conn.prepare(
-        "SELECT id, name, color, description FROM tag_category"
+        "SELECT id, name, color, description, ordering FROM tag_category ORDER BY ordering, name"
     )?;
 
     let tag_category_iter = stmt.query_map((), |row| {
@@ -52,6 +63,7 @@ pub fn find(conn: &Connection) -> Result<Vec<TagCategory>> {
             name: row.get(1)?,
             color: row.get(2)?,
             description: row.get(3)?,
+            ordering: row.get(4)?,
         })
     })?;
 
@@ -64,7 +76,7 @@ pub fn find(conn: &Connection) -> Result<Vec<TagCategory>> {
 
 pub fn find_by_id(conn: &Connection, id: i64) -> Result<Option<TagCategory>> {
     let mut stmt = conn.prepare(
-        "SELECT id, name, color, description FROM tag_category WHERE id = ?"
+        "SELECT id, name, color, description, ordering FROM tag_category WHERE id = ?"
     )?;
 
     Ok(stmt.query_row(params![id], |row| {
@@ -73,13 +85,14 @@ pub fn find_by_id(conn: &Connection, id: i64) -> Result<Option<TagCategory>> {
             name: row.get(1)?,
             color: row.get(2)?,
             description: row.get(3)?,
+            ordering: row.get(4)?,
         })
     }).optional()?)
 }
 
 pub fn find_by_name(conn: &Connection, name: &str) -> Result<Option<TagCategory>> {
     let mut stmt = conn.prepare(
-        "SELECT id, name, color, description FROM tag_category WHERE name = ?"
+        "SELECT id, name, color, description, ordering FROM tag_category WHERE name = ?"
     )?;
 
     Ok(stmt.query_row(params![name], |row| {
@@ -88,11 +101,36 @@ pub fn find_by_name(conn: &Connection, name: &str) -> Result<Option<TagCategory>
             name: row.get(1)?,
             color: row.get(2)?,
             description: row.get(3)?,
+            ordering: row.get(4)?,
         })
     }).optional()?)
 }
 
 
+pub fn find_by_color(conn: &Connection, color: &str) -> Result<Vec<TagCategory>> {
+    let color = normalize_color(color).unwrap_or_else(|| color.to_owned());
+    let mut stmt = conn.prepare(
+        "SELECT id, name, color, description, ordering FROM tag_category WHERE color = ? ORDER BY name"
+    )?;
+
+    let tag_category_iter = stmt.query_map(params![color], |row| {
+        Ok(TagCategory{
+            id: row.get(0)?,
+            name: row.get(1)?,
+            color: row.get(2)?,
+            description: row.get(3)?,
+            ordering: row.get(4)?,
+        })
+    })?;
+
+    let mut tag_cats = vec![];
+    for tc in tag_category_iter {
+        tag_cats.push(tc?);
+    }
+    Ok(tag_cats)
+}
+
+/// Invalid or missing colors fall back to white, so this never fails validation
 pub fn find_or_create(conn: &Connection, name: &str, color: Option<String>) -> Result<TagCategory> {
     let tag_category_result = find_by_name(conn, name)?;
 
@@ -102,25 +140,70 @@ pub fn find_or_create(conn: &Connection, name: &str, color: Option<String>) -> R
             let new_tag_category = PartialTagCategory {
                 id: -1,
                 name: name.to_owned(),
-                color: color.unwrap_or_else(|| "#FFFFFF".to_owned()),
+                color: color.and_then(|c| normalize_color(&c)).unwrap_or_else(|| "#FFFFFF".to_owned()),
                 description: None,
+                ordering: None,
             };
 
-            Ok(create(conn, &new_tag_category)?)
+            Ok(insert(conn, &new_tag_category)?)
         }
     }
 }
 
-pub fn create(conn: &Connection, partial: &PartialTagCategory) -> Result<TagCategory> {
+/// Accepts #RGB and #RRGGBB, returns the color as uppercase #RRGGBB
+pub fn normalize_color(color: &str) -> Option<String> {
+    let hex = color.strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    match hex.len() {
+        3 => Some(format!("#{}", hex.chars().flat_map(|c| [c, c]).collect::<String>().to_uppercase())),
+        6 => Some(format!("#{}", hex.to_uppercase())),
+        _ => None,
+    }
+}
+
+fn validate(partial: &PartialTagCategory) -> error::Result<PartialTagCategory> {
+    match normalize_color(&partial.color) {
+        Some(color) => Ok(PartialTagCategory { color, ..partial.clone() }),
+        None => error::InvalidColorSnafu { color: partial.color.clone() }.fail(),
+    }
+}
+
+pub fn create(conn: &Connection, partial: &PartialTagCategory) -> error::Result<TagCategory> {
+    let partial = validate(partial)?;
+    insert(conn, &partial).context(error::SqliteSnafu)
+}
+
+/// Unvalidated insert, callers are expected to have normalized the color already
+pub(crate) fn insert(conn: &Connection, partial: &PartialTagCategory) -> Result<TagCategory> {
     let mut new_tag_category: TagCategory = partial.into();
     let mut stmt = conn.prepare(
-        "INSERT INTO tag_category (name, color, description) VALUES (?, ?, ?) RETURNING id"
+        "INSERT INTO tag_category (name, color, description, ordering) VALUES (?, ?, ?, ?) RETURNING id"
     )?;
-    new_tag_category.id = stmt.query_row(params![new_tag_category.name, new_tag_category.color, new_tag_category.description], |row| row.get(0))?;
+    new_tag_category.id = stmt.query_row(params![new_tag_category.name, new_tag_category.color, new_tag_category.description, new_tag_category.ordering], |row| row.get(0))?;
+    Ok(new_tag_category)
+}
+
+pub fn create_with_id(conn: &Connection, partial: &PartialTagCategory) -> error::Result<TagCategory> {
+    let partial = validate(partial)?;
+    let mut new_tag_category: TagCategory = (&partial).into();
+    new_tag_category.id = partial.id;
+    let mut stmt = conn.prepare(
+        "INSERT INTO tag_category (id, name, color, description, ordering) VALUES (?, ?, ?, ?, ?)"
+    ).context(error::SqliteSnafu)?;
+    stmt.execute(params![new_tag_category.id, new_tag_category.name, new_tag_category.color, new_tag_category.description, new_tag_category.ordering])
+        .context(error::SqliteSnafu)?;
     Ok(new_tag_category)
 }
 
-pub fn save(conn: &Connection, partial: &PartialTagCategory) -> Result<TagCategory> {
+pub fn save(conn: &Connection, partial: &PartialTagCategory) -> error::Result<TagCategory> {
+    let partial = validate(partial)?;
+    update(conn, &partial).context(error::SqliteSnafu)
+}
+
+/// Unvalidated update, callers are expected to have normalized the color already
+pub(crate) fn update(conn: &Connection, partial: &PartialTagCategory) -> Result<TagCategory> {
     let mut tag_category = match find_by_id(conn, partial.id)? {
         Some(tc) => tc,
         None => return Err(rusqlite::Error::QueryReturnedNoRows)
@@ -128,8 +211,17 @@ pub fn save(conn: &Connection, partial: &PartialTagCategory) -> Result<TagCatego
 
     tag_category.apply_partial(partial);
 
-    let mut stmt = conn.prepare("UPDATE tag_category SET name = ?, color = ?, description = ? WHERE id = ?")?;
-    stmt.execute(params![&tag_category.name, &tag_category.color, &tag_category.description, &tag_category.id])?;
+    let mut stmt = conn.prepare("UPDATE tag_category SET name = ?, color = ?, description = ?, ordering = ? WHERE id = ?")?;
+    stmt.execute(params![&tag_category.name, &tag_category.color, &tag_category.description, &tag_category.ordering, &tag_category.id])?;
 
     Ok(tag_category)
+}
+
+/// Assigns sequential orderings following `ordered_ids`, categories not listed keep their current ordering
+pub fn reorder(conn: &Connection, ordered_ids: &[i64]) -> Result<()> {
+    let mut stmt = conn.prepare("UPDATE tag_category SET ordering = ? WHERE id = ?")?;
+    for (idx, id) in ordered_ids.iter().enumerate() {
+        stmt.execute(params![idx as i64, id])?;
+    }
+    Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,124 @@
+use rusqlite::Connection;
+use snafu::ResultExt;
+
+use crate::{
+    clock::Clock,
+    error::{self, Result},
+    game::{self, AdditionalApp, Game, PartialGame},
+    game_data::{self, GameData, PartialGameData},
+    platform,
+    tag::{self, PartialTag, Tag},
+    user_data::{self, GameUserData, PartialGameUserData},
+};
+
+/// Handle given to `FlashpointArchive::transaction`. Every call shares one transaction,
+/// which is committed when the closure returns Ok and rolled back when it returns Err.
+pub struct ArchiveTransaction<'a> {
+    conn: &'a Connection,
+    clock: &'a dyn Clock,
+}
+
+impl<'a> ArchiveTransaction<'a> {
+    pub(crate) fn new(conn: &'a Connection, clock: &'a dyn Clock) -> Self {
+        ArchiveTransaction { conn, clock }
+    }
+
+    pub fn find_game(&self, id: &str) -> Result<Option<Game>> {
+        game::find(self.conn, id).context(error::SqliteSnafu)
+    }
+
+    pub fn create_game(&self, partial_game: &PartialGame) -> Result<Game> {
+        let mut partial_game = partial_game.clone();
+        let now = self.clock.canonical_now();
+        if partial_game.date_added.is_none() {
+            partial_game.date_added = Some(now.clone());
+        }
+        if partial_game.date_modified.is_none() {
+            partial_game.date_modified = Some(now);
+        }
+        game::create(self.conn, &partial_game).context(error::SqliteSnafu)
+    }
+
+    pub fn save_game(&self, partial_game: &mut PartialGame) -> Result<Game> {
+        if partial_game.date_modified.is_none() {
+            partial_game.date_modified = Some(self.clock.canonical_now());
+        }
+        game::save(self.conn, partial_game)
+    }
+
+    pub fn delete_game(&self, id: &str) -> Result<()> {
+        game::delete(self.conn, id).context(error::SqliteSnafu)
+    }
+
+    pub fn create_add_app(&self, add_app: &mut AdditionalApp) -> Result<()> {
+        game::create_add_app(self.conn, add_app).context(error::SqliteSnafu)
+    }
+
+    pub fn create_game_data(&self, game_data: &PartialGameData) -> Result<GameData> {
+        let mut game_data = game_data.clone();
+        if game_data.date_added.is_none() {
+            game_data.date_added = Some(self.clock.canonical_now());
+        }
+        game::create_game_data(self.conn, &game_data).context(error::SqliteSnafu)
+    }
+
+    pub fn save_game_data(&self, game_data: &PartialGameData) -> Result<GameData> {
+        game::save_game_data(self.conn, game_data).context(error::SqliteSnafu)
+    }
+
+    pub fn delete_game_data(&self, id: i64) -> Result<()> {
+        game_data::delete(self.conn, id).context(error::SqliteSnafu)
+    }
+
+    pub fn add_game_playtime(&self, game_id: &str, seconds: i64) -> Result<()> {
+        game::add_playtime(self.conn, game_id, seconds, &self.clock.canonical_now()).context(error::SqliteSnafu)
+    }
+
+    pub fn create_game_redirect(&self, src_id: &str, dest_id: &str) -> Result<()> {
+        game::create_redirect(self.conn, src_id, dest_id).context(error::SqliteSnafu)
+    }
+
+    pub fn delete_game_redirect(&self, src_id: &str, dest_id: &str) -> Result<()> {
+        game::delete_redirect(self.conn, src_id, dest_id).context(error::SqliteSnafu)
+    }
+
+    pub fn create_tag(&self, name: &str, category: Option<String>, id: Option<i64>) -> Result<Tag> {
+        tag::create(self.conn, name, category, id, Some(self.clock.canonical_now())).context(error::SqliteSnafu)
+    }
+
+    pub fn save_tag(&self, partial: &mut PartialTag) -> Result<Tag> {
+        if partial.date_modified.is_none() {
+            partial.date_modified = Some(self.clock.canonical_now());
+        }
+        tag::save(self.conn, partial).context(error::SqliteSnafu)
+    }
+
+    pub fn delete_tag(&self, name: &str) -> Result<()> {
+        tag::delete(self.conn, name).context(error::SqliteSnafu)
+    }
+
+    /// `record_merge` defaults to true, leaving a redirect from the old tag id to the merged tag
+    pub fn merge_tags(&self, name: &str, merged_into: &str, record_merge: Option<bool>) -> Result<Tag> {
+        let redirect_added = record_merge.unwrap_or(true).then(|| self.clock.canonical_now());
+        tag::merge_tag(self.conn, name, merged_into, redirect_added.as_deref())
+    }
+
+    pub fn create_platform(&self, name: &str, id: Option<i64>) -> Result<Tag> {
+        platform::create(self.conn, name, id, Some(self.clock.canonical_now())).context(error::SqliteSnafu)
+    }
+
+    pub fn save_platform(&self, partial: &mut PartialTag) -> Result<Tag> {
+        if partial.date_modified.is_none() {
+            partial.date_modified = Some(self.clock.canonical_now());
+        }
+        platform::save(self.conn, partial).context(error::SqliteSnafu)
+    }
+
+    pub fn delete_platform(&self, name: &str) -> Result<()> {
+        platform::delete(self.conn, name).context(error::SqliteSnafu)
+    }
+
+    pub fn set_user_data(&self, partial: &PartialGameUserData) -> Result<GameUserData> {
+        user_data::set(self.conn, partial, &self.clock.canonical_now()).context(error::SqliteSnafu)
+    }
+}
@@ -1,7 +1,7 @@
 use std::rc::Rc;
 
 use rusqlite::types::{ToSqlOutput, Value};
-use rusqlite::{params, Connection, ToSql};
+use rusqlite::{params, Connection, OptionalExtension, ToSql};
 use snafu::ResultExt;
 use uuid::Uuid;
 
@@ -145,6 +145,24 @@ pub struct RemotePlatform {
     pub deleted: bool,
 }
 
+/// A local platform folded into the incoming platform that owns one of its aliases
+#[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone)]
+pub struct PlatformMerge {
+    pub local_id: i64,
+    pub remote_id: i64,
+    /// The colliding alias
+    pub name: String,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Default)]
+pub struct ApplyPlatformsReport {
+    pub merged_platforms: Vec<PlatformMerge>,
+}
+
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Debug)]
 pub struct Alias {
@@ -152,7 +170,7 @@ pub struct Alias {
     value: String,
 }
 
-pub fn apply_platforms(conn: &Connection, platforms: Vec<RemotePlatform>) -> Result<()> {
+pub fn apply_platforms(conn: &Connection, platforms: Vec<RemotePlatform>) -> Result<ApplyPlatformsReport> {
     // Allow use of rarray() in SQL queries
     rusqlite::vtab::array::load_module(conn).context(error::SqliteSnafu)?;
     
@@ -161,7 +179,10 @@ pub fn apply_platforms(conn: &Connection, platforms: Vec<RemotePlatform>) -> Res
         .flat_map(|cur| cur.aliases.iter().map(move |alias| Alias { id: cur.id, value: alias.clone() }))
         .collect();
 
-    let existing_platforms = platform::find(conn).context(error::SqliteSnafu)?;
+    // Local platforms sharing an alias with an incoming one under another id would lose it below, merge them in instead
+    let report = merge_colliding_platforms(conn, &platforms)?;
+
+    let existing_platforms = platform::find(conn, false).context(error::SqliteSnafu)?;
     let existing_ids: std::collections::HashSet<i64> = existing_platforms.iter().map(|p| p.id).collect();
 
     // Delete old platform aliases
@@ -221,7 +242,53 @@ pub fn apply_platforms(conn: &Connection, platforms: Vec<RemotePlatform>) -> Res
         insert_platform_stmt.execute(params![platform.id, platform.date_modified, platform.name, platform.description]).context(error::SqliteSnafu)?;
     }
 
-    Ok(())
+    if !report.merged_platforms.is_empty() {
+        // Games moved by a merge still list the local platform's name
+        let remote_ids = SqlVec(report.merged_platforms.iter().map(|m| m.remote_id).collect::<Vec<i64>>());
+        let mut stmt = conn.prepare("SELECT DISTINCT gameId FROM game_platforms_platform WHERE platformId IN rarray(?)").context(error::SqliteSnafu)?;
+        let game_ids = stmt.query_map(params![remote_ids], |row| row.get(0))
+            .context(error::SqliteSnafu)?
+            .collect::<rusqlite::Result<Vec<String>>>()
+            .context(error::SqliteSnafu)?;
+        game::rebuild_denormalized(conn, &game_ids).context(error::SqliteSnafu)?;
+    }
+
+    Ok(report)
+}
+
+/// Moves the games of each local platform owning an incoming alias under a different id over to the incoming platform,
+/// then removes the local platform. Aliases moving between two incoming platforms aren't collisions.
+fn merge_colliding_platforms(conn: &Connection, platforms: &[RemotePlatform]) -> Result<ApplyPlatformsReport> {
+    let incoming_ids: std::collections::HashSet<i64> = platforms.iter().map(|p| p.id).collect();
+    let mut report = ApplyPlatformsReport::default();
+
+    let mut owner_stmt = conn.prepare("SELECT platformId FROM platform_alias WHERE name = ?").context(error::SqliteSnafu)?;
+    for platform in platforms.iter().filter(|p| !p.deleted) {
+        for alias in &platform.aliases {
+            let owner = owner_stmt.query_row(params![alias], |row| row.get::<_, Option<i64>>(0))
+                .optional()
+                .context(error::SqliteSnafu)?
+                .flatten();
+            let local_id = match owner {
+                Some(id) if !incoming_ids.contains(&id) => id,
+                _ => continue,
+            };
+
+            conn.execute("INSERT OR IGNORE INTO game_platforms_platform (gameId, platformId)
+                SELECT gameId, ? FROM game_platforms_platform WHERE platformId = ?", params![platform.id, local_id]).context(error::SqliteSnafu)?;
+            conn.execute("DELETE FROM game_platforms_platform WHERE platformId = ?", params![local_id]).context(error::SqliteSnafu)?;
+            conn.execute("DELETE FROM platform_alias WHERE platformId = ?", params![local_id]).context(error::SqliteSnafu)?;
+            conn.execute("DELETE FROM platform WHERE id = ?", params![local_id]).context(error::SqliteSnafu)?;
+
+            report.merged_platforms.push(PlatformMerge {
+                local_id,
+                remote_id: platform.id,
+                name: alias.clone(),
+            });
+        }
+    }
+
+    Ok(report)
 }
 
 pub fn apply_categories(conn: &Connection, categories: Vec<RemoteCategory>) -> Result<()> {
@@ -231,14 +298,17 @@ pub fn apply_categories(conn: &Connection, categories: Vec<RemoteCategory>) -> R
     let mut update_stmt = conn.prepare("UPDATE tag_category SET description = ?, color = ?, name = ? WHERE id = ?").context(error::SqliteSnafu)?;
     let mut insert_stmt = conn.prepare("INSERT INTO tag_category (id, description, color, name) VALUES (?, ?, ?, ?)").context(error::SqliteSnafu)?;
 
-    // Handle updated platforms
+    // Remote colors skip local validation, store them normalized or fall back to white
+    let color = |cat: &RemoteCategory| tag_category::normalize_color(&cat.color).unwrap_or_else(|| "#FFFFFF".to_owned());
+
+    // Ordering is local only, the remote doesn't send it so existing rows keep theirs
     for cat in categories.iter().filter(|p| existing_ids.contains(&p.id)) {
-        update_stmt.execute(params![cat.description, cat.color, cat.name, cat.id]).context(error::SqliteSnafu)?;
+        update_stmt.execute(params![cat.description, color(cat), cat.name, cat.id]).context(error::SqliteSnafu)?;
     }
 
-    // Handle new platforms
+    // Handle new categories
     for cat in categories.iter().filter(|p| !existing_ids.contains(&p.id)) {
-        insert_stmt.execute(params![cat.id, cat.description, cat.color, cat.name]).context(error::SqliteSnafu)?;
+        insert_stmt.execute(params![cat.id, cat.description, color(cat), cat.name]).context(error::SqliteSnafu)?;
     }
 
     Ok(())
@@ -247,6 +317,14 @@ pub fn apply_categories(conn: &Connection, categories: Vec<RemoteCategory>) -> R
 pub fn apply_tags(conn: &Connection, tags: Vec<RemoteTag>) -> Result<()> {
     // Allow use of rarray() in SQL queries
     rusqlite::vtab::array::load_module(conn).context(error::SqliteSnafu)?;
+
+    // Tags merged away locally must not be resurrected, their aliases are folded into the merged tag instead
+    let redirects: std::collections::HashMap<i64, i64> = tag::find_redirects(conn).context(error::SqliteSnafu)?
+        .into_iter()
+        .map(|r| (r.old_tag_id, r.new_tag_id))
+        .collect();
+    let (redirected, tags): (Vec<RemoteTag>, Vec<RemoteTag>) = tags.into_iter()
+        .partition(|cur| redirects.contains_key(&cur.id));
     
     // Create a list of Alias structs from the aliases
     let changed_aliases: Vec<Alias> = tags.iter()
@@ -314,6 +392,14 @@ pub fn apply_tags(conn: &Connection, tags: Vec<RemoteTag>) -> Result<()> {
         insert_tag_stmt.execute(params![tag.id, tag.date_modified, tag.name, tag.description, tag.category]).context(error::SqliteSnafu)?;
     }
 
+    // Aliases already owned by another tag stay where they are
+    let mut insert_redirected_alias_stmt = conn.prepare("INSERT OR IGNORE INTO tag_alias (tagId, name) VALUES (?, ?)").context(error::SqliteSnafu)?;
+    for tag in redirected.iter().filter(|p| !p.deleted) {
+        for alias in &tag.aliases {
+            insert_redirected_alias_stmt.execute(params![redirects[&tag.id], alias]).context(error::SqliteSnafu)?;
+        }
+    }
+
     mark_index_dirty(conn).context(error::SqliteSnafu)?;
 
     Ok(())
@@ -331,8 +417,9 @@ pub fn apply_games(conn: &Connection, games_res: &RemoteGamesRes) -> Result<()>
     conn.execute("DELETE FROM game_tags_tag WHERE gameId IN rarray(?)", params![changed_ids]).context(error::SqliteSnafu)?;
     conn.execute("DELETE FROM game_platforms_platform WHERE gameId IN rarray(?)", params![changed_ids]).context(error::SqliteSnafu)?;
     // Insert game relations
-    let mut insert_tag_relation_stmt = conn.prepare("INSERT INTO game_tags_tag (gameId, tagId) 
-    VALUES (?, ?)").context(error::SqliteSnafu)?;
+    // Relations to merged away tags follow the redirect, which may duplicate an existing relation
+    let mut insert_tag_relation_stmt = conn.prepare("INSERT OR IGNORE INTO game_tags_tag (gameId, tagId) 
+    VALUES (?1, COALESCE((SELECT newTagId FROM tag_redirect WHERE oldTagId = ?2), ?2))").context(error::SqliteSnafu)?;
     let mut insert_platform_relation_stmt = conn.prepare("INSERT INTO game_platforms_platform (gameId, platformId) 
     VALUES (?, ?)").context(error::SqliteSnafu)?;
     for ta in &games_res.tag_relations {
@@ -375,7 +462,7 @@ pub fn apply_games(conn: &Connection, games_res: &RemoteGamesRes) -> Result<()>
             .context(error::SqliteSnafu)?;
     }
 
-    let existing_ids = game::find_all_ids(conn).context(error::SqliteSnafu)?;
+    let existing_ids = game::find_all_ids_with_deleted(conn).context(error::SqliteSnafu)?;
 
     println!("Updating games");
 
@@ -384,7 +471,7 @@ pub fn apply_games(conn: &Connection, games_res: &RemoteGamesRes) -> Result<()>
         platformName = ?, platformId = (SELECT platformId FROM platform_alias WHERE name = ?), platformsStr = ?, dateAdded = ?, dateModified = ?, 
         playMode = ?, status = ?, notes = ?, source = ?, activeDataId = -1,
         applicationPath = ?, launchCommand = ?, releaseDate = ?, version = ?,
-        originalDescription = ?, language = ?, archiveState = ?, ruffleSupport = ? WHERE id = ?").context(error::SqliteSnafu)?;
+        originalDescription = ?, language = ?, archiveState = ?, ruffleSupport = ?, releaseYear = ? WHERE id = ?").context(error::SqliteSnafu)?;
 
     for g in games_res.games.iter().filter(|p| existing_ids.contains(&p.id)) {
         update_game_stmt.execute(params![
@@ -392,7 +479,8 @@ pub fn apply_games(conn: &Connection, games_res: &RemoteGamesRes) -> Result<()>
             g.platform_name, g.platform_name, "", g.date_added, g.date_modified,
             g.play_mode, g.status, g.notes, g.source,
             g.application_path, g.launch_command, g.release_date, g.version,
-            g.original_description, g.language, g.archive_state, g.ruffle_support, g.id]).context(error::SqliteSnafu)?;
+            g.original_description, g.language, g.archive_state, g.ruffle_support,
+            game::parse_release_year(&g.release_date), g.id]).context(error::SqliteSnafu)?;
     }
 
     println!("Inserting games");
@@ -402,8 +490,8 @@ pub fn apply_games(conn: &Connection, games_res: &RemoteGamesRes) -> Result<()>
         platformName, platformId, platformsStr, dateAdded, dateModified, broken, extreme, playMode, status,
         notes, tagsStr, source, applicationPath, launchCommand, releaseDate, version,
         originalDescription, language, activeDataId, activeDataOnDisk, playtime,
-        archiveState, orderTitle, ruffleSupport) VALUES (?, ?, ?, ?, ?, ?, ?,
-        ?, ?, (SELECT platformId FROM platform_alias WHERE name = ?), ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)").context(error::SqliteSnafu)?;
+        archiveState, orderTitle, ruffleSupport, releaseYear) VALUES (?, ?, ?, ?, ?, ?, ?,
+        ?, ?, (SELECT platformId FROM platform_alias WHERE name = ?), ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)").context(error::SqliteSnafu)?;
 
     for g in games_res.games.iter().filter(|p| !existing_ids.contains(&p.id)) {
         insert_game_stmt.execute(params![
@@ -411,7 +499,7 @@ pub fn apply_games(conn: &Connection, games_res: &RemoteGamesRes) -> Result<()>
             g.platform_name, g.platform_name, "", g.date_added, g.date_modified, false, false, g.play_mode, g.status,
             g.notes, "", g.source, g.application_path, g.launch_command, g.release_date, g.version,
             g.original_description, g.language, -1, false, 0,
-            g.archive_state, "", g.ruffle_support,
+            g.archive_state, "", g.ruffle_support, game::parse_release_year(&g.release_date),
         ]).context(error::SqliteSnafu)?;
     }
 
@@ -457,6 +545,7 @@ pub fn delete_games(conn: &Connection, games_res: &RemoteDeletedGamesRes) -> Res
     conn.execute("DELETE FROM game_platforms_platform WHERE gameId IN rarray(?)", params![ids]).context(error::SqliteSnafu)?;
     conn.execute("DELETE FROM game_data WHERE gameId IN rarray(?)", params![ids]).context(error::SqliteSnafu)?;
     conn.execute("DELETE FROM additional_app WHERE parentGameId IN rarray(?)", params![ids]).context(error::SqliteSnafu)?;
+    // game_user_data is left alone so a game that comes back gets its favorites and notes back too
     conn.execute("DELETE FROM game WHERE id IN rarray(?)", params![ids]).context(error::SqliteSnafu)?;
 
     Ok(())
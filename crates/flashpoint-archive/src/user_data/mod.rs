@@ -0,0 +1,90 @@
+use rusqlite::{params, Connection, OptionalExtension, Result};
+
+use crate::game::{search::{self, GameSearch}, Game};
+
+/// Local user state for a game, kept apart from the game table so metadata syncs never touch it
+#[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone)]
+pub struct GameUserData {
+    pub game_id: String,
+    pub favorite: bool,
+    pub user_notes: String,
+    pub user_rating: Option<i64>,
+    pub updated_at: String,
+}
+
+#[cfg_attr(feature = "napi", napi(object))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Default)]
+pub struct PartialGameUserData {
+    pub game_id: String,
+    pub favorite: Option<bool>,
+    pub user_notes: Option<String>,
+    pub user_rating: Option<i64>,
+    /// Set the user rating back to unrated, takes precedence over `user_rating`
+    pub clear_user_rating: Option<bool>,
+    pub updated_at: Option<String>,
+}
+
+pub fn get(conn: &Connection, game_id: &str) -> Result<Option<GameUserData>> {
+    let mut stmt = conn.prepare(
+        "SELECT gameId, favorite, userNotes, userRating, updatedAt FROM game_user_data WHERE gameId = ?",
+    )?;
+
+    stmt.query_row(params![game_id], |row| {
+        Ok(GameUserData {
+            game_id: row.get(0)?,
+            favorite: row.get(1)?,
+            user_notes: row.get(2)?,
+            user_rating: row.get(3)?,
+            updated_at: row.get(4)?,
+        })
+    })
+    .optional()
+}
+
+/// Creates or updates the user data for a game, fields left as None keep their current value.
+/// The rating can only be unset with `clear_user_rating`, as None leaves it alone.
+pub fn set(conn: &Connection, partial: &PartialGameUserData, now: &str) -> Result<GameUserData> {
+    let mut data = match get(conn, &partial.game_id)? {
+        Some(data) => data,
+        None => GameUserData {
+            game_id: partial.game_id.clone(),
+            favorite: false,
+            user_notes: String::new(),
+            user_rating: None,
+            updated_at: now.to_owned(),
+        },
+    };
+
+    if let Some(favorite) = partial.favorite {
+        data.favorite = favorite;
+    }
+    if let Some(user_notes) = partial.user_notes.clone() {
+        data.user_notes = user_notes;
+    }
+    if partial.clear_user_rating == Some(true) {
+        data.user_rating = None;
+    } else if partial.user_rating.is_some() {
+        data.user_rating = partial.user_rating;
+    }
+    data.updated_at = partial.updated_at.clone().unwrap_or_else(|| now.to_owned());
+
+    conn.execute(
+        "INSERT INTO game_user_data (gameId, favorite, userNotes, userRating, updatedAt) VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(gameId) DO UPDATE SET favorite = excluded.favorite, userNotes = excluded.userNotes,
+        userRating = excluded.userRating, updatedAt = excluded.updatedAt",
+        params![data.game_id, data.favorite, data.user_notes, data.user_rating, data.updated_at],
+    )?;
+
+    Ok(data)
+}
+
+/// `tag_filter` hides games as `GameSearch::with_tag_filter` does, its index must be up to date
+pub fn find_favorites(conn: &Connection, tag_filter: Option<Vec<String>>) -> Result<Vec<Game>> {
+    let mut search = GameSearch::default();
+    search.filter.bool_comp.favorite = Some(true);
+    search.with_tag_filter = tag_filter;
+    search::search_limited(conn, &search, None)
+}
@@ -10,20 +10,24 @@ pub struct ContentTreeNode {
     pub size: i64,
     pub node_type: String,
     pub children: Vec<ContentTreeNode>,
-    pub count: i64
+    pub count: i64,
+    /// Sum of every file size in the tree, only set on the root node
+    pub total_size: Option<i64>,
 }
 
 pub fn gen_content_tree(root: &str) -> Result<ContentTreeNode, Box<dyn std::error::Error + Send + Sync>> {
     let children = load_branch(std::path::Path::new(root))?;
     let children_total: i64 = children.iter().map(|n| n.count).sum();
     let count = (children.len() as i64) + children_total;
+    let size: i64 = children.iter().map(|n| n.size).sum();
     let node = ContentTreeNode {
         name: String::from("content"),
         expanded: true,
         node_type: String::from("directory"),
-        size: 0,
+        size,
         children,
         count,
+        total_size: Some(size),
     };
     Ok(node)
 }
@@ -38,13 +42,15 @@ fn load_branch(root: &std::path::Path) -> Result<Vec<ContentTreeNode>, Box<dyn s
             let children = load_branch(path.as_path())?;
             let children_total: i64 = children.iter().map(|n| n.count).sum();
             let count = (children.len() as i64) + children_total;
+            let size: i64 = children.iter().map(|n| n.size).sum();
             let node = ContentTreeNode {
                 name: String::from(path.file_name().unwrap().to_str().unwrap()),
                 expanded: true,
                 node_type: String::from("directory"),
                 children,
-                size: 0,
-                count: count as i64
+                size,
+                count: count as i64,
+                total_size: None,
             };
             nodes.push(node);
         } else {
@@ -54,7 +60,8 @@ fn load_branch(root: &std::path::Path) -> Result<Vec<ContentTreeNode>, Box<dyn s
                 node_type: String::from("file"),
                 children: Vec::new(),
                 size: path.metadata()?.len() as i64, 
-                count: 0
+                count: 0,
+                total_size: None,
             };
             nodes.push(node);
         }
@@ -62,6 +69,27 @@ fn load_branch(root: &std::path::Path) -> Result<Vec<ContentTreeNode>, Box<dyn s
     Ok(nodes)
 }
 
+/// Lists every file below `node` as `(relative_path, size)`, always using '/' as the separator
+pub fn flatten_content_tree(node: &ContentTreeNode) -> Vec<(String, i64)> {
+    let mut files = Vec::new();
+    for child in &node.children {
+        flatten_branch(child, "", &mut files);
+    }
+    files
+}
+
+fn flatten_branch(node: &ContentTreeNode, prefix: &str, files: &mut Vec<(String, i64)>) {
+    let name = node.name.replace('\\', "/");
+    let path = if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) };
+    if node.node_type == "directory" {
+        for child in &node.children {
+            flatten_branch(child, &path, files);
+        }
+    } else {
+        files.push((path, node.size));
+    }
+}
+
 pub fn copy_folder(src: &str, dest: &str) -> Result<u64, Box<dyn std::error::Error>> {
     let root_path = Path::new(src);
     let dest_path = Path::new(dest);
@@ -74,4 +102,80 @@ pub fn copy_folder(src: &str, dest: &str) -> Result<u64, Box<dyn std::error::Err
     from_paths.push(root_path);
     let copied_items = copy_items(&from_paths, dest_path, &options)?;
     Ok(copied_items)
-}
\ No newline at end of file
+}
+
+/// ISO 639-1 codes with the names and older codes commonly used for them in the language column
+const LANGUAGES: &[(&str, &[&str])] = &[
+    ("ar", &["arabic", "ara"]),
+    ("bg", &["bulgarian", "bul"]),
+    ("ca", &["catalan", "cat"]),
+    ("cs", &["czech", "ces", "cze"]),
+    ("da", &["danish", "dansk", "dan"]),
+    ("de", &["german", "deutsch", "deu", "ger"]),
+    ("el", &["greek", "ell", "gre"]),
+    ("en", &["english", "eng"]),
+    ("eo", &["esperanto", "epo"]),
+    ("es", &["spanish", "español", "espanol", "castellano", "spa"]),
+    ("et", &["estonian", "est"]),
+    ("fa", &["persian", "farsi", "fas", "per"]),
+    ("fi", &["finnish", "suomi", "fin"]),
+    ("fr", &["french", "français", "francais", "fra", "fre"]),
+    ("he", &["hebrew", "heb"]),
+    ("hi", &["hindi", "hin"]),
+    ("hr", &["croatian", "hrv"]),
+    ("hu", &["hungarian", "magyar", "hun"]),
+    ("id", &["indonesian", "bahasa indonesia", "ind"]),
+    ("it", &["italian", "italiano", "ita"]),
+    ("ja", &["japanese", "日本語", "jpn", "jp"]),
+    ("ko", &["korean", "한국어", "kor"]),
+    ("la", &["latin", "lat"]),
+    ("lt", &["lithuanian", "lit"]),
+    ("lv", &["latvian", "lav"]),
+    ("ms", &["malay", "msa", "may"]),
+    ("nl", &["dutch", "nederlands", "nld", "dut"]),
+    ("no", &["norwegian", "norsk", "nor"]),
+    ("pl", &["polish", "polski", "pol"]),
+    ("pt", &["portuguese", "português", "portugues", "brazilian portuguese", "por"]),
+    ("ro", &["romanian", "ron", "rum"]),
+    ("ru", &["russian", "русский", "rus"]),
+    ("sk", &["slovak", "slk", "slo"]),
+    ("sr", &["serbian", "srp"]),
+    ("sv", &["swedish", "svenska", "swe"]),
+    ("th", &["thai", "tha"]),
+    ("tl", &["tagalog", "filipino", "tgl", "fil"]),
+    ("tr", &["turkish", "türkçe", "turkce", "tur"]),
+    ("uk", &["ukrainian", "ukr"]),
+    ("vi", &["vietnamese", "vie"]),
+    ("zh", &["chinese", "中文", "mandarin", "cantonese", "zho", "chi"]),
+];
+
+/// Splits a freeform language value into ISO 639-1 codes, e.g. "Japanese, English" -> ["ja", "en"].
+/// Region subtags and notes are dropped ("pt-BR" -> "pt"), unrecognized parts are kept lowercased and duplicates removed.
+pub fn normalize_languages(raw: &str) -> Vec<String> {
+    let mut codes: Vec<String> = vec![];
+    let parts = raw
+        .split(|c: char| matches!(c, ';' | ',' | '/' | '&' | '+' | '|'))
+        .flat_map(|part| part.split(" and "));
+    for part in parts {
+        // Drop notes like "English (US)"
+        let part = part.split('(').next().unwrap_or_default().trim().to_lowercase();
+        if part.is_empty() {
+            continue;
+        }
+        let code = normalize_language(&part)
+            .or_else(|| part.split(|c: char| c == '-' || c == '_').next().and_then(normalize_language))
+            .map(|code| code.to_owned())
+            .unwrap_or(part);
+        if !codes.contains(&code) {
+            codes.push(code);
+        }
+    }
+    codes
+}
+
+fn normalize_language(part: &str) -> Option<&'static str> {
+    LANGUAGES
+        .iter()
+        .find(|(code, names)| *code == part || names.contains(&part))
+        .map(|(code, _)| *code)
+}
@@ -0,0 +1,14 @@
+use snafu::prelude::*;
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum Error {
+    #[snafu(display("Request to {} failed: {}", url, source))]
+    Request { url: String, source: reqwest::Error },
+    #[snafu(display("Request to {} returned status {}", url, status))]
+    Status { url: String, status: u16 },
+    #[snafu(display("Failed to decode response from {}: {}", url, source))]
+    Decode { url: String, source: reqwest::Error },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -0,0 +1,395 @@
+#![allow(non_snake_case)]
+
+use std::time::Duration;
+
+use flashpoint_archive::game::GameRedirect;
+use flashpoint_archive::update::{RemoteCategory, RemoteDeletedGamesRes, RemoteGamesRes, RemotePlatform, RemoteTag};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use snafu::ResultExt;
+
+pub mod error;
+
+use error::Result;
+
+pub const DEFAULT_BASE_URL: &str = "https://fpfss.unstable.life";
+
+/// Typed client for the FPFSS API. Responses are mapped into the canonical
+/// `flashpoint_archive::update` types, ready to pass to the `update_apply_*` methods.
+#[derive(Debug, Clone)]
+pub struct FpfssClient {
+    base_url: String,
+    token: Option<String>,
+    max_retries: u32,
+    retry_delay: Duration,
+    http: reqwest::Client,
+}
+
+#[derive(Debug, Clone)]
+pub struct RemoteTagsRes {
+    pub tags: Vec<RemoteTag>,
+    pub categories: Vec<RemoteCategory>,
+}
+
+impl Default for FpfssClient {
+    fn default() -> Self {
+        FpfssClient::new(DEFAULT_BASE_URL)
+    }
+}
+
+impl FpfssClient {
+    pub fn new(base_url: &str) -> Self {
+        FpfssClient {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            token: None,
+            max_retries: 3,
+            retry_delay: Duration::from_millis(500),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Sends the token as a bearer `Authorization` header, required by admin endpoints
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_owned());
+        self
+    }
+
+    /// Retries failed requests up to `max_retries` times, doubling `delay` after each attempt
+    pub fn with_retries(mut self, max_retries: u32, delay: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_delay = delay;
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub async fn fetch_platforms(&self) -> Result<Vec<RemotePlatform>> {
+        let res = self.get::<Vec<RemotePlatformRaw>>("/api/platforms", &[]).await?;
+        Ok(res.into_iter().map(|r| r.into()).collect())
+    }
+
+    pub async fn fetch_tags(&self) -> Result<RemoteTagsRes> {
+        let res = self.get::<RemoteTagsResRaw>("/api/tags", &[]).await?;
+        Ok(RemoteTagsRes {
+            tags: res.tags.into_iter().map(|t| t.into()).collect(),
+            categories: res.categories,
+        })
+    }
+
+    /// Fetches a single page of games modified after `after`. Pass the id of the last game
+    /// of the previous page as `after_id` to fetch the next page, an empty page marks the end.
+    pub async fn fetch_games(&self, after: &str, after_id: Option<&str>) -> Result<RemoteGamesRes> {
+        let mut query = vec![("broad", "true"), ("after", after)];
+        if let Some(id) = after_id {
+            query.push(("afterId", id));
+        }
+        self.get::<RemoteGamesRes>("/api/games", &query).await
+    }
+
+    pub async fn fetch_deleted(&self, after: &str) -> Result<RemoteDeletedGamesRes> {
+        self.get::<RemoteDeletedGamesRes>("/api/games/deleted", &[("after", after)]).await
+    }
+
+    pub async fn fetch_redirects(&self) -> Result<Vec<GameRedirect>> {
+        let res = self.get::<Vec<RemoteRedirectRaw>>("/api/game-redirects", &[]).await?;
+        Ok(res.into_iter().map(|r| GameRedirect {
+            source_id: r.source_id,
+            dest_id: r.id,
+        }).collect())
+    }
+
+    /// Number of games modified after `after`
+    pub async fn fetch_update_count(&self, after: &str) -> Result<i64> {
+        let res = self.get::<UpdateInfo>("/api/games/updates", &[("after", after)]).await?;
+        Ok(res.total)
+    }
+
+    /// `query` values are percent encoded, timestamps can hold `+` and `:`
+    async fn get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, &str)]) -> Result<T> {
+        let url = format!("{}{}", self.base_url, path);
+        let mut attempt = 0;
+        loop {
+            let mut req = self.http.get(&url).query(query);
+            if let Some(token) = &self.token {
+                req = req.bearer_auth(token);
+            }
+
+            match req.send().await {
+                Ok(resp) => {
+                    let status = resp.status();
+                    if status.is_success() {
+                        return resp.json::<T>().await.context(error::DecodeSnafu { url });
+                    }
+                    if attempt >= self.max_retries || !is_retryable(status) {
+                        return error::StatusSnafu { url, status: status.as_u16() }.fail();
+                    }
+                },
+                Err(e) => {
+                    if attempt >= self.max_retries || !(e.is_connect() || e.is_timeout()) {
+                        return Err(e).context(error::RequestSnafu { url });
+                    }
+                }
+            }
+
+            tokio::time::sleep(self.retry_delay * 2u32.pow(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// FPFSS sends aliases as a single `;` separated string
+pub fn split_aliases(aliases: &str) -> Vec<String> {
+    aliases.split(';')
+        .map(|a| a.trim())
+        .filter(|a| !a.is_empty())
+        .map(|a| a.to_owned())
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct UpdateInfo {
+    total: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoteRedirectRaw {
+    source_id: String,
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemotePlatformRaw {
+    id: i64,
+    name: String,
+    description: String,
+    date_modified: String,
+    aliases: String,
+    Deleted: bool,
+}
+
+impl From<RemotePlatformRaw> for RemotePlatform {
+    fn from(value: RemotePlatformRaw) -> Self {
+        RemotePlatform {
+            id: value.id,
+            name: value.name,
+            description: value.description,
+            date_modified: value.date_modified,
+            aliases: split_aliases(&value.aliases),
+            deleted: value.Deleted,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoteTagsResRaw {
+    tags: Vec<RemoteTagRaw>,
+    categories: Vec<RemoteCategory>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoteTagRaw {
+    id: i64,
+    name: String,
+    description: String,
+    date_modified: String,
+    category: String,
+    aliases: String,
+    Deleted: bool,
+}
+
+impl From<RemoteTagRaw> for RemoteTag {
+    fn from(value: RemoteTagRaw) -> Self {
+        RemoteTag {
+            id: value.id,
+            name: value.name,
+            description: value.description,
+            category: value.category,
+            date_modified: value.date_modified,
+            aliases: split_aliases(&value.aliases),
+            deleted: value.Deleted,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// Serves canned responses on a local port. `handler` receives the request path and the
+    /// Authorization header and returns the status code and body.
+    async fn stub_server<F>(handler: F) -> String
+    where F: Fn(&str, Option<&str>) -> (u16, String) + Send + Sync + 'static {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handler = Arc::new(handler);
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = match listener.accept().await {
+                    Ok(s) => s,
+                    Err(_) => return,
+                };
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    let mut buf = vec![0u8; 8192];
+                    let mut len = 0;
+                    while !String::from_utf8_lossy(&buf[..len]).contains("\r\n\r\n") {
+                        match socket.read(&mut buf[len..]).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => len += n,
+                        }
+                    }
+                    let req = String::from_utf8_lossy(&buf[..len]).to_string();
+                    let path = req.split_whitespace().nth(1).unwrap_or("/").to_owned();
+                    let auth = req.lines()
+                        .find(|l| l.to_lowercase().starts_with("authorization:"))
+                        .map(|l| l["authorization:".len()..].trim().to_owned());
+                    let (status, body) = handler(&path, auth.as_deref());
+                    let resp = format!(
+                        "HTTP/1.1 {} STUB\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    );
+                    let _ = socket.write_all(resp.as_bytes()).await;
+                    let _ = socket.shutdown().await;
+                });
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    fn games_page(ids: &[&str]) -> String {
+        let games = ids.iter().map(|id| format!(
+            r#"{{"id":"{}","title":"Game {}","alternate_titles":"","series":"","developer":"","publisher":"","date_added":"2024-01-01T00:00:00.000Z","date_modified":"2024-01-01T00:00:00.000Z","play_mode":"","status":"","notes":"","source":"","application_path":"","launch_command":"","release_date":"","version":"","original_description":"","language":"","library":"arcade","platform_name":"Flash","archive_state":0,"ruffle_support":""}}"#,
+            id, id
+        )).collect::<Vec<String>>().join(",");
+        format!(r#"{{"games":[{}],"add_apps":[],"game_data":[],"tag_relations":[],"platform_relations":[]}}"#, games)
+    }
+
+    #[test]
+    fn split_aliases_edge_cases() {
+        assert_eq!(split_aliases("Action; Shooter"), vec!["Action", "Shooter"]);
+        assert_eq!(split_aliases("Action; Shooter;"), vec!["Action", "Shooter"]);
+        assert_eq!(split_aliases("Action;;  ; Shooter ; "), vec!["Action", "Shooter"]);
+        assert!(split_aliases("").is_empty());
+        assert!(split_aliases(" ; ").is_empty());
+    }
+
+    #[tokio::test]
+    async fn fetch_tags_and_platforms() {
+        let base_url = stub_server(|path, _| match path {
+            "/api/platforms" => (200, r#"[
+                {"id":1,"name":"Flash","description":"","date_modified":"2024-01-01","aliases":"Flash; Shockwave Flash;","Deleted":false},
+                {"id":2,"name":"Gone","description":"","date_modified":"2024-01-01","aliases":"","Deleted":true}
+            ]"#.to_owned()),
+            "/api/tags" => (200, r##"{
+                "tags":[{"id":5,"name":"Action","description":"","date_modified":"2024-01-01","category":"genre","aliases":"Action;","Deleted":false}],
+                "categories":[{"id":1,"name":"genre","color":"#ffffff","description":""}]
+            }"##.to_owned()),
+            _ => (404, String::new()),
+        }).await;
+        let client = FpfssClient::new(&base_url);
+
+        let plats_res = client.fetch_platforms().await;
+        assert!(plats_res.is_ok());
+        let plats = plats_res.unwrap();
+        assert_eq!(plats.len(), 2);
+        assert_eq!(plats[0].aliases, vec!["Flash", "Shockwave Flash"]);
+        assert!(!plats[0].deleted);
+        assert!(plats[1].aliases.is_empty());
+        assert!(plats[1].deleted);
+
+        let tags_res = client.fetch_tags().await;
+        assert!(tags_res.is_ok());
+        let tags = tags_res.unwrap();
+        assert_eq!(tags.categories.len(), 1);
+        assert_eq!(tags.tags.len(), 1);
+        assert_eq!(tags.tags[0].aliases, vec!["Action"]);
+        assert_eq!(tags.tags[0].category, "genre");
+    }
+
+    #[tokio::test]
+    async fn fetch_games_pagination() {
+        let base_url = stub_server(|path, _| {
+            if !path.starts_with("/api/games?broad=true&after=1970-01-01") {
+                return (404, String::new());
+            }
+            if path.ends_with("afterId=b") {
+                (200, games_page(&[]))
+            } else if path.ends_with("afterId=a") {
+                (200, games_page(&["b"]))
+            } else {
+                (200, games_page(&["a"]))
+            }
+        }).await;
+        let client = FpfssClient::new(&base_url);
+
+        let mut seen = vec![];
+        let mut next_id: Option<String> = None;
+        loop {
+            let page_res = client.fetch_games("1970-01-01", next_id.as_deref()).await;
+            assert!(page_res.is_ok());
+            let page = page_res.unwrap();
+            if page.games.is_empty() {
+                break;
+            }
+            next_id = Some(page.games.last().unwrap().id.clone());
+            seen.extend(page.games.into_iter().map(|g| g.id));
+        }
+        assert_eq!(seen, vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn fetch_encodes_query_values() {
+        let base_url = stub_server(|path, _| match path {
+            "/api/games?broad=true&after=2024-01-01T00%3A00%3A00%2B01%3A00&afterId=a%26b" => (200, games_page(&["c"])),
+            _ => (404, String::new()),
+        }).await;
+        let client = FpfssClient::new(&base_url);
+
+        let page_res = client.fetch_games("2024-01-01T00:00:00+01:00", Some("a&b")).await;
+        assert!(page_res.is_ok());
+        assert_eq!(page_res.unwrap().games[0].id, "c");
+    }
+
+    #[tokio::test]
+    async fn retries_and_auth() {
+        let calls = Arc::new(Mutex::new(0));
+        let counter = calls.clone();
+        let base_url = stub_server(move |path, auth| {
+            if auth != Some("Bearer secret") {
+                return (401, String::new());
+            }
+            let mut count = counter.lock().unwrap();
+            *count += 1;
+            match (path, *count) {
+                ("/api/games/updates?after=1970-01-01", 1) => (503, String::new()),
+                ("/api/games/updates?after=1970-01-01", _) => (200, r#"{"total":42}"#.to_owned()),
+                _ => (404, String::new()),
+            }
+        }).await;
+
+        // Missing token is not retried
+        let client = FpfssClient::new(&base_url).with_retries(2, Duration::from_millis(1));
+        let unauth_res = client.fetch_update_count("1970-01-01").await;
+        assert!(matches!(unauth_res, Err(error::Error::Status { status: 401, .. })));
+
+        let client = client.with_token("secret");
+        let count_res = client.fetch_update_count("1970-01-01").await;
+        assert!(count_res.is_ok());
+        assert_eq!(count_res.unwrap(), 42);
+        assert_eq!(*calls.lock().unwrap(), 2);
+    }
+}